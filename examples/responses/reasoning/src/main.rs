@@ -1,7 +1,7 @@
 use ftail::Ftail;
 use openai_dive::v1::api::Client;
 use openai_dive::v1::resources::response::request::{ResponseInput, ResponseParametersBuilder};
-use openai_dive::v1::resources::response::response::ResponseReasoning;
+use openai_dive::v1::resources::response::response::{ReasoningSummary, ResponseReasoning};
 use openai_dive::v1::resources::shared::ReasoningEffort;
 
 #[tokio::main]
@@ -19,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ))
         .reasoning(ResponseReasoning {
             effort: Some(ReasoningEffort::Low),
-            // generate_summary: Some(ReasoningSummary::Concise),
+            summary: Some(ReasoningSummary::Auto),
         })
         .build()?;
 
@@ -27,5 +27,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("{result:#?}");
 
+    if let Some(summary) = result.reasoning_summary_text() {
+        println!("{summary}");
+    }
+
     Ok(())
 }
@@ -0,0 +1,131 @@
+//! Compares the peak memory and latency of parsing a large embeddings response directly into the typed struct
+//! with parsing it into a `serde_json::Value` first and then into f64 vectors, as before.
+//! Criterion measures the latency, the peak memory is tracked by the global allocator and printed before.
+//!
+//! Run with `cargo bench -p openai_dive --features bench --bench embeddings`.
+use criterion::{criterion_group, criterion_main, Criterion};
+use openai_dive::v1::resources::embedding::EmbeddingResponse;
+use serde::Deserialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const EMBEDDINGS: usize = 2048;
+const DIMENSIONS: usize = 1536;
+
+struct PeakAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// The response types before the change, an untagged f64 vector parsed through a `Value`.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ValueEmbeddingResponse {
+    data: Vec<ValueEmbedding>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ValueEmbedding {
+    embedding: ValueEmbeddingOutput,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum ValueEmbeddingOutput {
+    Float(Vec<f64>),
+    Base64(String),
+}
+
+fn fixture() -> String {
+    let mut seed: u32 = 42;
+    let mut value = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (seed >> 8) as f32 / (1 << 24) as f32 * 0.2 - 0.1
+    };
+
+    let data: Vec<String> = (0..EMBEDDINGS)
+        .map(|index| {
+            let embedding: Vec<String> = (0..DIMENSIONS).map(|_| value().to_string()).collect();
+
+            format!(
+                r#"{{"object":"embedding","index":{index},"embedding":[{}]}}"#,
+                embedding.join(",")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"object":"list","data":[{}],"model":"text-embedding-3-small","usage":{{"prompt_tokens":8,"total_tokens":8}}}}"#,
+        data.join(",")
+    )
+}
+
+fn through_value(payload: &str) -> ValueEmbeddingResponse {
+    let value: serde_json::Value = serde_json::from_str(payload).unwrap();
+    serde_json::from_value(value).unwrap()
+}
+
+fn direct(payload: &str) -> EmbeddingResponse {
+    serde_json::from_str(payload).unwrap()
+}
+
+fn peak_memory<T>(name: &str, payload: &str, parse: impl Fn(&str) -> T) {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    black_box(parse(black_box(payload)));
+
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+
+    println!(
+        "{name:<24} peak {:>7.1} MB ({:.1}x the payload)",
+        peak as f64 / 1_048_576.0,
+        peak as f64 / payload.len() as f64
+    );
+}
+
+fn embeddings(c: &mut Criterion) {
+    let payload = fixture();
+
+    println!(
+        "{EMBEDDINGS} embeddings of {DIMENSIONS} dimensions, payload {:.1} MB",
+        payload.len() as f64 / 1_048_576.0
+    );
+
+    peak_memory("through Value, f64", &payload, through_value);
+    peak_memory("direct", &payload, direct);
+
+    let mut group = c.benchmark_group("embeddings");
+    group.sample_size(10);
+
+    group.bench_function("through Value, f64", |b| {
+        b.iter(|| through_value(black_box(&payload)))
+    });
+    group.bench_function("direct", |b| b.iter(|| direct(black_box(&payload))));
+
+    group.finish();
+}
+
+criterion_group!(benches, embeddings);
+criterion_main!(benches);
@@ -25,7 +25,16 @@
 //! - [Set API key](#set-api-key)
 //! - [Using OpenAI-compatible APIs](#using-openai-compatible-apis)
 //! - [Set organization/project id](#set-organizationproject-id)
+//! - [Set Accept-Language](#set-accept-language)
+//! - [Log the configuration](#log-the-configuration)
 //! - [Add proxy](#add-proxy)
+//! - [Redirects](#redirects)
+//! - [Warm up the connection](#warm-up-the-connection)
+//! - [Validate parameters](#validate-parameters)
+//! - [Redact logs and errors](#redact-logs-and-errors)
+//! - [Sign requests](#sign-requests)
+//! - [Dry run](#dry-run)
+//! - [Optional features](#optional-features)
 //! - [Available models](#available-models)
 //!
 //! ## Endpoints
@@ -37,6 +46,7 @@
 //!   - [Function calling](#function-calling)
 //!   - [Structured outputs](#structured-outputs)
 //!   - [Web search](#web-search)
+//!   - [Conversations](#conversations)
 //! - [Responses](#responses)
 //! - [Images](#images)
 //! - [Audio](#audio)
@@ -84,6 +94,56 @@
 //!
 //! More information: [Create chat completion](https://platform.openai.com/docs/api-reference/chat/create)
 //!
+//! ### Streaming adapters
+//!
+//! With the `stream` feature, `ChatStreamExt` adapts a chat completion stream for UI frameworks and channels. `into_text_stream` yields only the content deltas. `on_token` calls a callback with every delta and returns the collected response. `forward_to` sends `ChatStreamEvent`s (content, reasoning, refusal and tool call deltas, finish reasons, usage and the final `Done` response) to a `tokio::sync::mpsc` channel. It waits while the channel is full and stops as soon as the receiver is dropped, returning the response collected so far.
+//!
+//! ```rust
+//! let (sender, mut receiver) = tokio::sync::mpsc::channel(32);
+//!
+//! let stream = client.chat().create_stream(parameters).await?;
+//! tokio::spawn(stream.forward_to(sender));
+//!
+//! while let Some(event) = receiver.recv().await {
+//!     if let ChatStreamEvent::Content { delta, .. } = event {
+//!         print!("{delta}");
+//!     }
+//! }
+//! ```
+//!
+//! `into_ui_events` yields `UiEvent`s of the first choice: text, reasoning, tool call name and argument appends, the start of a tool call (once per tool call), and a final `Done` with the finish reason and usage. Appends are never empty. To render less often, `into_ui_events_with_options` merges consecutive appends of the same kind within a coalesce window, which is disabled by default.
+//!
+//! ```rust
+//! let options = UiEventOptionsBuilder::default()
+//!     .coalesce_window(Duration::from_millis(30))
+//!     .build()?;
+//!
+//! let mut events = client
+//!     .chat()
+//!     .create_stream(parameters)
+//!     .await?
+//!     .into_ui_events_with_options(options);
+//!
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         UiEvent::TextAppend(text) => print!("{text}"),
+//!         UiEvent::ToolCallNameAppend { fragment, .. } => print!("{fragment}"),
+//!         UiEvent::Done { finish_reason, .. } => println!("\n{finish_reason:?}"),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+//!
+//! The errors of stream items show what went wrong. `StreamError::Transport` means the connection failed or dropped, its `source` is the error of the connection. A dropped connection isn't opened again, which would generate the response again (and bill it twice): the stream ends after the transport error, send the request again to retry. `StreamError::ServerEvent` is an error sent by the server, with the parsed `ErrorBody`. `StreamError::Parse` is a chunk that couldn't be parsed, with the chunk as payload. `StreamError::NotAnEventStream` is a response that isn't server-sent events, with its content type and body. `StreamError::Timeout` is a stream that sent no data in time, before the first chunk (`TimeoutPhase::FirstByte`) or between two chunks (`TimeoutPhase::Idle`). Only transport errors and timeouts are retryable (`APIError::is_retryable`), the other errors would fail again.
+//!
+//! ```rust
+//! match stream.next().await {
+//!     Some(Err(APIError::StreamError(StreamError::ServerEvent(body)))) => eprintln!("{}", body.message),
+//!     Some(Err(error)) if error.is_retryable() => { /* send the request again */ }
+//!     _ => {}
+//! }
+//! ```
+//!
 //! ### Vision
 //!
 //! Learn how to use vision capabilities to understand images.
@@ -119,6 +179,14 @@
 //!     .await?;
 //! ```
 //!
+//! With the `image-processing` feature, local images are prepared and embedded as data URL. The image is decoded, rotated by its EXIF orientation, downscaled to the maximum dimension of the policy and re-encoded as JPEG with the quality of the policy (images with transparency are re-encoded as PNG). The token cost is estimated for the target detail level. Without the feature a supported image returns `APIError::FeatureDisabled`.
+//!
+//! ```rust
+//! let image = ChatMessageContentPart::image_auto(&std::fs::read("photo.jpg")?, ImagePolicy::default())?;
+//!
+//! println!("{}x{}, ~{} tokens", image.width, image.height, image.estimated_tokens);
+//! ```
+//!
 //! More information: [Vision](https://platform.openai.com/docs/guides/vision)
 //!
 //! ### Voice
@@ -292,6 +360,25 @@
 //! let result = client.chat().create(parameters).await?;
 //! ```
 //!
+//! For models and providers without JSON Schema support, `create_json` uses the `json_object` response format and parses the content into your type. The API requires a message that mentions JSON, which is checked before the request is sent. Prose around the JSON object (ie. "Here is the JSON: {...}") is ignored. With `repair`, content that can't be parsed is sent back once and the model is asked to fix it. The raw content is returned with the value, so differences can be logged.
+//!
+//! ```rust
+//! #[derive(Deserialize)]
+//! struct Event {
+//!     name: String,
+//!     date: String,
+//! }
+//!
+//! let completion = client
+//!     .chat()
+//!     .create_json_with_options::<Event>(parameters, JsonModeOptions { repair: true })
+//!     .await?;
+//!
+//! if let Some(original) = &completion.repaired_from {
+//!     println!("repaired {original} to {}", completion.content);
+//! }
+//! ```
+//!
 //! More information: [Structured outputs](https://platform.openai.com/docs/guides/structured-outputs)
 //!
 //! ### Web search
@@ -327,6 +414,41 @@
 //!
 //! More information: [Web search](https://platform.openai.com/docs/guides/web-search)
 //!
+//! ### Parameter profiles
+//!
+//! A `ProfileStore` keeps named sets of chat completion parameters (everything except the messages) outside the code, ie. in a versioned JSON or TOML file. A profile can extend another one with `extends`, and `apply_layers` layers several profiles in order. Keys that aren't chat completion parameters are sent in `extra_body`, so provider-specific parameters can be part of a profile. An invalid value returns `APIError::ValidationError` with the file and the key (ie. `profiles.json: creative.temperature`).
+//!
+//! ```json
+//! {
+//!     "base": { "model": "gpt-4o", "temperature": 0.2, "top_p": 0.9 },
+//!     "creative": { "extends": "base", "temperature": 0.9, "top_k": 40 }
+//! }
+//! ```
+//!
+//! ```rust
+//! let profiles = ProfileStore::from_file("profiles.json")?;
+//!
+//! let parameters = profiles.apply("creative", messages)?;
+//!
+//! let result = client.chat().create(parameters).await?;
+//! ```
+//!
+//! With the `toml-profiles` feature, `from_file` reads a `.toml` file (a table per profile) and `from_toml` parses TOML text. For other formats, deserialize the file into a `serde_json::Value` and use `ProfileStore::from_value`. `insert` and `to_json` export parameters as profiles.
+//!
+//! ### Conversations
+//!
+//! A `Conversation` keeps the messages of a multi-turn chat in a `ConversationStore`, so it can be reloaded and continued later. `ask` sends the stored messages with the new user message, and appends the user message and the reply (including its tool calls) to the store together once the reply is received. Tool results are sent with `send`. The messages of the parameters (ie. a system message) are sent first but aren't stored.
+//!
+//! ```rust
+//! let store = Arc::new(JsonlConversationStore::new("conversations.jsonl"));
+//!
+//! let conversation = client.chat().conversation(store, "user-42", parameters);
+//!
+//! let reply = conversation.ask("What is the weather in Paris?").await?;
+//! ```
+//!
+//! `SqliteConversationStore` (`sqlite-store` feature) keeps the conversations in a SQLite database, one row per message, and migrates its schema when it's opened. `JsonlConversationStore` writes every turn as one line of a JSON lines file. Both skip records that can't be read when loading (ie. a turn cut off by a crash), a conversation passes them to the warning handler of the client as `ValidationWarning::UnreadableConversationRecord`. `MemoryConversationStore` keeps the conversations in memory. Implement `ConversationStore` to keep conversations in another database.
+//!
 //! ## Responses
 //!
 //! OpenAI's most advanced interface for generating model responses. Supports text and image inputs, and text outputs. Create stateful interactions with the model, using the output of previous responses as input. Extend the model's capabilities with built-in tools for file search, web search, computer use, and more. Allow the model access to external systems and data using function calling.
@@ -341,6 +463,41 @@
 //! - Computer use
 //! - Function calling
 //!
+//! `submit_tool_outputs` answers the function calls of a response and creates the next response. Outputs are text or JSON (`ToolOutput::text`, `ToolOutput::json`), and JSON is sent as a string. Every function call needs exactly one output. Missing, unknown and duplicate call ids return a `ValidationError` before the request is sent. `submit_tool_outputs_stream` streams the next response after the same check.
+//!
+//! ```rust
+//! let next = client
+//!     .responses()
+//!     .submit_tool_outputs(
+//!         &response,
+//!         vec![ToolOutput::json("call_1", serde_json::json!({"celsius": 21}))],
+//!         ResponseParametersBuilder::default().tools(tools).build()?,
+//!     )
+//!     .await?;
+//! ```
+//!
+//! Reasoning models return a summary of their reasoning when `reasoning.summary` is set (`Auto`, `Concise` or `Detailed`), and `reasoning_summary_text` returns it. With `store: false`, include `ResponseInclude::ReasoningEncryptedContent` to receive the reasoning as `encrypted_content`. `output_as_input` returns the output as input items for the next turn and passes the reasoning items back unchanged.
+//!
+//! ```rust
+//! let parameters = ResponseParametersBuilder::default()
+//!     .model("o4-mini")
+//!     .input("How much wood would a woodchuck chuck?")
+//!     .store(false)
+//!     .include(vec![ResponseInclude::ReasoningEncryptedContent])
+//!     .reasoning(ResponseReasoning {
+//!         effort: Some(ReasoningEffort::Low),
+//!         summary: Some(ReasoningSummary::Auto),
+//!     })
+//!     .build()?;
+//!
+//! let response = client.responses().create(parameters.clone()).await?;
+//!
+//! println!("{:?}", response.reasoning_summary_text());
+//!
+//! let mut input = response.output_as_input();
+//! input.push(follow_up);
+//! ```
+//!
 //! ## Images
 //!
 //! Given a prompt and/or an input image, the model will generate a new image.
@@ -349,6 +506,16 @@
 //! - Create image edit
 //! - Create image variation
 //!
+//! `content_hash` of the image, chat completion and embedding parameters returns a SHA-256 hex digest of the fields that determine the result, to use as cache or dedup key. Volatile fields (`user`, `safety_identifier`, `metadata`, `stream`, ...) are left out. The fields are serialized with sorted keys and without `None` values, so new optional fields in later versions don't change the hashes of existing requests.
+//!
+//! ```rust
+//! let key = parameters.content_hash();
+//!
+//! if let Some(images) = cache.get(&key) {
+//!     return Ok(images.clone());
+//! }
+//! ```
+//!
 //! For more information see the examples in the [examples/images](https://github.com/tjardoo/openai-client/tree/master/examples/images) directory.
 //!
 //! More information [Images](https://platform.openai.com/docs/api-reference/images)
@@ -359,7 +526,202 @@
 //!
 //! - Create speech
 //! - Create transcription
-//! - Create translation
+//! - Create translation (`create_translation_verbose` returns the detected language, duration and segments)
+//! - Stream a translation (`create_translation_stream`, `stream` feature, for providers whose translation models stream; a provider that answers without events fails the stream with `StreamError::NotAnEventStream`)
+//! - Voice chat (transcribe, chat and speak in one call)
+//!
+//! Apps that use one model per endpoint can set default models on the client. They are used when the parameters leave the model empty, a model set in the parameters always wins.
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client
+//!     .set_default_speech_model("gpt-4o-mini-tts")
+//!     .set_default_transcription_model("gpt-4o-transcribe")
+//!     .set_default_translation_model("whisper-1");
+//! ```
+//!
+//! Speech requests that leave the voice or the response format unset use `set_default_speech_voice` (ie. `AudioVoice::Coral`) and `set_default_speech_format`. Without these defaults, the unset values are left to the server.
+//!
+//! Transcriptions are billed by the seconds of audio. A verbose transcription (`verbose_json`) returns the duration. `duration_seconds` reads it, and `estimated_cost` applies a price per minute, ie. `response.estimated_cost(0.006)` for whisper-1.
+//!
+//! A verbose transcription or translation can be turned into captions without another request: `to_vtt` formats its segments as WebVTT and `to_srt` as SubRip, with the speaker labels of diarized segments.
+//!
+//! ```rust
+//! let parameters = VoiceChatParametersBuilder::default()
+//!     .audio_in(FileUpload::File("question.mp3".to_string()))
+//!     .history(history)
+//!     .transcription_model("gpt-4o-transcribe")
+//!     .chat_model("gpt-4o")
+//!     .speech(AudioSpeechParametersBuilder::default().model("tts-1").voice("alloy").build()?)
+//!     .build()?;
+//!
+//! let response = client.audio().voice_chat(parameters).await?;
+//!
+//! history = response.updated_history;
+//! ```
+//!
+//! If a stage fails, `APIError::VoiceChatError` contains the stage and its error.
+//!
+//! Some compatible TTS providers accept a weighted blend of voices, which is sent as `[{"voice": "af_bella", "weight": 0.7}, ...]`. OpenAI only accepts a single voice, so a blend returns `APIError::ValidationError` before the request is sent. The `voice` of the speech parameters is a `SpeechVoice`, `.voice("alloy")` and `.voice(AudioVoice::Alloy)` set a single voice and `as_single` returns it (see the [changelog](CHANGELOG.md) for code that assigned a `String`).
+//!
+//! ```rust
+//! let parameters = AudioSpeechParametersBuilder::default()
+//!     .model("kokoro")
+//!     .input("Hello!")
+//!     .voice(SpeechVoice::blend([("af_bella", 0.7), ("af_sky", 0.3)]))
+//!     .build()?;
+//! ```
+//!
+//! Some providers answer a speech request with HTTP 200 and a JSON error (ie. for an invalid voice). `create_speech` returns such a body as `APIError` instead of as audio. A body is treated as JSON when the `Content-Type` is JSON, or when it starts with `{` and has an `error` member. For providers that return the audio as base64 in JSON, disable the check and decode the body at a JSON pointer (`stream` or `download` feature).
+//!
+//! ```rust
+//! client.set_detect_speech_json_errors(false);
+//!
+//! let response = client.audio().create_speech(parameters).await?;
+//! let audio = AudioSpeechResponse::from_json_base64(&response.bytes, "/audio")?;
+//! ```
+//!
+//! Some compatible providers accept a word-boost list of names and jargon with weights. Set `word_boost` on the transcription parameters; it is sent as `word_boost[]` form fields of `word:boost` (`[{"word": ..., "boost": ...}]` for a file URL, change the shape with a request converter). OpenAI has no word-boost list, so for OpenAI the words are appended to the `prompt` by decreasing weight, which counts towards the prompt limit.
+//!
+//! ```rust
+//! let parameters = AudioTranscriptionParametersBuilder::default()
+//!     .file(FileUpload::File("./audio/talk.mp3".to_string()))
+//!     .model("whisper-large-v3")
+//!     .word_boost(vec![("Kubernetes".to_string(), 2.0), ("kubectl".to_string(), 1.5)])
+//!     .build()?;
+//! ```
+//!
+//! Speech and transcription requests send an `Accept` header with the mime type of the response format (ie. `audio/mpeg` for mp3 and `application/x-subrip` for srt), for providers that select the format by the header. Set `accept` on the parameters to override it.
+//!
+//! To show the progress of a long speech download, use `create_speech_with_progress`, which calls the callback with the bytes downloaded so far and the `Content-Length` (`None` for a chunked response) as the chunks arrive. `create_speech_to` writes the chunks to a `tokio::io::AsyncWrite` instead, without holding the whole audio in memory.
+//!
+//! ```rust
+//! let mut file = tokio::fs::File::create("speech.mp3").await?;
+//!
+//! client
+//!     .audio()
+//!     .create_speech_to(parameters, &mut file, |downloaded, total| {
+//!         println!("{downloaded} of {total:?} bytes");
+//!     })
+//!     .await?;
+//! ```
+//!
+//! A speech stream (`stream` feature) holds its connection until it is read to the end. To stop early, ie. when playback is cancelled, call `close` or drop the stream, both close the connection right away. Chat completion streams also stop reading as soon as they are dropped.
+//!
+//! ```rust
+//! let mut stream = client.audio().create_speech_stream(parameters).await?;
+//!
+//! let first_chunk = stream.next().await;
+//!
+//! stream.close();
+//! ```
+//!
+//! The speech stream is `Send` and `Unpin`, so it works with the combinators of `futures::StreamExt` and `tokio_stream::StreamExt` (ie. `throttle`). Use `into_async_read` to copy the audio with `tokio::io::copy`, and `timeout_stream` to fail the stream with `StreamError::Timeout` when no chunk arrives in time.
+//!
+//! ```rust
+//! let stream = client.audio().create_speech_stream(parameters).await?;
+//!
+//! tokio::io::copy(&mut stream.into_async_read(), &mut file).await?;
+//! ```
+//!
+//! With `stream_format(SpeechStreamFormat::Sse)` the audio is streamed as server-sent events, which are decoded into the same chunks. The stream ends at the `data: [DONE]` terminator, like chat completion streams.
+//!
+//! The response is read in a background task into a bounded channel, up to 8 chunks ahead of the consumer. A slow consumer doesn't stall the connection between its reads, and the audio doesn't pile up in memory either. Change the size with `set_stream_buffer_size`, 0 reads the response only when the stream is polled.
+//!
+//! To pace playback, `chunk_by_duration` re-frames PCM and WAV audio into chunks of a fixed duration (the WAV header is the first chunk). Compressed formats can't be framed without decoding them, so they pass through unchanged.
+//!
+//! ```rust
+//! let mut stream = client
+//!     .audio()
+//!     .create_speech_stream(parameters)
+//!     .await?
+//!     .chunk_by_duration(100, AudioSpeechResponseFormat::Pcm);
+//! ```
+//!
+//! When the text arrives incrementally, ie. from a chat completion stream, `create_speech_from_text_stream` buffers it up to sentence boundaries and synthesizes every complete sentence. The audio of a sentence is one chunk, in the order of the text. The next sentence is synthesized while the current one plays. A failed sentence is an error item and the stream goes on.
+//!
+//! ```rust
+//! let text = client
+//!     .chat()
+//!     .create_stream(chat_parameters)
+//!     .await?
+//!     .into_text_stream()
+//!     .filter_map(|delta| async move { delta.ok() });
+//!
+//! let audio = client.audio().create_speech_from_text_stream(text, parameters);
+//! ```
+//!
+//! Sentence buffering trades latency for natural speech:
+//!
+//! - The first audio arrives only after the first sentence is complete and synthesized. A long first sentence delays playback.
+//! - Every sentence is a separate request, so short sentences add request overhead. Intonation can also break between sentences.
+//! - A sentence ends at a line break, or at `.`, `!` or `?` followed by whitespace, so a terminator at the end of the text waits for more text. Text without terminators is synthesized only when the input stream ends.
+//!
+//! Transcriptions can be streamed as well (`stream` feature). `create_transcription_stream` yields the text deltas, the segments finalized by the provider (ie. with diarization) and the full text. For captions, `create_transcription_segment_stream` also yields complete segments when the provider only sends text deltas, these are synthesized at sentence boundaries and are approximate (they have no timing).
+//!
+//! ```rust
+//! let mut stream = client.audio().create_transcription_segment_stream(parameters).await?;
+//!
+//! while let Some(event) = stream.next().await {
+//!     if let TranscriptionStreamEvent::Segment(segment) = event? {
+//!         println!("{}", segment.text);
+//!     }
+//! }
+//! ```
+//!
+//! To pipe live captions into a text sink, `create_transcription_text_reader` returns the streamed transcript as `tokio::io::AsyncRead` of UTF-8 text. The deltas are written as they arrive, a read can end within a multi-byte character.
+//!
+//! ```rust
+//! let mut reader = client.audio().create_transcription_text_reader(parameters).await?;
+//!
+//! tokio::io::copy(&mut reader, &mut tokio::io::stdout()).await?;
+//! ```
+//!
+//! Audio longer than a single request allows can be transcribed with the `long-transcription` feature. The WAV file is split into overlapping chunks (5 minutes and at most 25 MB each by default), which are transcribed concurrently and retried after rate limits or server errors. The segment and word timestamps are offset to the original audio and the overlap is removed. Compressed audio needs to be converted to WAV first.
+//!
+//! ```rust
+//! let options = LongTranscriptionOptionsBuilder::default()
+//!     .model("whisper-1")
+//!     .concurrency(4usize)
+//!     .build()?;
+//!
+//! let transcription = client.audio().transcribe_long(std::fs::read("lecture.wav")?, options).await?;
+//! ```
+//!
+//! If a chunk keeps failing, `APIError::TranscriptionChunkError` contains its time range and error.
+//!
+//! Leading and trailing silence can be trimmed before a transcription with `trim_silence` of the `audio-processing` feature, which uploads less audio. Windows of 10 ms with an RMS level below the threshold (in dBFS) are removed from the start and the end. Only WAV and raw PCM (24kHz 16-bit mono) audio is supported, compressed audio needs to be decoded first.
+//!
+//! ```rust
+//! let audio = trim_silence(&std::fs::read("recording.wav")?, AudioSpeechResponseFormat::Wav, -40.0)?;
+//! ```
+//!
+//! `probe_duration` of the `audio-processing` feature reads the duration of a WAV file from its header, without reading the audio, ie. to decide whether to use `transcribe_long` or to estimate the cost. Compressed formats (ie. mp3) have no duration in their header and return a validation error.
+//!
+//! ```rust
+//! let duration = probe_duration("recording.wav")?;
+//! ```
+//!
+//! To transcribe a batch of files, `create_transcriptions` runs a few requests at a time and returns the transcriptions in the order of the files, with the number of files that succeeded and failed. The progress callback is called after each file, ie. for a progress bar.
+//!
+//! ```rust
+//! let options = BatchTranscriptionOptions::default()
+//!     .with_progress(|completed, total| println!("{completed}/{total}"));
+//!
+//! let batch = client.audio().create_transcriptions(files, options).await;
+//!
+//! println!("{} transcribed, {} failed", batch.succeeded, batch.failed);
+//! ```
+//!
+//! Audio that is too short (OpenAI requires at least 0.1 seconds) returns `APIError::AudioTooShort` instead of a generic bad request, so these files can be skipped. The raw error is kept, `body()` returns its message.
+//!
+//! ```rust
+//! let transcribed = batch
+//!     .results
+//!     .into_iter()
+//!     .filter(|result| !matches!(result, Err(APIError::AudioTooShort { .. })));
+//! ```
 //!
 //! For more information see the examples in the [examples/audio](https://github.com/tjardoo/openai-client/tree/master/examples/audio) directory.
 //!
@@ -390,6 +752,14 @@
 //! - Retrieve model
 //! - Delete fine-tune model
 //!
+//! Models include `parent`, `root` and `permission` when the server returns them. Compatible servers often add fields that aren't part of the OpenAI model object, these are kept in `extra`. `context_length` reads the context length from the keys used by OpenRouter, vLLM and Groq. `can_use` checks whether the API key can use a model.
+//!
+//! ```rust
+//! let model = client.models().retrieve("meta-llama/Meta-Llama-3-8B-Instruct").await?;
+//!
+//! println!("{} ({:?} tokens)", model.id, model.context_length());
+//! ```
+//!
 //! More information [Models](https://platform.openai.com/docs/api-reference/models)
 //!
 //! ## Files
@@ -404,6 +774,33 @@
 //! - Retrieve file
 //! - Retrieve file content
 //!
+//! The API detects the format of an uploaded file by its filename extension. The filename defaults to the file name of the path (or the name given to `FileUploadBytes`) and the MIME type is inferred from the extension. Both can be set explicitly:
+//!
+//! ```rust
+//! let file = FileUpload::Bytes(FileUploadBytes::new(recording, "blob"))
+//!     .with_filename("recording.wav")
+//!     .with_mime_type("audio/wav");
+//! ```
+//!
+//! The extension is checked before sending: transcriptions and translations accept audio formats, image edits accept png, jpeg and webp, masks and variations accept png, and batch and fine-tuning files must end in `.jsonl`. Other extensions return `APIError::ValidationError`. The final filename and MIME type are shown in the dry-run output.
+//!
+//! In-memory uploads (`FileUpload::Bytes`) have a known length, so the request is sent with a `Content-Length`. Uploads from a path (`File` and `FilePath`) are streamed while the file is read and use chunked encoding; read the file into `FileUploadBytes` for servers that reject chunked uploads. If the file can't be read midway (ie. a disk read error), the request is aborted and `APIError::FileError` is returned.
+//!
+//! Large file contents, ie. the output of a batch, can be downloaded with progress: `retrieve_content_with_progress` returns the bytes and `download_to` writes them to a `tokio::io::AsyncWrite` as the chunks arrive. Both call the callback with the bytes downloaded so far and the `Content-Length`, if any.
+//!
+//! ```rust
+//! let mut file = tokio::fs::File::create("output.jsonl").await?;
+//!
+//! client
+//!     .files()
+//!     .download_to("file-abc123", &mut file, |downloaded, total| {
+//!         println!("{downloaded} of {total:?} bytes");
+//!     })
+//!     .await?;
+//! ```
+//!
+//! When an uploaded file has no MIME type and no known extension, it is sent as `application/octet-stream` with a warning (see `set_on_warning`), as the server may reject it. With strict warnings it is returned as `ValidationError` instead.
+//!
 //! More information [Files](https://platform.openai.com/docs/api-reference/files)
 //!
 //! ## Embeddings
@@ -414,6 +811,21 @@
 //!
 //! - Create embeddings
 //!
+//! The response is parsed straight into the typed struct and the vectors are stored as `f32`, the precision the API returns. Enable the `embeddings-f64` feature to store them as `f64`. `cargo bench --features bench --bench embeddings` compares the peak memory and latency on a large response.
+//!
+//! `embed_and_sink` embeds documents in batches and upserts the vectors with their metadata into a `VectorSink`, ie. a collection of a vector database. Batches run a few at a time and are retried after rate limits, server errors and `SinkError::Transient`. The ids of the documents of batches that still fail are returned, with an `EmbedAndSinkError` per batch that holds the `APIError` of the embedding or the `SinkError` of the upsert. The embedding and the upsert of a batch have their own retries.
+//!
+//! ```rust
+//! let options = EmbedAndSinkOptions::new("text-embedding-3-small")
+//!     .with_progress(|completed, total| println!("{completed}/{total}"));
+//!
+//! let result = client.embeddings().embed_and_sink(documents, &sink, options).await;
+//!
+//! println!("{} upserted, failed: {:?}", result.upserted, result.failed_ids);
+//! ```
+//!
+//! `MemoryVectorSink` keeps the records in memory. Implement `VectorSink` to write to a database.
+//!
 //! More information: [Embeddings](https://platform.openai.com/docs/api-reference/embeddings)
 //!
 //! ## Moderation
@@ -423,6 +835,25 @@
 //! For more information see the examples in the [examples/moderations](https://github.com/tjardoo/openai-client/tree/master/examples/moderations) directory.
 //!
 //! - Create moderation
+//! - Create batched moderation
+//!
+//! `create_batched` classifies any number of texts in requests of 32 inputs, a few at a time. Every input gets its own result, in the order of the inputs, with the model that was used. The inputs of a request that still failed after the retries get its error.
+//!
+//! ```rust
+//! let options = ModerationBatchOptionsBuilder::default()
+//!     .concurrency(2usize)
+//!     .build()?;
+//!
+//! let results = client.moderations().create_batched(comments, options).await;
+//!
+//! for (comment, result) in results.iter().enumerate() {
+//!     match result {
+//!         Ok(result) if result.result.flagged => println!("comment {comment} flagged by {}", result.model),
+//!         Ok(_) => {}
+//!         Err(error) => println!("comment {comment} not classified: {error}"),
+//!     }
+//! }
+//! ```
 //!
 //! More information [Moderation](https://platform.openai.com/docs/api-reference/moderations)
 //!
@@ -453,6 +884,35 @@
 //! - Cancel fine-tuning job
 //! - List fine-tuning events
 //! - List fine-tuning checkpoints
+//! - Create fine-tuning job from files
+//!
+//! `create_from_files` uploads the training file and the optional validation file with purpose `fine-tune`, waits until both are processed and creates the job. It returns the job with the IDs of the uploaded files. Before anything is uploaded every line is checked to be a JSON object, the lines that are not are listed in a `ValidationError`. Disable this with `validate_lines(false)`.
+//!
+//! ```rust
+//! let files = CreateFromFilesBuilder::default()
+//!     .training(FileUpload::File("./train.jsonl".to_string()))
+//!     .validation(FileUpload::File("./validation.jsonl".to_string()))
+//!     .base_model("gpt-4o-mini")
+//!     .suffix("support")
+//!     .timeout(Duration::from_secs(300))
+//!     .build()?;
+//!
+//! let created = client.fine_tuning().create_from_files(files).await?;
+//!
+//! println!("{} trains on {}", created.job.id, created.training_file_id);
+//! ```
+//!
+//! To build a chat dataset, convert the messages into `FineTuneMessage` and write every `FineTuneExample` with `to_jsonl_line`. `with_weight(0)` excludes an assistant message from training, the check before uploading rejects weights other than 0 and 1 and weights on other messages. `estimate_training_tokens` estimates the tokens trained on, without the messages of weight 0.
+//!
+//! ```rust
+//! let example = FineTuneExample::new(vec![
+//!     ChatMessage::User { content: ChatMessageContent::Text("Hi".to_string()), name: None }.into(),
+//!     FineTuneMessage::from(draft_reply).with_weight(0),
+//!     FineTuneMessage::from(final_reply).with_weight(1),
+//! ]);
+//!
+//! writeln!(file, "{}", example.to_jsonl_line())?;
+//! ```
 //!
 //! More information [Fine-tuning](https://platform.openai.com/docs/api-reference/fine-tuning)
 //!
@@ -466,6 +926,40 @@
 //! - List batches
 //! - Retrieve batch
 //! - Cancel batch
+//! - Failed requests (parsed error file)
+//! - Resubmit failed requests
+//!
+//! Failed requests can be resubmitted as a new batch, taking the original requests from the input file (or a lookup closure). The new batch links to the original batch via the `resubmitted_from` metadata.
+//!
+//! ```rust
+//! let batch = client.batches().retrieve("batch_abc123").await?;
+//!
+//! let resubmitted = client
+//!     .batches()
+//!     .resubmit_failed(&batch.id, OriginalRequests::InputFile(batch.input_file_id.clone()))
+//!     .await?;
+//!
+//! println!("resubmitted {} requests as {}", resubmitted.resubmitted_custom_ids.len(), resubmitted.batch.id);
+//! ```
+//!
+//! The output file of a batch can be downloaded with progress: `output_with_progress` returns the bytes and `download_output_to` writes them to a `tokio::io::AsyncWrite` as the chunks arrive. A batch without an output file returns `APIError::BadRequestError`.
+//!
+//! ```rust
+//! let mut file = tokio::fs::File::create("batch_output.jsonl").await?;
+//!
+//! client
+//!     .batches()
+//!     .download_output_to("batch_abc123", &mut file, |downloaded, total| {
+//!         println!("{downloaded} of {total:?} bytes");
+//!     })
+//!     .await?;
+//! ```
+//!
+//! The metadata of batches, chat completions, responses, fine-tuning jobs and vector stores is a `Metadata`: at most 16 keys of up to 64 characters, with values of up to 512 characters. `Metadata::try_from` (of a `HashMap` or an array of pairs) and `Metadata::insert` return a `ValidationError` naming the offending key, the metadata returned by the API isn't checked.
+//!
+//! ```rust
+//! let metadata = Metadata::try_from([("project", "demo"), ("owner", "data-team")])?;
+//! ```
 //!
 //! More information [Batch](https://platform.openai.com/docs/api-reference/batch)
 //!
@@ -510,13 +1004,27 @@
 //!
 //! Communicate with a GPT-4o class model live, in real time, over WebSocket. Produces both audio and text transcriptions.
 //!
-//! Enable the feature flag `realtime` to use this feature.
+//! Enable the feature flag `realtime` to use this feature. Without it, `audio_session` returns `APIError::FeatureDisabled`.
 //!
 //! For more information see the examples in the [examples/realtime](https://github.com/tjardoo/openai-client/tree/master/examples/realtime) directory.
 //!
 //! - All client events
 //! - All server events
 //!
+//! The experimental `RealtimeAudioSession` covers the audio part of the API. It reuses the API key, base URL and headers of the client, sends audio with `send_audio` and returns the server events, such as audio and transcription deltas, as `RealtimeEvent`s.
+//!
+//! ```rust
+//! let mut session = client.realtime().audio_session("gpt-realtime").await?;
+//!
+//! session.send_audio(audio_bytes).await?;
+//!
+//! while let Some(event) = session.next_event().await {
+//!     if let RealtimeEvent::AudioTranscriptDelta(delta) = event? {
+//!         print!("{}", delta.delta);
+//!     }
+//! }
+//! ```
+//!
 //! More information [Realtime](https://platform.openai.com/docs/api-reference/realtime)
 //!
 //! ## Configuration
@@ -536,6 +1044,8 @@
 //! export OPENAI_API_KEY='sk-...'
 //! ```
 //!
+//! To catch an obviously malformed key (ie. a missing `sk-` prefix or a truncated key) without a round trip, use `Client::validate_key_format`, or `Client::try_new_from_env`, which returns an error instead of panicking and enables strict key validation. With `set_strict_key_validation(true)` the key is checked before each request. When a custom base URL is set, any key without whitespace is accepted.
+//!
 //! ### Using OpenAI-compatible APIs
 //!
 //! By simply changing the base URL, you can use this crate with other OpenAI-compatible APIs.
@@ -547,10 +1057,115 @@
 //! client.set_base_url("https://api.deepseek.com");
 //! ```
 //!
+//! Providers that serve a route at another path can override the path per endpoint, instead of running a reverse proxy. `Endpoint` has a variant for every route the crate calls. The IDs of a request fill the placeholders in braces, in order. The query string is kept. The realtime websocket URL is overridden in the same way (`Endpoint::Realtime`). The dry-run output shows the final URL.
+//!
+//! ```rust
+//! use openai_dive::v1::paths::Endpoint;
+//!
+//! client
+//!     .set_base_url("https://llm.example.com")
+//!     .set_path_override(Endpoint::ChatCompletions, "/api/chat/completions")
+//!     .set_path_override(Endpoint::AudioTranscriptions, "/v1/audio/transcribe")
+//!     .set_path_override(Endpoint::FileContent, "/api/files/{id}/download");
+//! ```
+//!
 //! Use `extra_body` in `ChatCompletionParameters` to pass non-standard parameters supported by OpenAI-compatible APIs.
 //!
+//! Multipart requests (ie. transcriptions) send each key of `extra_body` as a form field, a nested object as JSON in one field. For servers that parse nested fields in bracket notation, enable it on the client and `{"vad": {"threshold": 0.5}}` is sent as `vad[threshold]=0.5`. Arrays of values are sent as repeated `key[]` fields.
+//!
+//! ```rust
+//! client.set_bracket_form_fields(true);
+//! ```
+//!
+//! By default the file parts of multipart requests are sent before the text fields, like the OpenAI SDKs do. For servers that need the text fields first, send the files last. The order of the file parts among themselves, and of the text fields among themselves, is kept.
+//!
+//! ```rust
+//! client.set_multipart_order(MultipartOrder::FileLast);
+//! ```
+//!
 //! Use `query_params` in `ChatCompletionParameters` to pass non-standard `query` parameters supported by OpenAI-compatible APIs.
 //!
+//! Some compatible transcription providers fetch the audio from a URL instead of an upload. Use `FileUpload::Url` and enable it on the client, the transcription is then sent as JSON with a `file_url` field. OpenAI doesn't support this, so without the flag a validation error is returned.
+//!
+//! ```rust
+//! client.set_allow_file_urls(true);
+//!
+//! let parameters = AudioTranscriptionParametersBuilder::default()
+//!     .file(FileUpload::Url("https://example.com/audio.mp3".to_string()))
+//!     .model("whisper-large-v3")
+//!     .build()?;
+//! ```
+//!
+//! Transcription providers that aren't OpenAI-compatible return other JSON shapes. Attach a `ResponseAdapter` to the audio endpoint to convert the JSON responses of transcriptions and translations into the OpenAI shape. `CloudflareAdapter` (Workers AI) and `DeepgramAdapter` are included, `FieldRenameAdapter` renames fields (ie. `result` to `text`), or implement the trait for your provider.
+//!
+//! ```rust
+//! use openai_dive::v1::adapters::{CloudflareAdapter, FieldRenameAdapter};
+//!
+//! let transcription = client
+//!     .audio()
+//!     .with_adapter(CloudflareAdapter)
+//!     .create_transcription(parameters)
+//!     .await?;
+//!
+//! let adapter = FieldRenameAdapter::new([("result", "text")]);
+//! ```
+//!
+//! The other way around, request converters change the JSON body of raw requests (ie. speech) into the shape a provider expects. Converters added with `add_request_converter` are applied in the order they were added, so reusable converters can be layered: `CamelCaseKeys` converts the keys to camelCase, `FieldRenameAdapter` renames fields, or implement `RequestConverter` for your provider.
+//!
+//! ```rust
+//! use openai_dive::v1::adapters::{CamelCaseKeys, FieldRenameAdapter};
+//!
+//! client
+//!     .add_request_converter(CamelCaseKeys)
+//!     .add_request_converter(FieldRenameAdapter::new([("input", "text")]));
+//! ```
+//!
+//! Converters don't change multipart forms. `with_field_name_map` renames the multipart fields of transcriptions and translations (`file`, `model`, `prompt`, ...) for providers that expect other names. Fields that aren't in the map keep their OpenAI name.
+//!
+//! ```rust
+//! let response = client
+//!     .audio()
+//!     .with_field_name_map(HashMap::from([("file", "audio")]))
+//!     .create_transcription(parameters)
+//!     .await?;
+//! ```
+//!
+//! Some compatible servers return numbers as strings (ie. `"created": "1715000000"` or string token counts) and booleans as 0/1. These are accepted for the timestamps, indexes, token usage and flags of the response types, the standard representation is always used when serializing.
+//!
+//! Compatible servers return errors in different shapes. Use `APIError::body` to parse the error message from the OpenAI shape, the shapes returned by vLLM and gateways, or the plain text body. The `shape` field shows which one matched.
+//!
+//! ```rust
+//! if let Err(error) = client.chat().create(parameters).await {
+//!     if let Some(body) = error.body() {
+//!         println!("{:?}: {}", body.shape, body.message);
+//!     }
+//! }
+//! ```
+//!
+//! An error that names the offending parameter (ie. `"param": "messages[1].content"`, or the location of a vLLM validation error) has it in `param`, `APIError::param` returns it directly. `ErrorBody::suggested_field` returns the field of the parameters and their builder to fix, `messages` in this case.
+//!
+//! The `x-request-id` of a failed response is returned by `APIError::request_id`, the errors of a response have it in their `request_id` field and the error message ends with it, ie. `Invalid model (request id: req_123)`. Quote it when contacting support. Successful responses have it in `headers.x_request_id` of the wrapped response (ie. `create_wrapped`). For a stream, `StreamMetadata::request_id` of `create_stream_with_metadata` returns it as soon as the headers arrive, before the first chunk. With the `tracing` feature each request (and the request of a stream) runs in an `openai_dive.request` span, which records the `method`, `path`, `status` and `request_id`.
+//!
+//! Local servers (ie. LM Studio or Ollama) ignore or reject some parameters. `detect_capabilities` lists the models and probes the first one with two chat completions of a few tokens, one with a tool, an image and log probabilities and a stream with the usage and a json_schema response format, and caches the result on the client. A feature counts as supported only when the response shows it was used (ie. the tool was called); when a probe is rejected it is sent once more without the features the error names, and a feature that wasn't tested counts as unsupported. The OpenAI API isn't probed. With `set_degrade_gracefully(true)` chat completions leave out the tools, json_schema response format, stream options and log probabilities the backend doesn't support, with an `UnsupportedParameter` warning, instead of failing. Set the capabilities with `set_backend_capabilities` to skip the probe.
+//!
+//! ```rust
+//! use openai_dive::v1::backend::BackendCapabilities;
+//!
+//! let capabilities = client.detect_capabilities().await?;
+//!
+//! if !capabilities.supports_tools {
+//!     println!("the backend doesn't call tools");
+//! }
+//!
+//! client.set_degrade_gracefully(true);
+//!
+//! // or without probing
+//! client.set_backend_capabilities(BackendCapabilities {
+//!     supports_logprobs: false,
+//!     ..BackendCapabilities::all()
+//! });
+//! ```
+//!
 //! ### Set organization/project ID
 //!
 //! You can create multiple organizations and projects in the OpenAI platform. This allows you to group files, fine-tuned models and other resources.
@@ -565,6 +1180,34 @@
 //!     .set_project("proj_XXX");
 //! ```
 //!
+//! `Client::new_from_env` also reads the organization and project ID from the `OPENAI_ORG_ID` and `OPENAI_PROJECT_ID` environment variables. To use a different project for a single call, use `with_project` (or `with_organization`), which returns a copy of the client. The organization and project that served a request are returned in the response headers.
+//!
+//! ```rust
+//! let result = client
+//!     .with_project("proj_YYY")
+//!     .chat()
+//!     .create_wrapped(parameters)
+//!     .await?;
+//!
+//! println!("{:?}", result.headers.openai_project);
+//! ```
+//!
+//! ### Set Accept-Language
+//!
+//! Some OpenAI compatible providers localize their error messages according to the `Accept-Language` header, which can be set with `set_accept_language`. OpenAI ignores the header.
+//!
+//! ```rust
+//! client.set_accept_language("de-DE, de;q=0.9");
+//! ```
+//!
+//! ### Log the configuration
+//!
+//! `config_summary` returns the effective configuration of the client, ie. to log it at startup: the base URL, whether it is the OpenAI API, the timeouts, default models and other settings. The API key is redacted and only the names of custom headers are included.
+//!
+//! ```rust
+//! log::info!("{:#?}", client.config_summary());
+//! ```
+//!
 //! ### Add proxy
 //!
 //! This crate uses `reqwest` as HTTP Client. Reqwest has proxies enabled by default. You can set the proxy via the system environment variable or by overriding the default client.
@@ -592,12 +1235,237 @@
 //!     http_client,
 //!     base_url: "https://api.openai.com/v1".to_string(),
 //!     api_key,
-//!     headers: None,
-//!     organization: None,
-//!     project: None,
+//!     ..Default::default()
 //! };
 //! ```
 //!
+//! ### Redirects
+//!
+//! The default HTTP client follows at most 5 redirects and refuses to redirect from HTTPS to HTTP. When a redirect changes the host, port or scheme, the `Authorization` header is removed, so the API key is not sent to another origin. Gateways that redirect to a different host will then return an authentication error, instead of receiving your API key.
+//!
+//! You can override the policy, ie. to disable redirects completely:
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_redirect_policy(reqwest::redirect::Policy::none());
+//! ```
+//!
+//! Note that this replaces the HTTP client, when overriding the default client, set the policy on its builder instead.
+//!
+//! ### Client certificates and pinned certificates
+//!
+//! Private endpoints that require mutual TLS get a client certificate with `set_identity_pem`, a PEM file with the private key and the certificate chain. With `set_pinned_certificate` only the given DER certificates (ie. the CA of the endpoint) are trusted, instead of the system roots. Both replace the HTTP client and are also used by the Realtime WebSocket. They need the `rustls-tls` feature, which is enabled by default.
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client
+//!     .set_identity_pem(&std::fs::read("client.pem")?)?
+//!     .set_pinned_certificate(&std::fs::read("ca.der")?)?;
+//! ```
+//!
+//! PKCS#12 identities are not supported, they need native-tls while this crate uses rustls. A PKCS#12 bundle can be converted to PEM with `openssl pkcs12 -in client.p12 -out client.pem -nodes`. When the handshake fails, ie. the server certificate is not signed by a pinned certificate or the server rejects the client certificate, requests return `APIError::TlsError` with the underlying error.
+//!
+//! ### Warm up the connection
+//!
+//! For latency-critical first calls, you can open the connection to the base URL up front. This is best-effort and depends on the connection pool of the HTTP client.
+//!
+//! ```rust
+//! let client = Client::new_from_env();
+//!
+//! client.warmup().await?;
+//! ```
+//!
+//! ### Validate parameters
+//!
+//! By default, request parameters are sent to the API as-is. You can set a validation profile to check sampling parameters such as `temperature`, `top_p` and the penalties locally. Invalid values are returned as `APIError::ValidationError`.
+//!
+//! ```rust
+//! use openai_dive::v1::validation::{ParameterRanges, ValidationProfile};
+//!
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_validation_profile(ValidationProfile::OpenAI);
+//!
+//! // or use your own ranges for OpenAI-compatible providers
+//! client.set_validation_profile(ValidationProfile::Custom(ParameterRanges {
+//!     temperature: Some(0.0..=1.0),
+//!     ..Default::default()
+//! }));
+//! ```
+//!
+//! The `OpenAI` profile also rejects sampling parameters for o-series models (ie. `o3-mini`), and more than 4 `stop` sequences.
+//!
+//! Transcription requests are always checked against their model family before they are sent. For example, `gpt-4o-transcribe` only returns `json` and doesn't accept `timestamp_granularities`, and `include` (logprobs) is only accepted by the gpt-4o models. The `ValidationError` lists all incompatible fields. The rules are in `TRANSCRIPTION_MODEL_RULES`, and unknown models are passed through. For providers with other rules for the same model names, set `skip_validation` on the parameters.
+//!
+//! OpenAI truncates transcription prompts of more than 224 tokens without an error. A prompt that likely exceeds the limit (estimated at 4 characters per token) causes a warning, which is passed to the `on_warning` handler or logged (`log` feature). With strict warnings it is returned as `ValidationError` instead.
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_on_warning(|warning| eprintln!("{warning}"));
+//!
+//! // or fail the request
+//! client.set_strict_warnings(true);
+//!
+//! // another limit, or None to disable the check
+//! client.set_transcription_prompt_limit(Some(448));
+//! ```
+//!
+//! ### Redact logs and errors
+//!
+//! Response bodies are included in the `log` output and in error messages. The API key is always redacted, you can set a redactor to remove other sensitive content (ie. PII in transcriptions).
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_redactor(|text| text.replace("John Doe", "[NAME]"));
+//! ```
+//!
+//! ### End user ID
+//!
+//! Set an end user provider to send the ID of the user behind a request as `safety_identifier`, which OpenAI uses for abuse monitoring. The closure is called for every chat completion and response request, so it can read the user from a task local. The ID is sent as SHA-256 hash, disable this with `set_hash_end_user(false)` if the IDs are already pseudonymous. A `safety_identifier` set on the parameters takes precedence.
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_end_user(|| CURRENT_USER.try_with(|user| user.id.clone()).ok());
+//! ```
+//!
+//! ### Sign requests
+//!
+//! For providers that require a signature (ie. HMAC or SigV4) over the request, set a request signer. It is called with the final request, right before it is sent, so it can read the method, URL and body and attach headers. Multipart bodies are buffered in memory when a signer is set, so the signer can read them.
+//!
+//! ```rust
+//! let mut client = Client::new_from_env();
+//!
+//! client.set_request_signer(|request| {
+//!     let body = request.body().and_then(|body| body.as_bytes()).unwrap_or_default();
+//!     let signature = sign(request.method().as_str(), request.url().path(), body);
+//!
+//!     request.headers_mut().insert("x-signature", signature.parse().unwrap());
+//! });
+//! ```
+//!
+//! ### Dry run
+//!
+//! Use `dry_run` to see the exact request the client would send, without sending it. The request is built by the same code path as real requests and the authorization header is redacted. This can also be used to generate the request bodies of a batch from typed parameters.
+//!
+//! ```rust
+//! let prepared = client
+//!     .dry_run()
+//!     .prepare(|client| async move { client.chat().create(parameters).await })
+//!     .await?;
+//!
+//! println!("{} {}", prepared.method, prepared.url);
+//!
+//! if let PreparedBody::Json(body) = prepared.body {
+//!     println!("{body}");
+//! }
+//! ```
+//!
+//! Multipart requests (ie. audio transcriptions) return a description of each form part instead of a JSON body.
+//!
+//! ### Token budget
+//!
+//! When the tokens-per-minute limit is reached before the request limit, share a `TokenBudget` across tasks. `acquire` waits until the estimated tokens are available, `settle` corrects the estimate with the usage of the response. A request that is larger than the whole budget waits until the budget is full and then goes through alone (with a warning in the `log` output). `utilization` returns the share of the budget in use, ie. for a dashboard.
+//!
+//! ```rust
+//! let budget = TokenBudget::new(200_000);
+//!
+//! let reservation = budget.acquire(estimate_tokens(&prompt) + 500).await;
+//! let response = client.chat().create(parameters).await?;
+//!
+//! reservation.settle(response.usage.map(|usage| usage.total_tokens).unwrap_or_default());
+//! ```
+//!
+//! Set the budget on the client to acquire it for every chat completion and embedding request. The estimate is about 4 characters per token, it's corrected with the usage of the response (streams keep the estimate).
+//!
+//! ```rust
+//! client.set_token_budget(budget.clone());
+//! ```
+//!
+//! A rate limit error has the wait of its `retry-after-ms`, `retry-after` or `x-ratelimit-reset-*` headers (ie. `6m12s`), and which limit was reached, in the `backpressure` field of `APIError::RateLimitError` (`APIError::backpressure` also looks into the source of a chunk or voice chat error). The retries of `transcribe_long`, `embed_and_sink` and `create_batched` wait for it instead of the exponential backoff. The client throttles itself: all requests of the client and its clones wait until the backpressure has passed, and its token budget is paused (`TokenBudget::pause`), so concurrent requests slow down as well. Your own scheduler can do the same.
+//!
+//! ```rust
+//! if let Some(backpressure) = error.backpressure() {
+//!     println!("{:?} limit reached, retry in {:?}", backpressure.scope, backpressure.wait);
+//! }
+//! ```
+//!
+//! ### Prompt caching lint
+//!
+//! Prompt caching only applies to a byte-identical prefix of at least 1024 tokens, the serialized tools followed by the messages. `cache_lint` reports the estimated tokens of the static prefix, the messages before the first content that likely changes between requests (a timestamp, date, time or UUID) and that content. `prefix_divergence` compares two requests and returns the byte offset and text where their prefixes differ; a request that extends the other, ie. the next turn of a conversation, doesn't diverge.
+//!
+//! ```rust
+//! use openai_dive::v1::cache_lint::{cache_lint, prefix_divergence};
+//!
+//! let report = cache_lint(&parameters);
+//!
+//! if !report.is_cacheable() {
+//!     println!("{:?} ends the static prefix", report.first_dynamic);
+//! }
+//!
+//! if let Some(divergence) = prefix_divergence(&previous, &parameters) {
+//!     println!("{:?} became {:?}", divergence.left, divergence.right);
+//! }
+//! ```
+//!
+//! For debugging, `set_cache_lint(true)` compares every chat completion with the previous one to the same model and warns with `ValidationWarning::CachePrefixDiverged` when they differ before the 1024-token mark. With `set_strict_warnings` the request fails instead.
+//!
+//! ### Custom clock
+//!
+//! The retry backoff and the timeouts use the `Clock` of the client, the wall clock by default. The `testing` feature adds `MockClock`, which only moves forward when it is advanced, so tests of retries and timeouts run without waiting. `MockClock::auto_advancing` ends every sleep right away.
+//!
+//! ```rust
+//! let clock = MockClock::auto_advancing();
+//!
+//! client.set_clock(clock.clone());
+//!
+//! // ...
+//!
+//! assert_eq!(clock.sleeps(), vec![Duration::from_secs(1), Duration::from_secs(2)]);
+//! ```
+//!
+//! ### Endpoint traits
+//!
+//! The chat, embeddings, audio, moderation and models endpoints implement the object-safe traits `ChatApi`, `EmbeddingsApi`, `AudioApi`, `ModerationsApi` and `ModelsApi`, so services can depend on a trait object and swap in a test double or a decorator (ie. a cache). `Client::chat_api` and the other accessors return an `Arc<dyn ChatApi>` with a clone of the client. With the `stream` feature `ChatStreamApi` adds `create_stream`, `Client::chat_stream_api` returns it. The methods return boxed futures (`ApiFuture` of the `future` module, which `ConversationStore` and `VectorSink` return as well).
+//!
+//! ```rust
+//! struct CachingChat {
+//!     inner: Arc<dyn ChatApi>,
+//!     cache: Mutex<HashMap<String, ChatCompletionResponse>>,
+//! }
+//!
+//! impl ChatApi for CachingChat {
+//!     fn create(&self, parameters: ChatCompletionParameters) -> ApiFuture<'_, ChatCompletionResponse> {
+//!         Box::pin(async move {
+//!             // look up the cache, or call self.inner.create(parameters).await
+//!         })
+//!     }
+//! }
+//!
+//! let chat: Arc<dyn ChatApi> = Arc::new(CachingChat { inner: client.chat_api(), cache: Mutex::default() });
+//! ```
+//!
+//! ### Optional features
+//!
+//! The heavier optional features keep their types and methods when they are disabled, so a crate that depends on openai_dive can offer degraded behavior without `cfg` attributes of its own. These methods return `APIError::FeatureDisabled` with the name of the feature instead of doing the work.
+//!
+//! - `image-processing`: `ChatMessageContentPart::image_auto`
+//! - `audio-processing`: `trim_silence` and `probe_duration`
+//! - `realtime`: `client.realtime().audio_session` and the methods of `RealtimeAudioSession` (`Realtime::websocket` returns a type of the WebSocket crate and only exists with the feature)
+//!
+//! The methods of the other features (ie. `stream`, `download` and `long-transcription`) only exist when the feature is enabled, as their signatures use types of the optional dependencies.
+//!
+//! ```rust
+//! match ChatMessageContentPart::image_auto(&bytes, ImagePolicy::default()) {
+//!     Err(APIError::FeatureDisabled(_)) => { /* send the image by URL instead */ }
+//!     result => parts.push(result?.part),
+//! }
+//! ```
+//!
 //! ### Available Models
 //!
 //! You can use these predefined constants to set the model in the parameters or use any string representation (ie. for your custom models).
@@ -0,0 +1,284 @@
+//! Conversions of provider specific responses into the shape of the OpenAI responses, ie. for transcriptions of compatible providers.
+use crate::v1::error::APIError;
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+
+/// Converts the JSON response of a provider into the OpenAI response.
+/// Attach it with `Audio::with_adapter`, ie. `client.audio().with_adapter(CloudflareAdapter)`.
+pub trait ResponseAdapter: Debug + Send + Sync {
+    fn to_openai(&self, raw: Value) -> Result<Value, APIError>;
+}
+
+/// Converts the JSON body of a request into the shape a provider expects, the counterpart of `ResponseAdapter`.
+/// Add it with `Client::add_request_converter`, the converters are applied in the order they were added.
+pub trait RequestConverter: Debug + Send + Sync {
+    fn to_provider(&self, body: Value) -> Result<Value, APIError>;
+}
+
+/// Renames top level fields, ie. `{"result": "..."}` to `{"text": "..."}`. Other fields are kept.
+/// As `RequestConverter` it renames the fields of the request body.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldRenameAdapter {
+    renames: Vec<(String, String)>,
+}
+
+impl FieldRenameAdapter {
+    /// Renames the fields from the first to the second name of each pair.
+    pub fn new<I, F, T>(renames: I) -> Self
+    where
+        I: IntoIterator<Item = (F, T)>,
+        F: Into<String>,
+        T: Into<String>,
+    {
+        Self {
+            renames: renames
+                .into_iter()
+                .map(|(from, to)| (from.into(), to.into()))
+                .collect(),
+        }
+    }
+
+    fn rename(&self, mut map: Map<String, Value>) -> Value {
+        for (from, to) in &self.renames {
+            if let Some(value) = map.remove(from) {
+                map.insert(to.clone(), value);
+            }
+        }
+
+        Value::Object(map)
+    }
+}
+
+impl ResponseAdapter for FieldRenameAdapter {
+    fn to_openai(&self, raw: Value) -> Result<Value, APIError> {
+        let Value::Object(map) = raw else {
+            return Err(unexpected_shape("FieldRenameAdapter", "an object", &raw));
+        };
+
+        Ok(self.rename(map))
+    }
+}
+
+impl RequestConverter for FieldRenameAdapter {
+    fn to_provider(&self, body: Value) -> Result<Value, APIError> {
+        let Value::Object(map) = body else {
+            return Err(unexpected_request("FieldRenameAdapter", &body));
+        };
+
+        Ok(self.rename(map))
+    }
+}
+
+/// Converts the top level fields of the request body from snake_case to camelCase, ie. `response_format` to `responseFormat`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CamelCaseKeys;
+
+impl RequestConverter for CamelCaseKeys {
+    fn to_provider(&self, body: Value) -> Result<Value, APIError> {
+        let Value::Object(map) = body else {
+            return Err(unexpected_request("CamelCaseKeys", &body));
+        };
+
+        Ok(Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (camel_case(&key), value))
+                .collect(),
+        ))
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut words = key.split('_');
+    let mut converted = words.next().unwrap_or_default().to_string();
+
+    for word in words {
+        let mut characters = word.chars();
+
+        if let Some(first) = characters.next() {
+            converted.extend(first.to_uppercase());
+            converted.push_str(characters.as_str());
+        }
+    }
+
+    converted
+}
+
+/// Workers AI responses of Cloudflare, which wrap the transcription in `{"result": ..., "success": true}`.
+/// An unsuccessful response is returned as `BadRequestError` with the first error message.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CloudflareAdapter;
+
+impl ResponseAdapter for CloudflareAdapter {
+    fn to_openai(&self, raw: Value) -> Result<Value, APIError> {
+        if raw["success"] == Value::Bool(false) {
+            let message = raw["errors"][0]["message"]
+                .as_str()
+                .unwrap_or("unknown error");
+
+            return Err(APIError::BadRequestError {
+                message: message.to_string(),
+                request_id: None,
+            });
+        }
+
+        match raw.get("result") {
+            Some(Value::String(text)) => Ok(text_object(text)),
+            Some(Value::Object(result)) => Ok(Value::Object(result.clone())),
+            _ => Err(unexpected_shape("CloudflareAdapter", "a result", &raw)),
+        }
+    }
+}
+
+/// Deepgram responses, the transcript and words of the first alternative of the first channel.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DeepgramAdapter;
+
+impl ResponseAdapter for DeepgramAdapter {
+    fn to_openai(&self, raw: Value) -> Result<Value, APIError> {
+        let channel = &raw["results"]["channels"][0];
+        let alternative = &channel["alternatives"][0];
+
+        let Some(transcript) = alternative["transcript"].as_str() else {
+            return Err(unexpected_shape("DeepgramAdapter", "a transcript", &raw));
+        };
+
+        let mut converted = text_object(transcript);
+
+        if let Some(duration) = raw["metadata"].get("duration") {
+            converted["duration"] = duration.clone();
+        }
+
+        if let Some(language) = channel.get("detected_language") {
+            converted["language"] = language.clone();
+        }
+
+        if let Some(Value::Array(words)) = alternative.get("words") {
+            converted["words"] = words
+                .iter()
+                .map(|word| {
+                    Value::Object(Map::from_iter(["word", "start", "end"].map(|key| {
+                        (key.to_string(), word.get(key).cloned().unwrap_or_default())
+                    })))
+                })
+                .collect();
+        }
+
+        Ok(converted)
+    }
+}
+
+fn text_object(text: &str) -> Value {
+    Value::Object(Map::from_iter([(
+        "text".to_string(),
+        Value::String(text.to_string()),
+    )]))
+}
+
+fn unexpected_shape(adapter: &str, expected: &str, raw: &Value) -> APIError {
+    APIError::ParseError(format!(
+        "{adapter} expected {expected} in the response: {raw}"
+    ))
+}
+
+fn unexpected_request(converter: &str, body: &Value) -> APIError {
+    APIError::ParseError(format!(
+        "{converter} expected an object as request body: {body}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::adapters::{
+        CloudflareAdapter, DeepgramAdapter, FieldRenameAdapter, ResponseAdapter,
+    };
+    use crate::v1::error::APIError;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_rename_adapter() {
+        let adapter = FieldRenameAdapter::new([("result", "text"), ("lang", "language")]);
+
+        assert_eq!(
+            adapter
+                .to_openai(json!({"result": "Hello!", "lang": "en", "duration": 1.5}))
+                .unwrap(),
+            json!({"text": "Hello!", "language": "en", "duration": 1.5})
+        );
+        assert!(matches!(
+            adapter.to_openai(json!("Hello!")),
+            Err(APIError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_cloudflare_adapter() {
+        let converted = CloudflareAdapter
+            .to_openai(json!({
+                "result": {"text": "Hello!", "word_count": 1, "words": [{"word": "Hello!", "start": 0.0, "end": 0.5}]},
+                "success": true,
+                "errors": [],
+                "messages": []
+            }))
+            .unwrap();
+
+        assert_eq!(converted["text"], "Hello!");
+        assert_eq!(converted["words"][0]["end"], 0.5);
+
+        assert_eq!(
+            CloudflareAdapter
+                .to_openai(json!({"result": "Hello!", "success": true}))
+                .unwrap(),
+            json!({"text": "Hello!"})
+        );
+
+        let error = CloudflareAdapter
+            .to_openai(json!({"result": null, "success": false, "errors": [{"code": 5006, "message": "invalid audio"}]}))
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            APIError::BadRequestError {
+                message: "invalid audio".to_string(),
+                request_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deepgram_adapter() {
+        let converted = DeepgramAdapter
+            .to_openai(json!({
+                "metadata": {"request_id": "a1b2", "duration": 2.25, "channels": 1},
+                "results": {"channels": [{
+                    "detected_language": "en",
+                    "alternatives": [{
+                        "transcript": "hello world",
+                        "confidence": 0.99,
+                        "words": [
+                            {"word": "hello", "start": 0.1, "end": 0.5, "confidence": 0.99},
+                            {"word": "world", "start": 0.6, "end": 1.0, "confidence": 0.98}
+                        ]
+                    }]
+                }]}
+            }))
+            .unwrap();
+
+        assert_eq!(
+            converted,
+            json!({
+                "text": "hello world",
+                "duration": 2.25,
+                "language": "en",
+                "words": [
+                    {"word": "hello", "start": 0.1, "end": 0.5},
+                    {"word": "world", "start": 0.6, "end": 1.0}
+                ]
+            })
+        );
+
+        assert!(matches!(
+            DeepgramAdapter.to_openai(json!({"results": {}})),
+            Err(APIError::ParseError(_))
+        ));
+    }
+}
@@ -1,22 +1,44 @@
-use crate::v1::helpers::check_status_code;
+use crate::v1::adapters::RequestConverter;
+use crate::v1::backend::BackendCapabilities;
+use crate::v1::cache_lint::{PromptLayout, MIN_CACHED_PREFIX_TOKENS};
+use crate::v1::clock::{Clock, SystemClock};
+use crate::v1::dry_run::DryRun;
+#[cfg(feature = "stream")]
+use crate::v1::error::{StreamError, TransportError};
+use crate::v1::helpers::{check_status_code, send_error, MultipartForm};
+use crate::v1::paths::{Endpoint, PathOverrides};
+use crate::v1::resources::audio::{
+    AudioOutputFormat, AudioSpeechParameters, AudioSpeechResponseFormat, SpeechVoice,
+};
+use crate::v1::resources::chat::ChatCompletionParameters;
+use crate::v1::resources::model::ModelCapabilityCache;
+use crate::v1::resources::shared::MultipartOrder;
+use crate::v1::token_budget::{TokenBudget, TokenReservation};
+use crate::v1::validation::{
+    ValidationProfile, ValidationWarning, MAX_TRANSCRIPTION_PROMPT_TOKENS,
+};
 use crate::v1::{error::APIError, resources::shared::Headers};
 use bytes::Bytes;
 #[cfg(feature = "stream")]
 use futures::{stream::StreamExt, Stream};
 use reqwest::{multipart::Form, Method, RequestBuilder};
 #[cfg(feature = "stream")]
-use aha_reqwest_eventsource::{Event, EventSource, RequestBuilderExt};
-#[cfg(feature = "stream")]
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 #[cfg(feature = "stream")]
 use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use super::resources::shared::ResponseWrapper;
+#[cfg(feature = "stream")]
+use super::resources::shared::StreamMetadata;
 
 const OPENAI_API_V1_ENDPOINT: &str = "https://api.openai.com/v1";
 const MIME_TYPE_APPLICATION_JSON: &str = "application/json";
+const REDACTED: &str = "[REDACTED]";
+const MAX_REDIRECTS: usize = 5;
 
 #[derive(Clone, Debug)]
 pub struct Client {
@@ -26,6 +48,162 @@ pub struct Client {
     pub headers: Option<HashMap<String, String>>,
     pub organization: Option<String>,
     pub project: Option<String>,
+    /// Sent as Accept-Language header, for providers that localize their error messages.
+    pub accept_language: Option<String>,
+    pub validation_profile: ValidationProfile,
+    pub redactor: Option<Redactor>,
+    pub request_signer: Option<RequestSigner>,
+    pub default_transcription_format: Option<AudioOutputFormat>,
+    /// The models used when the parameters of a request leave the model empty.
+    pub default_speech_model: Option<String>,
+    pub default_transcription_model: Option<String>,
+    pub default_translation_model: Option<String>,
+    /// The voice and format of speech requests whose parameters leave them unset.
+    pub default_speech_voice: Option<SpeechVoice>,
+    pub default_speech_format: Option<AudioSpeechResponseFormat>,
+    pub first_byte_timeout: Option<Duration>,
+    /// Whether `FileUpload::Url` may be sent, for compatible providers that fetch files remotely.
+    pub allow_file_urls: bool,
+    /// Whether nested objects in the `extra_body` of multipart requests are sent in bracket notation, ie. `parent[child]`.
+    pub bracket_form_fields: bool,
+    /// Whether the file parts of multipart requests are sent before or after the text fields, before by default.
+    pub multipart_order: MultipartOrder,
+    /// Whether the format of the API key is checked before each request.
+    pub strict_key_validation: bool,
+    /// Whether a JSON body of a successful speech response is returned as error instead of as audio, true by default.
+    pub detect_speech_json_errors: bool,
+    /// The number of chunks of a speech stream that are read ahead of the consumer, 8 by default.
+    pub stream_buffer_size: usize,
+    pub model_capabilities: ModelCapabilityCache,
+    /// The detected or set chat completion features of the backend, shared by the clones of the client.
+    pub backend_capabilities: Arc<RwLock<Option<BackendCapabilities>>>,
+    /// Whether chat completions leave out the parameters the backend doesn't support, see `set_degrade_gracefully`.
+    pub degrade_gracefully: bool,
+    /// The time source of the retry backoff and the timeouts.
+    pub clock: Arc<dyn Clock>,
+    /// Chat completion and embedding requests wait for this budget, if set.
+    pub token_budget: Option<TokenBudget>,
+    /// Set by the backpressure of a rate limit, all requests wait until then. Shared by the clones of the client.
+    pub throttled_until: Arc<Mutex<Option<Instant>>>,
+    /// Provides the ID of the end user of a request, sent as safety_identifier.
+    pub end_user: Option<EndUser>,
+    /// Whether the end user ID is sent as SHA-256 hash instead of as is, true by default.
+    pub hash_end_user: bool,
+    /// Called with the warnings of the request checks, ie. a transcription prompt that is too long.
+    pub on_warning: Option<WarningHandler>,
+    /// The prompt of the last chat completion per model, to warn when the cached prefix is broken, see `set_cache_lint`.
+    pub cache_lint: Option<Arc<Mutex<HashMap<String, PromptLayout>>>>,
+    /// Convert the JSON bodies of raw requests (ie. speech) in order, see `add_request_converter`.
+    pub request_converters: Vec<Arc<dyn RequestConverter>>,
+    /// Whether warnings are returned as `ValidationError` instead of passed to `on_warning`.
+    pub strict_warnings: bool,
+    /// The estimated tokens above which a transcription prompt causes a warning, 224 by default.
+    pub transcription_prompt_limit: Option<u32>,
+    /// Set by `dry_run`, requests are captured instead of sent.
+    pub dry_run: Option<DryRun>,
+    /// The paths that replace the default paths of routes, for providers that serve a route at another path.
+    pub path_overrides: PathOverrides,
+    /// The client certificate and pinned certificates of the default HTTP client, see `set_identity_pem`.
+    pub(crate) tls: TlsSettings,
+    /// The redirect policy of the default HTTP client, kept to rebuild it when the TLS settings change.
+    redirect_policy: Arc<reqwest::redirect::Policy>,
+}
+
+/// The client certificate (mutual TLS) and the pinned server certificates of the default HTTP client.
+/// Set them with `Client::set_identity_pem` and `Client::set_pinned_certificate`, the private key is not shown by `Debug`.
+#[derive(Clone, Default, PartialEq)]
+pub(crate) struct TlsSettings {
+    /// The PEM encoded private key and certificate chain of the client.
+    pub identity_pem: Option<Vec<u8>>,
+    /// The DER encoded certificates that are trusted instead of the system roots, ie. a private CA.
+    pub pinned_certificates: Vec<Vec<u8>>,
+}
+
+impl std::fmt::Debug for TlsSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsSettings")
+            .field(
+                "identity_pem",
+                &self.identity_pem.as_ref().map(|_| REDACTED),
+            )
+            .field("pinned_certificates", &self.pinned_certificates.len())
+            .finish()
+    }
+}
+
+/// Receives the warnings of the request checks, ie. to log them or count them in metrics.
+#[derive(Clone)]
+pub struct WarningHandler(pub Arc<dyn Fn(&ValidationWarning) + Send + Sync>);
+
+impl std::fmt::Debug for WarningHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WarningHandler")
+    }
+}
+
+/// Provides the ID of the end user of the current request, ie. from a task local or the user of a request handler.
+#[derive(Clone)]
+pub struct EndUser(pub Arc<dyn Fn() -> Option<String> + Send + Sync>);
+
+impl std::fmt::Debug for EndUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EndUser")
+    }
+}
+
+/// Redacts sensitive content (ie. PII in transcriptions) from logged bodies and error messages.
+#[derive(Clone)]
+pub struct Redactor(pub Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl std::fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Redactor")
+    }
+}
+
+/// Signs the final request before it is sent, ie. to add an HMAC or SigV4 signature.
+#[derive(Clone)]
+pub struct RequestSigner(pub Arc<dyn Fn(&mut reqwest::Request) + Send + Sync>);
+
+impl std::fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RequestSigner")
+    }
+}
+
+/// The effective configuration of a client, returned by `Client::config_summary` to log the setup at startup.
+/// The API key is redacted, and only the names of the custom headers are shown because their values can be secrets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfigSummary {
+    pub base_url: String,
+    /// `[REDACTED]` when an API key is set, empty otherwise.
+    pub api_key: String,
+    /// Whether the base URL is the OpenAI API, the checks that only apply to OpenAI are skipped for other providers.
+    pub is_openai: bool,
+    pub organization: Option<String>,
+    pub project: Option<String>,
+    pub accept_language: Option<String>,
+    /// The names of the custom headers, sorted.
+    pub headers: Vec<String>,
+    pub path_overrides: PathOverrides,
+    pub validation_profile: ValidationProfile,
+    pub first_byte_timeout: Option<Duration>,
+    pub default_speech_model: Option<String>,
+    pub default_transcription_model: Option<String>,
+    pub default_translation_model: Option<String>,
+    pub default_speech_voice: Option<SpeechVoice>,
+    pub default_speech_format: Option<AudioSpeechResponseFormat>,
+    pub default_transcription_format: Option<AudioOutputFormat>,
+    /// The tokens per minute of the token budget, if set.
+    pub token_budget: Option<u32>,
+    pub strict_key_validation: bool,
+    pub strict_warnings: bool,
+    pub cache_lint: bool,
+    pub degrade_gracefully: bool,
+    pub request_converters: usize,
+    pub has_client_identity: bool,
+    pub pinned_certificates: usize,
+    pub dry_run: bool,
 }
 
 impl Client {
@@ -48,13 +226,74 @@ impl Client {
     }
 
     /// Create a new instance of the OpenAI client and set the API key from the environment variable `OPENAI_API_KEY`.
+    /// The organization and project are set from `OPENAI_ORG_ID` and `OPENAI_PROJECT_ID` when present.
     pub fn new_from_env() -> Self {
         let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY is not set");
 
         Self {
             api_key,
+            organization: std::env::var("OPENAI_ORG_ID").ok(),
+            project: std::env::var("OPENAI_PROJECT_ID").ok(),
+            ..Default::default()
+        }
+    }
+
+    /// Like `new_from_env`, but returns an error instead of panicking when `OPENAI_API_KEY` is missing or malformed.
+    /// The client has strict key validation enabled, see `set_strict_key_validation`.
+    pub fn try_new_from_env() -> Result<Self, APIError> {
+        let api_key = std::env::var("OPENAI_API_KEY").map_err(|_| APIError::ValidationError {
+            field: "api_key".to_string(),
+            value: "OPENAI_API_KEY is not set".to_string(),
+            allowed: "an OpenAI API key".to_string(),
+        })?;
+
+        let client = Self {
+            api_key,
+            organization: std::env::var("OPENAI_ORG_ID").ok(),
+            project: std::env::var("OPENAI_PROJECT_ID").ok(),
+            strict_key_validation: true,
             ..Default::default()
+        };
+
+        client.check_key_format()?;
+
+        Ok(client)
+    }
+
+    /// Check that an API key looks like an OpenAI key (ie. `sk-...` or `sk-proj-...`), without sending a request.
+    /// The key itself is never included in the error.
+    pub fn validate_key_format(api_key: &str) -> Result<(), APIError> {
+        let invalid = |value: &str| APIError::ValidationError {
+            field: "api_key".to_string(),
+            value: value.to_string(),
+            allowed: "a key starting with sk- followed by at least 20 letters, digits, - or _"
+                .to_string(),
+        };
+
+        let Some(rest) = api_key.strip_prefix("sk-") else {
+            return Err(invalid("a key without the sk- prefix"));
+        };
+
+        if !rest
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid("a key with invalid characters"));
+        }
+
+        if rest.len() < 20 {
+            return Err(invalid(&format!("a key of {} characters", api_key.len())));
         }
+
+        Ok(())
+    }
+
+    /// Check the API key format before each request, see `validate_key_format`.
+    /// With a custom base URL, any key without whitespace is accepted, as compatible providers use other formats.
+    pub fn set_strict_key_validation(&mut self, strict_key_validation: bool) -> &mut Self {
+        self.strict_key_validation = strict_key_validation;
+
+        self
     }
 
     /// Set the base URL for the OpenAI client.
@@ -64,6 +303,14 @@ impl Client {
         self
     }
 
+    /// Replace the path of a route, ie. `/api/chat/completions` for `Endpoint::ChatCompletions`.
+    /// The placeholders in braces are filled with the IDs of the request in order, ie. `/v2/files/{id}` for `Endpoint::File`.
+    pub fn set_path_override(&mut self, endpoint: Endpoint, path: &str) -> &mut Self {
+        self.path_overrides.insert(endpoint, path);
+
+        self
+    }
+
     /// Set the organization header for the OpenAI client.
     pub fn set_organization(&mut self, organization: &str) -> &mut Self {
         self.organization = Some(organization.to_string());
@@ -78,320 +325,2263 @@ impl Client {
         self
     }
 
-    /// Add a custom header to the OpenAI client.
-    pub fn add_header(&mut self, key: &str, value: &str) -> &mut Self {
-        self.headers
-            .get_or_insert_with(HashMap::new)
-            .insert(key.to_string(), value.to_string());
+    /// Set the Accept-Language header, ie. `de-DE, de;q=0.9`, for compatible providers that localize their error messages.
+    /// OpenAI ignores it and returns English messages.
+    pub fn set_accept_language(&mut self, accept_language: &str) -> &mut Self {
+        self.accept_language = Some(accept_language.to_string());
 
         self
     }
 
-    fn build_request(
-        &self,
-        method: reqwest::Method,
-        path: &str,
-        content_type: Option<&str>,
-    ) -> RequestBuilder {
-        let url = format!("{}{}", &self.base_url, path);
-
-        let mut request = self
-            .http_client
-            .request(method, url)
-            .bearer_auth(&self.api_key);
+    /// Returns a copy of the client that uses the given organization, ie. to override it for a single call.
+    pub fn with_organization(&self, organization: &str) -> Self {
+        let mut client = self.clone();
+        client.set_organization(organization);
 
-        if let Some(content_type) = content_type {
-            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
-        }
+        client
+    }
 
-        if let Some(headers) = &self.headers {
-            for (key, value) in headers {
-                request = request.header(key, value);
-            }
-        }
+    /// Returns a copy of the client that uses the given project, ie. to override it for a single call.
+    pub fn with_project(&self, project: &str) -> Self {
+        let mut client = self.clone();
+        client.set_project(project);
 
-        if let Some(organization) = &self.organization {
-            request = request.header("OpenAI-Organization", organization);
-        }
+        client
+    }
 
-        if let Some(project) = &self.project {
-            request = request.header("OpenAI-Project", project);
-        }
+    /// Set the profile used to validate request parameters before they are sent.
+    pub fn set_validation_profile(&mut self, validation_profile: ValidationProfile) -> &mut Self {
+        self.validation_profile = validation_profile;
 
-        request
+        self
     }
 
-    pub(crate) async fn get(&self, path: &str) -> Result<String, APIError> {
-        let result = self
-            .build_request(Method::GET, path, Some(MIME_TYPE_APPLICATION_JSON))
-            .send()
-            .await;
+    /// Set the transcription response format used when a request doesn't specify one.
+    pub fn set_default_transcription_format(
+        &mut self,
+        response_format: AudioOutputFormat,
+    ) -> &mut Self {
+        self.default_transcription_format = Some(response_format);
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        let response_text = response
-            .text()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))?;
+    /// Set the speech model used when the parameters of a request leave the model empty.
+    pub fn set_default_speech_model(&mut self, model: &str) -> &mut Self {
+        self.default_speech_model = Some(model.to_string());
 
-        #[cfg(feature = "log")]
-        log::trace!("{response_text}");
+        self
+    }
 
-        Ok(response_text)
+    /// Set the voice of speech requests whose parameters leave the voice unset, ie. `AudioVoice::Coral`.
+    pub fn set_default_speech_voice(&mut self, voice: impl Into<SpeechVoice>) -> &mut Self {
+        self.default_speech_voice = Some(voice.into());
+
+        self
     }
 
-    pub(crate) async fn get_raw(&self, path: &str) -> Result<Bytes, APIError> {
-        let result = self.build_request(Method::GET, path, None).send().await;
+    /// Set the audio format of speech requests whose parameters leave the response format unset.
+    pub fn set_default_speech_format(
+        &mut self,
+        response_format: AudioSpeechResponseFormat,
+    ) -> &mut Self {
+        self.default_speech_format = Some(response_format);
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))?;
+    /// Set the transcription model used when the parameters of a request leave the model empty.
+    pub fn set_default_transcription_model(&mut self, model: &str) -> &mut Self {
+        self.default_transcription_model = Some(model.to_string());
 
-        Ok(bytes)
+        self
     }
 
-    pub(crate) async fn get_with_query<Q>(&self, path: &str, query: &Q) -> Result<String, APIError>
-    where
-        Q: Serialize,
-    {
-        let encoded_query = serde_html_form::to_string(query).unwrap_or_else(|_| "".to_string());
+    /// Set the translation model used when the parameters of a request leave the model empty.
+    pub fn set_default_translation_model(&mut self, model: &str) -> &mut Self {
+        self.default_translation_model = Some(model.to_string());
 
-        let path = format!("{path}?{encoded_query}");
+        self
+    }
 
-        let result = self
-            .build_request(Method::GET, &path, Some(MIME_TYPE_APPLICATION_JSON))
-            .send()
-            .await;
+    /// Set the maximum time to wait for the first chunk of a streamed response.
+    /// If no chunk arrives in time, the stream returns `StreamError::Timeout` and is closed.
+    pub fn set_first_byte_timeout(&mut self, first_byte_timeout: Duration) -> &mut Self {
+        self.first_byte_timeout = Some(first_byte_timeout);
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        let response_text = response
-            .text()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))?;
+    /// Set the time source of the retry backoff and the timeouts, ie. a `MockClock` (`testing` feature) in tests.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.clock = Arc::new(clock);
 
-        #[cfg(feature = "log")]
-        log::trace!("{response_text}");
+        self
+    }
 
-        Ok(response_text)
+    /// Set the redirect policy of the HTTP client, this replaces the HTTP client with a new one.
+    /// If you use a custom HTTP client, set the policy on its builder instead.
+    pub fn set_redirect_policy(&mut self, policy: reqwest::redirect::Policy) -> &mut Self {
+        self.redirect_policy = Arc::new(policy);
+        self.http_client =
+            http_client(&self.redirect_policy, &self.tls).expect("failed to build the HTTP client");
+
+        self
     }
 
-    pub(crate) async fn post<T: Serialize>(
-        &self,
-        path: &str,
-        parameters: &T,
-        query_params: impl Into<Option<&HashMap<String, String>>>,
-    ) -> Result<ResponseWrapper<String>, APIError> {
-        let result = self
-            .build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON))
-            .query(&query_params.into())
-            .json(&parameters)
-            .send()
-            .await;
+    /// Set the client certificate for mutual TLS, a PEM file with the private key and the certificate chain.
+    /// This replaces the HTTP client with a new one, it is also used by the Realtime WebSocket.
+    /// PKCS#12 identities are not supported, they need native-tls while this crate uses rustls. A PKCS#12 bundle
+    /// can be converted with `openssl pkcs12 -in client.p12 -out client.pem -nodes`.
+    #[cfg(feature = "rustls-tls")]
+    pub fn set_identity_pem(&mut self, pem: &[u8]) -> Result<&mut Self, APIError> {
+        let mut tls = self.tls.clone();
+        tls.identity_pem = Some(pem.to_vec());
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self.set_tls(tls)
+    }
 
-        let header_map = response.headers().clone();
+    /// Pin a DER encoded certificate, ie. the CA of a private endpoint. Once a certificate is pinned,
+    /// only the pinned certificates are trusted, not the system roots. It can be called again to pin more certificates.
+    /// This replaces the HTTP client with a new one, it is also used by the Realtime WebSocket.
+    #[cfg(feature = "rustls-tls")]
+    pub fn set_pinned_certificate(&mut self, der: &[u8]) -> Result<&mut Self, APIError> {
+        let mut tls = self.tls.clone();
+        tls.pinned_certificates.push(der.to_vec());
 
-        let response_text = response
-            .text()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))?;
-        let response_headers: Headers = header_map.into();
+        self.set_tls(tls)
+    }
 
-        #[cfg(feature = "log")]
-        log::trace!("{response_text}");
+    #[cfg(feature = "rustls-tls")]
+    fn set_tls(&mut self, tls: TlsSettings) -> Result<&mut Self, APIError> {
+        self.http_client = http_client(&self.redirect_policy, &tls)?;
+        self.tls = tls;
 
-        Ok(ResponseWrapper {
-            data: response_text.to_string(),
-            headers: response_headers,
-        })
+        Ok(self)
     }
 
-    pub(crate) async fn delete(&self, path: &str) -> Result<String, APIError> {
-        let result = self
-            .build_request(Method::DELETE, path, Some(MIME_TYPE_APPLICATION_JSON))
-            .send()
-            .await;
+    /// Return a JSON body of a successful speech response as error, ie. `{"error": ...}` for an invalid voice, true by default.
+    /// Disable it for providers that return the audio as base64 in JSON, see `AudioSpeechResponse::from_json_base64`.
+    pub fn set_detect_speech_json_errors(&mut self, detect_speech_json_errors: bool) -> &mut Self {
+        self.detect_speech_json_errors = detect_speech_json_errors;
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        response
-            .text()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))
+    /// Set the number of chunks of a speech stream that are read ahead of the consumer.
+    /// The response is read in a task into a channel of this size, so a slow consumer neither stalls the connection
+    /// until its next read nor lets the audio pile up in memory. 0 reads the response only when the stream is polled.
+    pub fn set_stream_buffer_size(&mut self, stream_buffer_size: usize) -> &mut Self {
+        self.stream_buffer_size = stream_buffer_size;
+
+        self
     }
 
-    pub(crate) async fn post_with_form(&self, path: &str, form: Form) -> Result<String, APIError> {
-        let result = self
-            .build_request(Method::POST, path, None)
-            .multipart(form)
-            .send()
-            .await;
+    /// Send the file parts of multipart requests (ie. transcriptions and uploads) before or after the text fields.
+    /// By default they are sent first, like the OpenAI SDKs do. Some servers need `MultipartOrder::FileLast`.
+    pub fn set_multipart_order(&mut self, multipart_order: MultipartOrder) -> &mut Self {
+        self.multipart_order = multipart_order;
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        response
-            .text()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))
+    /// Returns an empty multipart form in the order of the client.
+    pub(crate) fn multipart_form(&self) -> MultipartForm {
+        MultipartForm::new(self.multipart_order)
     }
 
-    pub(crate) async fn post_raw<T: Serialize>(
-        &self,
-        path: &str,
-        parameters: &T,
-    ) -> Result<Bytes, APIError> {
-        let result = self
-            .build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON))
-            .json(&parameters)
-            .send()
-            .await;
+    /// Allow `FileUpload::Url` in transcriptions, which are then sent as JSON with a `file_url` field.
+    /// OpenAI doesn't support this, only enable it for compatible providers that fetch the file themselves.
+    pub fn set_allow_file_urls(&mut self, allow_file_urls: bool) -> &mut Self {
+        self.allow_file_urls = allow_file_urls;
 
-        let response = match check_status_code(result).await {
-            Ok(response) => response,
-            Err(error) => return Err(error),
-        };
+        self
+    }
 
-        response
-            .bytes()
-            .await
-            .map_err(|error| APIError::ParseError(error.to_string()))
+    /// Send nested objects in the `extra_body` of multipart requests (ie. transcriptions) as fields in bracket notation,
+    /// `{"parent": {"child": 1}}` as `parent[child]=1`. Without it, a nested object is sent as JSON in one field.
+    /// Only enable it for servers that parse bracket fields, ie. PHP and Rails based gateways.
+    pub fn set_bracket_form_fields(&mut self, bracket_form_fields: bool) -> &mut Self {
+        self.bracket_form_fields = bracket_form_fields;
+
+        self
     }
 
-    #[cfg(feature = "stream")]
-    pub(crate) async fn post_stream<I, O>(
-        &self,
-        path: &str,
-        parameters: &I,
-        query_params: impl Into<Option<&HashMap<String, String>>>,
-    ) -> Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>
+    /// Set a redactor that is applied to response bodies included in logs and error messages.
+    /// The API key is always redacted, also without a custom redactor.
+    pub fn set_redactor<F>(&mut self, redactor: F) -> &mut Self
     where
-        I: Serialize,
-        O: DeserializeOwned + std::marker::Send + 'static,
+        F: Fn(&str) -> String + Send + Sync + 'static,
     {
-        let event_source = self
-            .build_request(Method::POST, path, None)
-            .json(&parameters)
-            .query(&query_params.into())
-            .eventsource()
-            .unwrap();
+        self.redactor = Some(Redactor(Arc::new(redactor)));
 
-        Client::process_stream::<O>(event_source).await
+        self
     }
 
-    #[cfg(feature = "stream")]
-    pub(crate) async fn post_stream_raw<I>(
-        &self,
-        path: &str,
-        parameters: &I,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes, APIError>> + Send>>, APIError>
+    /// Set a signer that is called with the final request (method, URL, headers and body) right before it is sent.
+    /// It runs for JSON, multipart and streamed requests. Multipart bodies are buffered in memory when a signer is set.
+    pub fn set_request_signer<F>(&mut self, request_signer: F) -> &mut Self
     where
-        I: Serialize,
+        F: Fn(&mut reqwest::Request) + Send + Sync + 'static,
     {
-        let stream = self
-            .build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON))
-            .json(&parameters)
-            .send()
-            .await
-            .unwrap()
-            .bytes_stream()
-            .map(|item| item.map_err(|error| APIError::StreamError(error.to_string())));
+        self.request_signer = Some(RequestSigner(Arc::new(request_signer)));
 
-        Ok(Box::pin(stream)
-            as Pin<
-                Box<dyn Stream<Item = Result<Bytes, APIError>> + Send>,
-            >)
+        self
     }
 
-    #[cfg(feature = "stream")]
-    pub(crate) async fn process_stream<O>(
-        mut event_soure: EventSource,
-    ) -> Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>
-    where
-        O: DeserializeOwned + Send + 'static,
-    {
-        use super::error::InvalidRequestError;
+    /// Set a tokens-per-minute budget, which every chat completion and embedding request acquires its estimated tokens from.
+    /// The estimate is corrected with the usage of the response. Streams keep the estimate.
+    pub fn set_token_budget(&mut self, token_budget: TokenBudget) -> &mut Self {
+        self.token_budget = Some(token_budget);
 
-        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self
+    }
 
-        tokio::spawn(async move {
-            while let Some(event_result) = event_soure.next().await {
-                match event_result {
-                    Ok(event) => match event {
-                        Event::Open => continue,
-                        Event::Message(message) => {
-                            if message.data == "[DONE]" {
-                                break;
-                            }
+    /// Waits until the backpressure of the last rate limit (of this client or one of its clones) has passed.
+    pub(crate) async fn wait_for_throttle(&self) {
+        loop {
+            let now = self.clock.now();
+            let remaining = self
+                .throttled_until
+                .lock()
+                .unwrap()
+                .map(|until| until.saturating_duration_since(now))
+                .filter(|remaining| !remaining.is_zero());
+
+            match remaining {
+                Some(remaining) => self.clock.sleep(remaining).await,
+                None => return,
+            }
+        }
+    }
+
+    /// Makes all requests wait for the backpressure of a rate limit, the concurrent requests would hit the same limit.
+    fn throttle(&self, wait: Duration) {
+        let until = self.clock.now() + wait;
+        let mut throttled_until = self.throttled_until.lock().unwrap();
+
+        *throttled_until = Some(throttled_until.map_or(until, |throttled| throttled.max(until)));
+    }
+
+    /// Acquires the estimated tokens from the token budget, if set.
+    pub(crate) async fn acquire_tokens(&self, estimated_tokens: u32) -> Option<TokenReservation> {
+        match &self.token_budget {
+            Some(token_budget) => Some(token_budget.acquire(estimated_tokens).await),
+            None => None,
+        }
+    }
+
+    /// Set a provider of the ID of the end user, which is called for every chat completion and response request.
+    /// The ID is sent as safety_identifier, unless the parameters already set one.
+    /// It is hashed with SHA-256 before it is sent, so raw IDs (ie. email addresses) don't leave the application.
+    pub fn set_end_user<F>(&mut self, end_user: F) -> &mut Self
+    where
+        F: Fn() -> Option<String> + Send + Sync + 'static,
+    {
+        self.end_user = Some(EndUser(Arc::new(end_user)));
+
+        self
+    }
+
+    /// Set whether the end user ID is hashed with SHA-256 before it is sent, true by default.
+    /// Only disable this if the IDs are already pseudonymous.
+    pub fn set_hash_end_user(&mut self, hash_end_user: bool) -> &mut Self {
+        self.hash_end_user = hash_end_user;
+
+        self
+    }
+
+    /// Adds a converter of the JSON bodies of raw requests (ie. speech), after the converters added before,
+    /// ie. `CamelCaseKeys` and then a `FieldRenameAdapter` for a provider.
+    pub fn add_request_converter(
+        &mut self,
+        converter: impl RequestConverter + 'static,
+    ) -> &mut Self {
+        self.request_converters.push(Arc::new(converter));
+
+        self
+    }
+
+    /// Compare every chat completion with the previous one to the same model, and warn (`CachePrefixDiverged`)
+    /// when their prompts differ before the 1024 tokens prompt caching needs, ie. because of a timestamp in the system prompt.
+    /// Meant for debugging, with `set_strict_warnings` the request fails instead.
+    pub fn set_cache_lint(&mut self, enabled: bool) -> &mut Self {
+        self.cache_lint = enabled.then(Default::default);
+
+        self
+    }
+
+    /// Warns when the prompt differs from the previous one to the same model before the minimum cached prefix.
+    pub(crate) fn lint_cache_prefix(
+        &self,
+        parameters: &ChatCompletionParameters,
+    ) -> Result<(), APIError> {
+        let Some(history) = &self.cache_lint else {
+            return Ok(());
+        };
+
+        let layout = PromptLayout::new(parameters);
+
+        let previous = history
+            .lock()
+            .unwrap()
+            .insert(parameters.model.clone(), layout.clone());
+
+        let Some(previous) = previous else {
+            return Ok(());
+        };
+
+        let Some(divergence) = previous.divergence(&layout) else {
+            return Ok(());
+        };
+
+        let cacheable =
+            previous.estimated_tokens().min(layout.estimated_tokens()) >= MIN_CACHED_PREFIX_TOKENS;
+
+        if !cacheable || divergence.prefix_tokens >= MIN_CACHED_PREFIX_TOKENS {
+            return Ok(());
+        }
+
+        self.warn(ValidationWarning::CachePrefixDiverged {
+            model: parameters.model.clone(),
+            byte_offset: divergence.byte_offset,
+            prefix_tokens: divergence.prefix_tokens,
+            previous: divergence.left,
+            current: divergence.right,
+        })
+    }
+
+    /// Set a handler for the warnings of the request checks, without a handler they are logged (`log` feature).
+    pub fn set_on_warning<F>(&mut self, on_warning: F) -> &mut Self
+    where
+        F: Fn(&ValidationWarning) + Send + Sync + 'static,
+    {
+        self.on_warning = Some(WarningHandler(Arc::new(on_warning)));
+
+        self
+    }
+
+    /// Set whether warnings are returned as `ValidationError`, so the request isn't sent.
+    pub fn set_strict_warnings(&mut self, strict_warnings: bool) -> &mut Self {
+        self.strict_warnings = strict_warnings;
+
+        self
+    }
+
+    /// Set the estimated tokens above which a transcription prompt causes a warning, None disables the check.
+    pub fn set_transcription_prompt_limit(&mut self, limit: Option<u32>) -> &mut Self {
+        self.transcription_prompt_limit = limit;
+
+        self
+    }
+
+    /// Passes the warning to the handler, or returns it as error when warnings are strict.
+    pub(crate) fn warn(&self, warning: ValidationWarning) -> Result<(), APIError> {
+        if self.strict_warnings {
+            return Err(warning.into());
+        }
+
+        if let Some(on_warning) = &self.on_warning {
+            (on_warning.0)(&warning);
+
+            return Ok(());
+        }
+
+        #[cfg(feature = "log")]
+        log::warn!("{warning}");
+
+        Ok(())
+    }
+
+    /// Returns the end user ID for the current request, hashed unless disabled.
+    pub(crate) fn end_user_id(&self) -> Option<String> {
+        let id = (self.end_user.as_ref()?.0)()?;
+
+        Some(match self.hash_end_user {
+            true => crate::v1::content_hash::sha256_hex(id.as_bytes()),
+            false => id,
+        })
+    }
+
+    /// Redact the API key and apply the custom redactor, if any, to the given text.
+    pub fn redact(&self, text: &str) -> String {
+        redact(&self.api_key, self.redactor.as_ref(), text)
+    }
+
+    /// Add a custom header to the OpenAI client.
+    pub fn add_header(&mut self, key: &str, value: &str) -> &mut Self {
+        self.headers
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.to_string());
+
+        self
+    }
+
+    fn build_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        content_type: Option<&str>,
+    ) -> RequestBuilder {
+        let url = self.url(path);
+
+        let mut request = self
+            .http_client
+            .request(method, url)
+            .bearer_auth(&self.api_key);
+
+        if let Some(content_type) = content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        if let Some(headers) = &self.headers {
+            for (key, value) in headers {
+                request = request.header(key, value);
+            }
+        }
+
+        if let Some(organization) = &self.organization {
+            request = request.header("OpenAI-Organization", organization);
+        }
+
+        if let Some(project) = &self.project {
+            request = request.header("OpenAI-Project", project);
+        }
+
+        if let Some(accept_language) = &self.accept_language {
+            request = request.header(reqwest::header::ACCEPT_LANGUAGE, accept_language);
+        }
+
+        request
+    }
+
+    /// Returns the URL of a path, with the path override of its route, if any.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", &self.base_url, self.path_overrides.resolve(path))
+    }
+
+    /// Open a connection to the base URL, so the first real request can skip the connection and TLS handshake.
+    /// This is best-effort, whether the connection is reused depends on the connection pool of the HTTP client.
+    /// Any HTTP response counts as a successful warmup, only connection errors are returned.
+    pub async fn warmup(&self) -> Result<(), APIError> {
+        self.sign(self.build_request(Method::HEAD, "", None))
+            .await?
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        Ok(())
+    }
+
+    /// Fills an empty model with the default model, if any. A model of the request always wins.
+    pub(crate) fn apply_default_model(model: &mut String, default_model: &Option<String>) {
+        if let (true, Some(default_model)) = (model.is_empty(), default_model) {
+            model.clone_from(default_model);
+        }
+    }
+
+    /// Fills the model, voice and response format of speech parameters that leave them unset with the defaults, if set.
+    pub(crate) fn apply_speech_defaults(&self, parameters: &mut AudioSpeechParameters) {
+        Client::apply_default_model(&mut parameters.model, &self.default_speech_model);
+
+        if parameters.voice.is_none() {
+            parameters.voice.clone_from(&self.default_speech_voice);
+        }
+
+        if parameters.response_format.is_none() {
+            parameters
+                .response_format
+                .clone_from(&self.default_speech_format);
+        }
+    }
+
+    pub(crate) fn transcription_format(
+        &self,
+        response_format: Option<AudioOutputFormat>,
+    ) -> Option<AudioOutputFormat> {
+        response_format.or_else(|| self.default_transcription_format.clone())
+    }
+
+    /// Applies the request signer, if any, to the final request.
+    /// Streamed bodies (ie. multipart forms) are buffered first, so the signer can read the body.
+    async fn sign(&self, request: RequestBuilder) -> Result<RequestBuilder, APIError> {
+        let Some(request_signer) = &self.request_signer else {
+            return Ok(request);
+        };
+
+        let (http_client, mut request) = buffer_body(request).await?;
+
+        (request_signer.0)(&mut request);
+
+        Ok(RequestBuilder::from_parts(http_client, request))
+    }
+
+    /// Returns the effective configuration, with the API key redacted.
+    pub fn config_summary(&self) -> ClientConfigSummary {
+        let mut headers: Vec<String> = self
+            .headers
+            .iter()
+            .flat_map(|headers| headers.keys().cloned())
+            .collect();
+        headers.sort();
+
+        ClientConfigSummary {
+            base_url: self.redact(&self.base_url),
+            api_key: match self.api_key.is_empty() {
+                true => String::new(),
+                false => REDACTED.to_string(),
+            },
+            is_openai: self.is_openai(),
+            organization: self.organization.clone(),
+            project: self.project.clone(),
+            accept_language: self.accept_language.clone(),
+            headers,
+            path_overrides: self.path_overrides.clone(),
+            validation_profile: self.validation_profile.clone(),
+            first_byte_timeout: self.first_byte_timeout,
+            default_speech_model: self.default_speech_model.clone(),
+            default_transcription_model: self.default_transcription_model.clone(),
+            default_translation_model: self.default_translation_model.clone(),
+            default_speech_voice: self.default_speech_voice.clone(),
+            default_speech_format: self.default_speech_format.clone(),
+            default_transcription_format: self.default_transcription_format.clone(),
+            token_budget: self
+                .token_budget
+                .as_ref()
+                .map(|budget| budget.tokens_per_minute()),
+            strict_key_validation: self.strict_key_validation,
+            strict_warnings: self.strict_warnings,
+            cache_lint: self.cache_lint.is_some(),
+            degrade_gracefully: self.degrade_gracefully,
+            request_converters: self.request_converters.len(),
+            has_client_identity: self.tls.identity_pem.is_some(),
+            pinned_certificates: self.tls.pinned_certificates.len(),
+            dry_run: self.dry_run.is_some(),
+        }
+    }
+
+    /// Whether the requests are sent to the OpenAI API, not to a compatible provider.
+    pub(crate) fn is_openai(&self) -> bool {
+        self.base_url.trim_end_matches('/') == OPENAI_API_V1_ENDPOINT
+    }
+
+    fn check_key_format(&self) -> Result<(), APIError> {
+        if !self.strict_key_validation {
+            return Ok(());
+        }
+
+        if self.is_openai() {
+            return Client::validate_key_format(&self.api_key);
+        }
+
+        if self.api_key.is_empty()
+            || self
+                .api_key
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(APIError::ValidationError {
+                field: "api_key".to_string(),
+                value: "an empty key or a key with whitespace".to_string(),
+                allowed: "a key without whitespace".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&self, request: RequestBuilder) -> Result<reqwest::Response, APIError> {
+        self.check_key_format()?;
+
+        let request = self.sign(request).await?;
+
+        if let Some(dry_run) = &self.dry_run {
+            return Err(dry_run.capture(request).await);
+        }
+
+        self.wait_for_throttle().await;
+
+        let (http_client, request) = request.build_split();
+
+        #[cfg(feature = "tracing")]
+        let span = request_span(request.as_ref().ok());
+
+        let response = async {
+            check_status_code(match request {
+                Ok(request) => http_client.execute(request).await,
+                Err(error) => Err(error),
+            })
+            .await
+        };
+
+        #[cfg(feature = "tracing")]
+        let response = tracing::Instrument::instrument(response, span.clone());
+
+        let response = response.await;
+
+        #[cfg(feature = "tracing")]
+        record_response(&span, &response);
+
+        match response {
+            Ok(response) => Ok(response),
+            Err(error) => {
+                if let Some(backpressure) = error.backpressure() {
+                    self.throttle(backpressure.wait);
+
+                    if let Some(budget) = &self.token_budget {
+                        budget.pause(backpressure.wait);
+                    }
+                }
+
+                Err(error.map_message(|message| self.redact(message)))
+            }
+        }
+    }
+
+    pub(crate) async fn get(&self, path: &str) -> Result<String, APIError> {
+        let response = self
+            .execute(self.build_request(Method::GET, path, Some(MIME_TYPE_APPLICATION_JSON)))
+            .await?;
+
+        let response_text = response
+            .text()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        #[cfg(feature = "log")]
+        log::trace!("{}", self.redact(&response_text));
+
+        Ok(response_text)
+    }
+
+    pub(crate) async fn get_raw(&self, path: &str) -> Result<Bytes, APIError> {
+        let response = self.get_raw_response(path).await?;
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        Ok(bytes)
+    }
+
+    /// Returns the response of `get_raw` before its body is read, ie. to read it in chunks.
+    pub(crate) async fn get_raw_response(&self, path: &str) -> Result<reqwest::Response, APIError> {
+        self.execute(self.build_request(Method::GET, path, None))
+            .await
+    }
+
+    pub(crate) async fn get_with_query<Q>(&self, path: &str, query: &Q) -> Result<String, APIError>
+    where
+        Q: Serialize,
+    {
+        let encoded_query = serde_html_form::to_string(query).unwrap_or_else(|_| "".to_string());
+
+        let path = format!("{path}?{encoded_query}");
+
+        let response = self
+            .execute(self.build_request(Method::GET, &path, Some(MIME_TYPE_APPLICATION_JSON)))
+            .await?;
+
+        let response_text = response
+            .text()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        #[cfg(feature = "log")]
+        log::trace!("{}", self.redact(&response_text));
+
+        Ok(response_text)
+    }
+
+    pub(crate) async fn post<T: Serialize>(
+        &self,
+        path: &str,
+        parameters: &T,
+        query_params: impl Into<Option<&HashMap<String, String>>>,
+    ) -> Result<ResponseWrapper<String>, APIError> {
+        let response = self
+            .execute(
+                self.build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON))
+                    .query(&query_params.into())
+                    .json(&parameters),
+            )
+            .await?;
+
+        let header_map = response.headers().clone();
+
+        let response_text = response
+            .text()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+        let response_headers: Headers = header_map.into();
+
+        #[cfg(feature = "log")]
+        log::trace!("{}", self.redact(&response_text));
+
+        Ok(ResponseWrapper {
+            data: response_text,
+            headers: response_headers,
+        })
+    }
+
+    pub(crate) async fn delete(&self, path: &str) -> Result<String, APIError> {
+        let response = self
+            .execute(self.build_request(Method::DELETE, path, Some(MIME_TYPE_APPLICATION_JSON)))
+            .await?;
+
+        response
+            .text()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))
+    }
+
+    pub(crate) async fn post_with_form(&self, path: &str, form: Form) -> Result<String, APIError> {
+        self.post_with_form_and_accept(path, form, None).await
+    }
+
+    pub(crate) async fn post_with_form_and_accept(
+        &self,
+        path: &str,
+        form: Form,
+        accept: Option<&str>,
+    ) -> Result<String, APIError> {
+        let response = self
+            .execute(with_accept(
+                self.build_request(Method::POST, path, None).multipart(form),
+                accept,
+            ))
+            .await?;
+
+        response
+            .text()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))
+    }
+
+    pub(crate) async fn post_raw<T: Serialize>(
+        &self,
+        path: &str,
+        parameters: &T,
+        accept: Option<&str>,
+    ) -> Result<Bytes, APIError> {
+        self.post_raw_with_content_type(path, parameters, accept)
+            .await
+            .map(|(bytes, _)| bytes)
+    }
+
+    /// Returns the body with the Content-Type of the response, if any.
+    pub(crate) async fn post_raw_with_content_type<T: Serialize>(
+        &self,
+        path: &str,
+        parameters: &T,
+        accept: Option<&str>,
+    ) -> Result<(Bytes, Option<String>), APIError> {
+        let response = self.post_raw_response(path, parameters, accept).await?;
+
+        let content_type = content_type(&response);
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        Ok((bytes, content_type))
+    }
+
+    /// Returns the response of `post_raw` before its body is read, ie. to read it in chunks.
+    pub(crate) async fn post_raw_response<T: Serialize>(
+        &self,
+        path: &str,
+        parameters: &T,
+        accept: Option<&str>,
+    ) -> Result<reqwest::Response, APIError> {
+        let request = self.build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON));
+
+        let request = match self.request_converters.is_empty() {
+            true => request.json(&parameters),
+            false => request.json(&self.convert_request(parameters)?),
+        };
+
+        self.execute(with_accept(request, accept)).await
+    }
+
+    /// Applies the request converters to the parameters, in the order they were added.
+    fn convert_request<T: Serialize>(&self, parameters: &T) -> Result<serde_json::Value, APIError> {
+        let mut body = serde_json::to_value(parameters)
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        for converter in &self.request_converters {
+            body = converter.to_provider(body)?;
+        }
+
+        Ok(body)
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) async fn post_stream<I, O>(
+        &self,
+        path: &str,
+        parameters: &I,
+        query_params: impl Into<Option<&HashMap<String, String>>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>
+    where
+        I: Serialize,
+        O: DeserializeOwned + std::marker::Send + 'static,
+    {
+        self.post_stream_with_metadata(path, parameters, query_params)
+            .await
+            .0
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) async fn post_stream_with_metadata<I, O>(
+        &self,
+        path: &str,
+        parameters: &I,
+        query_params: impl Into<Option<&HashMap<String, String>>>,
+    ) -> (
+        Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>,
+        StreamMetadata,
+    )
+    where
+        I: Serialize,
+        O: DeserializeOwned + std::marker::Send + 'static,
+    {
+        let request = self
+            .build_request(Method::POST, path, None)
+            .json(&parameters)
+            .query(&query_params.into());
+
+        self.stream_request(request).await
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) async fn post_stream_with_form<O>(
+        &self,
+        path: &str,
+        form: Form,
+    ) -> Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>
+    where
+        O: DeserializeOwned + std::marker::Send + 'static,
+    {
+        let request = self.build_request(Method::POST, path, None).multipart(form);
+
+        // the event source needs a body that can be cloned, so the streamed form is buffered
+        let request = match buffer_body(request).await {
+            Ok((http_client, request)) => RequestBuilder::from_parts(http_client, request),
+            Err(error) => return Box::pin(futures::stream::once(async { Err(error) })),
+        };
+
+        self.stream_request(request).await.0
+    }
+
+    #[cfg(feature = "stream")]
+    async fn stream_request<O>(
+        &self,
+        request: RequestBuilder,
+    ) -> (
+        Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>,
+        StreamMetadata,
+    )
+    where
+        O: DeserializeOwned + std::marker::Send + 'static,
+    {
+        let request = match self.check_key_format() {
+            Ok(()) => self.sign(request).await,
+            Err(error) => Err(error),
+        };
+
+        let request = match request {
+            Ok(request) => request,
+            Err(error) => {
+                return (
+                    Box::pin(futures::stream::once(async { Err(error) })),
+                    StreamMetadata::default(),
+                )
+            }
+        };
+
+        if let Some(dry_run) = &self.dry_run {
+            let error = dry_run.capture(request).await;
+
+            return (
+                Box::pin(futures::stream::once(async { Err(error) })),
+                StreamMetadata::default(),
+            );
+        }
+
+        self.wait_for_throttle().await;
+
+        let metadata = StreamMetadata::default();
+
+        let events = event_stream(request, metadata.clone());
+
+        let api_key = self.api_key.clone();
+        let redactor = self.redactor.clone();
+
+        let stream = Client::process_stream::<O>(
+            events,
+            move |text| redact(&api_key, redactor.as_ref(), text),
+            self.first_byte_timeout,
+            self.clock.clone(),
+            metadata.clone(),
+        )
+        .await;
+
+        (stream, metadata)
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) async fn post_stream_raw<I>(
+        &self,
+        path: &str,
+        parameters: &I,
+        accept: Option<&str>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes, APIError>> + Send>>, APIError>
+    where
+        I: Serialize,
+    {
+        let stream = self
+            .execute(with_accept(
+                self.build_request(Method::POST, path, Some(MIME_TYPE_APPLICATION_JSON))
+                    .json(&parameters),
+                accept,
+            ))
+            .await?
+            .bytes_stream()
+            .map(|item| {
+                item.map_err(|error| StreamError::Transport(TransportError::new(error)).into())
+            });
+
+        Ok(Box::pin(stream)
+            as Pin<
+                Box<dyn Stream<Item = Result<Bytes, APIError>> + Send>,
+            >)
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) async fn process_stream<O>(
+        mut events: EventStream,
+        redact: impl Fn(&str) -> String + Send + Sync + 'static,
+        first_byte_timeout: Option<Duration>,
+        clock: Arc<dyn Clock>,
+        metadata: StreamMetadata,
+    ) -> Pin<Box<dyn Stream<Item = Result<O, APIError>> + Send>>
+    where
+        O: DeserializeOwned + Send + 'static,
+    {
+        use super::error::{StreamError, TimeoutPhase};
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let started_at = clock.now();
+            let elapsed = || clock.now().saturating_duration_since(started_at);
+
+            loop {
+                // stop as soon as the receiver is dropped, instead of waiting for the next event
+                let next_event = async {
+                    let closed = std::pin::pin!(tx.closed());
+
+                    match futures::future::select(events.next(), closed).await {
+                        futures::future::Either::Left((event_result, _)) => event_result,
+                        futures::future::Either::Right(_) => None,
+                    }
+                };
+
+                let event_result = match first_byte_timeout {
+                    Some(first_byte_timeout) if metadata.time_to_first_byte().is_none() => {
+                        let remaining = first_byte_timeout.saturating_sub(elapsed());
+
+                        match crate::v1::clock::timeout(&clock, remaining, next_event).await {
+                            Ok(event_result) => event_result,
+                            Err(_) => {
+                                let _ = tx.send(Err(APIError::StreamError(StreamError::Timeout(
+                                    TimeoutPhase::FirstByte,
+                                ))));
 
-                            let response = match serde_json::from_str::<O>(&message.data) {
-                                Ok(result) => Ok(result),
-                                Err(error) => {
-                                    match serde_json::from_str::<InvalidRequestError>(&message.data)
-                                    {
-                                        Ok(invalid_request_error) => Err(APIError::StreamError(
-                                            invalid_request_error.to_string(),
-                                        )),
-                                        Err(_) => Err(APIError::StreamError(format!(
-                                            "{} {}",
-                                            error, message.data
-                                        ))),
-                                    }
-                                }
-                            };
-
-                            if let Err(_error) = tx.send(response) {
                                 break;
                             }
                         }
-                    },
+                    }
+                    _ => next_event.await,
+                };
+
+                let Some(event_result) = event_result else {
+                    break;
+                };
+
+                match event_result {
+                    Ok(message) => {
+                        metadata.set_time_to_first_byte(elapsed());
+
+                        // the end of the stream in the OpenAI format
+                        if message.data.trim() == "[DONE]" {
+                            break;
+                        }
+
+                        let response = match serde_json::from_str::<O>(&message.data) {
+                            Ok(result) => Ok(result),
+                            Err(error) => Err(stream_item_error(&message.data, error, &redact)),
+                        };
+
+                        if let Err(_error) = tx.send(response) {
+                            break;
+                        }
+                    }
                     Err(error) => {
-                        if let Err(_error) = tx.send(Err(APIError::StreamError(error.to_string())))
-                        {
+                        let error = event_source_error(error, &redact).await;
+
+                        if let Err(_error) = tx.send(Err(error)) {
                             break;
                         }
                     }
                 }
             }
+        });
+
+        Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        let redirect_policy = Arc::new(default_redirect_policy());
+
+        Client {
+            http_client: http_client(&redirect_policy, &TlsSettings::default())
+                .expect("failed to build the HTTP client"),
+            base_url: OPENAI_API_V1_ENDPOINT.to_string(),
+            api_key: "".to_string(),
+            headers: None,
+            organization: None,
+            project: None,
+            accept_language: None,
+            validation_profile: ValidationProfile::default(),
+            redactor: None,
+            request_signer: None,
+            default_transcription_format: None,
+            default_speech_model: None,
+            default_transcription_model: None,
+            default_translation_model: None,
+            default_speech_voice: None,
+            default_speech_format: None,
+            first_byte_timeout: None,
+            allow_file_urls: false,
+            multipart_order: MultipartOrder::default(),
+            detect_speech_json_errors: true,
+            stream_buffer_size: 8,
+            bracket_form_fields: false,
+            strict_key_validation: false,
+            model_capabilities: ModelCapabilityCache::default(),
+            backend_capabilities: Arc::default(),
+            degrade_gracefully: false,
+            clock: Arc::new(SystemClock),
+            token_budget: None,
+            throttled_until: Arc::default(),
+            end_user: None,
+            hash_end_user: true,
+            on_warning: None,
+            cache_lint: None,
+            request_converters: Vec::new(),
+            strict_warnings: false,
+            transcription_prompt_limit: Some(MAX_TRANSCRIPTION_PROMPT_TOKENS),
+            dry_run: None,
+            path_overrides: PathOverrides::default(),
+            tls: TlsSettings::default(),
+            redirect_policy,
+        }
+    }
+}
+
+/// The error of an event that isn't an item of the stream, an error sent by the server or a chunk that can't be parsed.
+#[cfg(feature = "stream")]
+fn stream_item_error(
+    data: &str,
+    error: serde_json::Error,
+    redact: &impl Fn(&str) -> String,
+) -> APIError {
+    use super::error::{ErrorBody, InvalidRequestError};
+
+    let is_error_event = serde_json::from_str::<InvalidRequestError>(data).is_ok()
+        || serde_json::from_str::<serde_json::Value>(data)
+            .is_ok_and(|value| value.get("error").is_some());
+
+    match is_error_event {
+        true => StreamError::ServerEvent(ErrorBody::parse(&redact(data))).into(),
+        false => StreamError::Parse {
+            payload: redact(data),
+            source: error.to_string(),
+        }
+        .into(),
+    }
+}
+
+/// The messages of a streamed response, followed by `EventError::Ended` when the body ends without `[DONE]`.
+#[cfg(feature = "stream")]
+type EventStream =
+    Pin<Box<dyn Stream<Item = Result<eventsource_stream::Event, EventError>> + Send>>;
+
+/// The errors of `event_stream`, classified by `event_source_error`.
+#[cfg(feature = "stream")]
+pub(crate) enum EventError {
+    /// Sending the request or reading the body failed.
+    Transport(reqwest::Error),
+    /// The body isn't valid server-sent events.
+    Parse(String),
+    /// The body ended, the connection isn't opened again.
+    Ended,
+    /// The stream was rejected with an error status.
+    Status(reqwest::Response),
+    /// The response isn't an event stream.
+    ContentType(reqwest::Response),
+}
+
+/// Sends the request of a stream, sets the request id of the metadata as soon as the headers arrive and parses the body as server-sent events.
+/// The connection isn't opened again when it's closed, that would generate the response again.
+#[cfg(feature = "stream")]
+fn event_stream(request: RequestBuilder, metadata: StreamMetadata) -> EventStream {
+    use eventsource_stream::{EventStreamError, Eventsource};
+    use futures::TryStreamExt;
+
+    let (http_client, request) = request
+        .header(reqwest::header::ACCEPT, "text/event-stream")
+        .build_split();
+
+    #[cfg(feature = "tracing")]
+    let span = request_span(request.as_ref().ok());
+
+    let response = async move {
+        match request {
+            Ok(request) => http_client.execute(request).await,
+            Err(error) => Err(error),
+        }
+    };
+
+    #[cfg(feature = "tracing")]
+    let response = tracing::Instrument::instrument(response, span.clone());
+
+    let events = async move {
+        let failed = |error| -> EventStream { Box::pin(futures::stream::iter([Err(error)])) };
+
+        let response = match response.await {
+            Ok(response) => response,
+            Err(error) => return failed(EventError::Transport(error)),
+        };
+
+        let request_id = crate::v1::helpers::request_id(response.headers());
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("status", response.status().as_u16());
+            span.record("request_id", request_id.as_deref());
+        }
+
+        if let Some(request_id) = request_id {
+            metadata.set_request_id(request_id);
+        }
+
+        if response.status() != reqwest::StatusCode::OK {
+            return failed(EventError::Status(response));
+        }
+
+        let is_event_stream = content_type(&response).is_some_and(|content_type| {
+            content_type
+                .to_ascii_lowercase()
+                .starts_with("text/event-stream")
+        });
+
+        if !is_event_stream {
+            return failed(EventError::ContentType(response));
+        }
+
+        let messages = response
+            .bytes_stream()
+            .eventsource()
+            .map_err(|error| match error {
+                EventStreamError::Transport(error) => EventError::Transport(error),
+                error => EventError::Parse(error.to_string()),
+            });
+
+        Box::pin(messages.chain(futures::stream::iter([Err(EventError::Ended)])))
+    };
+
+    Box::pin(futures::stream::once(events).flatten())
+}
+
+/// Classifies an error of the event stream, the body of a rejected stream is read for the error message.
+#[cfg(feature = "stream")]
+async fn event_source_error(error: EventError, redact: &impl Fn(&str) -> String) -> APIError {
+    use super::error::ErrorBody;
+
+    match error {
+        EventError::Transport(error) => {
+            if let Some(error) = crate::v1::error::tls_error(&error)
+                .or_else(|| crate::v1::helpers::file_read_error(&error))
+            {
+                return error;
+            }
+
+            StreamError::Transport(TransportError::new(error))
+        }
+        EventError::Ended => StreamError::Transport("Stream ended".to_string().into()),
+        EventError::Status(response) => {
+            let status = response.status();
+            let request_id = crate::v1::helpers::request_id(response.headers());
+            let text = response.text().await.unwrap_or_default();
+
+            StreamError::ServerEvent(ErrorBody {
+                request_id,
+                ..match text.is_empty() {
+                    true => ErrorBody::parse(&status.to_string()),
+                    false => ErrorBody::parse(&redact(&text)),
+                }
+            })
+        }
+        EventError::ContentType(response) => {
+            let content_type = content_type(&response).unwrap_or_default();
+
+            StreamError::NotAnEventStream {
+                content_type,
+                payload: redact(&response.text().await.unwrap_or_default()),
+            }
+        }
+        EventError::Parse(source) => StreamError::Parse {
+            payload: String::new(),
+            source,
+        },
+    }
+    .into()
+}
+
+/// The span of a request, with the `status` and `request_id` of the response (see `record_response`).
+#[cfg(feature = "tracing")]
+fn request_span(request: Option<&reqwest::Request>) -> tracing::Span {
+    tracing::info_span!(
+        "openai_dive.request",
+        method = request.map(|request| request.method().as_str()),
+        path = request.map(|request| request.url().path()),
+        status = tracing::field::Empty,
+        request_id = tracing::field::Empty,
+    )
+}
+
+/// Records the status and the request id of a response, or of the error of a failed response.
+#[cfg(feature = "tracing")]
+fn record_response(span: &tracing::Span, response: &Result<reqwest::Response, APIError>) {
+    match response {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            span.record(
+                "request_id",
+                crate::v1::helpers::request_id(response.headers()).as_deref(),
+            );
+        }
+        Err(error) => {
+            span.record("request_id", error.request_id().as_deref());
+        }
+    }
+}
+
+/// Returns the Content-Type of the response, if any.
+pub(crate) fn content_type(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(|content_type| content_type.to_string())
+}
+
+/// Reads the body chunk by chunk, calling `on_progress` with the bytes read so far and the Content-Length, if any.
+pub(crate) async fn read_with_progress<F>(
+    mut response: reqwest::Response,
+    on_progress: &mut F,
+) -> Result<Bytes, APIError>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let total = response.content_length();
+    let mut body = bytes::BytesMut::new();
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| APIError::ParseError(error.to_string()))?
+    {
+        body.extend_from_slice(&chunk);
+
+        on_progress(body.len() as u64, total);
+    }
+
+    Ok(body.freeze())
+}
+
+/// Writes the body chunk by chunk to the writer, without holding more than a chunk in memory.
+/// Returns the number of bytes written.
+pub(crate) async fn write_with_progress<W, F>(
+    mut response: reqwest::Response,
+    writer: &mut W,
+    on_progress: &mut F,
+) -> Result<u64, APIError>
+where
+    W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    F: FnMut(u64, Option<u64>),
+{
+    use tokio::io::AsyncWriteExt;
+
+    let total = response.content_length();
+    let mut written = 0;
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| APIError::ParseError(error.to_string()))?
+    {
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(|error| APIError::FileError(error.to_string()))?;
+
+        written += chunk.len() as u64;
+
+        on_progress(written, total);
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|error| APIError::FileError(error.to_string()))?;
+
+    Ok(written)
+}
+
+fn with_accept(request: RequestBuilder, accept: Option<&str>) -> RequestBuilder {
+    match accept {
+        Some(accept) => request.header(reqwest::header::ACCEPT, accept),
+        None => request,
+    }
+}
+
+/// Builds the request and buffers a streamed body (ie. a multipart form), so it can be read and cloned.
+async fn buffer_body(
+    request: RequestBuilder,
+) -> Result<(reqwest::Client, reqwest::Request), APIError> {
+    use http_body_util::BodyExt;
+
+    let (http_client, request) = request.build_split();
+
+    let mut request = request.map_err(|error| APIError::ServerError {
+        message: error.to_string(),
+        request_id: None,
+    })?;
+
+    if let Some(body) = request.body_mut().take() {
+        let body = match body.as_bytes() {
+            Some(_) => body,
+            None => body
+                .collect()
+                .await
+                .map(|collected| reqwest::Body::from(collected.to_bytes()))
+                .map_err(|error| APIError::ServerError {
+                    message: error.to_string(),
+                    request_id: None,
+                })?,
+        };
+
+        *request.body_mut() = Some(body);
+    }
+
+    Ok((http_client, request))
+}
+
+/// The redirect policy of the default HTTP client, it follows at most 5 redirects and refuses to downgrade from HTTPS to HTTP.
+/// Reqwest removes the authorization header when a redirect changes the host, port or scheme.
+pub fn default_redirect_policy() -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(|attempt| {
+        let downgrade = attempt.url().scheme() == "http"
+            && attempt.previous().iter().any(|url| url.scheme() == "https");
+
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if downgrade {
+            attempt.error("refusing to redirect from HTTPS to HTTP")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+fn http_client(
+    policy: &Arc<reqwest::redirect::Policy>,
+    tls: &TlsSettings,
+) -> Result<reqwest::Client, APIError> {
+    let policy = policy.clone();
+    let builder =
+        reqwest::Client::builder().redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            policy.redirect(attempt)
+        }));
+
+    #[cfg(feature = "rustls-tls")]
+    let builder = {
+        let mut builder = builder;
+
+        if let Some(pem) = &tls.identity_pem {
+            builder = builder.identity(
+                reqwest::Identity::from_pem(pem).map_err(|error| invalid_tls("identity", error))?,
+            );
+        }
+
+        if !tls.pinned_certificates.is_empty() {
+            let certificates = tls
+                .pinned_certificates
+                .iter()
+                .map(|der| reqwest::Certificate::from_der(der))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| invalid_tls("pinned certificate", error))?;
+
+            builder = builder.tls_certs_only(certificates);
+        }
+
+        builder
+    };
+
+    #[cfg(not(feature = "rustls-tls"))]
+    let _ = tls;
+
+    builder
+        .build()
+        .map_err(|error| invalid_tls("HTTP client", error))
+}
+
+fn invalid_tls(what: &str, error: reqwest::Error) -> APIError {
+    use std::error::Error;
+
+    match error.source() {
+        Some(source) => APIError::TlsError(format!("invalid {what}: {error}: {source}")),
+        None => APIError::TlsError(format!("invalid {what}: {error}")),
+    }
+}
+
+fn redact(api_key: &str, redactor: Option<&Redactor>, text: &str) -> String {
+    let text = if api_key.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(api_key, REDACTED)
+    };
+
+    match redactor {
+        Some(redactor) => (redactor.0)(&text),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::audio::{AudioOutputFormat, AudioTranscriptionParametersBuilder};
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+    use crate::v1::test_support::{json_response, response, serve};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_redact_api_key_by_default() {
+        let client = Client::new("sk-secret".to_string());
+
+        assert_eq!(
+            client.redact("invalid key sk-secret provided"),
+            "invalid key [REDACTED] provided"
+        );
+    }
+
+    #[test]
+    fn test_redact_with_custom_redactor() {
+        let mut client = Client::new("sk-secret".to_string());
+
+        client.set_redactor(|text| text.replace("John Doe", "<name>"));
+
+        let error = APIError::BadRequestError {
+            message: "sk-secret: John Doe said hello".to_string(),
+            request_id: Some("req_123".to_string()),
+        }
+        .map_message(|message| client.redact(message));
+
+        assert_eq!(
+            error,
+            APIError::BadRequestError {
+                message: "[REDACTED]: <name> said hello".to_string(),
+                request_id: Some("req_123".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_key_format() {
+        assert!(
+            Client::validate_key_format("sk-abcdefghijklmnopqrstuvwxyz0123456789ABCDEFGHIJKL")
+                .is_ok()
+        );
+        assert!(Client::validate_key_format("sk-proj-Ab3_dEf-GhIjKlMnOpQrStUvWxYz0123").is_ok());
+
+        for api_key in [
+            "",
+            "abcdefghijklmnopqrstuvwxyz",
+            "sk-short",
+            "sk-proj-abc",
+            "sk-abcdefghij klmnopqrstuvwxyz",
+        ] {
+            let error = Client::validate_key_format(api_key).unwrap_err();
+
+            assert!(
+                matches!(&error, APIError::ValidationError { field, value, .. } if field == "api_key" && (api_key.is_empty() || !value.contains(api_key)))
+            );
+        }
+
+        let mut client = Client::new("gsk_compatible_provider_key".to_string());
+        client.set_strict_key_validation(true);
+
+        let error = client.models().list().await.unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "api_key"));
+
+        client.set_base_url("http://127.0.0.1:1");
+
+        let error = client.models().list().await.unwrap_err();
+
+        assert!(!matches!(error, APIError::ValidationError { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_try_new_from_env_validates_like_requests() {
+        std::env::set_var("OPENAI_API_KEY", "gsk_compatible_provider_key");
+
+        let error = Client::try_new_from_env().unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "api_key"));
+
+        std::env::set_var("OPENAI_API_KEY", "sk-abcdefghijklmnopqrstuvwxyz");
+
+        let mut client = Client::try_new_from_env().unwrap();
+
+        assert!(client.config_summary().strict_key_validation);
 
-            event_soure.close();
+        client.api_key = "gsk_compatible_provider_key".to_string();
+
+        let error = client.models().list().await.unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "api_key"));
+
+        std::env::remove_var("OPENAI_API_KEY");
+
+        assert!(Client::try_new_from_env().is_err());
+    }
+
+    #[test]
+    fn test_default_transcription_format() {
+        let mut client = Client::new("sk-secret".to_string());
+
+        assert_eq!(client.transcription_format(None), None);
+
+        client.set_default_transcription_format(AudioOutputFormat::VerboseJson);
+
+        assert_eq!(
+            client.transcription_format(None),
+            Some(AudioOutputFormat::VerboseJson)
+        );
+        assert_eq!(
+            client.transcription_format(Some(AudioOutputFormat::Srt)),
+            Some(AudioOutputFormat::Srt)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_signer_observes_final_json_and_multipart_requests() {
+        let (base_url, server) = serve([
+            response(
+                "200 OK",
+                &[],
+                r#"{"id": "chatcmpl-1", "created": 1, "model": "gpt-4o", "choices": []}"#,
+            ),
+            response("200 OK", &[], "Hello!"),
+        ])
+        .await;
+
+        let observed = Arc::new(Mutex::new(vec![]));
+        let signer_observed = observed.clone();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_request_signer(move |request| {
+            let body = request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| bytes.to_vec())
+                .unwrap_or_default();
+
+            let signature = format!(
+                "{} {} {}",
+                request.method(),
+                request.url().path(),
+                body.len()
+            );
+
+            request
+                .headers_mut()
+                .insert("x-signature", signature.parse().unwrap());
+
+            signer_observed.lock().unwrap().push(body);
         });
 
-        Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        client.chat().create(parameters).await.unwrap();
+
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .model("whisper-1")
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "audio.mp3",
+            )))
+            .build()
+            .unwrap();
+
+        client
+            .audio()
+            .create_transcription(parameters)
+            .await
+            .unwrap();
+
+        let received = server.await.unwrap();
+        let observed = observed.lock().unwrap();
+
+        for (request, observed_body) in received.iter().zip(observed.iter()) {
+            let signature = request.header("x-signature").unwrap();
+
+            assert!(!observed_body.is_empty());
+            assert_eq!(&request.body, observed_body);
+            assert!(signature.starts_with("POST /"));
+            assert!(signature.ends_with(&format!(" {}", observed_body.len())));
+        }
+
+        assert_eq!(received.len(), 2);
+        assert!(received[1]
+            .header("content-type")
+            .unwrap()
+            .starts_with("multipart/form-data"));
     }
-}
 
-impl Default for Client {
-    fn default() -> Self {
-        Client {
-            http_client: reqwest::Client::new(),
-            base_url: OPENAI_API_V1_ENDPOINT.to_string(),
-            api_key: "".to_string(),
-            headers: None,
-            organization: None,
-            project: None,
+    #[tokio::test]
+    async fn test_end_user_is_hashed_into_safety_identifier() {
+        use crate::v1::dry_run::PreparedBody;
+        use crate::v1::resources::response::request::{ResponseInput, ResponseParametersBuilder};
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_end_user(|| Some("user@example.com".to_string()));
+
+        let chat = |safety_identifier: Option<&str>| {
+            let mut parameters = ChatCompletionParametersBuilder::default()
+                .model("gpt-4o")
+                .messages(vec![ChatMessage::User {
+                    content: ChatMessageContent::Text("Hello!".to_string()),
+                    name: None,
+                }])
+                .build()
+                .unwrap();
+
+            parameters.safety_identifier = safety_identifier.map(str::to_string);
+
+            parameters
+        };
+
+        let safety_identifier = |body: PreparedBody| match body {
+            PreparedBody::Json(body) => body["safety_identifier"].clone(),
+            _ => panic!("expected a JSON body"),
+        };
+
+        let parameters = chat(None);
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.chat().create(parameters).await })
+            .await
+            .unwrap();
+
+        // the SHA-256 digest of user@example.com
+        assert_eq!(
+            safety_identifier(prepared.body),
+            "b4c9a289323b21a01c3e940f150eb9b8c542587f1abfd8f0e1cc1ffc5e475514"
+        );
+
+        let parameters = chat(Some("explicit"));
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.chat().create(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(safety_identifier(prepared.body), "explicit");
+
+        client.set_hash_end_user(false);
+
+        let parameters = ResponseParametersBuilder::default()
+            .model("gpt-4o")
+            .input(ResponseInput::Text("Hello!".to_string()))
+            .build()
+            .unwrap();
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.responses().create(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(safety_identifier(prepared.body), "user@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_request_converters_are_applied_in_order() {
+        use crate::v1::adapters::{CamelCaseKeys, FieldRenameAdapter};
+        use crate::v1::dry_run::PreparedBody;
+        use crate::v1::resources::audio::{
+            AudioSpeechParametersBuilder, AudioSpeechResponseFormat, SpeechVoice,
+        };
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .add_request_converter(CamelCaseKeys)
+            .add_request_converter(FieldRenameAdapter::new([
+                ("responseFormat", "format"),
+                ("input", "text"),
+            ]));
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .voice(SpeechVoice::Single("alloy".to_string()))
+            .response_format(AudioSpeechResponseFormat::Wav)
+            .build()
+            .unwrap();
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_speech(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(body) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert_eq!(
+            body,
+            serde_json::json!({"model": "tts-1", "text": "Hello!", "voice": "alloy", "format": "wav"})
+        );
+    }
+
+    #[test]
+    fn test_config_summary_redacts_the_api_key() {
+        let mut client = Client::new("sk-secret".to_string());
+        client
+            .set_base_url("https://gateway.example.com/v1")
+            .add_header("x-gateway-key", "gw-secret");
+
+        let summary = client.config_summary();
+        let logged = format!("{summary:?}");
+
+        assert_eq!(summary.base_url, "https://gateway.example.com/v1");
+        assert!(!summary.is_openai);
+        assert_eq!(summary.headers, vec!["x-gateway-key".to_string()]);
+        assert!(logged.contains("https://gateway.example.com/v1"));
+        assert!(!logged.contains("sk-secret"));
+        assert!(!logged.contains("gw-secret"));
+        assert_eq!(Client::new(String::new()).config_summary().api_key, "");
+    }
+
+    #[tokio::test]
+    async fn test_accept_language_header_is_sent_when_set() {
+        let mut client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.models().list().await })
+            .await
+            .unwrap();
+
+        assert!(!prepared.headers.contains_key("accept-language"));
+
+        client.set_accept_language("de-DE, de;q=0.9");
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.models().list().await })
+            .await
+            .unwrap();
+
+        assert_eq!(prepared.headers["accept-language"], "de-DE, de;q=0.9");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_returned_with_responses_and_errors() {
+        async fn client_for(status: &str, body: &str) -> Client {
+            let (base_url, _) = serve([response(
+                status,
+                &["content-type: application/json", "x-request-id: req_123"],
+                body,
+            )])
+            .await;
+
+            let mut client = Client::new("sk-test".to_string());
+            client.set_base_url(&base_url);
+
+            client
+        }
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let client = client_for(
+            "200 OK",
+            r#"{"id": "chatcmpl-1", "object": "chat.completion", "created": 0, "model": "gpt-4o", "choices": []}"#,
+        )
+        .await;
+
+        let wrapped = client
+            .chat()
+            .create_wrapped(parameters.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(wrapped.headers.x_request_id.as_deref(), Some("req_123"));
+
+        let client = client_for(
+            "400 Bad Request",
+            r#"{"error": {"message": "Invalid model", "param": "model"}}"#,
+        )
+        .await;
+
+        let error = client.chat().create(parameters).await.unwrap_err();
+
+        assert!(matches!(
+            &error,
+            APIError::BadRequestError { message, request_id: Some(request_id) }
+                if message == r#"{"error": {"message": "Invalid model", "param": "model"}}"# && request_id == "req_123"
+        ));
+        assert_eq!(error.request_id().as_deref(), Some("req_123"));
+        assert!(error.to_string().ends_with("(request id: req_123)"));
+
+        let body = error.body().unwrap();
+
+        assert_eq!(body.message, "Invalid model");
+        assert_eq!(body.param.as_deref(), Some("model"));
+        assert_eq!(body.request_id.as_deref(), Some("req_123"));
+
+        let error = APIError::BadRequestError {
+            message: "failed (request id: req_456)".to_string(),
+            request_id: None,
+        };
+
+        assert_eq!(error.request_id(), None);
+        assert_eq!(
+            error.body().unwrap().message,
+            "failed (request id: req_456)"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_request_span_records_the_request_id() {
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<(String, String)>>>);
+
+        impl Visit for &Recorder {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((field.name().to_string(), value.to_string()));
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.record_str(field, &format!("{value:?}"));
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                span.record(&mut &*self);
+
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, values: &Record<'_>) {
+                values.record(&mut &*self);
+            }
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, _: &tracing::Event<'_>) {}
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let (base_url, _) = serve([response(
+            "400 Bad Request",
+            &["content-type: application/json", "x-request-id: req_123"],
+            r#"{"error": {"message": "Invalid model"}}"#,
+        )])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let error = client.models().list().await.unwrap_err();
+
+        assert_eq!(error.request_id().as_deref(), Some("req_123"));
+
+        let fields = recorder.0.lock().unwrap().clone();
+
+        assert!(fields.contains(&("method".to_string(), "GET".to_string())));
+        assert!(fields.contains(&("path".to_string(), "/models".to_string())));
+        assert!(fields.contains(&("request_id".to_string(), "req_123".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_of_rate_limits_pauses_the_token_budget() {
+        use crate::v1::clock::{Clock, MockClock};
+        use crate::v1::error::{Backpressure, BackpressureScope};
+        use crate::v1::token_budget::TokenBudget;
+        use std::time::Duration;
+
+        // the three formats OpenAI sends: milliseconds, seconds and the duration until the limit resets
+        let headers = [
+            "retry-after-ms: 1500\r\nx-ratelimit-remaining-tokens: 0",
+            "retry-after: 2\r\nx-ratelimit-remaining-requests: 0",
+            "x-ratelimit-remaining-tokens: 0\r\nx-ratelimit-reset-tokens: 6m12s\r\nx-ratelimit-reset-requests: 20ms",
+        ];
+
+        let (base_url, _) = serve(headers.map(|headers| {
+            response(
+                "429 Too Many Requests",
+                &[
+                    "content-type: application/json",
+                    headers,
+                    "x-request-id: req_123",
+                ],
+                r#"{"error": {"message": "Rate limit reached", "type": "requests"}}"#,
+            )
+        }))
+        .await;
+
+        let clock = MockClock::auto_advancing();
+        let budget = TokenBudget::with_clock(100_000, Arc::new(clock.clone()) as Arc<dyn Clock>);
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_base_url(&base_url)
+            .set_clock(clock.clone())
+            .set_token_budget(budget.clone());
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let expected = [
+            (Duration::from_millis(1500), BackpressureScope::Tokens),
+            (Duration::from_secs(2), BackpressureScope::Requests),
+            (Duration::from_secs(372), BackpressureScope::Tokens),
+        ];
+
+        for (wait, scope) in expected {
+            let error = client.chat().create(parameters.clone()).await.unwrap_err();
+
+            assert_eq!(error.backpressure(), Some(Backpressure { wait, scope }));
+            assert_eq!(error.request_id().as_deref(), Some("req_123"));
+            assert_eq!(error.body().unwrap().message, "Rate limit reached");
+            assert_eq!(error.retry_delay(1), wait);
+
+            // the next request that uses the budget waits out the rate limit
+            budget.acquire(1).await;
+
+            assert_eq!(clock.sleeps().last(), Some(&wait));
+        }
+
+        assert_eq!(
+            APIError::RateLimitError {
+                message: "Rate limit reached".to_string(),
+                backpressure: None,
+                request_id: None,
+            }
+            .retry_delay(2),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_of_rate_limits_throttles_other_requests() {
+        use crate::v1::clock::MockClock;
+        use std::time::Duration;
+
+        let (base_url, _) = serve([
+            response(
+                "429 Too Many Requests",
+                &["content-type: application/json", "retry-after: 3"],
+                r#"{"error": {"message": "Rate limit reached"}}"#,
+            ),
+            json_response("200 OK", r#"{"object": "list", "data": []}"#),
+        ])
+        .await;
+
+        let clock = MockClock::auto_advancing();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url).set_clock(clock.clone());
+
+        let error = client.models().list().await.unwrap_err();
+
+        assert_eq!(error.retry_delay(1), Duration::from_secs(3));
+        assert!(clock.sleeps().is_empty());
+
+        // a clone of the client, ie. in another task, waits out the rate limit before it sends its request
+        client.clone().models().list().await.unwrap();
+
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(3)]);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_stream_request_id_is_set_before_the_first_chunk() {
+        use crate::v1::test_support::{listen, read_request};
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        let (listener, base_url) = listen().await;
+        let (send_chunk, chunk_requested) = tokio::sync::oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nx-request-id: req_456\r\nconnection: close\r\n\r\n")
+                .await;
+
+            let _ = chunk_requested.await;
+            let _ = socket
+                .write_all(b"data: {\"id\": \"chatcmpl-1\", \"object\": \"chat.completion.chunk\", \"created\": 0, \"model\": \"gpt-4o\", \"choices\": []}\n\ndata: [DONE]\n\n")
+                .await;
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let (mut stream, metadata) = client
+            .chat()
+            .create_stream_with_metadata(parameters)
+            .await
+            .unwrap();
+
+        while metadata.request_id().is_none() {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(metadata.request_id().as_deref(), Some("req_456"));
+        assert_eq!(metadata.time_to_first_byte(), None);
+
+        send_chunk.send(()).unwrap();
+
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_stream_times_out_without_a_first_byte() {
+        use crate::v1::clock::MockClock;
+        use crate::v1::error::{StreamError, TimeoutPhase};
+        use crate::v1::test_support::{listen, read_request};
+        use futures::StreamExt;
+        use std::time::Duration;
+
+        let (listener, base_url) = listen().await;
+
+        // the connection is accepted, but never answered
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+
+            std::future::pending::<()>().await;
+        });
+
+        let clock = MockClock::new();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_clock(clock.clone());
+        client.set_first_byte_timeout(Duration::from_secs(10));
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let (mut stream, metadata) = client
+            .chat()
+            .create_stream_with_metadata(parameters)
+            .await
+            .unwrap();
+
+        let next = tokio::spawn(async move { stream.next().await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(!next.is_finished());
+
+        while !next.is_finished() {
+            clock.advance(Duration::from_secs(1));
+
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let error = next.await.unwrap().unwrap().unwrap_err();
+
+        assert!(matches!(
+            error,
+            APIError::StreamError(StreamError::Timeout(TimeoutPhase::FirstByte))
+        ));
+        assert!(error.is_retryable());
+        assert!(clock.elapsed() >= Duration::from_secs(10));
+        assert_eq!(metadata.time_to_first_byte(), None);
+
+        server.abort();
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_stream_errors_are_classified() {
+        use crate::v1::error::{StreamError, TransportError};
+        use crate::v1::test_support::serve_with;
+        use futures::StreamExt;
+
+        async fn first_error(response: &'static str) -> APIError {
+            // every connection is served with the response, the event source reconnects after the stream ends
+            let (base_url, _) = serve_with(usize::MAX, move |_| response.to_string()).await;
+
+            let mut client = Client::new("sk-test".to_string());
+            client.set_base_url(&base_url);
+
+            let parameters = ChatCompletionParametersBuilder::default()
+                .model("gpt-4o")
+                .messages(vec![ChatMessage::User {
+                    content: ChatMessageContent::Text("Hello!".to_string()),
+                    name: None,
+                }])
+                .build()
+                .unwrap();
+
+            let mut stream = client.chat().create_stream(parameters).await.unwrap();
+
+            loop {
+                if let Err(error) = stream.next().await.unwrap() {
+                    return error;
+                }
+            }
         }
+
+        let error = first_error(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\ndata: {\"error\": {\"message\": \"The server is overloaded\", \"code\": \"overloaded\"}}\n\n",
+        )
+        .await;
+
+        assert!(
+            matches!(&error, APIError::StreamError(StreamError::ServerEvent(body)) if body.message == "The server is overloaded")
+        );
+        assert_eq!(error.body().unwrap().code.as_deref(), Some("overloaded"));
+        assert!(!error.is_retryable());
+
+        let error = first_error(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\ndata: {\"choices\": 1}\n\n",
+        )
+        .await;
+
+        assert!(
+            matches!(&error, APIError::StreamError(StreamError::Parse { payload, .. }) if payload == "{\"choices\": 1}")
+        );
+        assert!(!error.is_retryable());
+
+        let error = first_error(
+            "HTTP/1.1 400 Bad Request\r\ncontent-type: application/json\r\ncontent-length: 39\r\nconnection: close\r\n\r\n{\"error\": {\"message\": \"Invalid model\"}}",
+        )
+        .await;
+
+        assert!(
+            matches!(&error, APIError::StreamError(StreamError::ServerEvent(body)) if body.message == "Invalid model"),
+            "{error:?}"
+        );
+
+        assert!(APIError::from(StreamError::Transport(
+            "connection reset".to_string().into()
+        ))
+        .is_retryable());
+
+        // the transport error keeps the error of the connection as its source
+        use std::error::Error;
+
+        let error = APIError::from(StreamError::Transport(TransportError::new(
+            std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset"),
+        )));
+        let transport = error.source().and_then(Error::source).unwrap();
+
+        assert_eq!(transport.to_string(), "connection reset");
+        assert!(transport
+            .source()
+            .unwrap()
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|error| error.kind() == std::io::ErrorKind::ConnectionReset));
     }
 }
@@ -0,0 +1,634 @@
+//! Splitting of long WAV audio into chunks and merging of the chunk transcriptions,
+//! and trimming of leading and trailing silence and probing of the duration (`audio-processing` feature).
+//! Without the `audio-processing` feature, `trim_silence` and `probe_duration` return `APIError::FeatureDisabled`.
+//!
+//! Audio is only split on sample frame boundaries, so every chunk is a valid WAV file.
+//! Compressed formats (ie. mp3) need to be converted to WAV first, as splitting them needs a decoder.
+use crate::v1::error::APIError;
+use crate::v1::resources::audio::AudioSpeechResponseFormat;
+use crate::v1::resources::audio::VerboseTranscriptionResponse;
+use bytes::Bytes;
+use std::ops::Range;
+use std::time::Duration;
+
+/// The maximum size of an audio file accepted by the transcription endpoint.
+pub const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+const WAV_HEADER_SIZE: usize = 44;
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavFormat {
+    /// The format code, PCM (1) or IEEE float (3).
+    pub format: u16,
+    /// The number of channels.
+    pub channels: u16,
+    /// The number of frames per second.
+    pub sample_rate: u32,
+    /// The number of bits per sample.
+    pub bits_per_sample: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioChunk {
+    /// The index of the chunk, starting at 0.
+    pub index: usize,
+    /// The start and end time of the chunk in the original audio, in seconds.
+    pub time_range: Range<f32>,
+    /// The chunk as WAV file.
+    pub wav: Bytes,
+}
+
+impl WavFormat {
+    fn block_align(&self) -> usize {
+        self.channels as usize * self.bits_per_sample.div_ceil(8) as usize
+    }
+}
+
+/// Splits a WAV file into chunks of at most `chunk_duration` (and at most 25 MB) that overlap by `overlap`.
+pub fn split_wav(
+    wav: &[u8],
+    chunk_duration: Duration,
+    overlap: Duration,
+) -> Result<Vec<AudioChunk>, APIError> {
+    let (format, data) = parse_wav(wav)?;
+
+    let block_align = format.block_align();
+    let total_frames = data.len() / block_align;
+
+    let frames_per_chunk = ((chunk_duration.as_secs_f64() * format.sample_rate as f64) as usize)
+        .min((MAX_UPLOAD_BYTES - WAV_HEADER_SIZE) / block_align);
+    let overlap_frames = (overlap.as_secs_f64() * format.sample_rate as f64) as usize;
+
+    if frames_per_chunk == 0 || overlap_frames * 2 >= frames_per_chunk {
+        return Err(APIError::ValidationError {
+            field: "overlap".to_string(),
+            value: format!("{overlap:?}"),
+            allowed: "less than half of the chunk duration".to_string(),
+        });
+    }
+
+    let seconds = |frame: usize| frame as f32 / format.sample_rate as f32;
+
+    let mut chunks = vec![];
+    let mut start = 0;
+
+    loop {
+        let end = (start + frames_per_chunk).min(total_frames);
+
+        chunks.push(AudioChunk {
+            index: chunks.len(),
+            time_range: seconds(start)..seconds(end),
+            wav: write_wav(&format, &data[start * block_align..end * block_align]),
+        });
+
+        if end == total_frames {
+            break;
+        }
+
+        start = end - overlap_frames;
+    }
+
+    Ok(chunks)
+}
+
+/// Merges the transcriptions of overlapping chunks, in order, into one transcription of the whole audio.
+/// Timestamps are offset by the start of their chunk. In the overlap of two chunks, segments and words
+/// that start before the middle of the overlap are taken from the first chunk, the others from the second.
+pub fn merge_transcriptions(
+    transcriptions: Vec<(Range<f32>, VerboseTranscriptionResponse)>,
+) -> VerboseTranscriptionResponse {
+    let cuts: Vec<f32> = transcriptions
+        .windows(2)
+        .map(|pair| (pair[0].0.end + pair[1].0.start) / 2.0)
+        .collect();
+
+    let mut merged = VerboseTranscriptionResponse {
+        task: String::new(),
+        language: String::new(),
+        duration: 0.0,
+        text: String::new(),
+        words: None,
+        segments: None,
+    };
+
+    let mut texts = vec![];
+
+    for (index, (time_range, transcription)) in transcriptions.into_iter().enumerate() {
+        let lower = if index == 0 {
+            f32::NEG_INFINITY
+        } else {
+            cuts[index - 1]
+        };
+        let upper = cuts.get(index).copied().unwrap_or(f32::INFINITY);
+        let keep = |start: f32| start >= lower && start < upper;
+
+        if index == 0 {
+            merged.task = transcription.task;
+            merged.language = transcription.language;
+        }
+
+        merged.duration = merged.duration.max(time_range.end);
+
+        if let Some(words) = transcription.words {
+            let merged_words = merged.words.get_or_insert_with(Vec::new);
+
+            for mut word in words {
+                word.start += time_range.start;
+                word.end += time_range.start;
+
+                if keep(word.start) {
+                    merged_words.push(word);
+                }
+            }
+        }
+
+        match transcription.segments {
+            Some(segments) => {
+                let merged_segments = merged.segments.get_or_insert_with(Vec::new);
+
+                for mut segment in segments {
+                    segment.start += time_range.start;
+                    segment.end += time_range.start;
+
+                    if keep(segment.start) {
+                        segment.id = merged_segments.len() as u32;
+                        texts.push(segment.text.trim().to_string());
+                        merged_segments.push(segment);
+                    }
+                }
+            }
+            None => texts.push(transcription.text.trim().to_string()),
+        }
+    }
+
+    merged.text = texts
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    merged
+}
+
+/// The duration of the windows of `trim_silence`, in milliseconds.
+#[cfg(feature = "audio-processing")]
+const SILENCE_WINDOW_MS: usize = 10;
+
+/// Trims the leading and trailing silence of WAV or PCM audio, ie. to upload less audio for a transcription.
+/// The RMS level of every 10 ms window is compared to `threshold_db` in dBFS (ie. -40.0), the windows
+/// below it at the start and the end are removed. Silence within the audio is kept.
+///
+/// Only uncompressed audio is supported: WAV (PCM or IEEE float), which is returned as WAV, and raw PCM
+/// (24kHz 16-bit mono, like speech responses), which is returned as PCM. Audio that is silent throughout is returned empty.
+#[cfg(feature = "audio-processing")]
+pub fn trim_silence(
+    bytes: &[u8],
+    format: AudioSpeechResponseFormat,
+    threshold_db: f32,
+) -> Result<Vec<u8>, APIError> {
+    let (wav_format, data) = match format {
+        AudioSpeechResponseFormat::Wav => parse_wav(bytes)?,
+        AudioSpeechResponseFormat::Pcm => (
+            WavFormat {
+                format: WAVE_FORMAT_PCM,
+                channels: 1,
+                sample_rate: 24_000,
+                bits_per_sample: 16,
+            },
+            bytes,
+        ),
+        _ => {
+            return Err(APIError::ValidationError {
+                field: "format".to_string(),
+                value: format!("{format:?}"),
+                allowed: "wav or pcm, compressed audio needs to be decoded first".to_string(),
+            })
+        }
+    };
+
+    let block_align = wav_format.block_align();
+    let window_size =
+        (wav_format.sample_rate as usize * SILENCE_WINDOW_MS / 1000).max(1) * block_align;
+
+    let threshold = 10f64.powf(threshold_db as f64 / 20.0);
+    let is_loud = |window: &[u8]| rms(&wav_format, window) >= threshold;
+
+    // a trailing partial frame is dropped, as it can't be played
+    let data = &data[..data.len() - data.len() % block_align];
+    let windows: Vec<&[u8]> = data.chunks(window_size).collect();
+
+    let trimmed = match (
+        windows.iter().position(|window| is_loud(window)),
+        windows.iter().rposition(|window| is_loud(window)),
+    ) {
+        (Some(first), Some(last)) => {
+            let end = (last * window_size + windows[last].len()).min(data.len());
+
+            &data[first * window_size..end]
+        }
+        _ => &[],
+    };
+
+    Ok(match format {
+        AudioSpeechResponseFormat::Wav => write_wav(&wav_format, trimmed).to_vec(),
+        _ => trimmed.to_vec(),
+    })
+}
+
+/// Trims the leading and trailing silence of WAV or PCM audio, which needs the `audio-processing` feature.
+#[cfg(not(feature = "audio-processing"))]
+pub fn trim_silence(
+    _bytes: &[u8],
+    _format: AudioSpeechResponseFormat,
+    _threshold_db: f32,
+) -> Result<Vec<u8>, APIError> {
+    Err(APIError::FeatureDisabled("audio-processing".to_string()))
+}
+
+/// Returns the duration of a WAV file from its header, without reading the audio.
+/// The duration is the size of the data chunk divided by the byte rate of the fmt chunk, so compressed WAV
+/// codecs are supported as well. Other formats (ie. mp3) have no duration in their header and return a validation error.
+#[cfg(feature = "audio-processing")]
+pub fn probe_duration(path: impl AsRef<std::path::Path>) -> Result<Duration, APIError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = path.as_ref();
+    let file_error =
+        |error: std::io::Error| APIError::FileError(format!("{}: {error}", path.display()));
+    let unsupported = |reason: &str| APIError::ValidationError {
+        field: "audio".to_string(),
+        value: format!("{}: {reason}", path.display()),
+        allowed: "a WAV file, compressed audio (ie. mp3) needs to be decoded to get its duration"
+            .to_string(),
+    };
+
+    let mut file = std::fs::File::open(path).map_err(file_error)?;
+    let length = file.metadata().map_err(file_error)?.len();
+
+    let mut header = [0; 12];
+
+    if file.read_exact(&mut header).is_err()
+        || &header[0..4] != b"RIFF"
+        || &header[8..12] != b"WAVE"
+    {
+        return Err(unsupported("not a WAV file"));
+    }
+
+    let mut byte_rate = None;
+    let mut offset = 12;
+
+    loop {
+        let mut chunk = [0; 8];
+
+        if file.read_exact(&mut chunk).is_err() {
+            return Err(unsupported("no data chunk"));
+        }
+
+        let size = u32::from_le_bytes(chunk[4..8].try_into().unwrap()) as u64;
+
+        match &chunk[0..4] {
+            b"fmt " => {
+                let mut body = [0; 16];
+
+                file.read_exact(&mut body)
+                    .map_err(|_| unsupported("invalid fmt chunk"))?;
+
+                byte_rate = Some(u32::from_le_bytes(body[8..12].try_into().unwrap()));
+            }
+            b"data" => {
+                let byte_rate = byte_rate
+                    .filter(|byte_rate| *byte_rate > 0)
+                    .ok_or_else(|| unsupported("data before the fmt chunk"))?;
+
+                // a WAV file that was streamed has no data size (0 or the maximum), the data ends with the file
+                let available = length.saturating_sub(offset + 8);
+                let size = match size {
+                    0 | 0xFFFF_FFFF => available,
+                    size => size.min(available),
+                };
+
+                return Ok(Duration::from_secs_f64(size as f64 / byte_rate as f64));
+            }
+            _ => {}
+        }
+
+        // chunks are padded to an even size
+        offset += 8 + size + size % 2;
+
+        file.seek(SeekFrom::Start(offset)).map_err(file_error)?;
+    }
+}
+
+/// Returns the duration of a WAV file from its header, which needs the `audio-processing` feature.
+#[cfg(not(feature = "audio-processing"))]
+pub fn probe_duration(_path: impl AsRef<std::path::Path>) -> Result<Duration, APIError> {
+    Err(APIError::FeatureDisabled("audio-processing".to_string()))
+}
+
+/// The RMS level of the samples of all channels, from 0.0 (silence) to 1.0 (full scale).
+#[cfg(feature = "audio-processing")]
+fn rms(format: &WavFormat, data: &[u8]) -> f64 {
+    let bytes_per_sample = format.bits_per_sample.div_ceil(8) as usize;
+
+    let sample = |bytes: &[u8]| -> f64 {
+        match (format.format, bytes_per_sample) {
+            (WAVE_FORMAT_IEEE_FLOAT, 4) => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            (WAVE_FORMAT_IEEE_FLOAT, 8) => f64::from_le_bytes(bytes.try_into().unwrap()),
+            // 8-bit PCM is unsigned
+            (_, 1) => (bytes[0] as f64 - 128.0) / 128.0,
+            (_, size) => {
+                // sign extend from the most significant byte
+                let mut value = [if bytes[size - 1] & 0x80 != 0 { 0xFF } else { 0 }; 8];
+                value[8 - size..].copy_from_slice(bytes);
+                value.rotate_left(8 - size);
+
+                i64::from_le_bytes(value) as f64 / (1u64 << (size * 8 - 1)) as f64
+            }
+        }
+    };
+
+    let samples = data.chunks_exact(bytes_per_sample);
+    let count = samples.len();
+
+    if count == 0 {
+        return 0.0;
+    }
+
+    (samples.map(|bytes| sample(bytes).powi(2)).sum::<f64>() / count as f64).sqrt()
+}
+
+fn parse_wav(wav: &[u8]) -> Result<(WavFormat, &[u8]), APIError> {
+    let invalid = |reason: &str| APIError::ValidationError {
+        field: "audio".to_string(),
+        value: reason.to_string(),
+        allowed: "a PCM or IEEE float WAV file".to_string(),
+    };
+
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let body = &wav[offset + 8..(offset + 8 + size).min(wav.len())];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let read_u16 = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+
+                let mut code = read_u16(0);
+
+                if code == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    code = read_u16(24);
+                }
+
+                if code != WAVE_FORMAT_PCM && code != WAVE_FORMAT_IEEE_FLOAT {
+                    return Err(invalid("compressed WAV audio"));
+                }
+
+                format = Some(WavFormat {
+                    format: code,
+                    channels: read_u16(2),
+                    sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+                    bits_per_sample: read_u16(14),
+                });
+            }
+            b"data" => {
+                let format = format.ok_or_else(|| invalid("data before the fmt chunk"))?;
+
+                if format.channels == 0 || format.sample_rate == 0 || format.bits_per_sample == 0 {
+                    return Err(invalid("invalid fmt chunk"));
+                }
+
+                return Ok((format, body));
+            }
+            _ => {}
+        }
+
+        // chunks are padded to an even size
+        offset += 8 + size + size % 2;
+    }
+
+    Err(invalid("no data chunk"))
+}
+
+pub(crate) fn write_wav(format: &WavFormat, data: &[u8]) -> Bytes {
+    let block_align = format.block_align() as u16;
+    let byte_rate = format.sample_rate * block_align as u32;
+
+    let mut wav = Vec::with_capacity(WAV_HEADER_SIZE + data.len());
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&format.format.to_le_bytes());
+    wav.extend_from_slice(&format.channels.to_le_bytes());
+    wav.extend_from_slice(&format.sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&format.bits_per_sample.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(data);
+
+    Bytes::from(wav)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::audio_chunking::{merge_transcriptions, split_wav, WavFormat};
+    use crate::v1::resources::audio::VerboseTranscriptionResponse;
+    use std::time::Duration;
+
+    #[test]
+    fn test_split_wav_on_frame_boundaries_with_overlap() {
+        let format = WavFormat {
+            format: 1,
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+        };
+
+        // 25 seconds of stereo 16-bit audio, 4 bytes per frame
+        let data: Vec<u8> = (0..25_000u32)
+            .flat_map(|frame| frame.to_le_bytes())
+            .collect();
+        let wav = super::write_wav(&format, &data);
+
+        let chunks = split_wav(&wav, Duration::from_secs(10), Duration::from_secs(2)).unwrap();
+
+        let time_ranges: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.time_range.clone())
+            .collect();
+
+        assert_eq!(time_ranges, vec![0.0..10.0, 8.0..18.0, 16.0..25.0]);
+
+        let (chunk_format, chunk_data) = super::parse_wav(&chunks[1].wav).unwrap();
+
+        assert_eq!(chunk_format, format);
+        assert_eq!(chunk_data.len(), 10_000 * 4);
+        assert_eq!(chunk_data[..4], 8000u32.to_le_bytes());
+
+        assert!(split_wav(&wav, Duration::from_secs(10), Duration::from_secs(5)).is_err());
+        assert!(split_wav(b"ID3\x04", Duration::from_secs(10), Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_merge_transcriptions_offsets_timestamps_and_removes_overlap() {
+        let transcription = |segments: &[(f32, f32, &str)]| -> VerboseTranscriptionResponse {
+            serde_json::from_value(serde_json::json!({
+                "task": "transcribe",
+                "language": "english",
+                "duration": 10.0,
+                "text": "",
+                "segments": segments.iter().enumerate().map(|(id, (start, end, text))| serde_json::json!({
+                    "id": id, "seek": 0, "start": start, "end": end, "text": text, "tokens": [],
+                    "temperature": 0.0, "avg_logprob": -0.1, "compression_ratio": 1.0, "no_speech_prob": 0.0
+                })).collect::<Vec<_>>(),
+            }))
+            .unwrap()
+        };
+
+        let merged = merge_transcriptions(vec![
+            (
+                0.0..10.0,
+                transcription(&[
+                    (0.0, 4.0, " One."),
+                    (4.0, 8.5, " Two."),
+                    (9.5, 10.0, " Thr"),
+                ]),
+            ),
+            (
+                8.0..18.0,
+                transcription(&[
+                    (0.5, 1.0, " Two."),
+                    (1.5, 4.0, " Three."),
+                    (4.0, 10.0, " Four."),
+                ]),
+            ),
+        ]);
+
+        let segments = merged.segments.unwrap();
+
+        assert_eq!(merged.text, "One. Two. Three. Four.");
+        assert_eq!(merged.duration, 18.0);
+        assert_eq!(segments.len(), 4);
+        assert_eq!(
+            (segments[2].id, segments[2].start, segments[2].end),
+            (2, 9.5, 12.0)
+        );
+        assert_eq!(segments[3].start, 12.0);
+    }
+
+    #[cfg(feature = "audio-processing")]
+    #[test]
+    fn test_trim_silence_of_padded_sine() {
+        use crate::v1::audio_chunking::trim_silence;
+        use crate::v1::resources::audio::AudioSpeechResponseFormat;
+
+        let format = WavFormat {
+            format: 1,
+            channels: 1,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+        };
+
+        // 0.5 seconds of silence with faint noise, 1 second of a 50 Hz sine and 0.3 seconds of silence
+        let samples: Vec<i16> = std::iter::repeat_n(0, 250)
+            .chain((0..250).map(|index| if index % 2 == 0 { 3 } else { -3 }))
+            .chain((0..1000).map(|index| {
+                ((index as f64 * 50.0 * std::f64::consts::TAU / 1000.0).sin() * 16_000.0) as i16
+            }))
+            .chain(std::iter::repeat_n(0, 300))
+            .collect();
+        let data: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+
+        let trimmed = trim_silence(
+            &super::write_wav(&format, &data),
+            AudioSpeechResponseFormat::Wav,
+            -40.0,
+        )
+        .unwrap();
+        let (trimmed_format, trimmed_data) = super::parse_wav(&trimmed).unwrap();
+
+        assert_eq!(trimmed_format, format);
+        assert_eq!(trimmed_data, &data[1000..3000]);
+
+        // raw PCM is read as 24kHz, so the windows are 240 samples and the cuts are on their boundaries
+        let trimmed = trim_silence(&data, AudioSpeechResponseFormat::Pcm, -40.0).unwrap();
+
+        assert_eq!(trimmed, &data[960..3360]);
+
+        assert!(
+            trim_silence(&data[..1000], AudioSpeechResponseFormat::Pcm, -40.0)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            trim_silence(&data, AudioSpeechResponseFormat::Mp3, -40.0),
+            Err(crate::v1::error::APIError::ValidationError { .. })
+        ));
+    }
+
+    #[cfg(feature = "audio-processing")]
+    #[test]
+    fn test_probe_duration_reads_the_wav_header() {
+        use crate::v1::audio_chunking::probe_duration;
+        use crate::v1::error::APIError;
+
+        let directory = std::env::temp_dir().join(format!("probe-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        // 2.5 seconds of 16-bit stereo audio at 8kHz, with a list chunk before the data
+        let format = WavFormat {
+            format: 1,
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+        };
+        let wav = super::write_wav(&format, &vec![0; 80_000]);
+
+        let mut with_list = wav[..36].to_vec();
+        with_list.extend_from_slice(b"LIST\x05\x00\x00\x00INFO\x00\x00");
+        with_list.extend_from_slice(&wav[36..]);
+
+        let path = directory.join("audio.wav");
+        std::fs::write(&path, &with_list).unwrap();
+
+        assert_eq!(probe_duration(&path).unwrap(), Duration::from_millis(2500));
+
+        // the data size of a streamed WAV file is unknown
+        let mut streamed = wav.to_vec();
+        streamed[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        streamed.truncate(44 + 32_000);
+        std::fs::write(&path, &streamed).unwrap();
+
+        assert_eq!(probe_duration(&path).unwrap(), Duration::from_secs(1));
+
+        let path = directory.join("audio.mp3");
+        std::fs::write(&path, b"ID3\x04\x00\x00\x00\x00\x00\x00").unwrap();
+
+        assert!(matches!(
+            probe_duration(&path),
+            Err(APIError::ValidationError { value, .. }) if value.ends_with("not a WAV file")
+        ));
+        assert!(matches!(
+            probe_duration(directory.join("missing.wav")),
+            Err(APIError::FileError(_))
+        ));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
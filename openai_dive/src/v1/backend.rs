@@ -0,0 +1,660 @@
+//! Detection of the features of an OpenAI-compatible backend, ie. LM Studio or Ollama, which ignore or reject some parameters.
+//!
+//! `Client::detect_capabilities` lists the models and sends two tiny chat completions that use the features,
+//! the OpenAI API is recognized by its base URL or its response headers and isn't probed.
+//! A feature counts as supported only when the response shows it was used, a feature that wasn't tested counts as unsupported.
+//! With `Client::set_degrade_gracefully` the chat completions leave out the parameters the backend doesn't support.
+use crate::v1::api::Client;
+use crate::v1::error::APIError;
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponseFormat};
+use crate::v1::validation::ValidationWarning;
+use serde_json::{json, Value};
+
+/// The headers the OpenAI API sends with every response.
+const OPENAI_HEADERS: [&str; 3] = [
+    "openai-organization",
+    "openai-processing-ms",
+    "openai-version",
+];
+
+/// A red PNG of 16x16 pixels, the image of the probe. A model that sees it names its color.
+const PROBE_IMAGE: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAABAAAAAQCAIAAACQkWg2AAAAFklEQVR42mP4z8BAEmIY1TCqYfhqAACQ+f8B8u7oVwAAAABJRU5ErkJggg==";
+
+/// The chat completion features of a backend, returned by `Client::detect_capabilities` or set with `Client::set_backend_capabilities`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    /// Whether the backend calls tools (`tools`, `tool_choice` and `parallel_tool_calls`).
+    pub supports_tools: bool,
+    /// Whether the backend accepts a `json_schema` response format.
+    pub supports_json_schema: bool,
+    /// Whether the backend sends the usage at the end of a stream (`stream_options`).
+    pub supports_stream_usage: bool,
+    /// Whether the model accepts images. Images are content, so they are never removed from the messages.
+    pub supports_vision: bool,
+    /// Whether the backend returns log probabilities (`logprobs` and `top_logprobs`).
+    pub supports_logprobs: bool,
+}
+
+/// A feature that a probe uses, to leave it out of the probe when the backend rejects it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProbeFeature {
+    Tools,
+    Vision,
+    Logprobs,
+    JsonSchema,
+    StreamUsage,
+}
+
+impl ProbeFeature {
+    /// Whether the error of a rejected probe names the feature.
+    fn is_named_by(self, text: &str) -> bool {
+        let words: &[&str] = match self {
+            ProbeFeature::Tools => &["tool"],
+            ProbeFeature::Vision => &["image", "vision", "multimodal"],
+            ProbeFeature::Logprobs => &["logprob"],
+            ProbeFeature::JsonSchema => &["response_format", "json_schema", "schema"],
+            ProbeFeature::StreamUsage => &["stream_options", "include_usage"],
+        };
+
+        words.iter().any(|word| text.contains(word))
+    }
+
+    /// Leaves the feature out of the probe.
+    fn remove_from(self, probe: &mut Value) {
+        let Some(probe) = probe.as_object_mut() else {
+            return;
+        };
+
+        match self {
+            ProbeFeature::Tools => {
+                probe.remove("tools");
+                probe.remove("tool_choice");
+            }
+            ProbeFeature::Vision => {
+                if let Some(content) = probe["messages"][0]["content"].as_array_mut() {
+                    content.retain(|part| part["type"] != "image_url");
+                }
+            }
+            ProbeFeature::Logprobs => {
+                probe.remove("logprobs");
+                probe.remove("top_logprobs");
+            }
+            ProbeFeature::JsonSchema => {
+                probe.remove("response_format");
+            }
+            ProbeFeature::StreamUsage => {
+                probe.remove("stream_options");
+            }
+        }
+    }
+}
+
+/// The outcome of a probe: the body of the accepted response, if any, and the features the backend rejected.
+struct ProbeOutcome {
+    response: Option<String>,
+    rejected: Vec<ProbeFeature>,
+}
+
+impl ProbeOutcome {
+    fn supports(&self, feature: ProbeFeature) -> bool {
+        self.response.is_some() && !self.rejected.contains(&feature)
+    }
+}
+
+impl BackendCapabilities {
+    /// The capabilities of the OpenAI API.
+    pub fn all() -> Self {
+        Self {
+            supports_tools: true,
+            supports_json_schema: true,
+            supports_stream_usage: true,
+            supports_vision: true,
+            supports_logprobs: true,
+        }
+    }
+
+    /// Reads the capabilities from the responses to the probes, a feature is supported when the response shows it was used:
+    /// the probe function was called, the color of the image was named, log probabilities were returned,
+    /// the content matches the JSON schema, and the usage was sent at the end of the stream.
+    fn from_probes(tools: &ProbeOutcome, structured: &ProbeOutcome) -> Self {
+        let response = tools
+            .response
+            .as_deref()
+            .and_then(|text| serde_json::from_str::<Value>(text).ok())
+            .unwrap_or_default();
+        let choice = &response["choices"][0];
+
+        let tool_calls = choice["message"]["tool_calls"]
+            .as_array()
+            .filter(|tool_calls| !tool_calls.is_empty());
+        let answer = match tool_calls {
+            Some(tool_calls) => tool_calls[0]["function"]["arguments"].to_string(),
+            None => choice["message"]["content"].to_string(),
+        };
+
+        let (content, streamed_usage) = structured
+            .response
+            .as_deref()
+            .map(read_structured_probe)
+            .unwrap_or_default();
+        let matches_schema =
+            serde_json::from_str::<Value>(&content).is_ok_and(|content| content["ok"].is_boolean());
+
+        Self {
+            supports_tools: tools.supports(ProbeFeature::Tools) && tool_calls.is_some(),
+            supports_json_schema: structured.supports(ProbeFeature::JsonSchema) && matches_schema,
+            supports_stream_usage: structured.supports(ProbeFeature::StreamUsage) && streamed_usage,
+            supports_vision: tools.supports(ProbeFeature::Vision)
+                && answer.to_lowercase().contains("red"),
+            supports_logprobs: tools.supports(ProbeFeature::Logprobs)
+                && !choice["logprobs"].is_null(),
+        }
+    }
+}
+
+/// Reads the content of the streamed probe, and whether a chunk of the stream had the usage.
+/// A backend that ignores `stream` answers with a chat completion, whose usage doesn't count.
+fn read_structured_probe(text: &str) -> (String, bool) {
+    let chunks: Vec<Value> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str(data.trim()).ok())
+        .collect();
+
+    if chunks.is_empty() {
+        let response: Value = serde_json::from_str(text).unwrap_or_default();
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default();
+
+        return (content.to_string(), false);
+    }
+
+    let content = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    let streamed_usage = chunks.iter().any(|chunk| chunk["usage"].is_object());
+
+    (content, streamed_usage)
+}
+
+impl Client {
+    /// Set the capabilities of the backend manually, so `detect_capabilities` doesn't probe it.
+    pub fn set_backend_capabilities(&mut self, capabilities: BackendCapabilities) -> &mut Self {
+        *self.backend_capabilities.write().unwrap() = Some(capabilities);
+
+        self
+    }
+
+    /// Set whether chat completions leave out the parameters the backend doesn't support, with an `UnsupportedParameter` warning,
+    /// instead of sending them and failing. The capabilities are detected before the first chat completion, unless they were set.
+    pub fn set_degrade_gracefully(&mut self, degrade_gracefully: bool) -> &mut Self {
+        self.degrade_gracefully = degrade_gracefully;
+
+        self
+    }
+
+    /// Returns the cached capabilities of the backend, without probing it.
+    pub fn backend_capabilities(&self) -> Option<BackendCapabilities> {
+        *self.backend_capabilities.read().unwrap()
+    }
+
+    /// Probes the chat completion features of the backend and caches them, the cached capabilities are returned without requests.
+    /// It lists the models and sends two chat completions of a few tokens to the first model: one with a tool, an image
+    /// and log probabilities, and a stream with the usage and a json_schema response format. A probe that is rejected
+    /// is sent once more without the features its error names. The OpenAI API is recognized by its base URL or response headers and isn't probed.
+    pub async fn detect_capabilities(&self) -> Result<BackendCapabilities, APIError> {
+        if let Some(capabilities) = self.backend_capabilities() {
+            return Ok(capabilities);
+        }
+
+        let capabilities = self.probe_capabilities().await?;
+
+        *self.backend_capabilities.write().unwrap() = Some(capabilities);
+
+        Ok(capabilities)
+    }
+
+    async fn probe_capabilities(&self) -> Result<BackendCapabilities, APIError> {
+        if self.is_openai() {
+            return Ok(BackendCapabilities::all());
+        }
+
+        let response = self.get_raw_response("/models").await?;
+
+        if OPENAI_HEADERS
+            .iter()
+            .any(|header| response.headers().contains_key(*header))
+        {
+            return Ok(BackendCapabilities::all());
+        }
+
+        let models: Value = response
+            .text()
+            .await
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        let Some(model) = models["data"][0]["id"].as_str() else {
+            return Err(APIError::ValidationError {
+                field: "models".to_string(),
+                value: "no models".to_string(),
+                allowed: "at least one model of the backend to probe".to_string(),
+            });
+        };
+
+        let tools = json!({
+            "model": model,
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "Call the probe function with the color of the image, or none without an image." },
+                    { "type": "image_url", "image_url": { "url": PROBE_IMAGE } }
+                ]
+            }],
+            "max_tokens": 16,
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": "probe",
+                    "description": "Checks that tools are called.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": { "color": { "type": "string" } },
+                        "required": ["color"]
+                    }
+                }
+            }],
+            "tool_choice": { "type": "function", "function": { "name": "probe" } },
+            "logprobs": true,
+            "top_logprobs": 1
+        });
+
+        let structured = json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": "Answer with {\"ok\": true}." }],
+            "max_tokens": 16,
+            "stream": true,
+            "stream_options": { "include_usage": true },
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "probe",
+                    "strict": true,
+                    "schema": {
+                        "type": "object",
+                        "properties": { "ok": { "type": "boolean" } },
+                        "required": ["ok"],
+                        "additionalProperties": false
+                    }
+                }
+            }
+        });
+
+        let tools = self
+            .probe(
+                tools,
+                &[
+                    ProbeFeature::Tools,
+                    ProbeFeature::Vision,
+                    ProbeFeature::Logprobs,
+                ],
+            )
+            .await?;
+        let structured = self
+            .probe(
+                structured,
+                &[ProbeFeature::JsonSchema, ProbeFeature::StreamUsage],
+            )
+            .await?;
+
+        Ok(BackendCapabilities::from_probes(&tools, &structured))
+    }
+
+    /// Sends a probe. When the backend rejects it and the error names some of the features,
+    /// the probe is sent once more without them. An error that names none of them rejects all of them.
+    async fn probe(
+        &self,
+        mut probe: Value,
+        features: &[ProbeFeature],
+    ) -> Result<ProbeOutcome, APIError> {
+        let mut rejected: Vec<ProbeFeature> = vec![];
+
+        loop {
+            let error = match self.post("/chat/completions", &probe, None).await {
+                Ok(response) => {
+                    return Ok(ProbeOutcome {
+                        response: Some(response.data),
+                        rejected,
+                    })
+                }
+                Err(
+                    error @ (APIError::BadRequestError { .. }
+                    | APIError::InvalidRequestError { .. }
+                    | APIError::UnknownError { status: 422, .. }),
+                ) => error,
+                Err(error) => return Err(error),
+            };
+
+            let text = error
+                .body()
+                .map(|body| format!("{} {}", body.param.unwrap_or_default(), body.message))
+                .unwrap_or_default()
+                .to_lowercase();
+
+            let named: Vec<ProbeFeature> = features
+                .iter()
+                .copied()
+                .filter(|feature| !rejected.contains(feature) && feature.is_named_by(&text))
+                .collect();
+
+            // the probe is sent again only once, without the features of the first error
+            if named.is_empty() || !rejected.is_empty() {
+                return Ok(ProbeOutcome {
+                    response: None,
+                    rejected: features.to_vec(),
+                });
+            }
+
+            for feature in named {
+                feature.remove_from(&mut probe);
+                rejected.push(feature);
+            }
+        }
+    }
+
+    /// Leaves out the parameters the backend doesn't support, if `set_degrade_gracefully` is enabled.
+    /// The capabilities are detected if they aren't known, the parameters are sent as they are when that fails.
+    pub(crate) async fn degrade_chat(
+        &self,
+        mut parameters: ChatCompletionParameters,
+    ) -> Result<ChatCompletionParameters, APIError> {
+        if !self.degrade_gracefully {
+            return Ok(parameters);
+        }
+
+        let capabilities = match self.backend_capabilities() {
+            Some(capabilities) => capabilities,
+            // a dry run captures the first request, which has to be the chat completion
+            None if self.dry_run.is_some() => return Ok(parameters),
+            None => match self.detect_capabilities().await {
+                Ok(capabilities) => capabilities,
+                Err(_) => return Ok(parameters),
+            },
+        };
+
+        let mut removed = vec![];
+
+        if !capabilities.supports_tools {
+            if parameters.tools.take().is_some() {
+                removed.push("tools");
+            }
+            if parameters.tool_choice.take().is_some() {
+                removed.push("tool_choice");
+            }
+            if parameters.parallel_tool_calls.take().is_some() {
+                removed.push("parallel_tool_calls");
+            }
+        }
+
+        if !capabilities.supports_json_schema
+            && matches!(
+                parameters.response_format,
+                Some(ChatCompletionResponseFormat::JsonSchema { .. })
+            )
+        {
+            parameters.response_format = None;
+            removed.push("response_format");
+        }
+
+        if !capabilities.supports_stream_usage && parameters.stream_options.take().is_some() {
+            removed.push("stream_options");
+        }
+
+        if !capabilities.supports_logprobs {
+            if parameters.logprobs.take().is_some() {
+                removed.push("logprobs");
+            }
+            if parameters.top_logprobs.take().is_some() {
+                removed.push("top_logprobs");
+            }
+        }
+
+        for parameter in removed {
+            self.warn(ValidationWarning::UnsupportedParameter {
+                parameter: parameter.to_string(),
+            })?;
+        }
+
+        Ok(parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::backend::BackendCapabilities;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::resources::chat::{
+        ChatCompletionFunction, ChatCompletionParametersBuilder, ChatCompletionTool,
+        ChatCompletionToolType, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::test_support::{json_response, serve, Request};
+    use crate::v1::validation::ValidationWarning;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::task::JoinHandle;
+
+    /// Serves the JSON responses in order, one per connection.
+    async fn serve_json(
+        responses: Vec<(&'static str, String)>,
+    ) -> (String, JoinHandle<Vec<Request>>) {
+        serve(
+            responses
+                .into_iter()
+                .map(|(status, body)| json_response(status, &body)),
+        )
+        .await
+    }
+
+    fn models() -> (&'static str, String) {
+        (
+            "200 OK",
+            json!({ "object": "list", "data": [{ "id": "qwen2.5-7b-instruct", "object": "model" }] })
+                .to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_probe_detects_ignored_features_and_degrades_chat_completions() {
+        // the backend answers with text, so it ignored the tool, the image and the log probabilities
+        let answer = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "qwen2.5-7b-instruct",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "{}" },
+                "finish_reason": "stop"
+            }]
+        });
+        // it ignores the stream too, the usage of a chat completion doesn't show that the stream has it
+        let structured = json!({
+            "id": "chatcmpl-2",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "qwen2.5-7b-instruct",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "{\"ok\": true}" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 6, "total_tokens": 18 }
+        });
+        let (base_url, server) = serve_json(vec![
+            models(),
+            ("200 OK", answer.to_string()),
+            ("200 OK", structured.to_string()),
+        ])
+        .await;
+
+        let warnings = Arc::new(Mutex::new(vec![]));
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_base_url(&base_url)
+            .set_degrade_gracefully(true)
+            .set_on_warning({
+                let warnings = warnings.clone();
+
+                move |warning| warnings.lock().unwrap().push(warning.clone())
+            });
+
+        let expected = BackendCapabilities {
+            supports_tools: false,
+            supports_json_schema: true,
+            supports_stream_usage: false,
+            supports_vision: false,
+            supports_logprobs: false,
+        };
+
+        assert_eq!(client.detect_capabilities().await.unwrap(), expected);
+        assert_eq!(client.detect_capabilities().await.unwrap(), expected);
+        // the server only answers the probes, the cached capabilities are returned without requests
+        assert_eq!(server.await.unwrap().len(), 3);
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("qwen2.5-7b-instruct")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("What's the weather?".to_string()),
+                name: None,
+            }])
+            .tools(vec![ChatCompletionTool {
+                r#type: ChatCompletionToolType::Function,
+                function: ChatCompletionFunction {
+                    name: "get_weather".to_string(),
+                    description: None,
+                    parameters: json!({ "type": "object", "properties": {} }),
+                },
+            }])
+            .logprobs(true)
+            .temperature(0.2)
+            .build()
+            .unwrap();
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.chat().create(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(body) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert!(body.get("tools").is_none());
+        assert!(body.get("logprobs").is_none());
+        assert_eq!(body["temperature"], 0.2);
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                ValidationWarning::UnsupportedParameter {
+                    parameter: "tools".to_string()
+                },
+                ValidationWarning::UnsupportedParameter {
+                    parameter: "logprobs".to_string()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_probes_and_manual_capabilities() {
+        let rejection = json!({
+            "error": { "message": "Model does not support images. Please use a model that does.", "type": "invalid_request_error" }
+        });
+        // the probe is sent again without the image, and the model calls the tool with a guess
+        let answer = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "qwen2.5-7b-instruct",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": { "name": "probe", "arguments": "{\"color\": \"red\"}" }
+                    }]
+                },
+                "logprobs": { "content": [] },
+                "finish_reason": "tool_calls"
+            }]
+        });
+        // an error that names none of the features rejects all of them
+        let unnamed = json!({
+            "error": { "message": "Unrecognized request argument supplied", "type": "invalid_request_error" }
+        });
+        let (base_url, server) = serve_json(vec![
+            models(),
+            ("400 Bad Request", rejection.to_string()),
+            ("200 OK", answer.to_string()),
+            ("400 Bad Request", unnamed.to_string()),
+        ])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        assert_eq!(
+            client.detect_capabilities().await.unwrap(),
+            BackendCapabilities {
+                supports_tools: true,
+                supports_logprobs: true,
+                ..BackendCapabilities::default()
+            }
+        );
+        assert_eq!(server.await.unwrap().len(), 4);
+
+        let structured = [
+            json!({ "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "{\"ok\"" } }] }),
+            json!({ "choices": [{ "index": 0, "delta": { "content": ": true}" } }] }),
+            json!({ "choices": [], "usage": { "prompt_tokens": 12, "completion_tokens": 6, "total_tokens": 18 } }),
+        ]
+        .iter()
+        .map(|chunk| format!("data: {chunk}\n\n"))
+        .collect::<String>()
+            + "data: [DONE]\n\n";
+        let (base_url, _) = serve_json(vec![
+            models(),
+            ("200 OK", answer.to_string()),
+            ("200 OK", structured),
+        ])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        assert_eq!(
+            client.detect_capabilities().await.unwrap(),
+            BackendCapabilities::all()
+        );
+
+        // capabilities that are set aren't probed, the base URL doesn't answer
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_base_url("http://127.0.0.1:9")
+            .set_backend_capabilities(BackendCapabilities::default());
+
+        assert_eq!(
+            client.detect_capabilities().await.unwrap(),
+            BackendCapabilities::default()
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Checks of the prompt layout for prompt caching, which only applies to a byte-identical prefix of at least 1024 tokens.
+//!
+//! The prefix is the serialized tools followed by the serialized messages, the part of a chat completion the provider caches.
+//! A timestamp early in the system prompt changes it with every request, so nothing after it is cached.
+use crate::v1::resources::chat::ChatCompletionParameters;
+use crate::v1::token_budget::estimate_tokens;
+use std::ops::Range;
+
+/// The minimum length of a cached prefix, in tokens.
+pub const MIN_CACHED_PREFIX_TOKENS: u32 = 1024;
+
+/// The length of the text returned around a divergence, in characters.
+const EXCERPT_CHARACTERS: usize = 40;
+
+/// The result of `cache_lint`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheLintReport {
+    /// The estimated tokens of the static prefix, up to the first dynamic content or the whole prompt without one.
+    pub static_prefix_tokens: u32,
+    /// The indexes of the messages before the first dynamic content, these are cached.
+    pub static_messages: Vec<usize>,
+    /// The first content that likely changes between requests, which ends the static prefix.
+    pub first_dynamic: Option<DynamicContent>,
+}
+
+impl CacheLintReport {
+    /// Whether the static prefix is long enough to be cached.
+    pub fn is_cacheable(&self) -> bool {
+        self.static_prefix_tokens >= MIN_CACHED_PREFIX_TOKENS
+    }
+}
+
+/// Content that likely changes between requests, ie. the current time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicContent {
+    /// The index of the message that contains it.
+    pub message_index: usize,
+    pub kind: DynamicKind,
+    /// The matched text, ie. `2024-05-01T12:30`.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicKind {
+    /// A date with a time, ie. `2024-05-01T12:30:00Z` or `2024-05-01 12:30`.
+    Timestamp,
+    /// A date, ie. `2024-05-01`.
+    Date,
+    /// A time of day, ie. `12:30`.
+    Time,
+    /// Seconds since the Unix epoch, ie. `1715000000`.
+    UnixTimestamp,
+    /// A UUID, ie. a request or session id.
+    Uuid,
+}
+
+/// Where the serialized prefixes of two requests differ, the result of `prefix_divergence`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixDivergence {
+    /// The byte offset in the serialized prefix where the requests differ.
+    pub byte_offset: usize,
+    /// The estimated tokens of the common prefix, the part that can be cached.
+    pub prefix_tokens: u32,
+    /// The message of the first request at the offset, `None` when the tools differ.
+    pub message_index: Option<usize>,
+    /// The text of the first request from the offset, up to 40 characters.
+    pub left: String,
+    /// The text of the second request from the offset, up to 40 characters.
+    pub right: String,
+}
+
+/// The serialized prefix of a request, with the ranges of its messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptLayout {
+    text: String,
+    messages: Vec<Range<usize>>,
+}
+
+impl PromptLayout {
+    pub(crate) fn new(parameters: &ChatCompletionParameters) -> Self {
+        let mut text = parameters
+            .tools
+            .as_ref()
+            .map(|tools| serde_json::to_string(tools).unwrap_or_default())
+            .unwrap_or_default();
+
+        let messages = parameters
+            .messages
+            .iter()
+            .map(|message| {
+                let start = text.len();
+
+                text.push_str(&serde_json::to_string(message).unwrap_or_default());
+
+                start..text.len()
+            })
+            .collect();
+
+        Self { text, messages }
+    }
+
+    pub(crate) fn estimated_tokens(&self) -> u32 {
+        estimate_tokens(&self.text)
+    }
+
+    fn message_at(&self, offset: usize) -> Option<usize> {
+        self.messages
+            .iter()
+            .position(|range| range.contains(&offset))
+    }
+
+    /// Returns where the prefixes differ, `None` when they are equal or one extends the other (ie. a longer conversation).
+    pub(crate) fn divergence(&self, other: &PromptLayout) -> Option<PrefixDivergence> {
+        let byte_offset = self
+            .text
+            .char_indices()
+            .zip(other.text.chars())
+            .find(|((_, left), right)| left != right)
+            .map(|((offset, _), _)| offset)?;
+
+        let excerpt = |text: &str| {
+            text[byte_offset..]
+                .chars()
+                .take(EXCERPT_CHARACTERS)
+                .collect()
+        };
+
+        Some(PrefixDivergence {
+            byte_offset,
+            prefix_tokens: estimate_tokens(&self.text[..byte_offset]),
+            message_index: self.message_at(byte_offset),
+            left: excerpt(&self.text),
+            right: excerpt(&other.text),
+        })
+    }
+}
+
+/// Reports the static prefix of the prompt, the messages before the first content that likely changes between requests
+/// (ie. a timestamp, a date or a UUID). Move such content to the end of the prompt, so the prefix before it is cached.
+pub fn cache_lint(parameters: &ChatCompletionParameters) -> CacheLintReport {
+    let layout = PromptLayout::new(parameters);
+
+    let first_dynamic = layout
+        .messages
+        .iter()
+        .enumerate()
+        .find_map(|(index, range)| {
+            find_dynamic(&layout.text[range.clone()])
+                .map(|(offset, kind, length)| (index, range.start + offset, kind, length))
+        });
+
+    let Some((message_index, offset, kind, length)) = first_dynamic else {
+        return CacheLintReport {
+            static_prefix_tokens: layout.estimated_tokens(),
+            static_messages: (0..layout.messages.len()).collect(),
+            first_dynamic: None,
+        };
+    };
+
+    CacheLintReport {
+        static_prefix_tokens: estimate_tokens(&layout.text[..offset]),
+        static_messages: (0..message_index).collect(),
+        first_dynamic: Some(DynamicContent {
+            message_index,
+            kind,
+            text: layout.text[offset..offset + length].to_string(),
+        }),
+    }
+}
+
+/// Returns where the serialized prefixes of two requests differ, `None` when they are equal
+/// or the second one extends the first one (ie. the next turn of a conversation), which keeps the cache.
+pub fn prefix_divergence(
+    first: &ChatCompletionParameters,
+    second: &ChatCompletionParameters,
+) -> Option<PrefixDivergence> {
+    PromptLayout::new(first).divergence(&PromptLayout::new(second))
+}
+
+/// Returns the byte offset, kind and length of the first dynamic content of the text.
+fn find_dynamic(text: &str) -> Option<(usize, DynamicKind, usize)> {
+    let bytes = text.as_bytes();
+
+    (0..bytes.len())
+        .filter(|&offset| offset == 0 || !bytes[offset - 1].is_ascii_alphanumeric())
+        .find_map(|offset| {
+            dynamic_at(bytes, offset)
+                .filter(|(_, length)| {
+                    bytes
+                        .get(offset + length)
+                        .is_none_or(|next| !next.is_ascii_alphanumeric())
+                })
+                .map(|(kind, length)| (offset, kind, length))
+        })
+}
+
+fn dynamic_at(bytes: &[u8], offset: usize) -> Option<(DynamicKind, usize)> {
+    let matches = |pattern: &str| {
+        let pattern = pattern.as_bytes();
+
+        bytes.len() >= offset + pattern.len()
+            && pattern
+                .iter()
+                .zip(&bytes[offset..])
+                .all(|(expected, byte)| match expected {
+                    b'd' => byte.is_ascii_digit(),
+                    b'x' => byte.is_ascii_hexdigit(),
+                    expected => expected == byte,
+                })
+    };
+
+    if matches("xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx") {
+        return Some((DynamicKind::Uuid, 36));
+    }
+
+    if matches("dddd-dd-ddTdd:dd") || matches("dddd-dd-dd dd:dd") {
+        return Some((DynamicKind::Timestamp, 16));
+    }
+
+    if matches("dddd-dd-dd") {
+        return Some((DynamicKind::Date, 10));
+    }
+
+    if matches("dd:dd") {
+        return Some((DynamicKind::Time, 5));
+    }
+
+    if matches("d:dd") {
+        return Some((DynamicKind::Time, 4));
+    }
+
+    if matches("1ddddddddd") {
+        return Some((DynamicKind::UnixTimestamp, 10));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::cache_lint::{cache_lint, prefix_divergence, DynamicKind};
+    use crate::v1::resources::chat::{
+        ChatCompletionParameters, ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::validation::ValidationWarning;
+    use std::sync::{Arc, Mutex};
+
+    fn parameters(system: &str, user: &str) -> ChatCompletionParameters {
+        ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![
+                ChatMessage::System {
+                    content: ChatMessageContent::Text(system.to_string()),
+                    name: None,
+                },
+                ChatMessage::User {
+                    content: ChatMessageContent::Text(user.to_string()),
+                    name: None,
+                },
+            ])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cache_lint_reports_the_static_prefix() {
+        let instructions = "Answer in English. ".repeat(300);
+
+        let late = cache_lint(&parameters(&instructions, "It is 2024-05-01T12:30:00Z."));
+
+        assert!(late.is_cacheable());
+        assert_eq!(late.static_messages, vec![0]);
+        assert_eq!(
+            late.first_dynamic
+                .as_ref()
+                .map(|dynamic| (dynamic.kind, dynamic.text.as_str())),
+            Some((DynamicKind::Timestamp, "2024-05-01T12:30"))
+        );
+
+        let early = cache_lint(&parameters(
+            &format!("Session 3f2b8c1e-9a4d-4e6f-b1c2-7d8e9f0a1b2c. {instructions}"),
+            "Hello!",
+        ));
+
+        assert!(!early.is_cacheable());
+        assert!(early.static_messages.is_empty());
+        assert_eq!(early.first_dynamic.unwrap().kind, DynamicKind::Uuid);
+
+        let fixed = cache_lint(&parameters("Version 12.5 of 3000 items.", "Hello!"));
+
+        assert_eq!(fixed.first_dynamic, None);
+        assert_eq!(fixed.static_messages, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_prefix_divergence_shows_where_the_prompts_differ() {
+        let first = parameters("Now: 12:30. Be brief.", "Hello!");
+        let second = parameters("Now: 12:31. Be brief.", "Hello!");
+
+        let divergence = prefix_divergence(&first, &second).unwrap();
+
+        assert_eq!(
+            &serde_json::to_string(&first.messages[0]).unwrap()[..divergence.byte_offset],
+            r#"{"role":"system","content":"Now: 12:3"#
+        );
+        assert_eq!(divergence.message_index, Some(0));
+        assert!(divergence.left.starts_with("0. Be brief."));
+        assert!(divergence.right.starts_with("1. Be brief."));
+
+        let mut next_turn = first.clone();
+        next_turn.messages.push(ChatMessage::User {
+            content: ChatMessageContent::Text("And now?".to_string()),
+            name: None,
+        });
+
+        assert_eq!(prefix_divergence(&first, &first), None);
+        assert_eq!(prefix_divergence(&first, &next_turn), None);
+    }
+
+    #[tokio::test]
+    async fn test_client_warns_when_consecutive_prompts_diverge_early() {
+        let warnings = Arc::new(Mutex::new(vec![]));
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_cache_lint(true).set_on_warning({
+            let warnings = warnings.clone();
+
+            move |warning| warnings.lock().unwrap().push(warning.clone())
+        });
+
+        let instructions = "Answer in English. ".repeat(300);
+
+        for time in ["12:30", "12:30", "12:31"] {
+            let parameters = parameters(&format!("Now: {time}. {instructions}"), "Hello!");
+
+            let _ = client
+                .dry_run()
+                .prepare(|client| async move { client.chat().create(parameters).await })
+                .await;
+        }
+
+        let warnings = warnings.lock().unwrap();
+
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            &warnings[0],
+            ValidationWarning::CachePrefixDiverged { model, prefix_tokens, .. }
+                if model == "gpt-4o" && *prefix_tokens < 1024
+        ));
+    }
+}
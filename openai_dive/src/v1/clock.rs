@@ -0,0 +1,278 @@
+//! The time source of the retry backoff and the timeouts, so tests can replace the wall clock.
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(any(test, feature = "stream"))]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A future that completes after a duration of the clock.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A source of time, `SystemClock` by default. Set another one with `Client::set_clock`.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Returns a future that completes after the duration.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// The wall clock, sleeps with `tokio::time::sleep`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Runs the future, or returns `Err(())` if it doesn't complete within the duration of the clock.
+#[cfg(any(test, feature = "stream"))]
+pub(crate) async fn timeout<F: Future>(
+    clock: &Arc<dyn Clock>,
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, ()> {
+    use std::task::Poll;
+
+    let mut future = std::pin::pin!(future);
+    let mut sleep = clock.sleep(duration);
+
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        sleep.as_mut().poll(cx).map(|_| Err(()))
+    })
+    .await
+}
+
+/// Returns an exponential backoff of 500ms doubled with every attempt, the first retry is attempt 1.
+pub(crate) fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(500).saturating_mul(2u32.saturating_pow(attempt))
+}
+
+/// Runs the operation until it succeeds or fails for good, with at most `max_retries` retries.
+/// After an error `retry_delay` returns how long to wait before the next attempt, counted from 1, or None if the error is final.
+pub(crate) async fn retry<T, E, F, Fut>(
+    clock: &dyn Clock,
+    max_retries: u32,
+    retry_delay: impl Fn(&E, u32) -> Option<Duration>,
+    mut operation: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        let error = match operation().await {
+            Err(error) if attempt < max_retries => error,
+            result => return result,
+        };
+
+        let Some(delay) = retry_delay(&error, attempt + 1) else {
+            return Err(error);
+        };
+
+        attempt += 1;
+        clock.sleep(delay).await;
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockClock;
+
+#[cfg(any(test, feature = "testing"))]
+mod mock {
+    use super::{Clock, Sleep};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Waker};
+    use std::time::{Duration, Instant};
+
+    /// A clock for tests, time only moves forward with `advance`, or with every sleep when it is auto-advancing.
+    #[derive(Debug, Clone)]
+    pub struct MockClock {
+        state: Arc<Mutex<MockClockState>>,
+    }
+
+    #[derive(Debug)]
+    struct MockClockState {
+        start: Instant,
+        elapsed: Duration,
+        auto_advance: bool,
+        sleeps: Vec<Duration>,
+        wakers: Vec<Waker>,
+    }
+
+    impl MockClock {
+        /// A clock that only moves forward with `advance`, ie. to test timeouts.
+        pub fn new() -> Self {
+            Self::with_auto_advance(false)
+        }
+
+        /// A clock that moves forward to the end of every sleep right away, ie. to test retries.
+        /// A timeout on this clock expires as soon as its future is pending.
+        pub fn auto_advancing() -> Self {
+            Self::with_auto_advance(true)
+        }
+
+        fn with_auto_advance(auto_advance: bool) -> Self {
+            Self {
+                state: Arc::new(Mutex::new(MockClockState {
+                    start: Instant::now(),
+                    elapsed: Duration::ZERO,
+                    auto_advance,
+                    sleeps: vec![],
+                    wakers: vec![],
+                })),
+            }
+        }
+
+        /// Moves the time forward and completes the sleeps that have ended.
+        pub fn advance(&self, duration: Duration) {
+            let wakers = {
+                let mut state = self.state.lock().unwrap();
+
+                state.elapsed += duration;
+
+                std::mem::take(&mut state.wakers)
+            };
+
+            wakers.into_iter().for_each(Waker::wake);
+        }
+
+        /// Returns the time that has passed on this clock.
+        pub fn elapsed(&self) -> Duration {
+            self.state.lock().unwrap().elapsed
+        }
+
+        /// Returns the durations of all sleeps, in order.
+        pub fn sleeps(&self) -> Vec<Duration> {
+            self.state.lock().unwrap().sleeps.clone()
+        }
+    }
+
+    impl Default for MockClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            let state = self.state.lock().unwrap();
+
+            state.start + state.elapsed
+        }
+
+        fn sleep(&self, duration: Duration) -> Sleep {
+            let mut state = self.state.lock().unwrap();
+
+            state.sleeps.push(duration);
+
+            Box::pin(MockSleep {
+                state: self.state.clone(),
+                deadline: state.elapsed + duration,
+            })
+        }
+    }
+
+    struct MockSleep {
+        state: Arc<Mutex<MockClockState>>,
+        deadline: Duration,
+    }
+
+    impl Future for MockSleep {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut state = self.state.lock().unwrap();
+
+            if state.auto_advance && state.elapsed < self.deadline {
+                state.elapsed = self.deadline;
+            }
+
+            if state.elapsed >= self.deadline {
+                return Poll::Ready(());
+            }
+
+            state.wakers.push(cx.waker().clone());
+
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::clock::{backoff, retry, timeout, Clock, MockClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_mock_clock_timeout_expires_when_advanced() {
+        let clock = MockClock::new();
+        let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+
+        let start = clock.now();
+        let pending = tokio::spawn(async move {
+            timeout(
+                &shared,
+                Duration::from_secs(30),
+                std::future::pending::<()>(),
+            )
+            .await
+        });
+
+        tokio::task::yield_now().await;
+        clock.advance(Duration::from_secs(10));
+        tokio::task::yield_now().await;
+
+        assert!(!pending.is_finished());
+
+        clock.advance(Duration::from_secs(20));
+
+        assert_eq!(pending.await.unwrap(), Err(()));
+        assert_eq!(clock.now() - start, Duration::from_secs(30));
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(30)]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_backs_off_until_the_error_is_final() {
+        let clock = MockClock::auto_advancing();
+        let mut attempts = 0;
+
+        let result: Result<(), u32> = retry(
+            &clock,
+            5,
+            |error, attempt| (*error < 3).then(|| backoff(attempt)),
+            || {
+                attempts += 1;
+
+                std::future::ready(Err(attempts))
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err(3));
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_millis(1000), Duration::from_millis(2000)]
+        );
+
+        // the backoff saturates instead of overflowing
+        assert_eq!(backoff(64), backoff(u32::MAX));
+        assert!(backoff(64) > Duration::from_secs(60 * 60 * 24 * 365));
+    }
+}
@@ -0,0 +1,200 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Returns the SHA-256 digest of the allowed fields of the value as lowercase hex.
+pub(crate) fn content_hash<T: Serialize>(value: &T, fields: &[&str]) -> String {
+    hash_fields(allowed_fields(value, fields))
+}
+
+/// Returns the top-level fields of the serialized value that are in the allow-list.
+pub(crate) fn allowed_fields<T: Serialize>(value: &T, fields: &[&str]) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(object)) => object
+            .into_iter()
+            .filter(|(key, _)| fields.contains(&key.as_str()))
+            .collect(),
+        _ => Map::new(),
+    }
+}
+
+/// Returns the SHA-256 digest of the canonical serialization of the fields as lowercase hex.
+pub(crate) fn hash_fields(fields: Map<String, Value>) -> String {
+    let mut canonical = String::new();
+
+    write_canonical(&Value::Object(fields), &mut canonical);
+
+    sha256_hex(canonical.as_bytes())
+}
+
+/// Writes the value as JSON with sorted object keys and without null values,
+/// so a field that is `None` is the same as a field that doesn't exist.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            out.push('{');
+
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+
+            out.push('}');
+        }
+        Value::Array(values) => {
+            out.push('[');
+
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+
+                write_canonical(value, out);
+            }
+
+            out.push(']');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+/// Returns the SHA-256 digest of the data as lowercase hex.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::resources::embedding::EmbeddingParametersBuilder;
+    use crate::v1::resources::image::{CreateImageParametersBuilder, ImageQuality, ImageSize};
+    use crate::v1::resources::shared::Metadata;
+    use serde::Serialize;
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        let parameters = CreateImageParametersBuilder::default()
+            .model("gpt-image-1")
+            .prompt("A lighthouse at dusk")
+            .size(ImageSize::Size1024X1024)
+            .quality(ImageQuality::High)
+            .build()
+            .unwrap();
+
+        // changing this value breaks the cache keys of existing users
+        assert_eq!(
+            parameters.content_hash(),
+            super::sha256_hex(
+                br#"{"model":"gpt-image-1","prompt":"A lighthouse at dusk","quality":"high","size":"1024x1024"}"#
+            )
+        );
+
+        // volatile fields are not part of the hash
+        let mut with_user = parameters.clone();
+        with_user.user = Some("user-123".to_string());
+        with_user.safety_identifier = Some("user-123".to_string());
+        with_user.stream = Some(true);
+
+        assert_eq!(with_user.content_hash(), parameters.content_hash());
+
+        let mut other_size = parameters.clone();
+        other_size.size = Some(ImageSize::Size1536X1024);
+
+        assert_ne!(other_size.content_hash(), parameters.content_hash());
+    }
+
+    #[test]
+    fn test_new_fields_that_are_none_keep_the_hash() {
+        #[derive(Serialize)]
+        struct Before {
+            model: String,
+            prompt: String,
+        }
+
+        #[derive(Serialize)]
+        struct After {
+            model: String,
+            prompt: String,
+            // a field added without skip_serializing_if is serialized as null
+            seed: Option<u32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            style: Option<String>,
+        }
+
+        let fields = &["model", "prompt", "seed", "style"];
+
+        let before = Before {
+            model: "gpt-image-1".to_string(),
+            prompt: "A lighthouse at dusk".to_string(),
+        };
+        let after = After {
+            model: "gpt-image-1".to_string(),
+            prompt: "A lighthouse at dusk".to_string(),
+            seed: None,
+            style: None,
+        };
+
+        assert_eq!(
+            super::content_hash(&before, fields),
+            super::content_hash(&after, fields)
+        );
+    }
+
+    #[test]
+    fn test_chat_and_embeddings_content_hash() {
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .temperature(0.2)
+            .build()
+            .unwrap();
+
+        let mut volatile = parameters.clone();
+        volatile.user = Some("user-123".to_string());
+        volatile.metadata = Some(Metadata::try_from([("run", "42")]).unwrap());
+        volatile.stream = Some(true);
+
+        assert_eq!(volatile.content_hash(), parameters.content_hash());
+
+        let mut extra_body = parameters.clone();
+        extra_body.extra_body = Some(serde_json::json!({"top_k": 40}));
+
+        assert_ne!(extra_body.content_hash(), parameters.content_hash());
+
+        let embedding = EmbeddingParametersBuilder::default()
+            .model("text-embedding-3-small")
+            .input("The food was delicious")
+            .build()
+            .unwrap();
+
+        let mut with_user = embedding.clone();
+        with_user.user = Some("user-123".to_string());
+
+        assert_eq!(with_user.content_hash(), embedding.content_hash());
+        assert_eq!(
+            embedding.content_hash(),
+            super::sha256_hex(
+                br#"{"input":"The food was delicious","model":"text-embedding-3-small"}"#
+            )
+        );
+    }
+}
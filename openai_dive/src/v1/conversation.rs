@@ -0,0 +1,763 @@
+//! Multi-turn conversations whose messages are persisted in a `ConversationStore`, so a chat can be reloaded and continued.
+use crate::v1::endpoints::chat::Chat;
+use crate::v1::error::APIError;
+use crate::v1::future::ApiFuture;
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatMessage, ChatMessageContent};
+use crate::v1::validation::ValidationWarning;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Storage of the messages of conversations, by conversation id.
+pub trait ConversationStore: Debug + Send + Sync {
+    /// Returns the messages of the conversation in order, or no messages for an unknown conversation.
+    fn load<'a>(&'a self, conversation_id: &'a str) -> ApiFuture<'a, Vec<ChatMessage>>;
+
+    /// Adds the messages to the end of the conversation. Either all messages are stored or none.
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: &'a [ChatMessage],
+    ) -> ApiFuture<'a, ()>;
+
+    /// Returns the ids of the stored conversations.
+    fn list_conversations(&self) -> ApiFuture<'_, Vec<String>>;
+
+    /// Like `load`, with a warning for every stored record that can't be read and was skipped.
+    /// `Conversation` passes them to the warning handler of the client (see `Client::set_on_warning`).
+    fn load_with_warnings<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> ApiFuture<'a, (Vec<ChatMessage>, Vec<ValidationWarning>)> {
+        Box::pin(async move { Ok((self.load(conversation_id).await?, vec![])) })
+    }
+}
+
+/// Runs the blocking IO of a store on the blocking threads of the runtime.
+async fn blocking<T, F>(f: F) -> Result<T, APIError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, APIError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|error| APIError::FileError(error.to_string()))?
+}
+
+/// Logs the skipped records of a `load`, the warnings of `load_with_warnings` go to the client instead.
+fn log_warnings(_warnings: &[ValidationWarning]) {
+    #[cfg(feature = "log")]
+    for warning in _warnings {
+        log::warn!("{warning}");
+    }
+}
+
+/// Keeps the conversations in memory, ie. for tests. Clones share the same conversations.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryConversationStore {
+    conversations: Arc<Mutex<BTreeMap<String, Vec<ChatMessage>>>>,
+}
+
+impl MemoryConversationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConversationStore for MemoryConversationStore {
+    fn load<'a>(&'a self, conversation_id: &'a str) -> ApiFuture<'a, Vec<ChatMessage>> {
+        let messages = self
+            .conversations
+            .lock()
+            .unwrap()
+            .get(conversation_id)
+            .cloned()
+            .unwrap_or_default();
+
+        Box::pin(async move { Ok(messages) })
+    }
+
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: &'a [ChatMessage],
+    ) -> ApiFuture<'a, ()> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .entry(conversation_id.to_string())
+            .or_default()
+            .extend_from_slice(messages);
+
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn list_conversations(&self) -> ApiFuture<'_, Vec<String>> {
+        let ids = self.conversations.lock().unwrap().keys().cloned().collect();
+
+        Box::pin(async move { Ok(ids) })
+    }
+}
+
+/// Keeps the conversations in a JSON lines file, one line per appended turn.
+///
+/// A turn is written as a single line, so a write that is cut off (ie. by a crash) loses the whole turn and not
+/// only some of its messages. Lines that can't be read are skipped when loading, see `load_with_warnings`.
+/// The file is read and written on the blocking threads of the runtime, it suits files of a few thousand turns.
+#[derive(Debug, Clone)]
+pub struct JsonlConversationStore {
+    path: PathBuf,
+    /// Serializes the appends of concurrent tasks.
+    lock: Arc<Mutex<()>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredTurn {
+    conversation_id: String,
+    messages: Vec<ChatMessage>,
+}
+
+impl JsonlConversationStore {
+    /// Stores the conversations in the file, which is created by the first append.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Arc::default(),
+        }
+    }
+
+    /// Returns the turns that can be read in order, and a warning for each line that can't be read.
+    async fn turns(&self) -> Result<(Vec<StoredTurn>, Vec<ValidationWarning>), APIError> {
+        let path = self.path.clone();
+
+        blocking(move || {
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(error) => {
+                    return Err(APIError::FileError(format!("{}: {error}", path.display())))
+                }
+            };
+
+            let mut turns = vec![];
+            let mut warnings = vec![];
+
+            for (index, line) in text.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                match serde_json::from_str(line) {
+                    Ok(turn) => turns.push(turn),
+                    Err(error) => warnings.push(ValidationWarning::UnreadableConversationRecord {
+                        source: path.display().to_string(),
+                        record: format!("line {}", index + 1),
+                        error: error.to_string(),
+                    }),
+                }
+            }
+
+            Ok((turns, warnings))
+        })
+        .await
+    }
+}
+
+impl ConversationStore for JsonlConversationStore {
+    fn load<'a>(&'a self, conversation_id: &'a str) -> ApiFuture<'a, Vec<ChatMessage>> {
+        Box::pin(async move {
+            let (messages, warnings) = self.load_with_warnings(conversation_id).await?;
+
+            log_warnings(&warnings);
+
+            Ok(messages)
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: &'a [ChatMessage],
+    ) -> ApiFuture<'a, ()> {
+        let path = self.path.clone();
+        let lock = self.lock.clone();
+
+        let line = serde_json::to_string(&StoredTurn {
+            conversation_id: conversation_id.to_string(),
+            messages: messages.to_vec(),
+        });
+
+        Box::pin(blocking(move || {
+            let file_error =
+                |error: std::io::Error| APIError::FileError(format!("{}: {error}", path.display()));
+
+            let mut line = line.map_err(|error| APIError::ParseError(error.to_string()))?;
+
+            line.push('\n');
+
+            let _guard = lock.lock().unwrap();
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .read(true)
+                .append(true)
+                .open(&path)
+                .map_err(file_error)?;
+
+            // a line that was cut off is ended first, so it doesn't take the new turn with it
+            if file.metadata().map_err(file_error)?.len() > 0 {
+                let mut last = [0; 1];
+
+                file.seek(SeekFrom::End(-1)).map_err(file_error)?;
+                file.read_exact(&mut last).map_err(file_error)?;
+
+                if last[0] != b'\n' {
+                    line.insert(0, '\n');
+                }
+            }
+
+            file.write_all(line.as_bytes()).map_err(file_error)
+        }))
+    }
+
+    fn list_conversations(&self) -> ApiFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let mut ids: Vec<String> = vec![];
+
+            for turn in self.turns().await?.0 {
+                if !ids.contains(&turn.conversation_id) {
+                    ids.push(turn.conversation_id);
+                }
+            }
+
+            Ok(ids)
+        })
+    }
+
+    fn load_with_warnings<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> ApiFuture<'a, (Vec<ChatMessage>, Vec<ValidationWarning>)> {
+        Box::pin(async move {
+            let (turns, warnings) = self.turns().await?;
+
+            let messages = turns
+                .into_iter()
+                .filter(|turn| turn.conversation_id == conversation_id)
+                .flat_map(|turn| turn.messages)
+                .collect();
+
+            Ok((messages, warnings))
+        })
+    }
+}
+
+/// Keeps the conversations in a SQLite database, one row per message, with the `sqlite-store` feature.
+///
+/// The schema is created and migrated when the database is opened, by its `user_version`. The messages of an append
+/// are inserted in one transaction. Rows that can't be read are skipped when loading, see `load_with_warnings`.
+/// The database is used on the blocking threads of the runtime.
+#[cfg(feature = "sqlite-store")]
+#[derive(Debug, Clone)]
+pub struct SqliteConversationStore {
+    connection: Arc<Mutex<rusqlite::Connection>>,
+    /// The path of the database, for errors and warnings.
+    source: String,
+}
+
+/// The migrations of the schema in order, the `user_version` of a database is the number of migrations applied.
+#[cfg(feature = "sqlite-store")]
+const SQLITE_MIGRATIONS: [&str; 1] = ["CREATE TABLE messages (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        conversation_id TEXT NOT NULL,
+        message TEXT NOT NULL
+    );
+    CREATE INDEX messages_by_conversation ON messages (conversation_id, id);"];
+
+#[cfg(feature = "sqlite-store")]
+impl SqliteConversationStore {
+    /// Opens the database, which is created if it doesn't exist, and migrates its schema.
+    /// A database of a newer version of the crate is rejected.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, APIError> {
+        let source = path.as_ref().display().to_string();
+        let connection = rusqlite::Connection::open(path)
+            .map_err(|error| APIError::FileError(format!("{source}: {error}")))?;
+
+        Self::migrate(connection, source)
+    }
+
+    /// Opens a database in memory, ie. for tests.
+    pub fn open_in_memory() -> Result<Self, APIError> {
+        let source = ":memory:".to_string();
+        let connection = rusqlite::Connection::open_in_memory()
+            .map_err(|error| APIError::FileError(format!("{source}: {error}")))?;
+
+        Self::migrate(connection, source)
+    }
+
+    fn migrate(mut connection: rusqlite::Connection, source: String) -> Result<Self, APIError> {
+        let sqlite_error =
+            |error: rusqlite::Error| APIError::FileError(format!("{source}: {error}"));
+
+        let version: usize = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(sqlite_error)?;
+
+        if version > SQLITE_MIGRATIONS.len() {
+            return Err(APIError::FileError(format!(
+                "{source}: the schema version {version} is newer than the supported version {}",
+                SQLITE_MIGRATIONS.len()
+            )));
+        }
+
+        for (index, migration) in SQLITE_MIGRATIONS.iter().enumerate().skip(version) {
+            let transaction = connection.transaction().map_err(sqlite_error)?;
+
+            transaction
+                .execute_batch(migration)
+                .and_then(|_| transaction.pragma_update(None, "user_version", index + 1))
+                .and_then(|_| transaction.commit())
+                .map_err(sqlite_error)?;
+        }
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            source,
+        })
+    }
+
+    /// Runs the query with the connection on the blocking threads of the runtime.
+    async fn query<T, F>(&self, query: F) -> Result<T, APIError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut rusqlite::Connection, &str) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let source = self.source.clone();
+
+        blocking(move || {
+            query(&mut connection.lock().unwrap(), &source)
+                .map_err(|error| APIError::FileError(format!("{source}: {error}")))
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "sqlite-store")]
+impl ConversationStore for SqliteConversationStore {
+    fn load<'a>(&'a self, conversation_id: &'a str) -> ApiFuture<'a, Vec<ChatMessage>> {
+        Box::pin(async move {
+            let (messages, warnings) = self.load_with_warnings(conversation_id).await?;
+
+            log_warnings(&warnings);
+
+            Ok(messages)
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: &'a [ChatMessage],
+    ) -> ApiFuture<'a, ()> {
+        let conversation_id = conversation_id.to_string();
+        let messages = messages
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<String>, _>>();
+
+        Box::pin(async move {
+            let messages = messages.map_err(|error| APIError::ParseError(error.to_string()))?;
+
+            self.query(move |connection, _| {
+                let transaction = connection.transaction()?;
+
+                for message in &messages {
+                    transaction.execute(
+                        "INSERT INTO messages (conversation_id, message) VALUES (?1, ?2)",
+                        (&conversation_id, message),
+                    )?;
+                }
+
+                transaction.commit()
+            })
+            .await
+        })
+    }
+
+    fn list_conversations(&self) -> ApiFuture<'_, Vec<String>> {
+        Box::pin(self.query(|connection, _| {
+            connection
+                .prepare(
+                    "SELECT conversation_id FROM messages GROUP BY conversation_id ORDER BY MIN(id)",
+                )?
+                .query_map([], |row| row.get(0))?
+                .collect()
+        }))
+    }
+
+    fn load_with_warnings<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> ApiFuture<'a, (Vec<ChatMessage>, Vec<ValidationWarning>)> {
+        let conversation_id = conversation_id.to_string();
+
+        Box::pin(self.query(move |connection, source| {
+            let rows = connection
+                .prepare("SELECT id, message FROM messages WHERE conversation_id = ?1 ORDER BY id")?
+                .query_map([&conversation_id], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut messages = vec![];
+            let mut warnings = vec![];
+
+            for (id, message) in rows {
+                let message = message
+                    .map_err(|error| error.to_string())
+                    .and_then(|message| {
+                        serde_json::from_str(&message).map_err(|error| error.to_string())
+                    });
+
+                match message {
+                    Ok(message) => messages.push(message),
+                    Err(error) => warnings.push(ValidationWarning::UnreadableConversationRecord {
+                        source: source.to_string(),
+                        record: format!("row {id}"),
+                        error,
+                    }),
+                }
+            }
+
+            Ok((messages, warnings))
+        }))
+    }
+}
+
+/// A conversation whose messages are loaded from and appended to a store. Create it with `Chat::conversation`.
+pub struct Conversation<'a> {
+    chat: Chat<'a>,
+    store: Arc<dyn ConversationStore>,
+    conversation_id: String,
+    parameters: ChatCompletionParameters,
+}
+
+impl<'a> Chat<'a> {
+    /// Continues the stored conversation, or starts it if the store doesn't have it yet.
+    /// The messages of the parameters (ie. a system message) are sent before the stored messages, but aren't stored.
+    pub fn conversation(
+        &self,
+        store: Arc<dyn ConversationStore>,
+        conversation_id: impl Into<String>,
+        parameters: ChatCompletionParameters,
+    ) -> Conversation<'a> {
+        Conversation {
+            chat: Chat {
+                client: self.client,
+            },
+            store,
+            conversation_id: conversation_id.into(),
+            parameters,
+        }
+    }
+}
+
+impl Conversation<'_> {
+    pub fn id(&self) -> &str {
+        &self.conversation_id
+    }
+
+    /// Returns the stored messages of the conversation.
+    pub async fn messages(&self) -> Result<Vec<ChatMessage>, APIError> {
+        self.history().await
+    }
+
+    /// Loads the stored messages, the records that were skipped are passed to the warning handler of the client.
+    async fn history(&self) -> Result<Vec<ChatMessage>, APIError> {
+        let (messages, warnings) = self.store.load_with_warnings(&self.conversation_id).await?;
+
+        for warning in warnings {
+            self.chat.client.warn(warning)?;
+        }
+
+        Ok(messages)
+    }
+
+    /// Sends a user message and returns the reply of the assistant.
+    pub async fn ask(&self, text: impl Into<String>) -> Result<ChatMessage, APIError> {
+        self.send(vec![ChatMessage::User {
+            content: ChatMessageContent::Text(text.into()),
+            name: None,
+        }])
+        .await
+    }
+
+    /// Sends the messages (ie. the results of the tool calls of the last reply) and returns the reply of the assistant.
+    /// The messages and the reply, including its tool calls, are appended to the store together once the reply
+    /// is received, so a failed request doesn't leave a turn without reply.
+    pub async fn send(&self, messages: Vec<ChatMessage>) -> Result<ChatMessage, APIError> {
+        let history = self.history().await?;
+
+        let mut parameters = self.parameters.clone();
+        parameters.messages.extend(history);
+        parameters.messages.extend(messages.iter().cloned());
+
+        let response = self.chat.create(parameters).await?;
+
+        let reply = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| APIError::ParseError("the response has no choices".to_string()))?;
+
+        let mut turn = messages;
+        turn.push(reply.clone());
+
+        self.store.append(&self.conversation_id, &turn).await?;
+
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::conversation::{
+        ConversationStore, JsonlConversationStore, MemoryConversationStore,
+    };
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::test_support::{json_response, serve};
+    use crate::v1::validation::ValidationWarning;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn user(text: &str) -> ChatMessage {
+        ChatMessage::User {
+            content: ChatMessageContent::Text(text.to_string()),
+            name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ask_stores_the_turn_with_tool_calls_and_sends_the_history() {
+        let (base_url, server) = serve([
+            json_response("200 OK", r#"{"id": "a", "object": "chat.completion", "created": 0, "model": "gpt-4o", "choices": [{"index": 0, "finish_reason": "tool_calls", "message": {"role": "assistant", "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}}]}}]}"#),
+            json_response("200 OK", r#"{"id": "b", "object": "chat.completion", "created": 0, "model": "gpt-4o", "choices": [{"index": 0, "finish_reason": "stop", "message": {"role": "assistant", "content": "It is sunny in Paris."}}]}"#),
+        ])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let store = MemoryConversationStore::new();
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::System {
+                content: ChatMessageContent::Text("Be brief.".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let conversation =
+            client
+                .chat()
+                .conversation(Arc::new(store.clone()), "trip", parameters.clone());
+
+        let reply = conversation
+            .ask("What is the weather in Paris?")
+            .await
+            .unwrap();
+
+        assert!(
+            matches!(&reply, ChatMessage::Assistant { tool_calls: Some(calls), .. } if calls[0].id == "call_1")
+        );
+
+        // a conversation created later continues from the store
+        let conversation = client
+            .chat()
+            .conversation(Arc::new(store.clone()), "trip", parameters);
+
+        conversation
+            .send(vec![ChatMessage::Tool {
+                content: ChatMessageContent::Text("sunny".to_string()),
+                tool_call_id: "call_1".to_string(),
+            }])
+            .await
+            .unwrap();
+
+        let messages = conversation.messages().await.unwrap();
+
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], user("What is the weather in Paris?"));
+        assert_eq!(messages[1], reply);
+        assert_eq!(store.list_conversations().await.unwrap(), vec!["trip"]);
+
+        let requests = server.await.unwrap();
+
+        // the system message is sent but not stored, the history is sent with the tool result
+        assert!(requests[1].text().contains("Be brief."));
+        assert!(requests[1].text().contains("call_1"));
+        assert!(requests[1].text().contains(r#""tool_call_id":"call_1""#));
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_store_skips_unreadable_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "openai_dive_conversations_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let store = JsonlConversationStore::new(&path);
+
+        assert!(store.load("first").await.unwrap().is_empty());
+
+        store.append("first", &[user("Hello!")]).await.unwrap();
+        store.append("second", &[user("Hi!")]).await.unwrap();
+
+        // a line with an unknown message and a turn that was cut off
+        let mut text = std::fs::read_to_string(&path).unwrap();
+        text.push_str(
+            "{\"conversation_id\": \"first\", \"messages\": [{\"role\": \"narrator\"}]}\n",
+        );
+        text.push_str("{\"conversation_id\": \"first\", \"messages\": [{\"role\": \"user\", \"con");
+        std::fs::write(&path, text).unwrap();
+
+        store
+            .append("first", &[user("Are you there?")])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.load("first").await.unwrap(),
+            vec![user("Hello!"), user("Are you there?")]
+        );
+        assert_eq!(
+            store.list_conversations().await.unwrap(),
+            vec!["first", "second"]
+        );
+
+        let (_, warnings) = store.load_with_warnings("first").await.unwrap();
+
+        assert!(matches!(
+            &warnings[..],
+            [
+                ValidationWarning::UnreadableConversationRecord { record: third, .. },
+                ValidationWarning::UnreadableConversationRecord { record: fourth, .. },
+            ] if third == "line 3" && fourth == "line 4"
+        ));
+
+        // a conversation passes the skipped lines to the warning handler of the client
+        let warned = Arc::new(AtomicUsize::new(0));
+        let mut client = Client::new("sk-test".to_string());
+
+        client.set_on_warning({
+            let warned = warned.clone();
+            move |_| {
+                warned.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![])
+            .build()
+            .unwrap();
+        let conversation = client
+            .chat()
+            .conversation(Arc::new(store), "first", parameters);
+
+        assert_eq!(conversation.messages().await.unwrap().len(), 2);
+        assert_eq!(warned.load(Ordering::SeqCst), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "sqlite-store")]
+    #[tokio::test]
+    async fn test_sqlite_store_migrates_and_skips_unreadable_rows() {
+        use crate::v1::conversation::SqliteConversationStore;
+        use crate::v1::error::APIError;
+
+        let path = std::env::temp_dir().join(format!(
+            "openai_dive_conversations_{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let store = SqliteConversationStore::open(&path).unwrap();
+
+        assert!(store.load("first").await.unwrap().is_empty());
+
+        store
+            .append("first", &[user("Hello!"), user("Anyone?")])
+            .await
+            .unwrap();
+        store.append("second", &[user("Hi!")]).await.unwrap();
+
+        drop(store);
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let version: usize = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(version, 1);
+
+        connection
+            .execute(
+                "INSERT INTO messages (conversation_id, message) VALUES ('first', '{\"role\": \"narrator\"}')",
+                [],
+            )
+            .unwrap();
+
+        drop(connection);
+
+        // the schema is not created again when the database is opened again
+        let store = SqliteConversationStore::open(&path).unwrap();
+
+        store
+            .append("first", &[user("Are you there?")])
+            .await
+            .unwrap();
+
+        let (messages, warnings) = store.load_with_warnings("first").await.unwrap();
+
+        assert_eq!(
+            messages,
+            vec![user("Hello!"), user("Anyone?"), user("Are you there?")]
+        );
+        assert!(matches!(
+            &warnings[..],
+            [ValidationWarning::UnreadableConversationRecord { record, .. }] if record == "row 4"
+        ));
+        assert_eq!(
+            store.list_conversations().await.unwrap(),
+            vec!["first", "second"]
+        );
+
+        drop(store);
+
+        // a database of a newer version is rejected instead of being changed
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", 2)
+            .unwrap();
+
+        assert!(matches!(
+            SqliteConversationStore::open(&path),
+            Err(APIError::FileError(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
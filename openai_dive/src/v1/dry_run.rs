@@ -0,0 +1,427 @@
+//! Prepare requests without sending them, ie. to debug the exact body sent to a provider.
+use crate::v1::api::Client;
+use crate::v1::error::APIError;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+const REDACTED_AUTHORIZATION: &str = "Bearer [REDACTED]";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PreparedRequest {
+    /// The HTTP method, ie. POST.
+    pub method: String,
+    /// The full URL, including the query string.
+    pub url: String,
+    /// The request headers, with the authorization header redacted.
+    pub headers: HashMap<String, String>,
+    /// The request body.
+    pub body: PreparedBody,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "content", rename_all = "snake_case")]
+pub enum PreparedBody {
+    Empty,
+    Json(Value),
+    Multipart(Vec<PartDescription>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PartDescription {
+    /// The name of the form field.
+    pub name: String,
+    /// The file name, if the part is a file.
+    pub filename: Option<String>,
+    /// The content type of the part, if set.
+    pub content_type: Option<String>,
+    /// The value of the part, if it is a text field.
+    pub text: Option<String>,
+    /// The size of the part, in bytes.
+    pub size: usize,
+}
+
+/// Captures the request of a client in dry-run mode.
+#[derive(Debug, Clone, Default)]
+pub struct DryRun(Arc<Mutex<Option<PreparedRequest>>>);
+
+/// A view of the client that prepares requests instead of sending them.
+pub struct DryRunClient {
+    client: Client,
+}
+
+impl Client {
+    /// Returns a view of the client that prepares requests instead of sending them.
+    /// The requests are built by the same code path as real requests.
+    pub fn dry_run(&self) -> DryRunClient {
+        let mut client = self.clone();
+        client.dry_run = Some(DryRun::default());
+
+        DryRunClient { client }
+    }
+}
+
+impl DryRunClient {
+    /// Calls an endpoint method and returns the request it would have sent.
+    /// Errors returned before the request is built, ie. validation errors, are returned as-is.
+    pub async fn prepare<'a, F, Fut, T>(&'a self, call: F) -> Result<PreparedRequest, APIError>
+    where
+        F: FnOnce(&'a Client) -> Fut,
+        Fut: Future<Output = Result<T, APIError>>,
+    {
+        let result = call(&self.client).await;
+
+        let prepared = self
+            .client
+            .dry_run
+            .as_ref()
+            .and_then(|dry_run| dry_run.take());
+
+        match (prepared, result) {
+            (Some(prepared), _) => Ok(prepared),
+            (None, Err(error)) => Err(error),
+            (None, Ok(_)) => Err(APIError::DryRunError(
+                "the endpoint method did not build a request".to_string(),
+            )),
+        }
+    }
+}
+
+impl DryRun {
+    pub(crate) async fn capture(&self, request: reqwest::RequestBuilder) -> APIError {
+        let request = match request.build() {
+            Ok(request) => request,
+            Err(error) => return APIError::DryRunError(error.to_string()),
+        };
+
+        match prepare(request).await {
+            Ok(prepared) => {
+                if let Ok(mut slot) = self.0.lock() {
+                    *slot = Some(prepared);
+                }
+
+                APIError::DryRunError("the request was prepared, but not sent".to_string())
+            }
+            Err(error) => error,
+        }
+    }
+
+    fn take(&self) -> Option<PreparedRequest> {
+        self.0.lock().ok()?.take()
+    }
+}
+
+async fn prepare(mut request: reqwest::Request) -> Result<PreparedRequest, APIError> {
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(key, value)| {
+            let value = if key == reqwest::header::AUTHORIZATION {
+                REDACTED_AUTHORIZATION.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).to_string()
+            };
+
+            (key.to_string(), value)
+        })
+        .collect::<HashMap<String, String>>();
+
+    let content_type = headers
+        .get(reqwest::header::CONTENT_TYPE.as_str())
+        .cloned()
+        .unwrap_or_default();
+
+    let bytes = match request.body_mut().take() {
+        Some(body) => body
+            .collect()
+            .await
+            .map(|collected| collected.to_bytes())
+            .map_err(|error| APIError::DryRunError(error.to_string()))?,
+        None => Bytes::new(),
+    };
+
+    let body = if bytes.is_empty() {
+        PreparedBody::Empty
+    } else if let Some(boundary) = content_type.strip_prefix("multipart/form-data; boundary=") {
+        PreparedBody::Multipart(describe_parts(&bytes, boundary))
+    } else {
+        PreparedBody::Json(
+            serde_json::from_slice(&bytes)
+                .map_err(|error| APIError::ParseError(error.to_string()))?,
+        )
+    };
+
+    Ok(PreparedRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers,
+        body,
+    })
+}
+
+fn describe_parts(bytes: &[u8], boundary: &str) -> Vec<PartDescription> {
+    let delimiter = format!("--{boundary}");
+
+    split(bytes, delimiter.as_bytes())
+        .into_iter()
+        .filter_map(|part| {
+            let part = part.strip_prefix(b"\r\n")?;
+            let header_end = find(part, b"\r\n\r\n")?;
+
+            let headers = String::from_utf8_lossy(&part[..header_end]);
+            let content = &part[header_end + 4..];
+            let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+
+            let mut name = None;
+            let mut filename = None;
+            let mut content_type = None;
+
+            for line in headers.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+
+                if key.eq_ignore_ascii_case("content-disposition") {
+                    name = disposition_parameter(value, "name");
+                    filename = disposition_parameter(value, "filename");
+                } else if key.eq_ignore_ascii_case("content-type") {
+                    content_type = Some(value.trim().to_string());
+                }
+            }
+
+            let text = match (&filename, &content_type) {
+                (None, None) => Some(String::from_utf8_lossy(content).to_string()),
+                _ => None,
+            };
+
+            Some(PartDescription {
+                name: name?,
+                filename,
+                content_type,
+                text,
+                size: content.len(),
+            })
+        })
+        .collect()
+}
+
+fn disposition_parameter(value: &str, parameter: &str) -> Option<String> {
+    value.split(';').find_map(|item| {
+        let (key, value) = item.trim().split_once('=')?;
+
+        (key == parameter).then(|| value.trim_matches('"').to_string())
+    })
+}
+
+fn split<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = vec![];
+    let mut rest = bytes;
+
+    while let Some(index) = find(rest, delimiter) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + delimiter.len()..];
+    }
+
+    parts.push(rest);
+
+    parts
+}
+
+fn find(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+    bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::resources::audio::AudioTranscriptionParametersBuilder;
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent,
+    };
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+
+    #[tokio::test]
+    async fn test_dry_run_prepares_json_and_multipart_requests() {
+        let mut client = Client::new("sk-test".to_string());
+        client.set_project("proj_abc");
+
+        let dry_run = client.dry_run();
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let prepared = dry_run
+            .prepare(|client| async move { client.chat().create(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(prepared.method, "POST");
+        assert_eq!(prepared.url, "https://api.openai.com/v1/chat/completions");
+        assert_eq!(prepared.headers["authorization"], "Bearer [REDACTED]");
+        assert_eq!(prepared.headers["openai-project"], "proj_abc");
+        assert!(matches!(prepared.body, PreparedBody::Json(body) if body["model"] == "gpt-4o"));
+
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .model("whisper-1")
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "audio.mp3",
+            )))
+            .build()
+            .unwrap();
+
+        let prepared = dry_run
+            .prepare(|client| async move { client.audio().create_transcription(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Multipart(parts) = prepared.body else {
+            panic!("expected a multipart body");
+        };
+
+        let file = parts.iter().find(|part| part.name == "file").unwrap();
+        let model = parts.iter().find(|part| part.name == "model").unwrap();
+
+        assert_eq!(file.filename.as_deref(), Some("audio.mp3"));
+        assert_eq!(file.content_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(file.size, 16);
+        assert_eq!(model.text.as_deref(), Some("whisper-1"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_shows_overridden_paths() {
+        use crate::v1::paths::Endpoint;
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_base_url("https://llm.example.com")
+            .set_path_override(Endpoint::ChatCompletions, "/api/chat/completions")
+            .set_path_override(Endpoint::FileContent, "/api/files/{id}/download");
+
+        let dry_run = client.dry_run();
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("llama3")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .build()
+            .unwrap();
+
+        let prepared = dry_run
+            .prepare(|client| async move { client.chat().create(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(prepared.url, "https://llm.example.com/api/chat/completions");
+
+        let prepared = dry_run
+            .prepare(|client| async move { client.files().retrieve_content("file-abc").await })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            prepared.url,
+            "https://llm.example.com/api/files/file-abc/download"
+        );
+
+        let prepared = dry_run
+            .prepare(|client| async move { client.files().retrieve("file-abc").await })
+            .await
+            .unwrap();
+
+        assert_eq!(prepared.url, "https://llm.example.com/files/file-abc");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_order_places_file_parts_first_or_last() {
+        use crate::v1::resources::file::{FilePurpose, UploadFileParameters};
+        use crate::v1::resources::shared::MultipartOrder;
+
+        async fn part_names(client: &Client) -> (Vec<String>, Vec<String>) {
+            let dry_run = client.dry_run();
+
+            let parameters = AudioTranscriptionParametersBuilder::default()
+                .model("whisper-1")
+                .file(FileUpload::Bytes(FileUploadBytes::new(
+                    vec![0; 16],
+                    "audio.mp3",
+                )))
+                .language("en")
+                .build()
+                .unwrap();
+
+            let transcription = dry_run
+                .prepare(
+                    |client| async move { client.audio().create_transcription(parameters).await },
+                )
+                .await
+                .unwrap();
+
+            let upload = dry_run
+                .prepare(|client| async move {
+                    client
+                        .files()
+                        .upload(UploadFileParameters {
+                            file: FileUpload::Bytes(FileUploadBytes::new(
+                                b"{}".to_vec(),
+                                "batch.jsonl",
+                            )),
+                            purpose: FilePurpose::Batch,
+                        })
+                        .await
+                })
+                .await
+                .unwrap();
+
+            let names = |body: PreparedBody| match body {
+                PreparedBody::Multipart(parts) => parts.into_iter().map(|part| part.name).collect(),
+                _ => panic!("expected a multipart body"),
+            };
+
+            (names(transcription.body), names(upload.body))
+        }
+
+        let mut client = Client::new("sk-test".to_string());
+
+        assert_eq!(
+            part_names(&client).await,
+            (
+                vec![
+                    "file".to_string(),
+                    "model".to_string(),
+                    "language".to_string()
+                ],
+                vec!["file".to_string(), "purpose".to_string()]
+            )
+        );
+
+        client.set_multipart_order(MultipartOrder::FileLast);
+
+        assert_eq!(
+            part_names(&client).await,
+            (
+                vec![
+                    "model".to_string(),
+                    "language".to_string(),
+                    "file".to_string()
+                ],
+                vec!["purpose".to_string(), "file".to_string()]
+            )
+        );
+    }
+}
@@ -1,154 +1,1411 @@
+use crate::v1::adapters::ResponseAdapter;
 use crate::v1::api::Client;
-use crate::v1::error::APIError;
+#[cfg(feature = "long-transcription")]
+use crate::v1::audio_chunking::{merge_transcriptions, split_wav, AudioChunk};
+use crate::v1::error::{APIError, VoiceChatStage};
+#[cfg(feature = "stream")]
+use crate::v1::error::{StreamError, TransportError};
+use crate::v1::helpers::{bracket_form_fields, buffered, MultipartForm};
 use crate::v1::resources::audio::AudioSpeechParameters;
 use crate::v1::resources::audio::AudioSpeechResponse;
 #[cfg(feature = "stream")]
 use crate::v1::resources::audio::AudioSpeechResponseChunkResponse;
-use crate::v1::resources::audio::{AudioTranscriptionParameters, AudioTranslationParameters};
+use crate::v1::resources::audio::AudioSpeechResponseFormat;
+use crate::v1::resources::audio::SpeechVoice;
+use crate::v1::resources::audio::{
+    AudioOutputFormat, AudioTranscriptionParameters, AudioTranslationParameters,
+    BatchTranscriptionOptions, BatchTranscriptionResult, VerboseTranslationResponse,
+    VoiceChatParameters, VoiceChatResponse,
+};
+#[cfg(feature = "long-transcription")]
+use crate::v1::resources::audio::{
+    LongTranscriptionOptions, TimestampGranularity, VerboseTranscriptionResponse,
+};
 #[cfg(feature = "stream")]
-use futures::Stream;
+use crate::v1::resources::audio::{SpeechStreamFormat, SpeechStreamResponse};
 #[cfg(feature = "stream")]
+use crate::v1::resources::audio::{
+    TranscriptionSegmenter, TranscriptionStreamEvent, TranscriptionStreamResponse,
+    TranslationStreamEvent, TranslationStreamResponse,
+};
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatMessage, ChatMessageContent};
+use crate::v1::resources::model::ModelCapability;
+use crate::v1::resources::shared::{FileKind, FileUpload};
+use crate::v1::validation::{
+    check_transcription_prompt, validate_transcription, TranscriptionModelRules,
+};
+#[cfg(feature = "stream")]
+use futures::Stream;
+#[cfg(any(feature = "stream", feature = "long-transcription"))]
 use futures::StreamExt;
 use serde_json::Value;
+use std::collections::HashMap;
 #[cfg(feature = "stream")]
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 pub struct Audio<'a> {
     pub client: &'a Client,
+    /// Converts the JSON responses of transcriptions and translations of another provider, if set.
+    pub adapter: Option<Arc<dyn ResponseAdapter>>,
+    /// Renames the multipart fields of transcriptions and translations, ie. `file` to `audio`. Empty sends the OpenAI names.
+    pub field_name_map: HashMap<&'a str, &'a str>,
+}
+
+#[cfg(feature = "stream")]
+/// A stream of transcription events, which holds the connection until it is dropped.
+pub type TranscriptionEventStream =
+    Pin<Box<dyn Stream<Item = Result<TranscriptionStreamEvent, APIError>> + Send>>;
+
+#[cfg(feature = "stream")]
+/// A stream of translation events, which holds the connection until it is dropped.
+pub type TranslationEventStream =
+    Pin<Box<dyn Stream<Item = Result<TranslationStreamEvent, APIError>> + Send>>;
+
+#[cfg(feature = "stream")]
+/// A stream of speech chunks, which holds the connection until it is dropped or closed.
+pub struct AudioSpeechStream {
+    stream: Pin<Box<dyn Stream<Item = Result<AudioSpeechResponseChunkResponse, APIError>> + Send>>,
+    clock: std::sync::Arc<dyn crate::v1::clock::Clock>,
+}
+
+#[cfg(feature = "stream")]
+impl AudioSpeechStream {
+    /// Stops reading the response and closes the connection, ie. when playback is cancelled.
+    /// The connection is not returned to the pool, as the rest of the body is not read.
+    pub fn close(self) {
+        drop(self.stream);
+    }
+
+    /// Returns the audio as `tokio::io::AsyncRead`, ie. to copy it into a file or an audio sink with `tokio::io::copy`.
+    /// Stream errors are returned as `std::io::Error`, with the `APIError` as inner error.
+    #[cfg(feature = "tokio-util")]
+    pub fn into_async_read(self) -> impl tokio::io::AsyncRead + Send + Unpin {
+        tokio_util::io::StreamReader::new(
+            self.map(|item| item.map(|chunk| chunk.bytes).map_err(std::io::Error::other)),
+        )
+    }
+
+    /// Fails the stream with `StreamError::Timeout` when no chunk arrives within the timeout, the stream ends after the error.
+    pub fn timeout_stream(self, timeout: std::time::Duration) -> AudioSpeechStream {
+        use crate::v1::error::TimeoutPhase;
+
+        let clock = self.clock.clone();
+
+        let stream = futures::stream::unfold(Some(self), move |stream| async move {
+            let mut stream = stream?;
+            let clock = stream.clock.clone();
+
+            match crate::v1::clock::timeout(&clock, timeout, stream.next()).await {
+                Ok(item) => item.map(|item| (item, Some(stream))),
+                Err(_) => Some((
+                    Err(APIError::StreamError(StreamError::Timeout(
+                        TimeoutPhase::Idle,
+                    ))),
+                    None,
+                )),
+            }
+        });
+
+        AudioSpeechStream {
+            stream: Box::pin(stream),
+            clock,
+        }
+    }
+
+    /// Re-frames PCM and WAV audio into chunks of `duration_ms` each, ie. to pace playback. The last chunk can be shorter.
+    /// PCM is 24kHz 16-bit mono, for WAV the format is read from its header, which is the first chunk.
+    /// Compressed formats (mp3, opus, aac and flac) can't be framed without decoding them, they pass through unchanged.
+    pub fn chunk_by_duration(
+        self,
+        duration_ms: u32,
+        format: AudioSpeechResponseFormat,
+    ) -> AudioSpeechStream {
+        let frame_size = match format {
+            AudioSpeechResponseFormat::Pcm => Some(frame_size(24_000, 2, duration_ms)),
+            AudioSpeechResponseFormat::Wav => None,
+            _ => return self,
+        };
+
+        let clock = self.clock.clone();
+
+        let state = DurationFraming {
+            stream: Some(self),
+            buffer: bytes::BytesMut::new(),
+            frame_size,
+            duration_ms,
+        };
+
+        let stream = futures::stream::unfold(state, |mut state| async move {
+            let item = state.next().await?;
+
+            Some((item, state))
+        });
+
+        AudioSpeechStream {
+            stream: Box::pin(stream),
+            clock,
+        }
+    }
+}
+
+/// The Accept header of a speech request, the override or the mime type of the format (mp3 by default).
+fn speech_accept(
+    accept: &Option<String>,
+    response_format: &Option<AudioSpeechResponseFormat>,
+) -> String {
+    accept.clone().unwrap_or_else(|| {
+        response_format
+            .as_ref()
+            .unwrap_or(&AudioSpeechResponseFormat::Mp3)
+            .mime_type()
+            .to_string()
+    })
+}
+
+/// Returns the error of a successful speech response that is JSON instead of audio.
+/// The body is checked when the Content-Type is JSON, or when the body starts with `{` and has an `error` member.
+fn speech_json_error(content_type: Option<&str>, bytes: &[u8]) -> Option<APIError> {
+    let is_json = content_type.is_some_and(|content_type| content_type.contains("json"));
+
+    if !is_json && bytes.trim_ascii_start().first() != Some(&b'{') {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(bytes).to_string();
+
+    let error = match serde_json::from_str::<Value>(&text) {
+        Ok(value) => value.get("error").cloned(),
+        Err(_) if is_json => {
+            return Some(APIError::ParseError(format!(
+                "expected audio, got invalid JSON: {text}"
+            )))
+        }
+        // audio that happens to start with `{`
+        Err(_) => return None,
+    };
+
+    let Some(error) = error else {
+        return is_json.then(|| {
+            APIError::ParseError(format!(
+                "expected audio, got JSON without an error (disable `detect_speech_json_errors` for base64 audio): {text}"
+            ))
+        });
+    };
+
+    let kind = ["type", "code"]
+        .iter()
+        .find_map(|key| error.get(key).and_then(Value::as_str))
+        .unwrap_or_default();
+
+    let (message, request_id) = (text, None);
+
+    Some(match kind {
+        "authentication_error" | "invalid_api_key" => APIError::AuthenticationError {
+            message,
+            request_id,
+        },
+        "permission_error" => APIError::PermissionError {
+            message,
+            request_id,
+        },
+        "not_found_error" | "model_not_found" => APIError::NotFoundError {
+            message,
+            request_id,
+        },
+        "rate_limit_error" | "rate_limit_exceeded" | "insufficient_quota" => {
+            APIError::RateLimitError {
+                message,
+                backpressure: None,
+                request_id,
+            }
+        }
+        "server_error" => APIError::ServerError {
+            message,
+            request_id,
+        },
+        _ => APIError::BadRequestError {
+            message,
+            request_id,
+        },
+    })
+}
+
+#[cfg(feature = "stream")]
+/// Decodes the base64 audio of a speech event.
+fn decode_audio(audio: &str) -> Result<AudioSpeechResponseChunkResponse, APIError> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    general_purpose::STANDARD
+        .decode(audio)
+        .map(|bytes| AudioSpeechResponseChunkResponse {
+            bytes: bytes.into(),
+        })
+        .map_err(|error| {
+            StreamError::Parse {
+                payload: audio.to_string(),
+                source: error.to_string(),
+            }
+            .into()
+        })
+}
+
+#[cfg(feature = "stream")]
+struct DurationFraming {
+    /// None after the end of the stream or an error.
+    stream: Option<AudioSpeechStream>,
+    buffer: bytes::BytesMut,
+    /// The size of a chunk in bytes, None until the WAV header is read.
+    frame_size: Option<usize>,
+    duration_ms: u32,
+}
+
+#[cfg(feature = "stream")]
+impl DurationFraming {
+    async fn next(&mut self) -> Option<Result<AudioSpeechResponseChunkResponse, APIError>> {
+        let chunk = |bytes: bytes::BytesMut| {
+            Ok(AudioSpeechResponseChunkResponse {
+                bytes: bytes.freeze(),
+            })
+        };
+
+        loop {
+            match self.frame_size {
+                None => match wav_stream_header(&self.buffer) {
+                    Some(Ok((header_size, sample_rate, block_align))) => {
+                        self.frame_size =
+                            Some(frame_size(sample_rate, block_align, self.duration_ms));
+
+                        return Some(chunk(self.buffer.split_to(header_size)));
+                    }
+                    Some(Err(error)) => {
+                        self.stream = None;
+                        self.buffer.clear();
+
+                        return Some(Err(error));
+                    }
+                    None => {}
+                },
+                Some(frame_size) if self.buffer.len() >= frame_size => {
+                    return Some(chunk(self.buffer.split_to(frame_size)));
+                }
+                Some(_) => {}
+            }
+
+            let Some(stream) = self.stream.as_mut() else {
+                // the remainder, or a WAV file that ended within its header
+                return (!self.buffer.is_empty()).then(|| chunk(self.buffer.split()));
+            };
+
+            match stream.next().await {
+                Some(Ok(response)) => self.buffer.extend_from_slice(&response.bytes),
+                Some(Err(error)) => {
+                    self.stream = None;
+                    self.buffer.clear();
+
+                    return Some(Err(error));
+                }
+                None => self.stream = None,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+/// The number of sentences synthesized at the same time by `create_speech_from_text_stream`,
+/// the one that is played and the next one.
+const SENTENCE_LOOKAHEAD: usize = 2;
+
+#[cfg(feature = "stream")]
+/// Splits the text of a stream into sentences as it arrives, the rest of the text is the last sentence.
+fn sentences<S>(text_stream: S) -> impl Stream<Item = String> + Send
+where
+    S: Stream<Item = String> + Send + 'static,
+{
+    let state = (Some(Box::pin(text_stream)), String::new());
+
+    futures::stream::unfold(state, |(mut text_stream, mut buffer)| async move {
+        loop {
+            if let Some(end) = sentence_end(&buffer) {
+                let sentence = buffer.drain(..end).collect::<String>();
+
+                if sentence.trim().is_empty() {
+                    continue;
+                }
+
+                return Some((sentence.trim().to_string(), (text_stream, buffer)));
+            }
+
+            let Some(stream) = text_stream.as_mut() else {
+                let rest = std::mem::take(&mut buffer);
+
+                return (!rest.trim().is_empty())
+                    .then(|| (rest.trim().to_string(), (None, String::new())));
+            };
+
+            match stream.next().await {
+                Some(text) => buffer.push_str(&text),
+                None => text_stream = None,
+            }
+        }
+    })
+}
+
+#[cfg(feature = "stream")]
+/// Returns the end of the first complete sentence of the text.
+/// A sentence ends at a line break, or at `.`, `!` or `?` (with closing quotes and brackets) followed by whitespace,
+/// so "3.14" and a terminator at the end of the text that may still be followed by more text don't end it.
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, char)) = chars.next() {
+        match char {
+            '\n' => return Some(index + 1),
+            '。' | '！' | '？' => return Some(index + char.len_utf8()),
+            '.' | '!' | '?' | '…' => {
+                while let Some((_, next)) = chars.peek() {
+                    if !matches!(
+                        next,
+                        '.' | '!' | '?' | '…' | '"' | '\'' | ')' | ']' | '”' | '’'
+                    ) {
+                        break;
+                    }
+
+                    chars.next();
+                }
+
+                if let Some((end, next)) = chars.peek() {
+                    if next.is_whitespace() {
+                        return Some(*end);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(feature = "stream")]
+/// A spawned task of a stream, ie. a synthesis, which is aborted when the stream is dropped.
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+#[cfg(feature = "stream")]
+impl std::future::Future for AbortOnDrop<Result<AudioSpeechResponse, APIError>> {
+    type Output = Result<AudioSpeechResponse, APIError>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map(|result| {
+            result.unwrap_or_else(|error| {
+                Err(StreamError::Transport(TransportError::new(error)).into())
+            })
+        })
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(feature = "stream")]
+/// Reads the stream in a task into a channel of `size` items, the task ends when the returned stream is dropped.
+/// A size of 0 returns the stream as is.
+fn buffered_stream<T: Send + 'static>(
+    mut stream: Pin<Box<dyn Stream<Item = T> + Send>>,
+    size: usize,
+) -> Pin<Box<dyn Stream<Item = T> + Send>> {
+    if size == 0 {
+        return stream;
+    }
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(size);
+
+    let reader = AbortOnDrop(tokio::spawn(async move {
+        while let Some(item) = stream.next().await {
+            // waits while the channel is full, so the response isn't read further
+            if sender.send(item).await.is_err() {
+                break;
+            }
+        }
+    }));
+
+    Box::pin(futures::stream::unfold(
+        (receiver, reader),
+        |(mut receiver, reader)| async move {
+            let item = receiver.recv().await?;
+
+            Some((item, (receiver, reader)))
+        },
+    ))
+}
+
+#[cfg(feature = "stream")]
+/// The size in bytes of `duration_ms` of audio, at least one frame.
+fn frame_size(sample_rate: u32, block_align: usize, duration_ms: u32) -> usize {
+    let frames = (sample_rate as u64 * duration_ms as u64 / 1000).max(1) as usize;
+
+    frames * block_align
+}
+
+#[cfg(feature = "stream")]
+/// Reads the header of a streamed WAV file up to the start of the data, returns None if more bytes are needed.
+/// Returns the size of the header, the sample rate and the bytes per frame.
+fn wav_stream_header(wav: &[u8]) -> Option<Result<(usize, u32, usize), APIError>> {
+    let invalid = |reason: &str| {
+        APIError::from(StreamError::Parse {
+            payload: String::new(),
+            source: format!("invalid WAV stream: {reason}"),
+        })
+    };
+
+    if wav.len() < 12 {
+        return None;
+    }
+
+    if &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return Some(Err(invalid("not a WAV file")));
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+
+    while wav.len() >= offset + 8 {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().unwrap()) as usize;
+
+        if id == b"data" {
+            return Some(
+                format
+                    .ok_or_else(|| invalid("data before the fmt chunk"))
+                    .map(|(sample_rate, block_align)| (offset + 8, sample_rate, block_align)),
+            );
+        }
+
+        let body_end = offset + 8 + size;
+
+        if wav.len() < body_end {
+            return None;
+        }
+
+        if id == b"fmt " && size >= 16 {
+            let body = &wav[offset + 8..body_end];
+            let sample_rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+            let block_align = u16::from_le_bytes([body[12], body[13]]) as usize;
+
+            if sample_rate == 0 || block_align == 0 {
+                return Some(Err(invalid("invalid fmt chunk")));
+            }
+
+            format = Some((sample_rate, block_align));
+        }
+
+        // chunks are padded to an even size
+        offset = body_end + size % 2;
+    }
+
+    None
+}
+
+#[cfg(feature = "stream")]
+impl Stream for AudioSpeechStream {
+    type Item = Result<AudioSpeechResponseChunkResponse, APIError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
 }
 
 impl Client {
     /// Learn how to turn audio into text or text into audio.
     pub fn audio(&self) -> Audio<'_> {
-        Audio { client: self }
+        Audio {
+            client: self,
+            adapter: None,
+            field_name_map: HashMap::new(),
+        }
     }
 }
 
-impl Audio<'_> {
+impl<'a> Audio<'a> {
+    /// Converts the JSON responses of transcriptions and translations with the adapter, ie. for another provider.
+    /// The streamed transcription events aren't converted.
+    pub fn with_adapter(mut self, adapter: impl ResponseAdapter + 'static) -> Self {
+        self.adapter = Some(Arc::new(adapter));
+
+        self
+    }
+
+    /// Renames the multipart fields of transcriptions and translations before they are sent, ie. `("file", "audio")`
+    /// for a provider that expects the audio in an `audio` field. Fields that aren't in the map keep their OpenAI name.
+    /// This is the multipart counterpart of a `RequestConverter`, transcriptions of a file URL are sent as JSON and aren't renamed.
+    pub fn with_field_name_map(mut self, field_name_map: HashMap<&'a str, &'a str>) -> Self {
+        self.field_name_map = field_name_map;
+
+        self
+    }
+
     /// Generates audio from the input text.
     pub async fn create_speech(
         &self,
         parameters: AudioSpeechParameters,
     ) -> Result<AudioSpeechResponse, APIError> {
-        let bytes = self.client.post_raw("/audio/speech", &parameters).await?;
+        self.create_speech_with_progress(parameters, |_, _| ())
+            .await
+    }
+
+    /// Generates audio from the input text, calling `on_progress` with the bytes downloaded so far and the Content-Length
+    /// as the chunks arrive, ie. for a progress bar. The Content-Length is `None` for a chunked response.
+    pub async fn create_speech_with_progress<F>(
+        &self,
+        parameters: AudioSpeechParameters,
+        mut on_progress: F,
+    ) -> Result<AudioSpeechResponse, APIError>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let response = self.speech_response(parameters).await?;
+
+        let content_type = crate::v1::api::content_type(&response);
+        let request_id = crate::v1::helpers::request_id(response.headers());
+
+        let bytes = crate::v1::api::read_with_progress(response, &mut on_progress).await?;
+
+        if self.client.detect_speech_json_errors {
+            if let Some(error) = speech_json_error(content_type.as_deref(), &bytes) {
+                return Err(error.with_request_id(request_id.as_deref()));
+            }
+        }
 
         Ok(AudioSpeechResponse { bytes })
     }
 
-    /// Transcribes audio into the input language.
-    pub async fn create_transcription(
+    /// Generates audio from the input text and writes it to the writer as the chunks arrive, ie. to a file,
+    /// without holding the whole audio in memory. Returns the number of bytes written.
+    /// A JSON response is read into memory first, to return the error instead of writing it.
+    pub async fn create_speech_to<W, F>(
         &self,
-        parameters: AudioTranscriptionParameters,
-    ) -> Result<String, APIError> {
-        let mut form = reqwest::multipart::Form::new();
-
-        let file = parameters.file.into_part().await?;
+        parameters: AudioSpeechParameters,
+        writer: &mut W,
+        mut on_progress: F,
+    ) -> Result<u64, APIError>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+        F: FnMut(u64, Option<u64>),
+    {
+        use tokio::io::AsyncWriteExt;
 
-        form = form.part("file", file);
+        let response = self.speech_response(parameters).await?;
 
-        form = form.text("model", parameters.model);
+        let content_type = crate::v1::api::content_type(&response);
+        let request_id = crate::v1::helpers::request_id(response.headers());
+        let is_json = content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.contains("json"));
 
-        if let Some(prompt) = parameters.prompt {
-            form = form.text("prompt", prompt);
+        if !(self.client.detect_speech_json_errors && is_json) {
+            return crate::v1::api::write_with_progress(response, writer, &mut on_progress).await;
         }
 
-        if let Some(language) = parameters.language {
-            form = form.text("language", language.to_string());
-        }
+        let bytes = crate::v1::api::read_with_progress(response, &mut on_progress).await?;
 
-        if let Some(chunking_strategy) = parameters.chunking_strategy {
-            form = form.text("chunking_strategy", chunking_strategy.to_string());
+        if let Some(error) = speech_json_error(content_type.as_deref(), &bytes) {
+            return Err(error.with_request_id(request_id.as_deref()));
         }
 
-        if let Some(response_format) = parameters.response_format {
-            form = form.text("response_format", response_format.to_string());
-        }
+        writer
+            .write_all(&bytes)
+            .await
+            .map_err(|error| APIError::FileError(error.to_string()))?;
+        writer
+            .flush()
+            .await
+            .map_err(|error| APIError::FileError(error.to_string()))?;
 
-        if let Some(stream) = parameters.stream {
-            form = form.text("stream", stream.to_string());
-        }
+        Ok(bytes.len() as u64)
+    }
 
-        if let Some(temperature) = parameters.temperature {
-            form = form.text("temperature", temperature.to_string());
+    /// Sends the speech request and returns the response before its body is read.
+    async fn speech_response(
+        &self,
+        mut parameters: AudioSpeechParameters,
+    ) -> Result<reqwest::Response, APIError> {
+        self.client.apply_speech_defaults(&mut parameters);
+
+        self.check_voice(&parameters.voice)?;
+
+        self.client
+            .check_model_capability(&parameters.model, ModelCapability::Speech)
+            .await?;
+
+        let accept = speech_accept(&parameters.accept, &parameters.response_format);
+
+        self.client
+            .post_raw_response("/audio/speech", &parameters, Some(&accept))
+            .await
+    }
+
+    /// Rejects a blend of voices for OpenAI, which only accepts a single voice.
+    fn check_voice(&self, voice: &Option<SpeechVoice>) -> Result<(), APIError> {
+        match voice {
+            Some(SpeechVoice::VoiceBlend(voices)) if self.client.is_openai() => {
+                Err(APIError::ValidationError {
+                    field: "voice".to_string(),
+                    value: format!("a blend of {} voices", voices.len()),
+                    allowed: "a single voice".to_string(),
+                })
+            }
+            _ => Ok(()),
         }
+    }
 
-        if let Some(timestamp_granularities) = parameters.timestamp_granularities {
-            form = form.text(
-                "timestamp_granularities",
-                timestamp_granularities
-                    .iter()
-                    .map(|t| t.to_string())
-                    .collect::<Vec<String>>()
-                    .join(","),
-            );
+    /// Moves the word-boost list into the prompt for OpenAI, which only takes a prompt.
+    fn apply_word_boost(&self, parameters: &mut AudioTranscriptionParameters) {
+        if self.client.is_openai() {
+            parameters.fold_word_boost_into_prompt();
         }
+    }
 
-        if let Some(extra_body) = parameters.extra_body {
-            match extra_body {
-                Value::Object(map) => {
-                    for (key, value) in map {
-                        form = form.text(key, value.to_string());
-                    }
-                }
-                _ => {
-                    return Err(APIError::BadRequestError(
-                        "extra_body must be formatted as a map of key: value".to_string(),
-                    ));
-                }
-            }
+    /// Checks the parameters against the rules of the model family and the length of the prompt, unless `skip_validation` is set.
+    fn validate_transcription(
+        &self,
+        parameters: &AudioTranscriptionParameters,
+    ) -> Result<(), APIError> {
+        if parameters.skip_validation {
+            return Ok(());
         }
 
-        let response = self
+        let response_format = self
             .client
-            .post_with_form("/audio/transcriptions", form)
-            .await?;
+            .transcription_format(parameters.response_format.clone());
+
+        validate_transcription(parameters, response_format.as_ref())?;
 
-        Ok(response)
+        let warning = self
+            .client
+            .transcription_prompt_limit
+            .and_then(|limit| check_transcription_prompt(parameters, limit));
+
+        match warning {
+            Some(warning) => self.client.warn(warning),
+            None => Ok(()),
+        }
     }
 
-    /// Translates audio into English.
-    pub async fn create_translation(
+    /// Transcribes audio into the input language.
+    /// Audio that is too short returns `APIError::AudioTooShort`.
+    pub async fn create_transcription(
         &self,
-        parameters: AudioTranslationParameters,
+        mut parameters: AudioTranscriptionParameters,
     ) -> Result<String, APIError> {
-        let mut form = reqwest::multipart::Form::new();
+        Client::apply_default_model(
+            &mut parameters.model,
+            &self.client.default_transcription_model,
+        );
 
-        let file = parameters.file.into_part().await?;
-        form = form.part("file", file);
+        let capability = match parameters.stream {
+            Some(true) => ModelCapability::TranscriptionStreaming,
+            _ => ModelCapability::Transcription,
+        };
 
-        form = form.text("model", parameters.model);
+        self.client
+            .check_model_capability(&parameters.model, capability)
+            .await?;
 
-        if let Some(prompt) = parameters.prompt {
-            form = form.text("prompt", prompt);
-        }
+        self.apply_word_boost(&mut parameters);
 
-        if let Some(response_format) = parameters.response_format {
-            form = form.text("response_format", response_format.to_string());
-        }
+        self.validate_transcription(&parameters)?;
 
-        if let Some(temperature) = parameters.temperature {
-            form = form.text("temperature", temperature.to_string());
+        if let FileUpload::Url(url) = &parameters.file {
+            return self
+                .create_transcription_from_url(url.clone(), parameters)
+                .await;
         }
 
+        let accept = self.transcription_accept(&parameters);
+
+        let form = self.transcription_form(parameters).await?;
+
         let response = self
             .client
-            .post_with_form("/audio/translations", form)
-            .await?;
+            .post_with_form_and_accept("/audio/transcriptions", form, Some(&accept))
+            .await
+            .map_err(APIError::into_audio_too_short)?;
 
-        Ok(response)
+        self.adapt(response)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Streams the transcription as text deltas, and as segments when the provider finalizes them (ie. with diarization).
+    /// Audio that is too short fails the stream with `APIError::AudioTooShort`.
+    pub async fn create_transcription_stream(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> Result<TranscriptionEventStream, APIError> {
+        self.transcription_event_stream(parameters, false).await
+    }
+
+    #[cfg(feature = "stream")]
+    /// Streams the transcription as text deltas and complete segments, ie. to render stable caption lines.
+    /// When the provider only sends text deltas, segments are synthesized at sentence boundaries.
+    /// Synthesized segments are approximate and have no timing, their start and end are 0.0.
+    pub async fn create_transcription_segment_stream(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> Result<TranscriptionEventStream, APIError> {
+        self.transcription_event_stream(parameters, true).await
+    }
+
+    #[cfg(all(feature = "stream", feature = "tokio-util"))]
+    /// Streams the transcription as `tokio::io::AsyncRead` of UTF-8 text, ie. to pipe live captions into a file or socket.
+    /// The text deltas are written as they arrive, so the text reads like the transcript so far.
+    /// A read can end within a multi-byte character, decode the text after reading it or with a streaming UTF-8 decoder.
+    /// Stream errors are returned as `std::io::Error`, with the `APIError` as inner error.
+    pub async fn create_transcription_text_reader(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> Result<impl tokio::io::AsyncRead + Send + Unpin, APIError> {
+        let mut received_delta = false;
+
+        let stream = self
+            .transcription_event_stream(parameters, false)
+            .await?
+            .filter_map(move |event| {
+                let text = match event {
+                    Ok(TranscriptionStreamEvent::Delta(delta)) => {
+                        received_delta = true;
+
+                        Some(Ok(bytes::Bytes::from(delta)))
+                    }
+                    // providers that don't send deltas only send the full text
+                    Ok(TranscriptionStreamEvent::Done(text)) if !received_delta => {
+                        Some(Ok(bytes::Bytes::from(text)))
+                    }
+                    Ok(_) => None,
+                    Err(error) => Some(Err(std::io::Error::other(error))),
+                };
+
+                futures::future::ready(text)
+            });
+
+        Ok(tokio_util::io::StreamReader::new(stream))
+    }
+
+    #[cfg(feature = "stream")]
+    async fn transcription_event_stream(
+        &self,
+        mut parameters: AudioTranscriptionParameters,
+        synthesize_segments: bool,
+    ) -> Result<TranscriptionEventStream, APIError> {
+        Client::apply_default_model(
+            &mut parameters.model,
+            &self.client.default_transcription_model,
+        );
+
+        self.client
+            .check_model_capability(&parameters.model, ModelCapability::TranscriptionStreaming)
+            .await?;
+
+        self.apply_word_boost(&mut parameters);
+
+        self.validate_transcription(&parameters)?;
+
+        let form = self
+            .transcription_form(AudioTranscriptionParameters {
+                stream: Some(true),
+                ..parameters
+            })
+            .await?;
+
+        let mut segmenter = TranscriptionSegmenter::new(synthesize_segments);
+
+        let stream = self
+            .client
+            .post_stream_with_form::<TranscriptionStreamResponse>("/audio/transcriptions", form)
+            .await
+            .flat_map(move |item| {
+                let events = match item {
+                    Ok(response) => segmenter.push(response).into_iter().map(Ok).collect(),
+                    Err(error) => vec![Err(error.into_audio_too_short())],
+                };
+
+                futures::stream::iter(events)
+            });
+
+        // the done event is the last one, so stop without waiting for the provider to close the stream
+        let stream = futures::stream::unfold((stream, false), |(mut stream, done)| async move {
+            if done {
+                return None;
+            }
+
+            let event = stream.next().await?;
+            let done = matches!(event, Ok(TranscriptionStreamEvent::Done(_)));
+
+            Some((event, (stream, done)))
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn transcription_form(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> Result<reqwest::multipart::Form, APIError> {
+        let mut form = self.client.multipart_form();
+
+        let file = parameters.file.into_part(FileKind::Audio).await?;
+
+        form = form.part("file", file);
+
+        form = form.text("model", parameters.model);
+
+        if let Some(prompt) = parameters.prompt {
+            form = form.text("prompt", prompt);
+        }
+
+        if let Some(language) = parameters.language {
+            form = form.text("language", language.to_string());
+        }
+
+        if let Some(chunking_strategy) = parameters.chunking_strategy {
+            form = form.text("chunking_strategy", chunking_strategy.to_string());
+        }
+
+        if let Some(response_format) = self.client.transcription_format(parameters.response_format)
+        {
+            form = form.text("response_format", response_format.to_string());
+        }
+
+        if let Some(stream) = parameters.stream {
+            form = form.text("stream", stream.to_string());
+        }
+
+        if let Some(temperature) = parameters.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+
+        if let Some(timestamp_granularities) = parameters.timestamp_granularities {
+            form = form.text(
+                "timestamp_granularities",
+                timestamp_granularities
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            );
+        }
+
+        for include in parameters.include.into_iter().flatten() {
+            form = form.text("include[]", include.to_string());
+        }
+
+        for (word, boost) in parameters.word_boost.into_iter().flatten() {
+            form = form.text("word_boost[]", format!("{word}:{boost}"));
+        }
+
+        if let Some(extra_body) = parameters.extra_body {
+            match extra_body {
+                Value::Object(map) if self.client.bracket_form_fields => {
+                    let mut fields = vec![];
+
+                    for (key, value) in map {
+                        bracket_form_fields(key, value, &mut fields);
+                    }
+
+                    for (key, value) in fields {
+                        form = form.text(key, value);
+                    }
+                }
+                Value::Object(map) => {
+                    for (key, value) in map {
+                        form = form.text(key, value.to_string());
+                    }
+                }
+                _ => {
+                    return Err(APIError::BadRequestError {
+                        message: "extra_body must be formatted as a map of key: value".to_string(),
+                        request_id: None,
+                    });
+                }
+            }
+        }
+
+        Ok(form.rename_fields(&self.field_name_map).into_form())
+    }
+
+    /// Sends the transcription as JSON with a `file_url`, so the provider fetches the audio itself.
+    async fn create_transcription_from_url(
+        &self,
+        url: String,
+        parameters: AudioTranscriptionParameters,
+    ) -> Result<String, APIError> {
+        if !self.client.allow_file_urls {
+            return Err(APIError::ValidationError {
+                field: "file".to_string(),
+                value: url,
+                allowed: "bytes or a file, OpenAI doesn't fetch file URLs (use `set_allow_file_urls` for compatible providers that do)".to_string(),
+            });
+        }
+
+        let accept = self.transcription_accept(&parameters);
+
+        let response_format = self
+            .client
+            .transcription_format(parameters.response_format.clone());
+
+        let mut body = serde_json::to_value(AudioTranscriptionParameters {
+            response_format,
+            ..parameters
+        })
+        .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        if let Value::Object(map) = &mut body {
+            map.remove("file");
+            map.insert("file_url".to_string(), Value::String(url));
+        }
+
+        let bytes = self
+            .client
+            .post_raw("/audio/transcriptions", &body, Some(&accept))
+            .await
+            .map_err(APIError::into_audio_too_short)?;
+
+        let response = String::from_utf8(bytes.to_vec())
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        self.adapt(response)
+    }
+
+    /// Converts a JSON response with the adapter, other responses (ie. text or srt) are returned as is.
+    fn adapt(&self, response: String) -> Result<String, APIError> {
+        let Some(adapter) = &self.adapter else {
+            return Ok(response);
+        };
+
+        let Ok(raw) = serde_json::from_str::<Value>(&response) else {
+            return Ok(response);
+        };
+
+        Ok(adapter.to_openai(raw)?.to_string())
+    }
+
+    /// The Accept header of a transcription, the override or the mime type of the format that is sent.
+    fn transcription_accept(&self, parameters: &AudioTranscriptionParameters) -> String {
+        parameters.accept.clone().unwrap_or_else(|| {
+            self.client
+                .transcription_format(parameters.response_format.clone())
+                .unwrap_or(AudioOutputFormat::Json)
+                .mime_type()
+                .to_string()
+        })
+    }
+
+    /// Translates audio into English.
+    /// Audio that is too short returns `APIError::AudioTooShort`.
+    pub async fn create_translation(
+        &self,
+        mut parameters: AudioTranslationParameters,
+    ) -> Result<String, APIError> {
+        Client::apply_default_model(
+            &mut parameters.model,
+            &self.client.default_translation_model,
+        );
+
+        self.client
+            .check_model_capability(&parameters.model, ModelCapability::Translation)
+            .await?;
+
+        let form = self.translation_form(parameters).await?;
+
+        let response = self
+            .client
+            .post_with_form(
+                "/audio/translations",
+                form.rename_fields(&self.field_name_map).into_form(),
+            )
+            .await
+            .map_err(APIError::into_audio_too_short)?;
+
+        self.adapt(response)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Streams the English translation as text deltas, for providers whose translation models stream.
+    /// A provider that answers without server-sent events fails the stream with `StreamError::NotAnEventStream`.
+    pub async fn create_translation_stream(
+        &self,
+        mut parameters: AudioTranslationParameters,
+    ) -> Result<TranslationEventStream, APIError> {
+        Client::apply_default_model(
+            &mut parameters.model,
+            &self.client.default_translation_model,
+        );
+
+        self.client
+            .check_model_capability(&parameters.model, ModelCapability::TranslationStreaming)
+            .await?;
+
+        let form = self
+            .translation_form(parameters)
+            .await?
+            .text("stream", "true")
+            .rename_fields(&self.field_name_map);
+
+        let stream = self
+            .client
+            .post_stream_with_form::<TranslationStreamResponse>(
+                "/audio/translations",
+                form.into_form(),
+            )
+            .await
+            .filter_map(|item| {
+                futures::future::ready(match item {
+                    Ok(TranslationStreamResponse::Delta { delta }) => {
+                        Some(Ok(TranslationStreamEvent::Delta(delta)))
+                    }
+                    Ok(TranslationStreamResponse::Done { text }) => {
+                        Some(Ok(TranslationStreamEvent::Done(text)))
+                    }
+                    Ok(TranslationStreamResponse::Other) => None,
+                    Err(error) => Some(Err(error)),
+                })
+            });
+
+        // the done event is the last one, so stop without waiting for the provider to close the stream
+        let stream = futures::stream::unfold((stream, false), |(mut stream, done)| async move {
+            if done {
+                return None;
+            }
+
+            let event = stream.next().await?;
+            let done = matches!(event, Ok(TranslationStreamEvent::Done(_)));
+
+            Some((event, (stream, done)))
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn translation_form(
+        &self,
+        parameters: AudioTranslationParameters,
+    ) -> Result<MultipartForm, APIError> {
+        let mut form = self.client.multipart_form();
+
+        let file = parameters.file.into_part(FileKind::Audio).await?;
+        form = form.part("file", file);
+
+        form = form.text("model", parameters.model);
+
+        if let Some(prompt) = parameters.prompt {
+            form = form.text("prompt", prompt);
+        }
+
+        if let Some(response_format) = parameters.response_format {
+            form = form.text("response_format", response_format.to_string());
+        }
+
+        if let Some(temperature) = parameters.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+
+        Ok(form)
+    }
+
+    /// Translates audio into English, with the detected language, duration and segments of the audio.
+    /// The response format must be unset or verbose_json.
+    pub async fn create_translation_verbose(
+        &self,
+        mut parameters: AudioTranslationParameters,
+    ) -> Result<VerboseTranslationResponse, APIError> {
+        match parameters.response_format {
+            None | Some(AudioOutputFormat::VerboseJson) => {}
+            Some(response_format) => {
+                return Err(APIError::ValidationError {
+                    field: "response_format".to_string(),
+                    value: response_format.to_string(),
+                    allowed: "verbose_json".to_string(),
+                })
+            }
+        }
+
+        parameters.response_format = Some(AudioOutputFormat::VerboseJson);
+
+        let response = self.create_translation(parameters).await?;
+
+        serde_json::from_str(&response).map_err(|error| APIError::ParseError(error.to_string()))
+    }
+
+    /// Transcribes the audio, generates a reply to the conversation and speaks it.
+    /// If a stage fails, `APIError::VoiceChatError` contains the stage and its error.
+    pub async fn voice_chat(
+        &self,
+        parameters: VoiceChatParameters,
+    ) -> Result<VoiceChatResponse, APIError> {
+        let stage_error = |stage: VoiceChatStage| {
+            move |error: APIError| APIError::VoiceChatError {
+                stage,
+                source: Box::new(error),
+            }
+        };
+
+        // plain text, unless the model only returns json (ie. gpt-4o-transcribe)
+        let response_format =
+            match TranscriptionModelRules::for_model(&parameters.transcription_model) {
+                Some(rules) if !rules.response_formats.contains(&"text") => AudioOutputFormat::Json,
+                _ => AudioOutputFormat::Text,
+            };
+
+        let transcript = self
+            .create_transcription(AudioTranscriptionParameters {
+                file: parameters.audio_in,
+                model: parameters.transcription_model,
+                response_format: Some(response_format.clone()),
+                ..Default::default()
+            })
+            .await
+            .map_err(stage_error(VoiceChatStage::Transcription))?;
+
+        let transcript_in = match response_format {
+            AudioOutputFormat::Json => serde_json::from_str::<Value>(&transcript)
+                .ok()
+                .and_then(|transcript| transcript["text"].as_str().map(str::to_string))
+                .ok_or_else(|| {
+                    stage_error(VoiceChatStage::Transcription)(APIError::ParseError(format!(
+                        "no text in the transcription: {transcript}"
+                    )))
+                })?,
+            _ => transcript,
+        }
+        .trim()
+        .to_string();
+
+        let mut updated_history = parameters.history;
+
+        updated_history.push(ChatMessage::User {
+            content: ChatMessageContent::Text(transcript_in.clone()),
+            name: None,
+        });
+
+        let response = self
+            .client
+            .chat()
+            .create(ChatCompletionParameters {
+                model: parameters.chat_model,
+                messages: updated_history.clone(),
+                ..Default::default()
+            })
+            .await
+            .map_err(stage_error(VoiceChatStage::Chat))?;
+
+        let reply = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| APIError::ParseError("the response has no choices".to_string()))
+            .map_err(stage_error(VoiceChatStage::Chat))?;
+
+        let reply_text = reply
+            .text()
+            .ok_or_else(|| APIError::ParseError("the reply has no text content".to_string()))
+            .map_err(stage_error(VoiceChatStage::Chat))?
+            .to_string();
+
+        updated_history.push(reply);
+
+        let reply_audio = self
+            .create_speech(AudioSpeechParameters {
+                input: reply_text.clone(),
+                ..parameters.speech
+            })
+            .await
+            .map_err(stage_error(VoiceChatStage::Speech))?;
+
+        Ok(VoiceChatResponse {
+            transcript_in,
+            reply_text,
+            reply_audio,
+            updated_history,
+        })
+    }
+
+    /// Transcribes the files, `concurrency` at a time, and returns the transcriptions in the order of the files.
+    /// A file that fails doesn't stop the others, its error is in the results.
+    pub async fn create_transcriptions(
+        &self,
+        parameters: Vec<AudioTranscriptionParameters>,
+        options: BatchTranscriptionOptions,
+    ) -> BatchTranscriptionResult {
+        let total = parameters.len();
+        let completed = AtomicUsize::new(0);
+
+        let transcriptions = parameters
+            .into_iter()
+            .map(|parameters| async {
+                let result = self.create_transcription(parameters).await;
+
+                // counted when the file finishes, so the count only goes up
+                let completed = completed.fetch_add(1, Ordering::SeqCst) + 1;
+
+                if let Some(on_progress) = &options.on_progress {
+                    (on_progress.0)(completed, total);
+                }
+
+                result
+            })
+            .collect();
+
+        let results = buffered(transcriptions, options.concurrency).await;
+        let succeeded = results.iter().filter(|result| result.is_ok()).count();
+
+        BatchTranscriptionResult {
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+        }
+    }
+
+    #[cfg(feature = "long-transcription")]
+    /// Transcribes a long WAV file by splitting it into overlapping chunks, which are transcribed concurrently.
+    /// The segment and word timestamps of the chunks are offset and stitched into one transcription.
+    /// If a chunk still fails after its retries, `APIError::TranscriptionChunkError` contains its time range.
+    pub async fn transcribe_long(
+        &self,
+        wav: impl Into<bytes::Bytes>,
+        options: LongTranscriptionOptions,
+    ) -> Result<VerboseTranscriptionResponse, APIError> {
+        use futures::TryStreamExt;
+
+        let chunks = split_wav(&wav.into(), options.chunk_duration, options.overlap)?;
+
+        let transcriptions = futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| self.transcribe_chunk(chunk, &options)),
+        )
+        .buffered(options.concurrency.max(1))
+        .try_collect()
+        .await?;
+
+        Ok(merge_transcriptions(transcriptions))
+    }
+
+    #[cfg(feature = "long-transcription")]
+    async fn transcribe_chunk(
+        &self,
+        chunk: AudioChunk,
+        options: &LongTranscriptionOptions,
+    ) -> Result<(std::ops::Range<f32>, VerboseTranscriptionResponse), APIError> {
+        use crate::v1::clock::retry;
+        use crate::v1::resources::shared::FileUploadBytes;
+
+        retry(
+            &*self.client.clock,
+            options.max_retries,
+            APIError::retry_after,
+            || async {
+                self.create_transcription(AudioTranscriptionParameters {
+                    file: FileUpload::Bytes(FileUploadBytes::new(
+                        chunk.wav.clone(),
+                        format!("chunk-{}.wav", chunk.index),
+                    )),
+                    model: options.model.clone(),
+                    language: options.language.clone(),
+                    prompt: options.prompt.clone(),
+                    response_format: Some(AudioOutputFormat::VerboseJson),
+                    timestamp_granularities: Some(vec![
+                        TimestampGranularity::Segment,
+                        TimestampGranularity::Word,
+                    ]),
+                    ..Default::default()
+                })
+                .await
+                .and_then(|text| {
+                    serde_json::from_str(&text)
+                        .map_err(|error| APIError::ParseError(error.to_string()))
+                })
+            },
+        )
+        .await
+        .map(|transcription| (chunk.time_range.clone(), transcription))
+        .map_err(|error| APIError::TranscriptionChunkError {
+            start: chunk.time_range.start,
+            end: chunk.time_range.end,
+            source: Box::new(error),
+        })
+    }
+
+    #[cfg(feature = "stream")]
+    /// Generates audio from text that arrives incrementally, ie. the deltas of a chat completion stream.
+    /// The text is buffered until a sentence is complete, every sentence is synthesized with `create_speech`
+    /// and its audio is one chunk of the stream, in the order of the text.
+    /// The next sentence is synthesized while the audio of the current one is played.
+    /// The input of the parameters is replaced by the sentences, a failed sentence is an error item and the stream goes on.
+    pub fn create_speech_from_text_stream<S>(
+        &self,
+        text_stream: S,
+        parameters: AudioSpeechParameters,
+    ) -> AudioSpeechStream
+    where
+        S: Stream<Item = String> + Send + 'static,
+    {
+        let client = self.client.clone();
+
+        let stream = sentences(text_stream)
+            .map(move |sentence| {
+                let client = client.clone();
+                let parameters = AudioSpeechParameters {
+                    input: sentence,
+                    ..parameters.clone()
+                };
+
+                AbortOnDrop(tokio::spawn(async move {
+                    client.audio().create_speech(parameters).await
+                }))
+            })
+            .buffered(SENTENCE_LOOKAHEAD)
+            .map(|result| {
+                result.map(|speech| AudioSpeechResponseChunkResponse {
+                    bytes: speech.bytes,
+                })
+            });
+
+        AudioSpeechStream {
+            stream: Box::pin(stream),
+            clock: self.client.clock.clone(),
+        }
     }
 
     #[cfg(feature = "stream")]
     /// Generates audio from the input text.
+    /// Dropping the stream early closes the connection, `close` does the same explicitly.
     pub async fn create_speech_stream(
         &self,
-        parameters: AudioSpeechParameters,
-    ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<AudioSpeechResponseChunkResponse, APIError>> + Send>>,
-        APIError,
-    > {
+        mut parameters: AudioSpeechParameters,
+    ) -> Result<AudioSpeechStream, APIError> {
         use crate::v1::resources::audio::StreamAudioSpeechParameters;
 
+        self.client.apply_speech_defaults(&mut parameters);
+
+        self.check_voice(&parameters.voice)?;
+
+        self.client
+            .check_model_capability(&parameters.model, ModelCapability::Speech)
+            .await?;
+
+        let accept = speech_accept(&parameters.accept, &parameters.response_format);
+
         let stream_parameters = StreamAudioSpeechParameters {
             model: parameters.model,
             input: parameters.input,
@@ -156,17 +1413,1697 @@ impl Audio<'_> {
             voice_text: None,
             response_format: parameters.response_format,
             speed: parameters.speed,
+            stream_format: parameters.stream_format,
             stream: true,
         };
 
-        let stream = Box::pin(
-            self.client
-                .post_stream_raw("/audio/speech", &stream_parameters)
+        let stream: Pin<Box<dyn Stream<Item = _> + Send>> = match stream_parameters.stream_format {
+            Some(SpeechStreamFormat::Sse) => Box::pin(
+                self.client
+                    .post_stream::<_, SpeechStreamResponse>(
+                        "/audio/speech",
+                        &stream_parameters,
+                        None,
+                    )
+                    .await
+                    .filter_map(|item| async move {
+                        match item {
+                            Ok(SpeechStreamResponse::Delta { audio }) => Some(decode_audio(&audio)),
+                            Ok(_) => None,
+                            Err(error) => Some(Err(error)),
+                        }
+                    }),
+            ),
+            _ => Box::pin(
+                self.client
+                    .post_stream_raw("/audio/speech", &stream_parameters, Some(&accept))
+                    .await?
+                    .map(|item| item.map(|bytes| AudioSpeechResponseChunkResponse { bytes })),
+            ),
+        };
+
+        Ok(AudioSpeechStream {
+            stream: buffered_stream(stream, self.client.stream_buffer_size),
+            clock: self.client.clock.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::error::{APIError, VoiceChatStage};
+    use crate::v1::resources::audio::{
+        AudioOutputFormat, AudioSpeechParameters, AudioSpeechParametersBuilder,
+        AudioSpeechResponseFormat, AudioTranscriptionParameters,
+        AudioTranscriptionParametersBuilder, VoiceChatParametersBuilder,
+    };
+    use crate::v1::resources::chat::{ChatMessage, ChatMessageContent};
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+    use crate::v1::test_support::{
+        json_response, listen, read_request, response, serve, serve_with, Request,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+    use tokio::task::JoinHandle;
+
+    /// Serves one speech request with a chunked response, waiting 20ms between the chunks.
+    async fn serve_chunked_speech(chunks: Vec<Vec<u8>>) -> String {
+        let (listener, base_url) = listen().await;
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: audio/mpeg\r\ntransfer-encoding: chunked\r\n\r\n")
                 .await
+                .unwrap();
+
+            for chunk in chunks {
+                socket
+                    .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                    .await
+                    .unwrap();
+                socket.write_all(&chunk).await.unwrap();
+                socket.write_all(b"\r\n").await.unwrap();
+                socket.flush().await.unwrap();
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+
+            socket.write_all(b"0\r\n\r\n").await.unwrap();
+        });
+
+        base_url
+    }
+
+    #[tokio::test]
+    async fn test_create_speech_collects_all_chunks_of_chunked_response() {
+        let chunks: Vec<Vec<u8>> = (0..3u8).map(|index| vec![index; 4096]).collect();
+        let expected = chunks.concat();
+
+        let base_url = serve_chunked_speech(chunks).await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .build()
+            .unwrap();
+
+        let response = client.audio().create_speech(parameters).await.unwrap();
+
+        assert_eq!(response.bytes.to_vec(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_create_speech_reports_progress_of_chunked_response() {
+        let chunks: Vec<Vec<u8>> = (0..3u8).map(|index| vec![index; 4096]).collect();
+
+        let base_url = serve_chunked_speech(chunks).await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .build()
+            .unwrap();
+
+        let mut progress = vec![];
+
+        let mut file = vec![];
+        let written = client
+            .audio()
+            .create_speech_to(parameters, &mut file, |downloaded, total| {
+                progress.push((downloaded, total))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(written, 3 * 4096);
+        assert_eq!(file.len(), 3 * 4096);
+        assert!(progress.len() >= 3);
+        assert!(progress.iter().all(|(_, total)| total.is_none()));
+        assert!(progress.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(progress.last(), Some(&(3 * 4096, None)));
+    }
+
+    #[tokio::test]
+    async fn test_transcription_with_file_url_is_sent_as_json() {
+        let mut client = Client::new("sk-test".to_string());
+
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Url("https://example.com/audio.mp3".to_string()))
+            .model("whisper-large-v3")
+            .language("en")
+            .response_format(AudioOutputFormat::Json)
+            .build()
+            .unwrap();
+
+        let error = client
+            .audio()
+            .create_transcription(parameters.clone())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "file"));
+
+        client.set_allow_file_urls(true);
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_transcription(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(prepared.headers["content-type"], "application/json");
+        assert_eq!(
+            prepared.body,
+            PreparedBody::Json(serde_json::json!({
+                "file_url": "https://example.com/audio.mp3",
+                "model": "whisper-large-v3",
+                "language": "en",
+                "response_format": "json"
+            }))
+        );
+    }
+
+    /// Serves the three requests of a voice chat, answering by path.
+    async fn serve_voice_chat(chat_status: &'static str) -> (Client, JoinHandle<Vec<Request>>) {
+        let (base_url, server) = serve_with(3, move |request| match request.route().as_str() {
+            "POST /audio/transcriptions" => json_response("200 OK", r#"{"text": "What is the capital of France?"}"#),
+            "POST /chat/completions" => json_response(
+                chat_status,
+                r#"{"id": "chatcmpl-1", "created": 1, "model": "gpt-4o", "choices": [{"index": 0, "message": {"role": "assistant", "content": "Paris."}, "finish_reason": "stop"}]}"#,
+            ),
+            _ => response("200 OK", &["content-type: audio/mpeg"], "audio"),
+        })
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_voice_chat_runs_all_stages_and_reports_failed_stage() {
+        let parameters = VoiceChatParametersBuilder::default()
+            .audio_in(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "question.mp3",
+            )))
+            .history(vec![ChatMessage::System {
+                content: ChatMessageContent::Text("Answer briefly.".to_string()),
+                name: None,
+            }])
+            .transcription_model("gpt-4o-transcribe")
+            .chat_model("gpt-4o")
+            .speech(
+                AudioSpeechParametersBuilder::default()
+                    .model("tts-1")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let (client, server) = serve_voice_chat("200 OK").await;
+
+        let response = client.audio().voice_chat(parameters.clone()).await.unwrap();
+
+        assert_eq!(response.transcript_in, "What is the capital of France?");
+        assert_eq!(response.reply_text, "Paris.");
+        assert_eq!(response.reply_audio.bytes.to_vec(), b"audio".to_vec());
+        assert_eq!(response.updated_history.len(), 3);
+        assert_eq!(response.updated_history[2].text(), Some("Paris."));
+
+        let requests = server.await.unwrap();
+
+        assert_eq!(requests[1].route(), "POST /chat/completions");
+        assert!(requests[1]
+            .text()
+            .contains("What is the capital of France?"));
+        assert_eq!(requests[2].route(), "POST /audio/speech");
+        assert!(requests[2].text().contains(r#""input":"Paris.""#));
+
+        let (client, _) = serve_voice_chat("500 Internal Server Error").await;
+
+        let error = client.audio().voice_chat(parameters).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            APIError::VoiceChatError {
+                stage: VoiceChatStage::Chat,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_closing_speech_stream_early_releases_connection() {
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let (listener, base_url) = listen().await;
+
+        let open_connections = Arc::new(AtomicUsize::new(0));
+        let server_open_connections = open_connections.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let open_connections = server_open_connections.clone();
+
+                open_connections.fetch_add(1, Ordering::SeqCst);
+
+                tokio::spawn(async move {
+                    read_request(&mut socket).await;
+
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: audio/mpeg\r\ntransfer-encoding: chunked\r\n\r\n")
+                        .await;
+
+                    // an endless response, which only ends when the client closes the connection
+                    while socket.write_all(b"400\r\n").await.is_ok()
+                        && socket.write_all(&[0; 1024]).await.is_ok()
+                        && socket.write_all(b"\r\n").await.is_ok()
+                    {
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                    }
+
+                    open_connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        for iteration in 0..20 {
+            let parameters = AudioSpeechParametersBuilder::default()
+                .model("tts-1")
+                .input("Hello!")
+                .build()
+                .unwrap();
+
+            let mut stream = client
+                .audio()
+                .create_speech_stream(parameters)
+                .await
+                .unwrap();
+
+            assert!(stream.next().await.unwrap().is_ok());
+
+            if iteration % 2 == 0 {
+                stream.close();
+            } else {
+                drop(stream);
+            }
+
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while open_connections.load(Ordering::SeqCst) > 0 {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .expect("the connection was not released");
+        }
+    }
+
+    #[cfg(all(feature = "stream", feature = "tokio-util"))]
+    #[tokio::test]
+    async fn test_transcription_text_reader_reads_deltas_as_they_arrive() {
+        use tokio::io::AsyncReadExt;
+
+        let (listener, base_url) = listen().await;
+
+        let (read_sender, read_receiver) = tokio::sync::oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n")
+                .await
+                .unwrap();
+
+            let write_events = |events: &[&str]| {
+                let body: String = events
+                    .iter()
+                    .map(|event| format!("data: {event}\n\n"))
+                    .collect();
+
+                format!("{:x}\r\n{body}\r\n", body.len())
+            };
+
+            socket
+                .write_all(
+                    write_events(&[r#"{"type":"transcript.text.delta","delta":"Hello "}"#])
+                        .as_bytes(),
+                )
+                .await
+                .unwrap();
+
+            // the rest is only sent after the first delta was read
+            read_receiver.await.unwrap();
+
+            socket
+                .write_all(
+                    write_events(&[
+                        r#"{"type":"transcript.text.delta","delta":"from the café."}"#,
+                        r#"{"type":"transcript.text.done","text":"Hello from the café."}"#,
+                    ])
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            socket.write_all(b"0\r\n\r\n").await.unwrap();
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "meeting.mp3",
+            )))
+            .model("gpt-4o-transcribe")
+            .build()
+            .unwrap();
+
+        let mut reader = client
+            .audio()
+            .create_transcription_text_reader(parameters)
+            .await
+            .unwrap();
+
+        let mut buffer = [0; 64];
+        let read = reader.read(&mut buffer).await.unwrap();
+
+        assert_eq!(&buffer[..read], b"Hello ");
+
+        read_sender.send(()).unwrap();
+
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).await.unwrap();
+
+        assert_eq!(rest, "from the café.");
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_translation_stream_yields_deltas_and_fails_clearly_without_streaming() {
+        use crate::v1::error::StreamError;
+        use crate::v1::resources::audio::{
+            AudioTranslationParametersBuilder, TranslationStreamEvent,
+        };
+        use futures::StreamExt;
+
+        let parameters = |model: &str| {
+            AudioTranslationParametersBuilder::default()
+                .file(FileUpload::Bytes(FileUploadBytes::new(
+                    vec![0; 16],
+                    "german.mp3",
+                )))
+                .model(model)
+                .build()
                 .unwrap()
-                .map(|item| item.map(|bytes| AudioSpeechResponseChunkResponse { bytes })),
+        };
+
+        let events = [
+            r#"{"type":"translation.text.delta","delta":"Good "}"#,
+            r#"{"type":"translation.text.delta","delta":"morning."}"#,
+            r#"{"type":"translation.text.done","text":"Good morning."}"#,
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .collect();
+
+        let (base_url, server) = serve([response(
+            "200 OK",
+            &["content-type: text/event-stream"],
+            &body,
+        )])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let stream = client
+            .audio()
+            .create_translation_stream(parameters("whisper-large-v3"))
+            .await
+            .unwrap();
+        let events: Vec<_> = stream.collect().await;
+
+        assert_eq!(
+            events.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            vec![
+                TranslationStreamEvent::Delta("Good ".to_string()),
+                TranslationStreamEvent::Delta("morning.".to_string()),
+                TranslationStreamEvent::Done("Good morning.".to_string()),
+            ]
+        );
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].text().contains("name=\"stream\"\r\n\r\ntrue"));
+
+        let (base_url, _) = serve([json_response("200 OK", r#"{"text":"Good morning."}"#)]).await;
+
+        client.set_base_url(&base_url);
+
+        let mut stream = client
+            .audio()
+            .create_translation_stream(parameters("whisper-large-v3"))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(APIError::StreamError(StreamError::NotAnEventStream { content_type, payload })))
+                if content_type == "application/json" && payload == r#"{"text":"Good morning."}"#
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_transcription_segment_stream_yields_provider_segments() {
+        use crate::v1::resources::audio::TranscriptionStreamEvent;
+        use futures::StreamExt;
+
+        let events = [
+            r#"{"type":"transcript.text.segment","id":"seg_0","start":0.0,"end":1.2,"text":"Hello there.","speaker":"A"}"#,
+            r#"{"type":"transcript.text.delta","delta":"Hello there. "}"#,
+            r#"{"type":"transcript.text.delta","delta":"How are"}"#,
+            r#"{"type":"transcript.text.done","text":"Hello there. How are"}"#,
+        ];
+
+        let body: String = events
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .collect();
+
+        let (base_url, server) = serve([response(
+            "200 OK",
+            &["content-type: text/event-stream"],
+            &body,
+        )])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "meeting.mp3",
+            )))
+            .model("gpt-4o-transcribe-diarize")
+            .build()
+            .unwrap();
+
+        let events: Vec<TranscriptionStreamEvent> = client
+            .audio()
+            .create_transcription_segment_stream(parameters)
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            &events[0],
+            TranscriptionStreamEvent::Segment(segment)
+                if segment.end == 1.2 && segment.speaker.as_deref() == Some("A")
+        ));
+        assert_eq!(
+            events[1],
+            TranscriptionStreamEvent::Delta("Hello there. ".to_string())
+        );
+        assert_eq!(
+            events[3],
+            TranscriptionStreamEvent::Done("Hello there. How are".to_string())
+        );
+
+        let requests = server.await.unwrap();
+
+        assert!(requests[0].text().contains("name=\"stream\"\r\n\r\ntrue"));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_speech_stream_works_with_tokio_stream_combinators() {
+        use tokio::io::AsyncReadExt;
+        use tokio_stream::StreamExt;
+
+        fn assert_send_unpin<T: Send + Unpin>(_: &T) {}
+
+        let chunks: Vec<Vec<u8>> = (0..3u8).map(|index| vec![index; 1024]).collect();
+        let expected = chunks.concat();
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .build()
+            .unwrap();
+
+        let mut client = Client::new("sk-test".to_string());
+
+        let base_url = serve_chunked_speech(chunks.clone()).await;
+        client.set_base_url(&base_url);
+
+        let stream = client
+            .audio()
+            .create_speech_stream(parameters.clone())
+            .await
+            .unwrap();
+
+        assert_send_unpin(&stream);
+
+        let bytes: Vec<u8> = stream
+            .throttle(Duration::from_millis(5))
+            .map(|chunk| chunk.unwrap().bytes.to_vec())
+            .collect::<Vec<Vec<u8>>>()
+            .await
+            .concat();
+
+        assert_eq!(bytes, expected);
+
+        let base_url = serve_chunked_speech(chunks.clone()).await;
+        client.set_base_url(&base_url);
+
+        let mut reader = client
+            .audio()
+            .create_speech_stream(parameters.clone())
+            .await
+            .unwrap()
+            .into_async_read();
+
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await.unwrap();
+
+        assert_eq!(bytes, expected);
+
+        let base_url = serve_chunked_speech(chunks).await;
+        client.set_base_url(&base_url);
+
+        let items: Vec<_> = client
+            .audio()
+            .create_speech_stream(parameters)
+            .await
+            .unwrap()
+            .timeout_stream(Duration::from_millis(1))
+            .collect()
+            .await;
+
+        assert!(matches!(
+            items.last(),
+            Some(Err(APIError::StreamError(
+                crate::v1::error::StreamError::Timeout(crate::v1::error::TimeoutPhase::Idle)
+            )))
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_sse_speech_stream_ends_at_done_sentinel() {
+        use crate::v1::resources::audio::SpeechStreamFormat;
+        use base64::{engine::general_purpose, Engine as _};
+        use futures::StreamExt;
+
+        let chunks: Vec<Vec<u8>> = (0..3u8).map(|index| vec![index; 512]).collect();
+        let expected = chunks.concat();
+
+        let (listener, base_url) = listen().await;
+
+        let (body_sender, body_receiver) = tokio::sync::oneshot::channel();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            body_sender
+                .send(read_request(&mut socket).await.text())
+                .unwrap();
+
+            let mut events: String = chunks
+                .iter()
+                .map(|chunk| {
+                    format!(
+                        "data: {{\"type\": \"speech.audio.delta\", \"audio\": \"{}\"}}\n\n",
+                        general_purpose::STANDARD.encode(chunk)
+                    )
+                })
+                .collect();
+            events.push_str("data: {\"type\": \"speech.audio.done\", \"usage\": {\"input_tokens\": 3, \"output_tokens\": 9, \"total_tokens\": 12}}\n\n");
+            events.push_str("data: [DONE]\n\n");
+
+            socket
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\n\r\n{events}",
+                        events.len()
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+
+            // keep the connection open, the stream has to end at the sentinel
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("gpt-4o-mini-tts")
+            .input("Hello!")
+            .stream_format(SpeechStreamFormat::Sse)
+            .build()
+            .unwrap();
+
+        let stream = client
+            .audio()
+            .create_speech_stream(parameters)
+            .await
+            .unwrap();
+
+        let items: Vec<_> = tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("the stream should end at [DONE]");
+
+        assert_eq!(items.len(), 3);
+
+        let bytes: Vec<u8> = items
+            .into_iter()
+            .map(|item| item.unwrap().bytes.to_vec())
+            .collect::<Vec<_>>()
+            .concat();
+
+        assert_eq!(bytes, expected);
+        assert!(body_receiver
+            .await
+            .unwrap()
+            .contains(r#""stream_format":"sse""#));
+
+        server.abort();
+    }
+
+    #[cfg(feature = "long-transcription")]
+    #[tokio::test]
+    async fn test_transcribe_long_retries_rate_limits_with_backoff_on_clock() {
+        use crate::v1::audio_chunking::{write_wav, WavFormat};
+        use crate::v1::clock::MockClock;
+        use crate::v1::resources::audio::LongTranscriptionOptionsBuilder;
+
+        let rate_limit = json_response(
+            "429 Too Many Requests",
+            r#"{"error": {"message": "Rate limit reached"}}"#,
+        );
+        let (base_url, server) = serve([
+            rate_limit.clone(),
+            rate_limit.clone(),
+            rate_limit,
+            json_response(
+                "200 OK",
+                r#"{"task": "transcribe", "language": "english", "duration": 1.0, "text": "Hello."}"#,
+            ),
+        ])
+        .await;
+
+        let clock = MockClock::auto_advancing();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_clock(clock.clone());
+
+        let format = WavFormat {
+            format: 1,
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+        };
+        let wav = write_wav(&format, &[0; 16000]);
+
+        let options = LongTranscriptionOptionsBuilder::default()
+            .model("whisper-1")
+            .max_retries(3u32)
+            .build()
+            .unwrap();
+
+        let started_at = std::time::Instant::now();
+
+        let transcription = client.audio().transcribe_long(wav, options).await.unwrap();
+
+        server.await.unwrap();
+
+        assert_eq!(transcription.text, "Hello.");
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4)
+            ]
         );
+        assert_eq!(clock.elapsed(), Duration::from_secs(7));
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_chunk_by_duration_frames_pcm_and_wav() {
+        use futures::StreamExt;
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .build()
+            .unwrap();
+
+        let chunk_sizes = |chunks: Vec<Vec<u8>>, format: AudioSpeechResponseFormat| {
+            let parameters = parameters.clone();
+
+            async move {
+                let mut client = Client::new("sk-test".to_string());
+                client.set_base_url(&serve_chunked_speech(chunks).await);
+
+                client
+                    .audio()
+                    .create_speech_stream(parameters)
+                    .await
+                    .unwrap()
+                    .chunk_by_duration(50, format)
+                    .map(|chunk| chunk.unwrap().bytes.len())
+                    .collect::<Vec<usize>>()
+                    .await
+            }
+        };
+
+        // 50ms of 24kHz 16-bit mono is 1200 frames of 2 bytes
+        let pcm = vec![vec![1; 1000], vec![2; 3000], vec![3; 1500], vec![4; 700]];
+
+        assert_eq!(
+            chunk_sizes(pcm, AudioSpeechResponseFormat::Pcm).await,
+            vec![2400, 2400, 1400]
+        );
+
+        // 50ms of 8kHz 16-bit stereo is 400 frames of 4 bytes, the header comes first
+        let mut wav = b"RIFF\xff\xff\xff\xffWAVEfmt \x10\0\0\0\x01\0\x02\0".to_vec();
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&32000u32.to_le_bytes());
+        wav.extend_from_slice(&[4, 0, 16, 0]);
+        wav.extend_from_slice(b"data\xff\xff\xff\xff");
+        wav.extend_from_slice(&[0; 4000]);
+
+        let wav_chunks = wav.chunks(1000).map(<[u8]>::to_vec).collect();
+
+        assert_eq!(
+            chunk_sizes(wav_chunks, AudioSpeechResponseFormat::Wav).await,
+            vec![44, 1600, 1600, 800]
+        );
+
+        // compressed audio passes through unchanged
+        let mp3 = vec![vec![1; 1000], vec![2; 3000]];
+
+        assert_eq!(
+            chunk_sizes(mp3, AudioSpeechResponseFormat::Mp3).await,
+            vec![1000, 3000]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcription_is_validated_against_the_model_before_sending() {
+        use crate::v1::resources::audio::TranscriptionInclude;
+
+        let client = Client::new("sk-test".to_string());
+
+        let mut parameters = AudioTranscriptionParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+            model: "gpt-4o-transcribe".to_string(),
+            response_format: Some(AudioOutputFormat::Srt),
+            include: Some(vec![TranscriptionInclude::Logprobs]),
+            ..Default::default()
+        };
+
+        // rejected without sending the request, the dry run would return the prepared request
+        let error = client
+            .dry_run()
+            .prepare(|client| {
+                let parameters = parameters.clone();
+
+                async move { client.audio().create_transcription(parameters).await }
+            })
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, APIError::ValidationError { field, .. } if field == "response_format")
+        );
+
+        parameters.skip_validation = true;
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_transcription(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Multipart(parts) = prepared.body else {
+            panic!("expected a multipart body");
+        };
+
+        assert!(parts.iter().any(|part| part.name == "include[]"));
+    }
+
+    #[tokio::test]
+    async fn test_field_name_map_renames_the_multipart_fields() {
+        let client = Client::new("sk-test".to_string());
+
+        let parameters = AudioTranscriptionParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+            model: "whisper-1".to_string(),
+            prompt: Some("OpenAI".to_string()),
+            ..Default::default()
+        };
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move {
+                client
+                    .audio()
+                    .with_field_name_map(HashMap::from([("file", "audio")]))
+                    .create_transcription(parameters)
+                    .await
+            })
+            .await
+            .unwrap();
+
+        let PreparedBody::Multipart(parts) = prepared.body else {
+            panic!("expected a multipart body");
+        };
+
+        let audio = parts.iter().find(|part| part.name == "audio").unwrap();
+
+        assert_eq!(audio.filename.as_deref(), Some("audio.mp3"));
+        assert!(parts.iter().all(|part| part.name != "file"));
+        // fields that aren't in the map keep their name
+        assert!(parts.iter().any(|part| part.name == "model"));
+        assert!(parts.iter().any(|part| part.name == "prompt"));
+    }
+
+    #[tokio::test]
+    async fn test_adapter_converts_transcription_of_another_provider() {
+        use crate::v1::adapters::CloudflareAdapter;
+
+        let (base_url, _) = serve([json_response(
+            "200 OK",
+            r#"{"result": {"text": "Hello!", "word_count": 1}, "success": true, "errors": [], "messages": []}"#,
+        )])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = AudioTranscriptionParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+            model: "@cf/openai/whisper".to_string(),
+            ..Default::default()
+        };
+
+        let response = client
+            .audio()
+            .with_adapter(CloudflareAdapter)
+            .create_transcription(parameters)
+            .await
+            .unwrap();
+
+        let response: Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(response, json!({"text": "Hello!", "word_count": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_batch_transcription_reports_progress_and_counts() {
+        use crate::v1::resources::audio::BatchTranscriptionOptions;
+        use std::sync::{Arc, Mutex};
+
+        let (base_url, _) = serve_with(5, |request| {
+            let body = request.text();
+            let name = body
+                .split("filename=\"")
+                .nth(1)
+                .and_then(|rest| rest.split('"').next())
+                .unwrap();
+
+            match name {
+                "audio-2.mp3" => json_response(
+                    "400 Bad Request",
+                    r#"{"error": {"message": "invalid file"}}"#,
+                ),
+                _ => response("200 OK", &[], &format!("transcript of {name}")),
+            }
+        })
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = (0..5)
+            .map(|index| AudioTranscriptionParameters {
+                file: FileUpload::Bytes(FileUploadBytes::new(
+                    vec![0; 16],
+                    format!("audio-{index}.mp3"),
+                )),
+                model: "whisper-1".to_string(),
+                response_format: Some(AudioOutputFormat::Text),
+                ..Default::default()
+            })
+            .collect();
+
+        let progress = Arc::new(Mutex::new(vec![]));
+
+        let options = BatchTranscriptionOptions {
+            concurrency: 2,
+            ..Default::default()
+        }
+        .with_progress({
+            let progress = progress.clone();
+
+            move |completed, total| progress.lock().unwrap().push((completed, total))
+        });
+
+        let batch = client
+            .audio()
+            .create_transcriptions(parameters, options)
+            .await;
+
+        assert_eq!(
+            *progress.lock().unwrap(),
+            (1..=5).map(|completed| (completed, 5)).collect::<Vec<_>>()
+        );
+        assert_eq!((batch.succeeded, batch.failed), (4, 1));
+        assert_eq!(batch.results[0].as_deref(), Ok("transcript of audio-0.mp3"));
+        assert!(matches!(
+            batch.results[2],
+            Err(APIError::BadRequestError { .. })
+        ));
+        assert_eq!(batch.results[4].as_deref(), Ok("transcript of audio-4.mp3"));
+    }
+
+    #[tokio::test]
+    async fn test_long_transcription_prompt_warns_or_fails_when_strict() {
+        use crate::v1::validation::ValidationWarning;
+        use std::sync::{Arc, Mutex};
+
+        let parameters = AudioTranscriptionParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+            model: "whisper-1".to_string(),
+            prompt: Some("word ".repeat(200)),
+            ..Default::default()
+        };
+
+        let warnings = Arc::new(Mutex::new(vec![]));
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_on_warning({
+            let warnings = warnings.clone();
+
+            move |warning| warnings.lock().unwrap().push(warning.clone())
+        });
+
+        // the request is still sent
+        client
+            .dry_run()
+            .prepare(|client| {
+                let parameters = parameters.clone();
+
+                async move { client.audio().create_transcription(parameters).await }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![ValidationWarning::PromptTooLong {
+                estimated_tokens: 250,
+                limit: 224
+            }]
+        );
+
+        client.set_strict_warnings(true);
+
+        let error = client
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_transcription(parameters).await })
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, APIError::ValidationError { field, allowed, .. } if field == "prompt" && allowed == "at most 224 tokens")
+        );
+        assert_eq!(warnings.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_voice_blend_is_rejected_for_openai_only() {
+        use crate::v1::resources::audio::SpeechVoice;
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .voice(SpeechVoice::blend([("af_bella", 0.75), ("af_sky", 0.25)]))
+            .build()
+            .unwrap();
+
+        let mut client = Client::new("sk-test".to_string());
+
+        let error = client
+            .audio()
+            .create_speech(parameters.clone())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "voice"));
+
+        client.set_base_url("https://tts.example.com/v1");
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_speech(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(body) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert_eq!(
+            body["voice"],
+            serde_json::json!([
+                {"voice": "af_bella", "weight": 0.75},
+                {"voice": "af_sky", "weight": 0.25}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_word_boost_is_sent_as_form_fields_or_folded_into_the_prompt() {
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 16],
+                "audio.wav",
+            )))
+            .model("whisper-1")
+            .word_boost(vec![("Kubernetes".to_string(), 2.0)])
+            .build()
+            .unwrap();
+
+        let form_fields = |client: Client, parameters: AudioTranscriptionParameters| async move {
+            let prepared = client
+                .dry_run()
+                .prepare(
+                    |client| async move { client.audio().create_transcription(parameters).await },
+                )
+                .await
+                .unwrap();
+
+            let PreparedBody::Multipart(parts) = prepared.body else {
+                panic!("expected a multipart body");
+            };
+
+            parts
+                .into_iter()
+                .filter_map(|part| Some((part.name, part.text?)))
+                .filter(|(name, _)| name == "prompt" || name == "word_boost[]")
+                .collect::<Vec<_>>()
+        };
+
+        let mut compatible = Client::new("sk-test".to_string());
+        compatible.set_base_url("https://asr.example.com/v1");
+
+        assert_eq!(
+            form_fields(compatible, parameters.clone()).await,
+            vec![("word_boost[]".to_string(), "Kubernetes:2".to_string())]
+        );
+
+        assert_eq!(
+            form_fields(Client::new("sk-test".to_string()), parameters).await,
+            vec![("prompt".to_string(), "Kubernetes".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accept_header_matches_response_format() {
+        let client = Client::new("sk-test".to_string());
+
+        let speech_accept = |response_format: Option<AudioSpeechResponseFormat>,
+                             accept: Option<&str>| {
+            let client = client.clone();
+            let parameters = AudioSpeechParameters {
+                model: "tts-1".to_string(),
+                input: "Hello!".to_string(),
+                response_format,
+                accept: accept.map(str::to_string),
+                ..Default::default()
+            };
+
+            async move {
+                client
+                    .dry_run()
+                    .prepare(|client| async move { client.audio().create_speech(parameters).await })
+                    .await
+                    .unwrap()
+                    .headers["accept"]
+                    .clone()
+            }
+        };
+
+        assert_eq!(speech_accept(None, None).await, "audio/mpeg");
+        assert_eq!(
+            speech_accept(Some(AudioSpeechResponseFormat::Opus), None).await,
+            "audio/opus"
+        );
+        assert_eq!(
+            speech_accept(Some(AudioSpeechResponseFormat::Pcm), None).await,
+            "audio/pcm"
+        );
+        assert_eq!(
+            speech_accept(Some(AudioSpeechResponseFormat::Wav), Some("audio/x-wav")).await,
+            "audio/x-wav"
+        );
+
+        let transcription_accept = |response_format: Option<AudioOutputFormat>,
+                                    accept: Option<&str>| {
+            let client = client.clone();
+            let parameters = AudioTranscriptionParameters {
+                file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+                model: "whisper-1".to_string(),
+                response_format,
+                accept: accept.map(str::to_string),
+                ..Default::default()
+            };
+
+            async move {
+                client
+                    .dry_run()
+                    .prepare(|client| async move {
+                        client.audio().create_transcription(parameters).await
+                    })
+                    .await
+                    .unwrap()
+                    .headers["accept"]
+                    .clone()
+            }
+        };
+
+        assert_eq!(transcription_accept(None, None).await, "application/json");
+        assert_eq!(
+            transcription_accept(Some(AudioOutputFormat::VerboseJson), None).await,
+            "application/json"
+        );
+        assert_eq!(
+            transcription_accept(Some(AudioOutputFormat::Text), None).await,
+            "text/plain"
+        );
+        assert_eq!(
+            transcription_accept(Some(AudioOutputFormat::Srt), None).await,
+            "application/x-subrip"
+        );
+        assert_eq!(
+            transcription_accept(Some(AudioOutputFormat::Vtt), None).await,
+            "text/vtt"
+        );
+        assert_eq!(
+            transcription_accept(Some(AudioOutputFormat::Srt), Some("text/srt")).await,
+            "text/srt"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_models_are_only_applied_to_an_empty_model() {
+        use crate::v1::resources::audio::AudioTranslationParameters;
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_default_speech_model("gpt-4o-mini-tts")
+            .set_default_transcription_model("gpt-4o-transcribe")
+            .set_default_translation_model("whisper-1");
+
+        let model_of = |body: PreparedBody| match body {
+            PreparedBody::Json(json) => json["model"].as_str().unwrap().to_string(),
+            PreparedBody::Multipart(parts) => parts
+                .into_iter()
+                .find(|part| part.name == "model")
+                .and_then(|part| part.text)
+                .unwrap(),
+            body => panic!("unexpected body {body:?}"),
+        };
+
+        for (model, expected) in [("", "gpt-4o-mini-tts"), ("tts-1", "tts-1")] {
+            let parameters = AudioSpeechParameters {
+                model: model.to_string(),
+                input: "Hello!".to_string(),
+                ..Default::default()
+            };
+
+            let prepared = client
+                .dry_run()
+                .prepare(|client| async move { client.audio().create_speech(parameters).await })
+                .await
+                .unwrap();
+
+            assert_eq!(model_of(prepared.body), expected);
+        }
+
+        for (model, expected) in [("", "gpt-4o-transcribe"), ("whisper-1", "whisper-1")] {
+            let parameters = AudioTranscriptionParameters {
+                file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+                model: model.to_string(),
+                ..Default::default()
+            };
+
+            let prepared = client
+                .dry_run()
+                .prepare(
+                    |client| async move { client.audio().create_transcription(parameters).await },
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(model_of(prepared.body), expected);
+        }
+
+        for (model, expected) in [("", "whisper-1"), ("whisper-large-v3", "whisper-large-v3")] {
+            let parameters = AudioTranslationParameters {
+                file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+                model: model.to_string(),
+                ..Default::default()
+            };
+
+            let prepared = client
+                .dry_run()
+                .prepare(
+                    |client| async move { client.audio().create_translation(parameters).await },
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(model_of(prepared.body), expected);
+        }
+
+        // without a default, an empty model is sent as is
+        let parameters = AudioSpeechParameters {
+            input: "Hello!".to_string(),
+            ..Default::default()
+        };
+
+        let prepared = Client::new("sk-test".to_string())
+            .dry_run()
+            .prepare(|client| async move { client.audio().create_speech(parameters).await })
+            .await
+            .unwrap();
+
+        assert_eq!(model_of(prepared.body), "");
+    }
+
+    #[tokio::test]
+    async fn test_default_speech_voice_and_format_fill_unset_parameters() {
+        use crate::v1::resources::audio::{AudioVoice, SpeechVoice};
+
+        async fn body_of(client: &Client, parameters: AudioSpeechParameters) -> (Value, String) {
+            let prepared = client
+                .dry_run()
+                .prepare(|client| async move { client.audio().create_speech(parameters).await })
+                .await
+                .unwrap();
+
+            let PreparedBody::Json(body) = prepared.body else {
+                panic!("expected a JSON body");
+            };
+
+            (body, prepared.headers["accept"].clone())
+        }
+
+        let mut client = Client::new("sk-test".to_string());
+        client
+            .set_default_speech_voice(AudioVoice::Coral)
+            .set_default_speech_format(AudioSpeechResponseFormat::Wav);
+
+        let parameters = AudioSpeechParameters {
+            model: "gpt-4o-mini-tts".to_string(),
+            input: "Hello!".to_string(),
+            ..Default::default()
+        };
+
+        let (body, accept) = body_of(&client, parameters.clone()).await;
+
+        assert_eq!(body["voice"], "coral");
+        assert_eq!(body["response_format"], "wav");
+        assert_eq!(accept, "audio/wav");
+
+        let (body, _) = body_of(
+            &client,
+            AudioSpeechParameters {
+                voice: Some(SpeechVoice::from("ash")),
+                response_format: Some(AudioSpeechResponseFormat::Opus),
+                ..parameters.clone()
+            },
+        )
+        .await;
+
+        assert_eq!(body["voice"], "ash");
+        assert_eq!(body["response_format"], "opus");
+
+        // without defaults, unset values are left to the server
+        let (body, _) = body_of(&Client::new("sk-test".to_string()), parameters).await;
+
+        assert!(body.get("voice").is_none());
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_nested_extra_body_is_sent_in_bracket_notation() {
+        let parameters = AudioTranscriptionParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![0; 16], "audio.mp3")),
+            model: "whisper-1".to_string(),
+            extra_body: Some(json!({
+                "vad": {"threshold": 0.5, "mode": "aggressive"},
+                "diarization": {"speakers": {"min": 1, "max": 3}, "labels": ["a", "b"]},
+                "beam_size": 5
+            })),
+            ..Default::default()
+        };
+
+        let fields = |client: Client, parameters: AudioTranscriptionParameters| async move {
+            let prepared = client
+                .dry_run()
+                .prepare(
+                    |client| async move { client.audio().create_transcription(parameters).await },
+                )
+                .await
+                .unwrap();
+
+            let PreparedBody::Multipart(parts) = prepared.body else {
+                panic!("expected a multipart body");
+            };
+
+            parts
+                .into_iter()
+                .filter(|part| !["file", "model"].contains(&part.name.as_str()))
+                .map(|part| (part.name, part.text.unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_bracket_form_fields(true);
+
+        let mut bracket_fields = fields(client, parameters.clone()).await;
+        bracket_fields.sort();
+
+        assert_eq!(
+            bracket_fields,
+            [
+                ("beam_size", "5"),
+                ("diarization[labels][]", "a"),
+                ("diarization[labels][]", "b"),
+                ("diarization[speakers][max]", "3"),
+                ("diarization[speakers][min]", "1"),
+                ("vad[mode]", "aggressive"),
+                ("vad[threshold]", "0.5"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+
+        // by default a nested object is sent as JSON in one field
+        let json_fields = fields(Client::new("sk-test".to_string()), parameters).await;
+
+        assert!(json_fields.contains(&(
+            "vad".to_string(),
+            r#"{"mode":"aggressive","threshold":0.5}"#.to_string()
+        )));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_speech_from_text_stream_synthesizes_sentences_in_order() {
+        use futures::StreamExt;
+
+        let (listener, base_url) = listen().await;
+
+        // answers with the input as audio, the first sentence is the slowest
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+
+                tokio::spawn(async move {
+                    let request = read_request(&mut socket).await;
+                    let input = request.json()["input"].as_str().unwrap().to_string();
+
+                    if input.starts_with("Hello") {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+
+                    socket
+                        .write_all(
+                            response("200 OK", &["content-type: audio/mpeg"], &input).as_bytes(),
+                        )
+                        .await
+                        .unwrap();
+                });
+            }
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let text = futures::stream::iter(
+            ["Hello wor", "ld. How are", " you?", " Pi is 3.", "14\nBye"].map(String::from),
+        );
+
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .build()
+            .unwrap();
+
+        let chunks = client
+            .audio()
+            .create_speech_from_text_stream(text, parameters)
+            .map(|chunk| String::from_utf8(chunk.unwrap().bytes.to_vec()).unwrap())
+            .collect::<Vec<String>>()
+            .await;
+
+        assert_eq!(
+            chunks,
+            vec!["Hello world.", "How are you?", "Pi is 3.14", "Bye"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_speech_json_error_at_200_is_returned_as_error() {
+        async fn serve_speech(content_type: &str, body: &str) -> String {
+            let content_type = format!("content-type: {content_type}");
+            let (base_url, _) = serve([response("200 OK", &[&content_type], body)]).await;
+
+            base_url
+        }
+
+        let speech = |base_url: String, detect: bool| async move {
+            let mut client = Client::new("sk-test".to_string());
+            client
+                .set_base_url(&base_url)
+                .set_detect_speech_json_errors(detect);
+
+            let parameters = AudioSpeechParametersBuilder::default()
+                .model("tts-1")
+                .input("Hello!")
+                .build()
+                .unwrap();
+
+            client.audio().create_speech(parameters).await
+        };
+
+        let error = r#"{"error": {"message": "voice not found", "type": "invalid_request_error"}}"#;
+
+        let result = speech(serve_speech("application/json", error).await, true).await;
+
+        assert!(
+            matches!(&result, Err(APIError::BadRequestError { message, .. }) if message == error),
+            "{result:?}"
+        );
+
+        // a provider that labels the error as audio
+        let result = speech(
+            serve_speech(
+                "audio/mpeg",
+                r#"{"error": {"message": "quota", "code": "insufficient_quota"}}"#,
+            )
+            .await,
+            true,
+        )
+        .await;
+
+        assert!(matches!(result, Err(APIError::RateLimitError { .. })));
+
+        let result = speech(serve_speech("audio/mpeg", "{ID3 not json").await, true).await;
+
+        assert_eq!(result.unwrap().bytes.as_ref(), b"{ID3 not json");
+
+        // JSON without an error is not audio either, unless detection is disabled
+        let result = speech(
+            serve_speech("application/json", r#"{"audio": "AAEC"}"#).await,
+            true,
+        )
+        .await;
+
+        assert!(matches!(result, Err(APIError::ParseError(_))));
+
+        #[cfg(any(feature = "stream", feature = "download"))]
+        {
+            use crate::v1::resources::audio::AudioSpeechResponse;
+
+            let response = speech(
+                serve_speech("application/json", r#"{"audio": "AAEC"}"#).await,
+                false,
+            )
+            .await
+            .unwrap();
+
+            let audio = AudioSpeechResponse::from_json_base64(&response.bytes, "/audio").unwrap();
+
+            assert_eq!(audio.bytes.as_ref(), &[0, 1, 2]);
+            assert!(AudioSpeechResponse::from_json_base64(&response.bytes, "/data").is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audio_too_short_is_returned_as_its_own_error() {
+        async fn transcribe(body: &'static str, model: &str) -> Result<String, APIError> {
+            let (base_url, _) = serve([response(
+                "400 Bad Request",
+                &["content-type: application/json", "x-request-id: req_123"],
+                body,
+            )])
+            .await;
+
+            let mut client = Client::new("sk-test".to_string());
+            client.set_base_url(&base_url);
+
+            let parameters = AudioTranscriptionParametersBuilder::default()
+                .file(FileUpload::Bytes(FileUploadBytes::new(
+                    vec![0u8; 44],
+                    "short.wav",
+                )))
+                .model(model)
+                .build()
+                .unwrap();
+
+            #[cfg(feature = "stream")]
+            if parameters.model == "gpt-4o-transcribe" {
+                use futures::StreamExt;
+
+                let mut stream = client
+                    .audio()
+                    .create_transcription_stream(parameters)
+                    .await?;
+
+                return match stream.next().await.unwrap() {
+                    Ok(event) => panic!("expected an error, got {event:?}"),
+                    Err(error) => Err(error),
+                };
+            }
+
+            client.audio().create_transcription(parameters).await
+        }
+
+        // a rejected stream of a streaming model is mapped as well
+        for model in ["whisper-1", "gpt-4o-transcribe"] {
+            let error = transcribe(
+                r#"{"error": {"message": "Audio file is too short. Minimum audio length is 0.1 seconds.", "type": "invalid_request_error", "param": "file", "code": "audio_too_short"}}"#,
+                model,
+            )
+            .await
+            .unwrap_err();
+
+            assert!(matches!(error, APIError::AudioTooShort { .. }), "{error:?}");
+            assert_eq!(
+                error.body().unwrap().message,
+                "Audio file is too short. Minimum audio length is 0.1 seconds."
+            );
+            assert_eq!(
+                error.body().unwrap().code.as_deref(),
+                Some("audio_too_short")
+            );
+            assert_eq!(error.request_id().as_deref(), Some("req_123"));
+            assert!(!error.is_retryable());
+        }
+
+        // other bad requests are not mapped
+        let error = transcribe(
+            r#"{"error": {"message": "Invalid file format.", "type": "invalid_request_error", "param": "file", "code": "invalid_value"}}"#,
+            "whisper-1",
+        )
+        .await
+        .unwrap_err();
+
+        assert!(
+            matches!(error, APIError::BadRequestError { .. }),
+            "{error:?}"
+        );
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_buffered_stream_reads_at_most_the_buffer_ahead_of_a_slow_consumer() {
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = pulled.clone();
+
+        let chunks = futures::stream::iter(0..1000).inspect(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut stream = super::buffered_stream(Box::pin(chunks), 4);
+
+        let settle = || async {
+            for _ in 0..100 {
+                tokio::task::yield_now().await;
+            }
+        };
+
+        // the consumer hasn't read anything yet: 4 chunks in the channel and 1 waiting to be sent
+        settle().await;
+
+        assert_eq!(pulled.load(Ordering::SeqCst), 5);
+
+        let consumed: Vec<i32> = (&mut stream).take(10).collect().await;
+
+        settle().await;
+
+        assert_eq!(consumed, (0..10).collect::<Vec<i32>>());
+        assert_eq!(pulled.load(Ordering::SeqCst), 15);
+
+        // dropping the stream stops reading
+        drop(stream);
+        settle().await;
 
-        Ok(stream)
+        assert_eq!(pulled.load(Ordering::SeqCst), 15);
     }
 }
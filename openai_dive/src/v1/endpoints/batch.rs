@@ -3,10 +3,17 @@ use crate::v1::{
     error::APIError,
     helpers::format_response,
     resources::{
-        batch::{Batch, CreateBatchParameters},
-        shared::{ListResponse, SimpleListParameters},
+        batch::{
+            Batch, BatchLineError, CreateBatchParameters, FailedRequests, OriginalRequests,
+            RequestLookup, ResubmittedBatch,
+        },
+        file::{FilePurpose, UploadFileParameters},
+        shared::{FileUpload, FileUploadBytes, ListResponse, SimpleListParameters},
     },
 };
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::HashMap;
 
 pub struct Batches<'a> {
     pub client: &'a Client,
@@ -61,4 +68,302 @@ impl Batches<'_> {
 
         Ok(response)
     }
+
+    /// Downloads and parses the error file of a batch, an empty list if the batch has no error file.
+    /// Lines that can't be parsed are reported in `parse_errors` instead of failing the whole file.
+    pub async fn failed_requests(&self, id: &str) -> Result<FailedRequests, APIError> {
+        let batch = self.retrieve(id).await?;
+
+        self.failed_requests_of(&batch).await
+    }
+
+    /// Creates a new batch with only the failed requests of a batch, taken from the original requests.
+    /// The new batch uses the same endpoint and completion window, its metadata links it to the original batch via `resubmitted_from`.
+    pub async fn resubmit_failed(
+        &self,
+        id: &str,
+        original_requests: OriginalRequests<'_>,
+    ) -> Result<ResubmittedBatch, APIError> {
+        let batch = self.retrieve(id).await?;
+
+        let failed_requests = self.failed_requests_of(&batch).await?;
+
+        let mut parse_errors = failed_requests.parse_errors;
+
+        let lookup: RequestLookup<'_> = match original_requests {
+            OriginalRequests::InputFile(file_id) => {
+                let content = self.client.files().retrieve_content(&file_id).await?;
+
+                let (requests, errors) = index_requests(&file_id, &content);
+
+                parse_errors.extend(errors);
+
+                Box::new(move |custom_id| requests.get(custom_id).cloned())
+            }
+            OriginalRequests::Lookup(lookup) => lookup,
+        };
+
+        let mut lines = vec![];
+        let mut resubmitted_custom_ids = vec![];
+        let mut missing_custom_ids = vec![];
+
+        for failed_request in failed_requests.requests {
+            match lookup(&failed_request.custom_id) {
+                Some(request) => {
+                    lines.push(request.to_string());
+                    resubmitted_custom_ids.push(failed_request.custom_id);
+                }
+                None => missing_custom_ids.push(failed_request.custom_id),
+            }
+        }
+
+        if lines.is_empty() {
+            return Err(APIError::BadRequestError {
+                message: format!("batch {id} has no failed requests to resubmit"),
+                request_id: None,
+            });
+        }
+
+        let mut metadata = batch.metadata.unwrap_or_default();
+        metadata.insert("resubmitted_from", id)?;
+
+        let file = self
+            .client
+            .files()
+            .upload(UploadFileParameters {
+                file: FileUpload::Bytes(FileUploadBytes::new(
+                    lines.join("\n"),
+                    format!("{id}_failed_requests.jsonl"),
+                )),
+                purpose: FilePurpose::Batch,
+            })
+            .await?;
+
+        let new_batch = self
+            .create(CreateBatchParameters {
+                input_file_id: file.id,
+                endpoint: batch.endpoint,
+                completion_window: batch.completion_window,
+                metadata: Some(metadata),
+            })
+            .await?;
+
+        Ok(ResubmittedBatch {
+            batch: new_batch,
+            resubmitted_custom_ids,
+            missing_custom_ids,
+            parse_errors,
+        })
+    }
+
+    /// Returns the output file of a batch, calling `on_progress` with the bytes downloaded so far and the Content-Length
+    /// as the chunks arrive. The Content-Length is `None` for a chunked response.
+    pub async fn output_with_progress<F>(&self, id: &str, on_progress: F) -> Result<Bytes, APIError>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let output_file_id = self.output_file_id(id).await?;
+
+        self.client
+            .files()
+            .retrieve_content_with_progress(&output_file_id, on_progress)
+            .await
+    }
+
+    /// Writes the output file of a batch to the writer as the chunks arrive, without holding the whole file in memory.
+    /// Returns the number of bytes written.
+    pub async fn download_output_to<W, F>(
+        &self,
+        id: &str,
+        writer: &mut W,
+        on_progress: F,
+    ) -> Result<u64, APIError>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+        F: FnMut(u64, Option<u64>),
+    {
+        let output_file_id = self.output_file_id(id).await?;
+
+        self.client
+            .files()
+            .download_to(&output_file_id, writer, on_progress)
+            .await
+    }
+
+    async fn output_file_id(&self, id: &str) -> Result<String, APIError> {
+        self.retrieve(id)
+            .await?
+            .output_file_id
+            .ok_or_else(|| APIError::BadRequestError {
+                message: format!("batch {id} has no output file"),
+                request_id: None,
+            })
+    }
+
+    async fn failed_requests_of(&self, batch: &Batch) -> Result<FailedRequests, APIError> {
+        let Some(error_file_id) = &batch.error_file_id else {
+            return Ok(FailedRequests::default());
+        };
+
+        let content = self.client.files().retrieve_content(error_file_id).await?;
+
+        Ok(FailedRequests::from_error_file(error_file_id, &content))
+    }
+}
+
+/// Reads the custom IDs of a JSONL file with batch requests, lines that can't be parsed are returned as errors.
+fn index_requests(file_id: &str, content: &str) -> (HashMap<String, Value>, Vec<BatchLineError>) {
+    let mut requests = HashMap::new();
+    let mut errors = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = serde_json::from_str::<Value>(line)
+            .map_err(|error| error.to_string())
+            .and_then(|request| {
+                let custom_id = request
+                    .get("custom_id")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| "the line has no custom_id".to_string())?
+                    .to_string();
+
+                Ok((custom_id, request))
+            });
+
+        match request {
+            Ok((custom_id, request)) => {
+                requests.insert(custom_id, request);
+            }
+            Err(message) => errors.push(BatchLineError {
+                file_id: file_id.to_string(),
+                line: index + 1,
+                message,
+            }),
+        }
+    }
+
+    (requests, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::resources::batch::OriginalRequests;
+    use crate::v1::test_support::{json_response, serve_with, Request};
+
+    const BATCH: &str = r#"{"id": "batch_1", "object": "batch", "endpoint": "/v1/chat/completions", "input_file_id": "file-input", "completion_window": "24h", "status": "completed", "output_file_id": "file-output", "error_file_id": "file-errors", "created_at": 1, "request_counts": {"total": 3, "completed": 1, "failed": 2}, "metadata": {"project": "demo"}}"#;
+
+    const OUTPUT: &str = r#"{"id": "batch_req_1", "custom_id": "request-1", "response": {"status_code": 200, "body": {}}, "error": null}"#;
+
+    /// Answers by method and path.
+    fn batch_response(request: &Request) -> String {
+        let body = match request.route().as_str() {
+            "GET /batches/batch_1" => BATCH.to_string(),
+            "GET /files/file-errors/content" => [
+                r#"{"id": "batch_req_2", "custom_id": "request-2", "response": {"status_code": 429, "body": {"error": {"message": "Rate limit reached", "code": "rate_limit_exceeded"}}}, "error": null}"#,
+                r#"{"id": "batch_req_3", "custom_id": "request-3", "response": null, "error": {"code": "batch_expired", "message": "Expired"}}"#,
+            ]
+            .join("\n"),
+            "GET /files/file-input/content" => [
+                r#"{"custom_id": "request-1", "method": "POST", "url": "/v1/chat/completions", "body": {"model": "gpt-4o"}}"#,
+                r#"{"custom_id": "request-2", "method": "POST", "url": "/v1/chat/completions", "body": {"model": "gpt-4o"}}"#,
+                "not json",
+            ]
+            .join("\n"),
+            "GET /files/file-output/content" => OUTPUT.to_string(),
+            "POST /files" => r#"{"id": "file-resubmit", "bytes": 1, "created_at": 2, "filename": "batch_1_failed_requests.jsonl", "object": "file", "purpose": "batch"}"#.to_string(),
+            _ => BATCH.replace("batch_1", "batch_2"),
+        };
+
+        json_response("200 OK", &body)
+    }
+
+    #[tokio::test]
+    async fn test_resubmit_failed_requests_from_input_file() {
+        let (base_url, server) = serve_with(5, batch_response).await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let resubmitted = client
+            .batches()
+            .resubmit_failed(
+                "batch_1",
+                OriginalRequests::InputFile("file-input".to_string()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resubmitted.batch.id, "batch_2");
+        assert_eq!(resubmitted.resubmitted_custom_ids, vec!["request-2"]);
+        assert_eq!(resubmitted.missing_custom_ids, vec!["request-3"]);
+        assert_eq!(resubmitted.parse_errors.len(), 1);
+        assert_eq!(resubmitted.parse_errors[0].file_id, "file-input");
+        assert_eq!(resubmitted.parse_errors[0].line, 3);
+
+        let requests = server.await.unwrap();
+
+        assert!(requests[3].text().contains(r#""custom_id":"request-2""#));
+        assert!(!requests[3].text().contains("request-1"));
+
+        let create = requests[4].json();
+
+        assert_eq!(create["input_file_id"], "file-resubmit");
+        assert_eq!(create["metadata"]["resubmitted_from"], "batch_1");
+        assert_eq!(create["metadata"]["project"], "demo");
+    }
+
+    #[tokio::test]
+    async fn test_download_output_of_a_batch() {
+        let (base_url, server) = serve_with(4, batch_response).await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let mut progress = vec![];
+
+        let output = client
+            .batches()
+            .output_with_progress("batch_1", |downloaded, total| {
+                progress.push((downloaded, total))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output, OUTPUT.as_bytes());
+        assert_eq!(
+            progress.last(),
+            Some(&(OUTPUT.len() as u64, Some(OUTPUT.len() as u64)))
+        );
+
+        let mut file = vec![];
+        let written = client
+            .batches()
+            .download_output_to("batch_1", &mut file, |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(written, OUTPUT.len() as u64);
+        assert_eq!(file, OUTPUT.as_bytes());
+
+        let routes = server
+            .await
+            .unwrap()
+            .iter()
+            .map(Request::route)
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            routes,
+            vec![
+                "GET /batches/batch_1",
+                "GET /files/file-output/content",
+                "GET /batches/batch_1",
+                "GET /files/file-output/content",
+            ]
+        );
+    }
 }
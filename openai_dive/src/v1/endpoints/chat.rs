@@ -1,17 +1,42 @@
+#[cfg(feature = "stream")]
+use crate::v1::clock::{timeout, Clock, SystemClock};
 use crate::v1::error::APIError;
 #[cfg(feature = "stream")]
 use crate::v1::resources::chat::ChatCompletionChunkResponse;
 #[cfg(feature = "stream")]
-use crate::v1::resources::chat::DeltaChatMessage;
-use crate::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponse};
+use crate::v1::resources::chat::{
+    ChatCompletionAccumulator, ChatStreamEvent, DeltaChatMessage, Role, UiEvent, UiEventOptions,
+};
+use crate::v1::resources::chat::{
+    ChatCompletionParameters, ChatCompletionResponse, ChatCompletionResponseFormat, ChatMessage,
+    ChatMessageContent, ChatMessageContentPart, JsonCompletion, JsonModeOptions,
+};
 use crate::v1::resources::shared::ResponseWrapper;
-use crate::v1::{api::Client, helpers::format_response};
 #[cfg(feature = "stream")]
-use futures::Stream;
+use crate::v1::resources::shared::{FinishReason, StreamMetadata, Usage};
+use crate::v1::token_budget::estimate_chat_tokens;
+use crate::v1::{
+    api::Client,
+    helpers::{format_response, validate_response},
+};
+#[cfg(feature = "stream")]
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+#[cfg(feature = "stream")]
+use std::collections::{HashSet, VecDeque};
+#[cfg(feature = "stream")]
+use std::future::Future;
 #[cfg(feature = "stream")]
 use std::pin::Pin;
 #[cfg(feature = "stream")]
+use std::sync::Arc;
+#[cfg(feature = "stream")]
 use std::task::{Context, Poll};
+#[cfg(feature = "stream")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "stream")]
+use tokio::sync::mpsc::Sender;
 
 pub struct Chat<'a> {
     pub client: &'a Client,
@@ -40,6 +65,18 @@ impl Chat<'_> {
         &self,
         parameters: ChatCompletionParameters,
     ) -> Result<ResponseWrapper<ChatCompletionResponse>, APIError> {
+        let parameters = self.client.degrade_chat(parameters).await?;
+
+        self.client.validation_profile.validate_chat(&parameters)?;
+        self.client.lint_cache_prefix(&parameters)?;
+
+        let parameters = self.with_end_user(parameters);
+
+        let reservation = self
+            .client
+            .acquire_tokens(estimate_chat_tokens(&parameters))
+            .await;
+
         let response = self
             .client
             .post(
@@ -54,6 +91,10 @@ impl Chat<'_> {
 
         let data: ChatCompletionResponse = format_response(response.data)?;
 
+        if let (Some(reservation), Some(usage)) = (reservation, &data.usage) {
+            reservation.settle(usage.total_tokens);
+        }
+
         Ok(ResponseWrapper {
             data,
             headers: response.headers,
@@ -69,34 +110,505 @@ impl Chat<'_> {
         Pin<Box<dyn Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Send>>,
         APIError,
     > {
+        let (stream, _metadata) = self.create_stream_with_metadata(parameters).await?;
+
+        Ok(stream)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Creates a model response for the given chat conversation.
+    /// The returned metadata contains the time to first byte once the first chunk has arrived.
+    pub async fn create_stream_with_metadata(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> Result<
+        (
+            Pin<Box<dyn Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Send>>,
+            StreamMetadata,
+        ),
+        APIError,
+    > {
+        let parameters = self.client.degrade_chat(parameters).await?;
+
+        self.client.validation_profile.validate_chat(&parameters)?;
+        self.client.lint_cache_prefix(&parameters)?;
+
         let mut stream_parameters = ChatCompletionParameters {
             query_params: None,
-            ..parameters
+            ..self.with_end_user(parameters)
         };
         stream_parameters.stream = Some(true);
 
+        self.client
+            .acquire_tokens(estimate_chat_tokens(&stream_parameters))
+            .await;
+
         Ok(self
             .client
-            .post_stream(
+            .post_stream_with_metadata(
                 "/chat/completions",
                 &stream_parameters,
                 stream_parameters.query_params.as_ref(),
             )
             .await)
     }
+
+    /// Creates a model response in JSON mode and parses its content into `T`.
+    /// At least one message has to mention JSON, as the API requires, this is checked before the request is sent.
+    /// Prose around the JSON object (ie. "Here is the JSON: {...}") is ignored.
+    pub async fn create_json<T: DeserializeOwned>(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> Result<JsonCompletion<T>, APIError> {
+        self.create_json_with_options(parameters, JsonModeOptions::default())
+            .await
+    }
+
+    /// Creates a model response in JSON mode and parses its content into `T`.
+    /// With `repair`, content that can't be parsed is sent back once and the model is asked to fix it.
+    pub async fn create_json_with_options<T: DeserializeOwned>(
+        &self,
+        mut parameters: ChatCompletionParameters,
+        options: JsonModeOptions,
+    ) -> Result<JsonCompletion<T>, APIError> {
+        if !parameters.messages.iter().any(mentions_json) {
+            return Err(APIError::ValidationError {
+                field: "messages".to_string(),
+                value: "no message mentions JSON".to_string(),
+                allowed: "at least one message with the word \"json\", which the API requires for the json_object response format".to_string(),
+            });
+        }
+
+        parameters.response_format = Some(ChatCompletionResponseFormat::JsonObject);
+
+        let response = self.create(parameters.clone()).await?;
+        let content = response_content(&response);
+
+        let error = match parse_json(&content) {
+            Ok(value) => {
+                return Ok(JsonCompletion {
+                    value,
+                    content,
+                    repaired_from: None,
+                    response,
+                })
+            }
+            Err(error) if !options.repair => return Err(error),
+            Err(error) => error,
+        };
+
+        parameters.messages.push(ChatMessage::Assistant {
+            content: Some(ChatMessageContent::Text(content.clone())),
+            reasoning_content: None,
+            refusal: None,
+            name: None,
+            audio: None,
+            tool_calls: None,
+        });
+        parameters.messages.push(ChatMessage::User {
+            content: ChatMessageContent::Text(format!(
+                "Your response could not be parsed: {error}. Reply with only the corrected JSON object."
+            )),
+            name: None,
+        });
+
+        let response = self.create(parameters).await?;
+        let repaired = response_content(&response);
+
+        Ok(JsonCompletion {
+            value: parse_json(&repaired)?,
+            content: repaired,
+            repaired_from: Some(content),
+            response,
+        })
+    }
+
+    /// Sets the safety identifier from `Client::set_end_user`, if the parameters don't set one.
+    fn with_end_user(&self, mut parameters: ChatCompletionParameters) -> ChatCompletionParameters {
+        if parameters.safety_identifier.is_none() {
+            parameters.safety_identifier = self.client.end_user_id();
+        }
+
+        parameters
+    }
+
+    /// Creates a model response from a pre-serialized JSON body.
+    pub async fn create_raw(&self, body: Value) -> Result<ChatCompletionResponse, APIError> {
+        let response = self.client.post("/chat/completions", &body, None).await?;
+
+        format_response(response.data)
+    }
+
+    /// Creates a model response from a pre-serialized JSON body and returns the response as raw JSON.
+    pub async fn create_raw_value(&self, body: Value) -> Result<Value, APIError> {
+        let response = self.client.post("/chat/completions", &body, None).await?;
+
+        validate_response(response.data)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Creates a streamed model response from a pre-serialized JSON body. The `stream` flag is set to true.
+    pub async fn create_stream_raw(
+        &self,
+        body: Value,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Send>>,
+        APIError,
+    > {
+        let body = stream_body(body)?;
+
+        Ok(self
+            .client
+            .post_stream("/chat/completions", &body, None)
+            .await)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Creates a streamed model response from a pre-serialized JSON body and returns the chunks as raw JSON.
+    /// The `stream` flag is set to true.
+    pub async fn create_stream_raw_value(
+        &self,
+        body: Value,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Value, APIError>> + Send>>, APIError> {
+        let body = stream_body(body)?;
+
+        Ok(self
+            .client
+            .post_stream("/chat/completions", &body, None)
+            .await)
+    }
+}
+
+#[cfg(feature = "stream")]
+fn stream_body(mut body: Value) -> Result<Value, APIError> {
+    let Some(map) = body.as_object_mut() else {
+        return Err(APIError::BadRequestError {
+            message: "body must be formatted as a map of key: value".to_string(),
+            request_id: None,
+        });
+    };
+
+    match map.get("stream") {
+        None | Some(Value::Bool(true)) => {
+            map.insert("stream".to_string(), Value::Bool(true));
+        }
+        Some(_) => {
+            return Err(APIError::BadRequestError {
+                message: "stream must be set to true for streamed requests".to_string(),
+                request_id: None,
+            });
+        }
+    }
+
+    Ok(body)
+}
+
+#[cfg(feature = "stream")]
+/// Adapters of a chat completion stream for channel and callback based code, ie. UI event loops.
+pub trait ChatStreamExt:
+    Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Unpin + Send + Sized + 'static
+{
+    /// Returns a stream of the content deltas only. With n > 1, the deltas of all choices are yielded in order.
+    fn into_text_stream(self) -> Pin<Box<dyn Stream<Item = Result<String, APIError>> + Send>> {
+        Box::pin(self.flat_map(|chunk| {
+            let deltas: Vec<Result<String, APIError>> = match chunk {
+                Ok(chunk) => ChatStreamEvent::from_chunk(&chunk)
+                    .into_iter()
+                    .filter_map(|event| match event {
+                        ChatStreamEvent::Content { delta, .. } => Some(Ok(delta)),
+                        _ => None,
+                    })
+                    .collect(),
+                Err(error) => vec![Err(error)],
+            };
+
+            futures::stream::iter(deltas)
+        }))
+    }
+
+    /// Returns a stream of higher level events for UIs, see `UiEvent`. The last event is `UiEvent::Done`.
+    fn into_ui_events(self) -> Pin<Box<dyn Stream<Item = Result<UiEvent, APIError>> + Send>> {
+        self.into_ui_events_with_options(UiEventOptions::default())
+    }
+
+    /// Returns a stream of higher level events for UIs, with the appends merged within the coalesce window of the options.
+    /// The events are yielded in the order of the chunks. An error of the stream is yielded after the pending appends and ends the stream.
+    fn into_ui_events_with_options(
+        self,
+        options: UiEventOptions,
+    ) -> Pin<Box<dyn Stream<Item = Result<UiEvent, APIError>> + Send>> {
+        let events = UiEvents {
+            stream: self,
+            window: options.coalesce_window.filter(|window| !window.is_zero()),
+            clock: options.clock.unwrap_or_else(|| Arc::new(SystemClock)),
+            pending: None,
+            ready: VecDeque::new(),
+            started: HashSet::new(),
+            finish_reason: None,
+            usage: None,
+            finished: false,
+        };
+
+        Box::pin(futures::stream::unfold(events, |mut events| async move {
+            events.next_event().await.map(|event| (event, events))
+        }))
+    }
+
+    /// Sends the events of the stream to the channel, waiting while the channel is full, and returns the collected response.
+    /// The last event is `ChatStreamEvent::Done`. Errors of the stream are returned instead of sent.
+    /// Stops as soon as the receiver is dropped, also while waiting for the next chunk, and returns the response collected so far.
+    fn forward_to(
+        mut self,
+        sender: Sender<ChatStreamEvent>,
+    ) -> impl Future<Output = Result<ChatCompletionResponse, APIError>> + Send {
+        async move {
+            let mut accumulator = ChatCompletionAccumulator::new();
+
+            'stream: loop {
+                let next =
+                    match futures::future::select(self.next(), Box::pin(sender.closed())).await {
+                        futures::future::Either::Left((next, _)) => next,
+                        futures::future::Either::Right(_) => break,
+                    };
+
+                let Some(chunk) = next else {
+                    let response = accumulator.finish();
+
+                    let _ = sender.send(ChatStreamEvent::Done(response.clone())).await;
+
+                    return Ok(response);
+                };
+
+                let chunk = chunk?;
+
+                for event in ChatStreamEvent::from_chunk(&chunk) {
+                    if sender.send(event).await.is_err() {
+                        accumulator.push(chunk);
+
+                        break 'stream;
+                    }
+                }
+
+                accumulator.push(chunk);
+            }
+
+            Ok(accumulator.finish())
+        }
+    }
+
+    /// Calls the callback with every content delta and returns the collected response,
+    /// which includes the tool calls and the usage.
+    fn on_token<F>(
+        mut self,
+        mut callback: F,
+    ) -> impl Future<Output = Result<ChatCompletionResponse, APIError>> + Send
+    where
+        F: FnMut(&str) + Send,
+    {
+        async move {
+            let mut accumulator = ChatCompletionAccumulator::new();
+
+            while let Some(chunk) = self.next().await {
+                let chunk = chunk?;
+
+                for event in ChatStreamEvent::from_chunk(&chunk) {
+                    if let ChatStreamEvent::Content { delta, .. } = event {
+                        callback(&delta);
+                    }
+                }
+
+                accumulator.push(chunk);
+            }
+
+            Ok(accumulator.finish())
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<S> ChatStreamExt for S where
+    S: Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Unpin + Send + 'static
+{
+}
+
+#[cfg(feature = "stream")]
+/// The state of `ChatStreamExt::into_ui_events_with_options`.
+struct UiEvents<S> {
+    stream: S,
+    window: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    /// The append that is being coalesced, with the time it was started.
+    pending: Option<(UiEvent, Instant)>,
+    ready: VecDeque<Result<UiEvent, APIError>>,
+    /// The indexes of the tool calls that were started.
+    started: HashSet<u32>,
+    finish_reason: Option<FinishReason>,
+    usage: Option<Usage>,
+    finished: bool,
+}
+
+#[cfg(feature = "stream")]
+impl<S> UiEvents<S>
+where
+    S: Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Unpin,
+{
+    async fn next_event(&mut self) -> Option<Result<UiEvent, APIError>> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(event);
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            let next = match (&self.pending, self.window) {
+                (Some((_, started)), Some(window)) => {
+                    let remaining =
+                        window.saturating_sub(self.clock.now().saturating_duration_since(*started));
+
+                    match timeout(&self.clock, remaining, self.stream.next()).await {
+                        Ok(next) => next,
+                        Err(()) => {
+                            self.flush();
+                            continue;
+                        }
+                    }
+                }
+                _ => self.stream.next().await,
+            };
+
+            match next {
+                Some(Ok(chunk)) => {
+                    for event in ChatStreamEvent::from_chunk(&chunk) {
+                        self.push(event);
+                    }
+                }
+                Some(Err(error)) => {
+                    self.flush();
+                    self.ready.push_back(Err(error));
+                    self.finished = true;
+                }
+                None => {
+                    self.flush();
+                    self.ready.push_back(Ok(UiEvent::Done {
+                        finish_reason: self.finish_reason.take(),
+                        usage: self.usage.take(),
+                    }));
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    /// Converts the events of the first choice, the finish reason and usage are kept for `UiEvent::Done`.
+    fn push(&mut self, event: ChatStreamEvent) {
+        let event = match event {
+            ChatStreamEvent::Content { index: 0, delta }
+            | ChatStreamEvent::Refusal { index: 0, delta } => UiEvent::TextAppend(delta),
+            ChatStreamEvent::ReasoningContent { index: 0, delta } => {
+                UiEvent::ReasoningAppend(delta)
+            }
+            ChatStreamEvent::ToolCall { index: 0, delta } => {
+                let index = delta.index.unwrap_or_default();
+
+                if self.started.insert(index) {
+                    self.push_event(UiEvent::ToolCallStarted { index });
+                }
+
+                if let Some(fragment) = delta.function.name.filter(|name| !name.is_empty()) {
+                    self.push_event(UiEvent::ToolCallNameAppend { index, fragment });
+                }
+
+                match delta
+                    .function
+                    .arguments
+                    .filter(|arguments| !arguments.is_empty())
+                {
+                    Some(fragment) => UiEvent::ToolCallArgumentsAppend { index, fragment },
+                    None => return,
+                }
+            }
+            ChatStreamEvent::Finished { index: 0, reason } => {
+                self.finish_reason = Some(reason);
+                return;
+            }
+            ChatStreamEvent::Usage(usage) => {
+                self.usage = Some(usage);
+                return;
+            }
+            _ => return,
+        };
+
+        self.push_event(event);
+    }
+
+    fn push_event(&mut self, event: UiEvent) {
+        let Some(window) = self.window else {
+            self.ready.push_back(Ok(event));
+            return;
+        };
+
+        let now = self.clock.now();
+
+        if let Some((pending, started)) = &mut self.pending {
+            if now.saturating_duration_since(*started) < window && merge_append(pending, &event) {
+                return;
+            }
+        }
+
+        self.flush();
+
+        match event {
+            UiEvent::TextAppend(_)
+            | UiEvent::ReasoningAppend(_)
+            | UiEvent::ToolCallNameAppend { .. }
+            | UiEvent::ToolCallArgumentsAppend { .. } => self.pending = Some((event, now)),
+            event => self.ready.push_back(Ok(event)),
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some((event, _)) = self.pending.take() {
+            self.ready.push_back(Ok(event));
+        }
+    }
 }
 
+/// Appends the event to the pending append if they are of the same kind, and of the same tool call.
 #[cfg(feature = "stream")]
-enum CurrentRole {
-    User,
-    System,
-    Assistant,
+fn merge_append(pending: &mut UiEvent, event: &UiEvent) -> bool {
+    match (pending, event) {
+        (UiEvent::TextAppend(text), UiEvent::TextAppend(delta))
+        | (UiEvent::ReasoningAppend(text), UiEvent::ReasoningAppend(delta)) => {
+            text.push_str(delta);
+            true
+        }
+        (
+            UiEvent::ToolCallNameAppend { index, fragment },
+            UiEvent::ToolCallNameAppend {
+                index: other,
+                fragment: delta,
+            },
+        )
+        | (
+            UiEvent::ToolCallArgumentsAppend { index, fragment },
+            UiEvent::ToolCallArgumentsAppend {
+                index: other,
+                fragment: delta,
+            },
+        ) if index == other => {
+            fragment.push_str(delta);
+            true
+        }
+        _ => false,
+    }
 }
 
 #[cfg(feature = "stream")]
 pub struct RoleTrackingStream<S> {
     stream: S,
-    current_role: Option<CurrentRole>,
+    current_role: Option<Role>,
 }
 
 #[cfg(feature = "stream")]
@@ -122,17 +634,10 @@ where
         match Pin::new(&mut this.stream).poll_next(cx) {
             Poll::Ready(Some(Ok(mut chat_response))) => {
                 chat_response.choices.iter_mut().for_each(|choice| {
-                    match &choice.delta {
-                        DeltaChatMessage::User { .. } => {
-                            this.current_role = Some(CurrentRole::User)
-                        }
-                        DeltaChatMessage::System { .. } => {
-                            this.current_role = Some(CurrentRole::System)
-                        }
-                        DeltaChatMessage::Assistant { .. } => {
-                            this.current_role = Some(CurrentRole::Assistant)
-                        }
-                        _ => {}
+                    if let Some(role @ (Role::User | Role::System | Role::Assistant)) =
+                        choice.delta.role()
+                    {
+                        this.current_role = Some(role);
                     }
 
                     if let DeltaChatMessage::Untagged {
@@ -145,19 +650,19 @@ where
                     } = &mut choice.delta
                     {
                         match this.current_role {
-                            Some(CurrentRole::User) => {
+                            Some(Role::User) => {
                                 choice.delta = DeltaChatMessage::User {
                                     name: Some("user".to_string()),
                                     content: content.clone().unwrap(),
                                 }
                             }
-                            Some(CurrentRole::System) => {
+                            Some(Role::System) => {
                                 choice.delta = DeltaChatMessage::System {
                                     name: Some("system".to_string()),
                                     content: content.clone().unwrap(),
                                 }
                             }
-                            Some(CurrentRole::Assistant) => {
+                            Some(Role::Assistant) => {
                                 choice.delta = DeltaChatMessage::Assistant {
                                     name: Some("assistant".to_string()),
                                     content: content.clone(),
@@ -177,3 +682,542 @@ where
         }
     }
 }
+
+/// Whether the text of the message, or of one of its text parts, contains "json" in any case.
+fn mentions_json(message: &ChatMessage) -> bool {
+    let contains_json = |text: &str| text.to_lowercase().contains("json");
+
+    match message.message() {
+        Some(ChatMessageContent::Text(text)) => contains_json(text),
+        Some(ChatMessageContent::ContentPart(parts)) => parts.iter().any(
+            |part| matches!(part, ChatMessageContentPart::Text(part) if contains_json(&part.text)),
+        ),
+        _ => false,
+    }
+}
+
+fn response_content(response: &ChatCompletionResponse) -> String {
+    response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.text())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Parses the content, or else the outermost JSON object in it, ie. without the prose of compatible providers.
+fn parse_json<T: DeserializeOwned>(content: &str) -> Result<T, APIError> {
+    serde_json::from_str(content.trim()).or_else(|error| {
+        let object = match (content.find('{'), content.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &content[start..=end],
+            _ => return Err(APIError::ParseError(error.to_string())),
+        };
+
+        serde_json::from_str(object).map_err(|error| APIError::ParseError(error.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod json_mode_tests {
+    use crate::v1::api::Client;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatMessage, ChatMessageContent, JsonModeOptions,
+    };
+    use crate::v1::test_support::{json_response, serve};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Event {
+        name: String,
+        day: String,
+    }
+
+    fn user(text: &str) -> ChatMessage {
+        ChatMessage::User {
+            content: ChatMessageContent::Text(text.to_string()),
+            name: None,
+        }
+    }
+
+    /// A chat completion with the content.
+    fn completion(content: &str) -> String {
+        let body = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1715000000,
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "finish_reason": "stop"
+            }]
+        });
+
+        json_response("200 OK", &body.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_create_json_ignores_prose_and_repairs_invalid_content() {
+        let (base_url, server) = serve(
+            [
+                r#"Here is the JSON: {"name": "science fair", "day": "Friday"} Let me know!"#,
+                r#"{"name": "science fair"}"#,
+                r#"{"name": "science fair", "day": "Friday"}"#,
+            ]
+            .map(completion),
+        )
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o-mini")
+            .messages(vec![user("Extract the event as JSON.")])
+            .build()
+            .unwrap();
+
+        let completion = client
+            .chat()
+            .create_json::<Event>(parameters.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(completion.value.day, "Friday");
+        assert!(completion.content.starts_with("Here is the JSON"));
+        assert_eq!(completion.repaired_from, None);
+
+        // the missing field is repaired by a second request with the invalid content
+        let completion = client
+            .chat()
+            .create_json_with_options::<Event>(parameters, JsonModeOptions { repair: true })
+            .await
+            .unwrap();
+
+        assert_eq!(completion.value.name, "science fair");
+        assert_eq!(
+            completion.repaired_from.as_deref(),
+            Some(r#"{"name": "science fair"}"#)
+        );
+
+        let requests = server.await.unwrap();
+
+        assert_eq!(
+            requests[0].json()["response_format"],
+            json!({"type": "json_object"})
+        );
+
+        let repair_messages = requests[2].json()["messages"].clone();
+
+        assert_eq!(repair_messages[1]["content"], r#"{"name": "science fair"}"#);
+        assert!(repair_messages[2]["content"]
+            .as_str()
+            .unwrap()
+            .contains("missing field `day`"));
+    }
+
+    #[tokio::test]
+    async fn test_create_json_requires_a_message_that_mentions_json() {
+        let client = Client::new("sk-test".to_string());
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o-mini")
+            .messages(vec![user("Extract the event.")])
+            .build()
+            .unwrap();
+
+        let error = client
+            .chat()
+            .create_json::<Event>(parameters)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, APIError::ValidationError { field, .. } if field == "messages"));
+    }
+}
+
+#[cfg(test)]
+mod raw_tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::error::APIError;
+    use crate::v1::test_support::{json_response, serve};
+    use serde_json::{json, Value};
+
+    fn body() -> Value {
+        json!({
+            "model": "gpt-4o-mini",
+            "messages": [{"role": "user", "content": "Hello!"}],
+            "provider_options": {"priority": "low"}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_raw_sends_the_body_as_is() {
+        let client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.chat().create_raw(body()).await })
+            .await
+            .unwrap();
+
+        assert!(prepared.url.ends_with("/chat/completions"));
+        assert_eq!(prepared.body, PreparedBody::Json(body()));
+    }
+
+    #[tokio::test]
+    async fn test_create_raw_parses_the_response() {
+        let completion = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1715000000,
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi!"},
+                "finish_reason": "stop"
+            }],
+            "provider_field": true
+        });
+
+        let (base_url, _) = serve([
+            json_response("200 OK", &completion.to_string()),
+            json_response("200 OK", &completion.to_string()),
+            json_response("200 OK", r#"{"error": {"message": "unknown model"}}"#),
+        ])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let response = client.chat().create_raw(body()).await.unwrap();
+
+        assert_eq!(response.id.as_deref(), Some("chatcmpl-1"));
+        assert_eq!(response.choices.len(), 1);
+
+        let value = client.chat().create_raw_value(body()).await.unwrap();
+
+        assert_eq!(value, completion);
+
+        let error = client.chat().create_raw_value(body()).await.unwrap_err();
+
+        assert!(matches!(error, APIError::InvalidRequestError { .. }));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_create_stream_raw_sets_the_stream_flag() {
+        let client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.chat().create_stream_raw(body()).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(sent) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert_eq!(sent["stream"], true);
+        assert_eq!(sent["provider_options"], body()["provider_options"]);
+
+        let mut streamed = body();
+        streamed["stream"] = json!(false);
+
+        assert!(matches!(
+            client.chat().create_stream_raw(streamed).await,
+            Err(APIError::BadRequestError { message, .. }) if message.contains("stream must be set to true")
+        ));
+        assert!(matches!(
+            client
+                .chat()
+                .create_stream_raw_value(json!(["not", "a", "map"]))
+                .await,
+            Err(APIError::BadRequestError { .. })
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_create_stream_raw_parses_the_chunks() {
+        use crate::v1::test_support::response;
+        use futures::StreamExt;
+
+        let chunk = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1715000000,
+            "model": "gpt-4o-mini",
+            "choices": [{"index": 0, "delta": {"content": "Hi!"}, "finish_reason": null}]
+        });
+        let events = format!("data: {chunk}\n\ndata: [DONE]\n\n");
+
+        let (base_url, server) = serve(
+            [0, 1].map(|_| response("200 OK", &["content-type: text/event-stream"], &events)),
+        )
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let chunks: Vec<_> = client
+            .chat()
+            .create_stream_raw(body())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].as_ref().unwrap().id.as_deref(),
+            Some("chatcmpl-1")
+        );
+
+        let values: Vec<_> = client
+            .chat()
+            .create_stream_raw_value(body())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].as_ref().unwrap(), &chunk);
+
+        let requests = server.await.unwrap();
+
+        assert!(requests
+            .iter()
+            .all(|request| request.json()["stream"] == true));
+    }
+}
+
+#[cfg(all(test, feature = "stream"))]
+mod tests {
+    use crate::v1::clock::MockClock;
+    use crate::v1::endpoints::chat::ChatStreamExt;
+    use crate::v1::error::{APIError, StreamError};
+    use crate::v1::resources::chat::{
+        ChatCompletionChunkResponse, ChatStreamEvent, UiEvent, UiEventOptionsBuilder,
+    };
+    use crate::v1::resources::shared::FinishReason;
+    use futures::{Stream, StreamExt};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    type ChunkStream =
+        Pin<Box<dyn Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Send>>;
+
+    fn chunks() -> ChunkStream {
+        let chunks = [
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":"{}"}}]}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[],"usage":{"prompt_tokens":5,"completion_tokens":3,"total_tokens":8}}"#,
+        ];
+
+        Box::pin(futures::stream::iter(
+            chunks.map(|chunk| Ok(serde_json::from_str(chunk).unwrap())),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_adapters() {
+        let texts: Vec<String> = chunks()
+            .into_text_stream()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(texts, vec!["Hel", "lo"]);
+
+        let mut tokens = vec![];
+        let response = chunks()
+            .on_token(|token| tokens.push(token.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(tokens, vec!["Hel", "lo"]);
+        assert_eq!(response.usage.unwrap().total_tokens, 8);
+
+        // a capacity of 1 makes the forwarding wait for the receiver
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+        let forward = tokio::spawn(chunks().forward_to(sender));
+
+        let mut events = vec![];
+
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+
+        let response = forward.await.unwrap().unwrap();
+
+        assert_eq!(events.len(), 6);
+        assert!(
+            matches!(&events[2], ChatStreamEvent::ToolCall { index: 0, delta } if delta.id.as_deref() == Some("call_1"))
+        );
+        assert!(matches!(&events[4], ChatStreamEvent::Usage(usage) if usage.total_tokens == 8));
+        assert_eq!(events[5], ChatStreamEvent::Done(response));
+
+        // a stream that never sends a chunk stops when the receiver is dropped
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let pending: ChunkStream = Box::pin(futures::stream::pending());
+        let forward = tokio::spawn(pending.forward_to(sender));
+
+        drop(receiver);
+
+        let response = tokio::time::timeout(Duration::from_secs(1), forward)
+            .await
+            .expect("forward_to should stop when the receiver is dropped")
+            .unwrap()
+            .unwrap();
+
+        assert!(response.choices.is_empty());
+    }
+
+    fn content_chunk(content: &str) -> Result<ChatCompletionChunkResponse, APIError> {
+        Ok(serde_json::from_value(serde_json::json!({
+            "id": "1", "created": 1, "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {"content": content}}]
+        }))
+        .unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ui_events() {
+        let events: Vec<UiEvent> = chunks()
+            .into_ui_events()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(
+            events[..5],
+            [
+                UiEvent::TextAppend("Hel".to_string()),
+                UiEvent::TextAppend("lo".to_string()),
+                UiEvent::ToolCallStarted { index: 0 },
+                UiEvent::ToolCallNameAppend {
+                    index: 0,
+                    fragment: "get_weather".to_string()
+                },
+                UiEvent::ToolCallArgumentsAppend {
+                    index: 0,
+                    fragment: "{}".to_string()
+                },
+            ]
+        );
+        assert!(matches!(
+            &events[5..],
+            [UiEvent::Done { finish_reason: Some(FinishReason::ToolCalls), usage: Some(usage) }] if usage.total_tokens == 8
+        ));
+
+        // a name that is split over the fragments starts the tool call once
+        let chunks = [
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_"}}]}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"name":"weather","arguments":"{\"city\":"}}]}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Paris\"}"}}]}}]}"#,
+            r#"{"id":"1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":1,"id":"call_2","type":"function","function":{"name":"get_time","arguments":"{}"}}]}}]}"#,
+        ];
+        let chunks: ChunkStream = Box::pin(futures::stream::iter(
+            chunks.map(|chunk| Ok(serde_json::from_str(chunk).unwrap())),
+        ));
+
+        let events: Vec<UiEvent> = chunks.into_ui_events().map(Result::unwrap).collect().await;
+
+        let fragment = |fragment: &str| fragment.to_string();
+
+        assert_eq!(
+            events[..events.len() - 1],
+            [
+                UiEvent::ToolCallStarted { index: 0 },
+                UiEvent::ToolCallNameAppend {
+                    index: 0,
+                    fragment: fragment("get_")
+                },
+                UiEvent::ToolCallNameAppend {
+                    index: 0,
+                    fragment: fragment("weather")
+                },
+                UiEvent::ToolCallArgumentsAppend {
+                    index: 0,
+                    fragment: fragment("{\"city\":")
+                },
+                UiEvent::ToolCallArgumentsAppend {
+                    index: 0,
+                    fragment: fragment("\"Paris\"}")
+                },
+                UiEvent::ToolCallStarted { index: 1 },
+                UiEvent::ToolCallNameAppend {
+                    index: 1,
+                    fragment: fragment("get_time")
+                },
+                UiEvent::ToolCallArgumentsAppend {
+                    index: 1,
+                    fragment: fragment("{}")
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ui_events_coalesce_appends_within_the_window() {
+        let clock = MockClock::new();
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let options = UiEventOptionsBuilder::default()
+            .coalesce_window(Duration::from_millis(30))
+            .clock(Arc::new(clock.clone()) as Arc<dyn crate::v1::clock::Clock>)
+            .build()
+            .unwrap();
+
+        let mut events = tokio_stream::wrappers::UnboundedReceiverStream::new(receiver)
+            .into_ui_events_with_options(options);
+
+        sender.send(content_chunk("Hel")).unwrap();
+        sender.send(content_chunk("")).unwrap();
+        sender.send(content_chunk("lo")).unwrap();
+
+        let next = tokio::spawn(async move {
+            let event = events.next().await;
+
+            (event, events)
+        });
+
+        tokio::task::yield_now().await;
+        clock.advance(Duration::from_millis(30));
+
+        let (event, mut events) = next.await.unwrap();
+
+        assert_eq!(
+            event.unwrap().unwrap(),
+            UiEvent::TextAppend("Hello".to_string())
+        );
+
+        // an error is yielded after the pending append and ends the stream
+        sender.send(content_chunk(" world")).unwrap();
+        sender
+            .send(Err(StreamError::Transport(
+                "connection reset".to_string().into(),
+            )
+            .into()))
+            .unwrap();
+
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            UiEvent::TextAppend(" world".to_string())
+        );
+        assert!(matches!(
+            events.next().await,
+            Some(Err(APIError::StreamError(StreamError::Transport(_))))
+        ));
+        assert!(events.next().await.is_none());
+    }
+}
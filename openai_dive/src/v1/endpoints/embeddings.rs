@@ -1,8 +1,19 @@
 use crate::v1::api::Client;
+use crate::v1::clock::{backoff, retry};
 use crate::v1::error::APIError;
-use crate::v1::helpers::format_response;
-use crate::v1::resources::embedding::{EmbeddingParameters, EmbeddingResponse};
+use crate::v1::helpers::{buffered, parse_response, validate_response};
+use crate::v1::resources::embedding::{
+    EmbeddingEncodingFormat, EmbeddingInput, EmbeddingOutput, EmbeddingParameters,
+    EmbeddingResponse,
+};
 use crate::v1::resources::shared::ResponseWrapper;
+use crate::v1::token_budget::estimate_embedding_tokens;
+use crate::v1::vector_sink::{
+    EmbedAndSinkError, EmbedAndSinkOptions, EmbedAndSinkResult, SinkError, VectorDocument,
+    VectorRecord, VectorSink,
+};
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub struct Embeddings<'a> {
     pub client: &'a Client,
@@ -31,13 +42,315 @@ impl Embeddings<'_> {
         &self,
         parameters: EmbeddingParameters,
     ) -> Result<ResponseWrapper<EmbeddingResponse>, APIError> {
+        let reservation = self
+            .client
+            .acquire_tokens(estimate_embedding_tokens(&parameters.input))
+            .await;
+
         let response = self.client.post("/embeddings", &parameters, None).await?;
 
-        let data: EmbeddingResponse = format_response(response.data)?;
+        let data: EmbeddingResponse = parse_response(&response.data)?;
+
+        if let (Some(reservation), Some(usage)) = (reservation, &data.usage) {
+            reservation.settle(usage.total_tokens);
+        }
 
         Ok(ResponseWrapper {
             data,
             headers: response.headers,
         })
     }
+
+    /// Creates an embedding vector from a pre-serialized JSON body.
+    pub async fn create_raw(&self, body: Value) -> Result<EmbeddingResponse, APIError> {
+        let response = self.client.post("/embeddings", &body, None).await?;
+
+        parse_response(&response.data)
+    }
+
+    /// Creates an embedding vector from a pre-serialized JSON body and returns the response as raw JSON.
+    pub async fn create_raw_value(&self, body: Value) -> Result<Value, APIError> {
+        let response = self.client.post("/embeddings", &body, None).await?;
+
+        validate_response(response.data)
+    }
+
+    /// Embeds the documents in batches and upserts every batch into the sink, `concurrency` batches at a time.
+    /// A batch is retried after rate limits, server errors and transient sink errors, a batch that still fails
+    /// doesn't stop the others and the ids of its documents are in the result.
+    pub async fn embed_and_sink(
+        &self,
+        documents: Vec<VectorDocument>,
+        sink: &dyn VectorSink,
+        options: EmbedAndSinkOptions,
+    ) -> EmbedAndSinkResult {
+        let total = documents.len();
+        let completed = &AtomicUsize::new(0);
+        let options = &options;
+
+        let batches = documents
+            .chunks(options.batch_size.max(1))
+            .map(|batch| async move {
+                let result = self.embed_batch(batch, sink, options).await;
+
+                let completed = completed.fetch_add(batch.len(), Ordering::SeqCst) + batch.len();
+
+                if let Some(on_progress) = &options.on_progress {
+                    (on_progress.0)(completed, total);
+                }
+
+                (batch, result)
+            })
+            .collect();
+
+        let mut result = EmbedAndSinkResult::default();
+
+        for (batch, outcome) in buffered(batches, options.concurrency).await {
+            match outcome {
+                Ok(()) => result.upserted += batch.len(),
+                Err(error) => {
+                    result
+                        .failed_ids
+                        .extend(batch.iter().map(|document| document.id.clone()));
+                    result.errors.push(error);
+                }
+            }
+        }
+
+        result
+    }
+
+    async fn embed_batch(
+        &self,
+        batch: &[VectorDocument],
+        sink: &dyn VectorSink,
+        options: &EmbedAndSinkOptions,
+    ) -> Result<(), EmbedAndSinkError> {
+        let parameters = EmbeddingParameters {
+            input: EmbeddingInput::StringArray(
+                batch.iter().map(|document| document.text.clone()).collect(),
+            ),
+            model: options.model.clone(),
+            encoding_format: Some(EmbeddingEncodingFormat::Float),
+            dimensions: options.dimensions,
+            user: None,
+        };
+
+        let clock = &*self.client.clock;
+
+        // the documents are only embedded again if the embedding failed, not if the upsert failed
+        let response = retry(clock, options.max_retries, APIError::retry_after, || {
+            self.create(parameters.clone())
+        })
+        .await
+        .map_err(EmbedAndSinkError::Embedding)?;
+
+        let records = vector_records(batch, response)
+            .map_err(|message| EmbedAndSinkError::Embedding(APIError::ParseError(message)))?;
+
+        retry(
+            clock,
+            options.max_retries,
+            |error, attempt| matches!(error, SinkError::Transient(_)).then(|| backoff(attempt)),
+            || sink.upsert(records.clone()),
+        )
+        .await
+        .map_err(EmbedAndSinkError::Sink)
+    }
+}
+
+/// Pairs the embeddings of the response with the documents, by the index of the embeddings.
+fn vector_records(
+    batch: &[VectorDocument],
+    response: EmbeddingResponse,
+) -> Result<Vec<VectorRecord>, String> {
+    let mut vectors = vec![None; batch.len()];
+
+    for embedding in response.data {
+        let EmbeddingOutput::Float(vector) = embedding.embedding else {
+            return Err("expected float embeddings, got base64".to_string());
+        };
+
+        if let Some(slot) = vectors.get_mut(embedding.index as usize) {
+            *slot = Some(vector);
+        }
+    }
+
+    batch
+        .iter()
+        .zip(vectors)
+        .map(|(document, vector)| {
+            Ok(VectorRecord {
+                id: document.id.clone(),
+                vector: vector
+                    .ok_or_else(|| format!("no embedding for document {}", document.id))?,
+                metadata: document.metadata.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::clock::MockClock;
+    use crate::v1::future::ApiFuture;
+    use crate::v1::test_support::{json_response, serve, serve_with};
+    use crate::v1::vector_sink::{
+        EmbedAndSinkError, EmbedAndSinkOptions, MemoryVectorSink, SinkError, VectorDocument,
+        VectorRecord, VectorSink,
+    };
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Fails the first upsert transiently and rejects the records with the id "rejected".
+    #[derive(Debug, Default)]
+    struct FlakySink {
+        inner: MemoryVectorSink,
+        failed_once: AtomicBool,
+    }
+
+    impl VectorSink for FlakySink {
+        fn upsert(&self, records: Vec<VectorRecord>) -> ApiFuture<'_, (), SinkError> {
+            if !self.failed_once.swap(true, Ordering::SeqCst) {
+                return Box::pin(async {
+                    Err(SinkError::Transient("connection reset".to_string()))
+                });
+            }
+
+            if records.iter().any(|record| record.id == "rejected") {
+                return Box::pin(async {
+                    Err(SinkError::Rejected("wrong dimensions".to_string()))
+                });
+            }
+
+            self.inner.upsert(records)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embed_and_sink_retries_batches_and_returns_failed_ids() {
+        let mut request = 0;
+
+        let (base_url, _) = serve_with(3, move |received| {
+            request += 1;
+
+            // the first request fails with a server error
+            if request == 1 {
+                return json_response(
+                    "500 Internal Server Error",
+                    &json!({"error": {"message": "overloaded"}}).to_string(),
+                );
+            }
+
+            let data: Vec<Value> = received.json()["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, input)| {
+                    json!({"object": "embedding", "index": index, "embedding": [input.as_str().unwrap().len(), 1.0]})
+                })
+                .collect();
+
+            json_response(
+                "200 OK",
+                &json!({"object": "list", "data": data, "model": "text-embedding-3-small"})
+                    .to_string(),
+            )
+        })
+        .await;
+
+        let clock = MockClock::auto_advancing();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_clock(clock.clone());
+
+        let documents = [("a", "one"), ("b", "three"), ("rejected", "seventeen")]
+            .map(|(id, text)| VectorDocument {
+                id: id.to_string(),
+                text: text.to_string(),
+                metadata: json!({"source": "test"}),
+            })
+            .to_vec();
+
+        let progress = Arc::new(Mutex::new(vec![]));
+        let reported = progress.clone();
+
+        let mut options = EmbedAndSinkOptions::new("text-embedding-3-small").with_progress(
+            move |completed, total| reported.lock().unwrap().push((completed, total)),
+        );
+        options.batch_size = 2;
+        options.concurrency = 1;
+
+        let sink = FlakySink::default();
+
+        let result = client
+            .embeddings()
+            .embed_and_sink(documents, &sink, options)
+            .await;
+
+        assert_eq!(result.upserted, 2);
+        assert_eq!(result.failed_ids, vec!["rejected"]);
+        assert_eq!(
+            result.errors,
+            vec![EmbedAndSinkError::Sink(SinkError::Rejected(
+                "wrong dimensions".to_string()
+            ))]
+        );
+
+        // the vectors are matched to the documents by index
+        let record = sink.inner.get("b").unwrap();
+
+        assert_eq!(record.vector, vec![5.0, 1.0]);
+        assert_eq!(record.metadata, json!({"source": "test"}));
+
+        // one retry of the embedding and one of the upsert, each is its first retry
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_millis(1000), Duration::from_millis(1000)]
+        );
+        assert_eq!(*progress.lock().unwrap(), vec![(2, 3), (3, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_create_raw_sends_the_body_as_is_and_parses_the_response() {
+        let body = json!({"model": "text-embedding-3-small", "input": ["hello"], "provider_options": {"truncate": "end"}});
+        let response = json!({
+            "object": "list",
+            "data": [{"object": "embedding", "index": 0, "embedding": [0.5, 1.0]}],
+            "model": "text-embedding-3-small"
+        });
+
+        let (base_url, server) = serve([
+            json_response("200 OK", &response.to_string()),
+            json_response("200 OK", &response.to_string()),
+        ])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let embeddings = client.embeddings().create_raw(body.clone()).await.unwrap();
+
+        assert_eq!(embeddings.data.len(), 1);
+        assert_eq!(embeddings.model, "text-embedding-3-small");
+        assert_eq!(
+            client
+                .embeddings()
+                .create_raw_value(body.clone())
+                .await
+                .unwrap(),
+            response
+        );
+
+        let requests = server.await.unwrap();
+
+        assert_eq!(requests[0].route(), "POST /embeddings");
+        assert!(requests.iter().all(|request| request.json() == body));
+    }
 }
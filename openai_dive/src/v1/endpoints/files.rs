@@ -1,10 +1,12 @@
-use crate::v1::api::Client;
+use crate::v1::api::{read_with_progress, write_with_progress, Client};
 use crate::v1::error::APIError;
 use crate::v1::helpers::format_response;
 use crate::v1::resources::file::ListFilesParameters;
-use crate::v1::resources::file::{File, UploadFileParameters};
+use crate::v1::resources::file::{File, FilePurpose, UploadFileParameters};
 use crate::v1::resources::shared::DeletedObject;
+use crate::v1::resources::shared::FileKind;
 use crate::v1::resources::shared::ListResponse;
+use bytes::Bytes;
 
 pub struct Files<'a> {
     pub client: &'a Client,
@@ -32,14 +34,26 @@ impl Files<'_> {
 
     /// Upload a file that can be used across various endpoints.
     pub async fn upload(&self, parameters: UploadFileParameters) -> Result<File, APIError> {
-        let mut form = reqwest::multipart::Form::new();
+        let mut form = self.client.multipart_form();
 
-        let file = parameters.file.into_part().await?;
+        let kind = match parameters.purpose {
+            FilePurpose::Batch | FilePurpose::FineTune => FileKind::Jsonl,
+            _ => FileKind::Any,
+        };
+
+        if let Some(warning) = parameters.file.mime_type_warning(kind) {
+            self.client.warn(warning)?;
+        }
+
+        let file = parameters.file.into_part(kind).await?;
         form = form.part("file", file);
 
         form = form.text("purpose", parameters.purpose.to_string());
 
-        let response = self.client.post_with_form("/files", form).await?;
+        let response = self
+            .client
+            .post_with_form("/files", form.into_form())
+            .await?;
 
         let response: File = format_response(response)?;
 
@@ -70,4 +84,183 @@ impl Files<'_> {
 
         Ok(response)
     }
+
+    /// Returns the contents of the specified file, ie. the output of a batch, calling `on_progress` with the bytes
+    /// downloaded so far and the Content-Length as the chunks arrive. The Content-Length is `None` for a chunked response.
+    pub async fn retrieve_content_with_progress<F>(
+        &self,
+        id: &str,
+        mut on_progress: F,
+    ) -> Result<Bytes, APIError>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let response = self
+            .client
+            .get_raw_response(&format!("/files/{id}/content"))
+            .await?;
+
+        read_with_progress(response, &mut on_progress).await
+    }
+
+    /// Writes the contents of the specified file to the writer as the chunks arrive, without holding the whole file in memory.
+    /// Returns the number of bytes written.
+    pub async fn download_to<W, F>(
+        &self,
+        id: &str,
+        writer: &mut W,
+        mut on_progress: F,
+    ) -> Result<u64, APIError>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+        F: FnMut(u64, Option<u64>),
+    {
+        let response = self
+            .client
+            .get_raw_response(&format!("/files/{id}/content"))
+            .await?;
+
+        write_with_progress(response, writer, &mut on_progress).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::resources::file::{FilePurpose, UploadFileParameters};
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+    use crate::v1::test_support::{listen, read_request};
+    use crate::v1::validation::ValidationWarning;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_download_to_writes_the_content_with_progress() {
+        let (listener, base_url) = listen().await;
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/jsonl\r\ncontent-length: 20000\r\n\r\n")
+                .await
+                .unwrap();
+
+            for _ in 0..4 {
+                socket.write_all(&[b'x'; 5000]).await.unwrap();
+                socket.flush().await.unwrap();
+
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let mut progress = vec![];
+
+        let mut file = vec![];
+        let written = client
+            .files()
+            .download_to("file-abc", &mut file, |downloaded, total| {
+                progress.push((downloaded, total))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(written, 20000);
+        assert_eq!(file, vec![b'x'; 20000]);
+        assert!(progress.len() > 1);
+        assert!(progress.iter().all(|(_, total)| *total == Some(20000)));
+        assert_eq!(progress.last(), Some(&(20000, Some(20000))));
+    }
+
+    #[tokio::test]
+    async fn test_upload_of_bytes_is_sent_with_content_length() {
+        let client = Client::new("sk-test".to_string());
+
+        let parameters = UploadFileParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![1; 1024], "batch.jsonl")),
+            purpose: FilePurpose::Batch,
+        };
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.files().upload(parameters).await })
+            .await
+            .unwrap();
+
+        let length: usize = prepared.headers["content-length"].parse().unwrap();
+
+        let PreparedBody::Multipart(parts) = prepared.body else {
+            panic!("expected a multipart body");
+        };
+
+        let file = parts.iter().find(|part| part.name == "file").unwrap();
+
+        assert_eq!(file.size, 1024);
+        assert!(length > 1024);
+    }
+
+    #[tokio::test]
+    async fn test_upload_without_extension_falls_back_to_octet_stream_with_warning() {
+        let warnings = Arc::new(Mutex::new(vec![]));
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_on_warning({
+            let warnings = warnings.clone();
+
+            move |warning| warnings.lock().unwrap().push(warning.clone())
+        });
+
+        let parameters = UploadFileParameters {
+            file: FileUpload::Bytes(FileUploadBytes::new(vec![1; 16], "notes")),
+            purpose: FilePurpose::UserData,
+        };
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.files().upload(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Multipart(parts) = prepared.body else {
+            panic!("expected a multipart body");
+        };
+
+        let file = parts.iter().find(|part| part.name == "file").unwrap();
+
+        assert_eq!(
+            file.content_type.as_deref(),
+            Some("application/octet-stream")
+        );
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![ValidationWarning::UnknownMimeType {
+                filename: "notes".to_string()
+            }]
+        );
+
+        // a known extension or an explicit MIME type doesn't warn
+        for file in [
+            FileUpload::Bytes(FileUploadBytes::new(vec![1; 16], "notes.txt")),
+            FileUpload::Bytes(FileUploadBytes::new(vec![1; 16], "notes"))
+                .with_mime_type("text/markdown"),
+        ] {
+            let parameters = UploadFileParameters {
+                file,
+                purpose: FilePurpose::UserData,
+            };
+
+            client
+                .dry_run()
+                .prepare(|client| async move { client.files().upload(parameters).await })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(warnings.lock().unwrap().len(), 1);
+    }
 }
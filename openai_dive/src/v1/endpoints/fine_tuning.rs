@@ -1,13 +1,17 @@
 use crate::v1::api::Client;
 use crate::v1::error::APIError;
 use crate::v1::helpers::format_response;
+use crate::v1::resources::file::{FilePurpose, FileStatus, UploadFileParameters};
 use crate::v1::resources::fine_tuning::CreateFineTuningJobParameters;
 use crate::v1::resources::fine_tuning::FineTuningJob;
 use crate::v1::resources::fine_tuning::FineTuningJobCheckpoint;
 use crate::v1::resources::fine_tuning::FineTuningJobEvent;
+use crate::v1::resources::fine_tuning::{CreateFromFiles, FineTuningJobFromFiles};
+use crate::v1::resources::shared::FileUpload;
 use crate::v1::resources::shared::ListResponse;
 use crate::v1::resources::shared::SimpleListParameters;
 use serde_json::Value;
+use std::time::Duration;
 
 pub struct FineTuning<'a> {
     pub client: &'a Client,
@@ -36,6 +40,98 @@ impl FineTuning<'_> {
         Ok(response)
     }
 
+    /// Uploads the training and validation files with purpose `fine-tune`, waits until they are processed and creates the job.
+    /// Unless `validate_lines` is disabled, every line of the files is checked to be a JSON object before anything is uploaded,
+    /// a `ValidationError` lists the line numbers that are not.
+    pub async fn create_from_files(
+        &self,
+        files: CreateFromFiles,
+    ) -> Result<FineTuningJobFromFiles, APIError> {
+        if files.validate_lines {
+            validate_jsonl("training", &files.training).await?;
+
+            if let Some(validation) = &files.validation {
+                validate_jsonl("validation", validation).await?;
+            }
+        }
+
+        let training_file_id = self
+            .upload_processed(files.training, files.poll_interval, files.timeout)
+            .await?;
+
+        let validation_file_id = match files.validation {
+            Some(validation) => Some(
+                self.upload_processed(validation, files.poll_interval, files.timeout)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let job = self
+            .create(CreateFineTuningJobParameters {
+                model: files.base_model,
+                training_file: training_file_id.clone(),
+                suffix: files.suffix,
+                validation_file: validation_file_id.clone(),
+                method: files.method,
+                metadata: files.metadata,
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(FineTuningJobFromFiles {
+            job,
+            training_file_id,
+            validation_file_id,
+        })
+    }
+
+    /// Uploads the file and returns its ID once it is processed.
+    async fn upload_processed(
+        &self,
+        file: FileUpload,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<String, APIError> {
+        let clock = &self.client.clock;
+        let started_at = clock.now();
+
+        let mut file = self
+            .client
+            .files()
+            .upload(UploadFileParameters {
+                file,
+                purpose: FilePurpose::FineTune,
+            })
+            .await?;
+
+        loop {
+            match file.status {
+                // compatible providers without a status can use the file right away
+                None | Some(FileStatus::Processed) => return Ok(file.id),
+                Some(FileStatus::Error) => {
+                    return Err(APIError::FileError(format!(
+                        "file {} could not be processed: {}",
+                        file.id,
+                        file.status_details.as_deref().unwrap_or("unknown error")
+                    )))
+                }
+                Some(FileStatus::Uploaded) => {}
+            }
+
+            if clock.now().saturating_duration_since(started_at) >= timeout {
+                return Err(APIError::FileError(format!(
+                    "file {} was not processed within {timeout:?}",
+                    file.id
+                )));
+            }
+
+            clock.sleep(poll_interval).await;
+
+            file = self.client.files().retrieve(&file.id).await?;
+        }
+    }
+
     /// List your organization's fine-tuning jobs.
     pub async fn list(
         &self,
@@ -108,3 +204,186 @@ impl FineTuning<'_> {
         Ok(response)
     }
 }
+
+/// Checks that every line of a JSONL file is a JSON object, and that weights are 0 or 1 and only on assistant messages.
+/// Empty lines are skipped, files that can't be read here (ie. a URL) are left to the API.
+async fn validate_jsonl(field: &str, file: &FileUpload) -> Result<(), APIError> {
+    let Some(contents) = file.contents().await? else {
+        return Ok(());
+    };
+
+    let text = String::from_utf8_lossy(&contents);
+
+    let errors: Vec<String> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(example)) => {
+                weight_error(&example).map(|error| format!("line {}: {error}", index + 1))
+            }
+            Ok(_) => Some(format!("line {}: not a JSON object", index + 1)),
+            Err(error) => Some(format!("line {}: {error}", index + 1)),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(APIError::ValidationError {
+        field: field.to_string(),
+        value: errors.join("; "),
+        allowed: "a JSON object on every line, with a weight of 0 or 1 on assistant messages only"
+            .to_string(),
+    })
+}
+
+/// Returns the first invalid `weight` of the messages of an example.
+fn weight_error(example: &serde_json::Map<String, Value>) -> Option<String> {
+    let messages = example.get("messages")?.as_array()?;
+
+    messages.iter().enumerate().find_map(|(index, message)| {
+        let weight = message.get("weight")?;
+
+        if message.get("role").and_then(Value::as_str) != Some("assistant") {
+            return Some(format!(
+                "weight on message {index}, which is not an assistant message"
+            ));
+        }
+
+        (weight.as_u64() != Some(0) && weight.as_u64() != Some(1))
+            .then(|| format!("weight {weight} of message {index} is not 0 or 1"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::clock::MockClock;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::fine_tuning::CreateFromFilesBuilder;
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+    use crate::v1::test_support::{json_response, serve_with, Request};
+    use std::time::Duration;
+
+    const JOB: &str = r#"{"id": "ftjob-1", "object": "fine_tuning.job", "created_at": 1, "hyperparameters": {"n_epochs": 3}, "model": "gpt-4o-mini", "organization_id": "org-1", "result_files": [], "status": "validating_files", "training_file": "file-train", "validation_file": "file-valid"}"#;
+
+    /// Answers by method and path.
+    fn fine_tuning_response(request: &Request) -> String {
+        let file = |id: &str, status: &str| {
+            format!(
+                r#"{{"id": "{id}", "bytes": 1, "created_at": 2, "filename": "{id}.jsonl", "object": "file", "purpose": "fine-tune", "status": "{status}"}}"#
+            )
+        };
+
+        let body = match request.route().as_str() {
+            "POST /files" if request.text().contains("train.jsonl") => {
+                file("file-train", "uploaded")
+            }
+            "POST /files" => file("file-valid", "processed"),
+            "GET /files/file-train" => file("file-train", "processed"),
+            _ => JOB.to_string(),
+        };
+
+        json_response("200 OK", &body)
+    }
+
+    fn jsonl(lines: &str, filename: &str) -> FileUpload {
+        FileUpload::Bytes(FileUploadBytes::new(lines.as_bytes().to_vec(), filename))
+    }
+
+    #[tokio::test]
+    async fn test_create_from_files_waits_until_files_are_processed() {
+        let (base_url, server) = serve_with(4, fine_tuning_response).await;
+
+        let clock = MockClock::auto_advancing();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_clock(clock.clone());
+
+        let files = CreateFromFilesBuilder::default()
+            .training(jsonl(r#"{"messages": []}"#, "train.jsonl"))
+            .validation(jsonl("{\"messages\": []}\n\n", "valid.jsonl"))
+            .base_model("gpt-4o-mini")
+            .suffix("support")
+            .poll_interval(Duration::from_secs(2))
+            .build()
+            .unwrap();
+
+        let created = client.fine_tuning().create_from_files(files).await.unwrap();
+
+        assert_eq!(created.job.id, "ftjob-1");
+        assert_eq!(created.training_file_id, "file-train");
+        assert_eq!(created.validation_file_id.as_deref(), Some("file-valid"));
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(2)]);
+
+        let requests = server.await.unwrap();
+
+        assert_eq!(requests[1].route(), "GET /files/file-train");
+        assert_eq!(requests[3].route(), "POST /fine_tuning/jobs");
+
+        let job = requests[3].text();
+
+        assert!(job.contains(r#""training_file":"file-train""#));
+        assert!(job.contains(r#""validation_file":"file-valid""#));
+        assert!(job.contains(r#""suffix":"support""#));
+    }
+
+    #[tokio::test]
+    async fn test_create_from_files_reports_invalid_lines_before_uploading() {
+        // nothing listens here, the files are checked before anything is sent
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url("http://127.0.0.1:9");
+
+        let files = CreateFromFilesBuilder::default()
+            .training(jsonl(
+                "{\"messages\": []}\nnot json\n[1, 2]\n",
+                "train.jsonl",
+            ))
+            .base_model("gpt-4o-mini")
+            .build()
+            .unwrap();
+
+        let error = client
+            .fine_tuning()
+            .create_from_files(files)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, APIError::ValidationError { field, value, .. } if field == "training" && value.starts_with("line 2: ") && value.ends_with("; line 3: not a JSON object")),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_from_files_checks_the_weights_of_messages() {
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url("http://127.0.0.1:9");
+
+        let lines = [
+            r#"{"messages": [{"role": "user", "content": "Hi"}, {"role": "assistant", "content": "Hello", "weight": 0}]}"#,
+            r#"{"messages": [{"role": "user", "content": "Hi", "weight": 1}]}"#,
+            r#"{"messages": [{"role": "user", "content": "Hi"}, {"role": "assistant", "content": "Hello", "weight": 2}]}"#,
+        ];
+
+        let files = CreateFromFilesBuilder::default()
+            .training(jsonl(&lines.join("\n"), "train.jsonl"))
+            .base_model("gpt-4o-mini")
+            .build()
+            .unwrap();
+
+        let error = client
+            .fine_tuning()
+            .create_from_files(files)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, APIError::ValidationError { value, .. } if value == "line 2: weight on message 0, which is not an assistant message; line 3: weight 2 of message 1 is not 0 or 1"),
+            "{error:?}"
+        );
+    }
+}
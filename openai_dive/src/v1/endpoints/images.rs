@@ -4,7 +4,7 @@ use crate::v1::helpers::format_response;
 use crate::v1::resources::image::{
     CreateImageParameters, CreateImageVariationParameters, EditImageParameters, ImageResponse,
 };
-use crate::v1::resources::shared::FileUpload;
+use crate::v1::resources::shared::FileKind;
 
 pub struct Images<'a> {
     pub client: &'a Client,
@@ -35,55 +35,30 @@ impl Images<'_> {
 
     /// Creates an edited or extended image given an original image and a prompt.
     pub async fn edit(&self, parameters: EditImageParameters) -> Result<ImageResponse, APIError> {
-        let mut form = reqwest::multipart::Form::new();
+        let mut form = self.client.multipart_form();
 
         let mime_type = parameters.mime_type;
 
-        match parameters.image {
-            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
-            FileUpload::File(_) => {
-                let mut image = parameters.image.into_part().await?;
+        if parameters.image.is_array() {
+            let images = parameters.image.into_parts(FileKind::Image).await?;
 
+            for mut image in images {
                 if let Some(ref mime_type) = mime_type {
                     image = image
                         .mime_str(&mime_type.to_string())
                         .map_err(|error| APIError::FileError(error.to_string()))?;
                 }
-                form = form.part("image", image);
+                form = form.part("image[]", image);
             }
-            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
-            FileUpload::FileArray(_) => {
-                let images = parameters.image.into_parts().await?;
-                for mut image in images {
-                    if let Some(ref mime_type) = mime_type {
-                        image = image
-                            .mime_str(&mime_type.to_string())
-                            .map_err(|error| APIError::FileError(error.to_string()))?;
-                    }
-                    form = form.part("image[]", image);
-                }
-            }
-            FileUpload::Bytes(_) => {
-                let mut image = parameters.image.into_part().await?;
+        } else {
+            let mut image = parameters.image.into_part(FileKind::Image).await?;
 
-                if let Some(ref mime_type) = mime_type {
-                    image = image
-                        .mime_str(&mime_type.to_string())
-                        .map_err(|error| APIError::FileError(error.to_string()))?;
-                }
-                form = form.part("image", image);
-            }
-            FileUpload::BytesArray(_) => {
-                let images = parameters.image.into_parts().await?;
-                for mut image in images {
-                    if let Some(ref mime_type) = mime_type {
-                        image = image
-                            .mime_str(&mime_type.to_string())
-                            .map_err(|error| APIError::FileError(error.to_string()))?;
-                    }
-                    form = form.part("image[]", image);
-                }
+            if let Some(ref mime_type) = mime_type {
+                image = image
+                    .mime_str(&mime_type.to_string())
+                    .map_err(|error| APIError::FileError(error.to_string()))?;
             }
+            form = form.part("image", image);
         }
 
         form = form.text("prompt", parameters.prompt);
@@ -101,7 +76,7 @@ impl Images<'_> {
         }
 
         if let Some(mask) = parameters.mask {
-            let image = mask.into_part().await?;
+            let image = mask.into_part(FileKind::Png).await?;
             form = form.part("mask", image);
         }
 
@@ -141,7 +116,10 @@ impl Images<'_> {
             form = form.text("user", user.to_string());
         }
 
-        let response = self.client.post_with_form("/images/edits", form).await?;
+        let response = self
+            .client
+            .post_with_form("/images/edits", form.into_form())
+            .await?;
 
         let response: ImageResponse = format_response(response)?;
 
@@ -153,9 +131,9 @@ impl Images<'_> {
         &self,
         parameters: CreateImageVariationParameters,
     ) -> Result<ImageResponse, APIError> {
-        let mut form = reqwest::multipart::Form::new();
+        let mut form = self.client.multipart_form();
 
-        let image = parameters.image.into_part().await?;
+        let image = parameters.image.into_part(FileKind::Png).await?;
         form = form.part("image", image);
 
         if let Some(model) = parameters.model {
@@ -180,7 +158,7 @@ impl Images<'_> {
 
         let response = self
             .client
-            .post_with_form("/images/variations", form)
+            .post_with_form("/images/variations", form.into_form())
             .await?;
 
         let response: ImageResponse = format_response(response)?;
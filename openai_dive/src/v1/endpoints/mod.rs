@@ -8,9 +8,9 @@ pub mod fine_tuning;
 pub mod images;
 pub mod models;
 pub mod moderations;
-#[cfg(feature = "realtime")]
 pub mod realtime;
 pub mod responses;
+pub mod traits;
 pub mod uploads;
 pub mod usage;
 pub mod vector_store_files;
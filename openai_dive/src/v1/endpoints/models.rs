@@ -1,7 +1,7 @@
 use crate::v1::api::Client;
 use crate::v1::error::APIError;
 use crate::v1::helpers::format_response;
-use crate::v1::resources::model::{ListModelResponse, Model};
+use crate::v1::resources::model::{ListModelResponse, Model, ModelCapabilities, ModelCapability};
 use crate::v1::resources::shared::DeletedObject;
 
 pub struct Models<'a> {
@@ -13,6 +13,66 @@ impl Client {
     pub fn models(&self) -> Models<'_> {
         Models { client: self }
     }
+
+    /// Fetch the models available to the account and use them to validate the model of audio requests.
+    /// The fetched models are cached and fetched again after the TTL of the cache.
+    /// If fetching fails, the error is returned and only the static capability table is used.
+    pub async fn load_model_capabilities(&self) -> Result<(), APIError> {
+        match self.models().list().await {
+            Ok(response) => {
+                self.model_capabilities.store(Some(
+                    response.data.into_iter().map(|model| model.id).collect(),
+                ));
+
+                Ok(())
+            }
+            Err(error) => {
+                self.model_capabilities.store(None);
+
+                Err(error)
+            }
+        }
+    }
+
+    /// Set how long the loaded model capabilities are used before they are fetched again.
+    pub fn set_model_capabilities_ttl(&mut self, ttl: std::time::Duration) -> &mut Self {
+        self.model_capabilities.ttl = ttl;
+
+        self
+    }
+
+    pub(crate) async fn check_model_capability(
+        &self,
+        model: &str,
+        capability: ModelCapability,
+    ) -> Result<(), APIError> {
+        if !self.model_capabilities.is_loaded() {
+            return Ok(());
+        }
+
+        if self.model_capabilities.is_expired() {
+            let _ = self.load_model_capabilities().await;
+        }
+
+        if self.model_capabilities.is_available(model) == Some(false) {
+            return Err(APIError::ValidationError {
+                field: "model".to_string(),
+                value: model.to_string(),
+                allowed: "one of the models available to the account".to_string(),
+            });
+        }
+
+        match ModelCapabilities::from_model_id(model) {
+            Some(capabilities) if !capabilities.supports(capability) => {
+                Err(APIError::ValidationError {
+                    field: "model".to_string(),
+                    value: model.to_string(),
+                    allowed: format!("a model that supports {capability}"),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Models<'_> {
@@ -26,7 +86,7 @@ impl Models<'_> {
     }
 
     /// Retrieves a model instance, providing basic information about the model such as the owner and permissioning.
-    pub async fn get(&self, model_id: &str) -> Result<Model, APIError> {
+    pub async fn retrieve(&self, model_id: &str) -> Result<Model, APIError> {
         let path = format!("/models/{model_id}");
 
         let response = self.client.get(&path).await?;
@@ -36,6 +96,20 @@ impl Models<'_> {
         Ok(response)
     }
 
+    /// Retrieves a model instance, the same as `retrieve`.
+    pub async fn get(&self, model_id: &str) -> Result<Model, APIError> {
+        self.retrieve(model_id).await
+    }
+
+    /// Returns whether the API key can use the model, the model is not found for keys that can't use it.
+    pub async fn can_use(&self, model_id: &str) -> Result<bool, APIError> {
+        match self.retrieve(model_id).await {
+            Ok(_) => Ok(true),
+            Err(APIError::NotFoundError { .. } | APIError::PermissionError { .. }) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
     /// Delete a fine-tuned model. You must have the Owner role in your organization to delete a model.
     pub async fn delete(&self, model_id: &str) -> Result<DeletedObject, APIError> {
         let path = format!("/models/{model_id}");
@@ -47,3 +121,47 @@ impl Models<'_> {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::model::ModelCapability;
+    use crate::v1::test_support::{json_response, serve};
+
+    #[tokio::test]
+    async fn test_load_model_capabilities_from_mocked_models_response() {
+        let (base_url, _) = serve([json_response(
+            "200 OK",
+            r#"{"object":"list","data":[{"id":"whisper-1","created":1677532384,"object":"model","owned_by":"openai-internal"},{"id":"tts-1","created":1681940951,"object":"model","owned_by":"openai-internal"}]}"#,
+        )])
+        .await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        assert!(client
+            .check_model_capability("gpt-4o-mini-transcribe", ModelCapability::Transcription)
+            .await
+            .is_ok());
+
+        client.load_model_capabilities().await.unwrap();
+
+        assert!(client
+            .check_model_capability("whisper-1", ModelCapability::Transcription)
+            .await
+            .is_ok());
+        assert!(matches!(
+            client
+                .check_model_capability("whisper-1", ModelCapability::Speech)
+                .await,
+            Err(APIError::ValidationError { .. })
+        ));
+        assert!(matches!(
+            client
+                .check_model_capability("gpt-4o-mini-transcribe", ModelCapability::Transcription)
+                .await,
+            Err(APIError::ValidationError { .. })
+        ));
+    }
+}
@@ -1,7 +1,11 @@
 use crate::v1::api::Client;
+use crate::v1::clock::retry;
 use crate::v1::error::APIError;
-use crate::v1::helpers::format_response;
-use crate::v1::resources::moderation::{ModerationParameters, ModerationResponse};
+use crate::v1::helpers::{buffered, format_response};
+use crate::v1::resources::moderation::{
+    BatchedModerationResult, ModerationBatchOptions, ModerationInput, ModerationParameters,
+    ModerationResponse,
+};
 
 pub struct Moderations<'a> {
     pub client: &'a Client,
@@ -26,4 +30,201 @@ impl Moderations<'_> {
 
         Ok(response)
     }
+
+    /// Classifies any number of texts, in requests of `chunk_size` inputs that are sent `concurrency` at a time.
+    /// The results are in the order of the inputs, the inputs of a request that failed (after the retries) get its error.
+    pub async fn create_batched(
+        &self,
+        inputs: Vec<String>,
+        options: ModerationBatchOptions,
+    ) -> Vec<Result<BatchedModerationResult, APIError>> {
+        let chunks: Vec<Vec<String>> = inputs
+            .chunks(options.chunk_size.max(1))
+            .map(<[String]>::to_vec)
+            .collect();
+
+        let requests = chunks
+            .iter()
+            .map(|chunk| self.create_chunk(chunk.clone(), &options))
+            .collect();
+
+        let responses = buffered(requests, options.concurrency).await;
+
+        chunks
+            .iter()
+            .zip(responses)
+            .flat_map(|(chunk, response)| match response {
+                Ok(response) => {
+                    let mut results = response.results.into_iter();
+
+                    (0..chunk.len())
+                        .map(|index| {
+                            let result = results.next().ok_or_else(|| {
+                                APIError::ParseError(format!(
+                                    "moderation {} returned no result for input {index} of {}",
+                                    response.id,
+                                    chunk.len()
+                                ))
+                            })?;
+
+                            Ok(BatchedModerationResult {
+                                id: response.id.clone(),
+                                model: response.model.clone(),
+                                result,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                }
+                Err(error) => vec![Err(error); chunk.len()],
+            })
+            .collect()
+    }
+
+    async fn create_chunk(
+        &self,
+        inputs: Vec<String>,
+        options: &ModerationBatchOptions,
+    ) -> Result<ModerationResponse, APIError> {
+        let parameters = ModerationParameters {
+            input: ModerationInput::Array(inputs),
+            model: options.model.clone(),
+        };
+
+        retry(
+            &*self.client.clock,
+            options.max_retries,
+            APIError::retry_after,
+            || self.create(parameters.clone()),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::clock::MockClock;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::moderation::ModerationBatchOptionsBuilder;
+    use crate::v1::test_support::{json_response, serve_with, Request};
+    use serde_json::{json, Map, Value};
+    use std::time::Duration;
+
+    const CATEGORIES: [&str; 13] = [
+        "hate",
+        "hate/threatening",
+        "harassment",
+        "harassment/threatening",
+        "illicit",
+        "illicit/violent",
+        "self-harm",
+        "self-harm/intent",
+        "self-harm/instructions",
+        "sexual",
+        "sexual/minors",
+        "violence",
+        "violence/graphic",
+    ];
+
+    fn result(flagged: bool) -> Value {
+        let categories = |value: Value| {
+            Value::Object(Map::from_iter(
+                CATEGORIES.map(|category| (category.to_string(), value.clone())),
+            ))
+        };
+
+        json!({
+            "flagged": flagged,
+            "categories": categories(json!(flagged)),
+            "category_scores": categories(json!(0.1)),
+            "category_applied_input_types": categories(json!(["text"])),
+        })
+    }
+
+    /// Answers by the first input of the chunk: "comment-32" fails, "comment-64" is rate limited once.
+    fn moderations_response(request: &Request, rate_limited: &mut bool) -> String {
+        let body = request.json();
+        let inputs = body["input"].as_array().unwrap();
+        let first: usize = inputs[0].as_str().unwrap()[8..].parse().unwrap();
+
+        let (status, response) = if first == 32 {
+            (
+                "400 Bad Request",
+                json!({"error": {"message": "invalid input", "type": "invalid_request_error"}}),
+            )
+        } else if first == 64 && !std::mem::replace(rate_limited, true) {
+            (
+                "429 Too Many Requests",
+                json!({"error": {"message": "slow down", "type": "rate_limit_error"}}),
+            )
+        } else {
+            let model = if first == 0 {
+                "omni-moderation-2024-09-26"
+            } else {
+                "omni-moderation-2025-01-01"
+            };
+
+            let results: Vec<Value> = (first..first + inputs.len())
+                .map(|index| result(index % 5 == 0))
+                .collect();
+
+            (
+                "200 OK",
+                json!({"id": format!("modr-{first}"), "model": model, "results": results}),
+            )
+        };
+
+        json_response(status, &response.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_create_batched_keeps_input_order_across_failed_chunks() {
+        let mut rate_limited = false;
+
+        // three chunks, the rate limited one is sent twice
+        let (base_url, _) = serve_with(4, move |request| {
+            moderations_response(request, &mut rate_limited)
+        })
+        .await;
+
+        let clock = MockClock::auto_advancing();
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_clock(clock.clone());
+
+        let inputs = (0..70).map(|index| format!("comment-{index}")).collect();
+
+        let options = ModerationBatchOptionsBuilder::default()
+            .concurrency(2usize)
+            .build()
+            .unwrap();
+
+        let results = client.moderations().create_batched(inputs, options).await;
+
+        assert_eq!(results.len(), 70);
+
+        for (index, result) in results.iter().enumerate() {
+            match index {
+                0..32 => {
+                    let result = result.as_ref().unwrap();
+
+                    assert_eq!(result.model, "omni-moderation-2024-09-26");
+                    assert_eq!(result.id, "modr-0");
+                    assert_eq!(result.result.flagged, index % 5 == 0);
+                }
+                32..64 => assert!(matches!(result, Err(APIError::BadRequestError { .. }))),
+                _ => {
+                    let result = result.as_ref().unwrap();
+
+                    assert_eq!(result.model, "omni-moderation-2025-01-01");
+                    assert_eq!(result.id, "modr-64");
+                    assert_eq!(result.result.flagged, index % 5 == 0);
+                }
+            }
+        }
+
+        // the rate limited chunk is retried once, the bad request is not retried
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(1000)]);
+    }
 }
@@ -1,28 +1,241 @@
-use reqwest_websocket::{RequestBuilderExt, WebSocket};
+//! The Realtime API over WebSocket (`realtime` feature).
+//! Without the feature the types and methods exist, `audio_session` returns `APIError::FeatureDisabled`.
+#[cfg(feature = "realtime")]
+use base64::{engine::general_purpose, Engine as _};
+use bytes::Bytes;
+#[cfg(feature = "realtime")]
+use futures::{SinkExt, StreamExt};
+#[cfg(feature = "realtime")]
+use reqwest_websocket::{Message, RequestBuilderExt, WebSocket};
+use serde::Serialize;
 
-use crate::v1::{api::Client, error::APIError};
+#[cfg(feature = "realtime")]
+use crate::v1::resources::realtime::client::{InputAudioBufferAppend, InputAudioBufferCommit};
+use crate::v1::{api::Client, error::APIError, resources::realtime::server::RealtimeEvent};
 
 pub struct Realtime<'a> {
     pub client: &'a Client,
 }
 
+/// An experimental audio session over the Realtime WebSocket.
+/// Audio is sent with `send_audio` and the server events are received with `next_event`.
+pub struct RealtimeAudioSession {
+    #[cfg(feature = "realtime")]
+    pub websocket: WebSocket,
+    /// Without the `realtime` feature a session can't be connected.
+    #[cfg(not(feature = "realtime"))]
+    _disabled: (),
+}
+
 impl Client {
     pub fn realtime(&self) -> Realtime<'_> {
         Realtime { client: self }
     }
 }
 
+#[cfg(feature = "realtime")]
 impl Realtime<'_> {
     pub async fn websocket(&self, model: &str) -> Result<WebSocket, APIError> {
-        let response = reqwest::Client::default()
-            .get(format!("wss://api.openai.com/v1/realtime?model={model}"))
-            .bearer_auth(&self.client.api_key)
-            .upgrade()
-            .send()
-            .await?;
+        let mut request = self
+            .http_client()?
+            .get(self.client.url(&format!("/realtime?model={model}")))
+            .bearer_auth(&self.client.api_key);
+
+        if let Some(headers) = &self.client.headers {
+            for (key, value) in headers {
+                request = request.header(key, value);
+            }
+        }
+
+        if let Some(organization) = &self.client.organization {
+            request = request.header("OpenAI-Organization", organization);
+        }
+
+        if let Some(project) = &self.client.project {
+            request = request.header("OpenAI-Project", project);
+        }
+
+        let response = request.upgrade().send().await?;
 
         let websocket = response.into_websocket().await?;
 
         Ok(websocket)
     }
+
+    /// The WebSocket client, with the client certificate and pinned certificates of the client.
+    fn http_client(&self) -> Result<reqwest_websocket_client::Client, APIError> {
+        use reqwest_websocket_client::{Certificate, Identity};
+
+        let tls = &self.client.tls;
+        let mut builder = reqwest_websocket_client::Client::builder();
+
+        if let Some(pem) = &tls.identity_pem {
+            builder = builder.identity(
+                Identity::from_pem(pem).map_err(|error| APIError::TlsError(error.to_string()))?,
+            );
+        }
+
+        if !tls.pinned_certificates.is_empty() {
+            builder = builder.tls_built_in_root_certs(false);
+
+            for der in &tls.pinned_certificates {
+                builder = builder.add_root_certificate(
+                    Certificate::from_der(der)
+                        .map_err(|error| APIError::TlsError(error.to_string()))?,
+                );
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|error| APIError::TlsError(error.to_string()))
+    }
+
+    /// Connects an experimental audio session to the given Realtime model.
+    pub async fn audio_session(&self, model: &str) -> Result<RealtimeAudioSession, APIError> {
+        let websocket = self.websocket(model).await?;
+
+        Ok(RealtimeAudioSession { websocket })
+    }
+}
+
+#[cfg(not(feature = "realtime"))]
+impl Realtime<'_> {
+    /// Connects an experimental audio session, which needs the `realtime` feature.
+    pub async fn audio_session(&self, _model: &str) -> Result<RealtimeAudioSession, APIError> {
+        Err(APIError::FeatureDisabled("realtime".to_string()))
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl RealtimeAudioSession {
+    /// Appends audio bytes to the input audio buffer, in the input audio format of the session.
+    pub async fn send_audio(&mut self, audio: Bytes) -> Result<(), APIError> {
+        self.send_event(&InputAudioBufferAppend {
+            r#type: "input_audio_buffer.append".to_string(),
+            audio: general_purpose::STANDARD.encode(audio),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Commits the input audio buffer, which is only needed when server VAD is disabled.
+    pub async fn commit_audio(&mut self) -> Result<(), APIError> {
+        self.send_event(&InputAudioBufferCommit {
+            r#type: "input_audio_buffer.commit".to_string(),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Sends any client event to the server.
+    pub async fn send_event<T: Serialize>(&mut self, event: &T) -> Result<(), APIError> {
+        let text = serde_json::to_string(event)
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        self.websocket.send(Message::Text(text)).await?;
+
+        Ok(())
+    }
+
+    /// Returns the next server event, or None when the connection is closed.
+    pub async fn next_event(&mut self) -> Option<Result<RealtimeEvent, APIError>> {
+        while let Some(message) = self.websocket.next().await {
+            match message {
+                Ok(Message::Text(text)) => {
+                    return Some(
+                        serde_json::from_str::<RealtimeEvent>(&text)
+                            .map_err(|error| APIError::ParseError(error.to_string())),
+                    )
+                }
+                Ok(Message::Close { .. }) => return None,
+                Ok(_) => continue,
+                Err(error) => return Some(Err(error.into())),
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(not(feature = "realtime"))]
+impl RealtimeAudioSession {
+    /// Appends audio bytes to the input audio buffer, which needs the `realtime` feature.
+    pub async fn send_audio(&mut self, _audio: Bytes) -> Result<(), APIError> {
+        Err(APIError::FeatureDisabled("realtime".to_string()))
+    }
+
+    /// Commits the input audio buffer, which needs the `realtime` feature.
+    pub async fn commit_audio(&mut self) -> Result<(), APIError> {
+        Err(APIError::FeatureDisabled("realtime".to_string()))
+    }
+
+    /// Sends any client event to the server, which needs the `realtime` feature.
+    pub async fn send_event<T: Serialize>(&mut self, _event: &T) -> Result<(), APIError> {
+        Err(APIError::FeatureDisabled("realtime".to_string()))
+    }
+
+    /// Returns the next server event, which needs the `realtime` feature.
+    pub async fn next_event(&mut self) -> Option<Result<RealtimeEvent, APIError>> {
+        Some(Err(APIError::FeatureDisabled("realtime".to_string())))
+    }
+}
+
+#[cfg(all(test, feature = "realtime"))]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::resources::realtime::server::RealtimeEvent;
+    use bytes::Bytes;
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_audio_session_against_mock_websocket_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut websocket = tungstenite::accept(stream).unwrap();
+
+            let message = websocket.read().unwrap();
+            let event: serde_json::Value =
+                serde_json::from_str(message.to_text().unwrap()).unwrap();
+
+            websocket
+                .send(tungstenite::Message::Text(
+                    r#"{"event_id":"event_1","type":"response.output_audio_transcript.delta","response_id":"resp_1","item_id":"item_1","output_index":0,"content_index":0,"delta":"Hello"}"#.to_string(),
+                ))
+                .unwrap();
+            websocket.close(None).unwrap();
+            let _ = websocket.flush();
+
+            event
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&format!("http://{address}"));
+
+        let mut session = client
+            .realtime()
+            .audio_session("gpt-realtime")
+            .await
+            .unwrap();
+
+        session
+            .send_audio(Bytes::from_static(&[0, 1, 2]))
+            .await
+            .unwrap();
+
+        let event = session.next_event().await.unwrap().unwrap();
+
+        assert!(matches!(
+            event,
+            RealtimeEvent::AudioTranscriptDelta(delta) if delta.delta == "Hello"
+        ));
+
+        let sent = server.join().unwrap();
+
+        assert_eq!(sent["type"], "input_audio_buffer.append");
+        assert_eq!(sent["audio"], "AAEC");
+    }
 }
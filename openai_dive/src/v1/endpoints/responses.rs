@@ -1,11 +1,17 @@
 use crate::v1::api::Client;
 use crate::v1::error::APIError;
 use crate::v1::helpers::format_response;
-use crate::v1::resources::response::request::ResponseParameters;
-use crate::v1::resources::response::response::ResponseObject;
+use crate::v1::resources::response::items::{FileSearchResult, ToolOutput};
+use crate::v1::resources::response::request::{
+    InputItem, ResponseInput, ResponseInputItem, ResponseParameters,
+};
+use crate::v1::resources::response::response::{OutputContent, ResponseObject, ResponseOutput};
 #[cfg(feature = "stream")]
 use crate::v1::resources::response::shared::ResponseStream;
+use crate::v1::resources::response::shared::{Annotation, ResolvedCitation, ResponseTool};
 use crate::v1::resources::shared::DeletedObject;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 pub struct Responses<'a> {
     pub client: &'a Client,
@@ -21,6 +27,8 @@ impl Client {
 impl Responses<'_> {
     /// Creates a model response.
     pub async fn create(&self, parameters: ResponseParameters) -> Result<ResponseObject, APIError> {
+        let parameters = self.with_end_user(parameters);
+
         let response = self.client.post("/responses", &parameters, None).await?;
 
         let response: ResponseObject = format_response(response.data)?;
@@ -28,6 +36,29 @@ impl Responses<'_> {
         Ok(response)
     }
 
+    /// Answers the function calls of the response with their outputs and creates the next response, of the model of the response
+    /// unless the parameters set one. The input and previous response of the parameters are replaced.
+    /// Before anything is sent, every function call needs exactly one output, a `ValidationError` lists the missing and other ids.
+    pub async fn submit_tool_outputs(
+        &self,
+        response: &ResponseObject,
+        outputs: Vec<ToolOutput>,
+        parameters: ResponseParameters,
+    ) -> Result<ResponseObject, APIError> {
+        let parameters = tool_output_parameters(response, outputs, parameters)?;
+
+        self.create(parameters).await
+    }
+
+    /// Sets the safety identifier from `Client::set_end_user`, if the parameters don't set one.
+    fn with_end_user(&self, mut parameters: ResponseParameters) -> ResponseParameters {
+        if parameters.safety_identifier.is_none() {
+            parameters.safety_identifier = self.client.end_user_id();
+        }
+
+        parameters
+    }
+
     /// Retrieves a model response with the given ID.
     pub async fn retrieve(&self, response_id: &str) -> Result<ResponseObject, APIError> {
         let response = self
@@ -52,13 +83,107 @@ impl Responses<'_> {
         Ok(response)
     }
 
+    /// Resolves the file citations in the output of a response to the names of the cited files and the cited text.
+    /// A citation cites the output text from the previous annotation (or the start of the text) up to its index, see `ResolvedCitation::index_range`.
+    /// The quote is the chunk of the file that matches the cited text best, a chunk is a file search result when they are included
+    /// in the response, otherwise a part of the parsed content of the file in the vector stores searched by the response.
+    /// A citation that could not be resolved, ie. because the file was deleted, carries its error instead of failing all citations.
+    pub async fn resolve_citations(&self, response: &ResponseObject) -> Vec<ResolvedCitation> {
+        let vector_store_ids: Vec<&str> = response
+            .tools
+            .iter()
+            .filter_map(|tool| match tool {
+                ResponseTool::FileSearch {
+                    vector_store_ids, ..
+                } => Some(vector_store_ids),
+                _ => None,
+            })
+            .flatten()
+            .map(String::as_str)
+            .collect();
+
+        let search_results: Vec<&FileSearchResult> = response
+            .output
+            .iter()
+            .filter_map(|output| match output {
+                ResponseOutput::FileSearchToolCall(call) => call.results.as_ref(),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let mut filenames: HashMap<&str, Result<String, APIError>> = HashMap::new();
+        let mut chunks: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut citations = vec![];
+
+        for span in cited_spans(response) {
+            if !filenames.contains_key(span.file_id) {
+                let filename = self
+                    .client
+                    .files()
+                    .retrieve(span.file_id)
+                    .await
+                    .map(|file| file.filename);
+
+                filenames.insert(span.file_id, filename);
+            }
+
+            if !chunks.contains_key(span.file_id) {
+                let mut file_chunks: Vec<String> = search_results
+                    .iter()
+                    .filter(|result| result.file_id == span.file_id)
+                    .map(|result| result.text.clone())
+                    .collect();
+
+                if file_chunks.is_empty() {
+                    file_chunks = self
+                        .vector_store_content(&vector_store_ids, span.file_id)
+                        .await;
+                }
+
+                chunks.insert(span.file_id, file_chunks);
+            }
+
+            let (filename, error) = match &filenames[span.file_id] {
+                Ok(filename) => (Some(filename.clone()), None),
+                Err(error) => (None, Some(error.clone())),
+            };
+
+            citations.push(ResolvedCitation {
+                index_range: span.index_range,
+                file_id: span.file_id.to_string(),
+                filename,
+                quote: best_quote(&span.text, &chunks[span.file_id]),
+                error,
+            });
+        }
+
+        citations
+    }
+
+    /// Returns the parts of the parsed content of the file, from the first vector store that has it.
+    async fn vector_store_content(&self, vector_store_ids: &[&str], file_id: &str) -> Vec<String> {
+        for vector_store_id in vector_store_ids {
+            if let Ok(content) = self
+                .client
+                .vector_store_files()
+                .retrieve_content(vector_store_id, file_id)
+                .await
+            {
+                return content.content.into_iter().map(|part| part.text).collect();
+            }
+        }
+
+        vec![]
+    }
+
     #[cfg(feature = "stream")]
     /// Creates a model response.
     pub async fn create_stream(
         &self,
         parameters: ResponseParameters,
     ) -> Result<ResponseStream, APIError> {
-        let mut stream_parameters = ResponseParameters { ..parameters };
+        let mut stream_parameters = self.with_end_user(parameters);
         stream_parameters.stream = Some(true);
 
         Ok(self
@@ -66,4 +191,499 @@ impl Responses<'_> {
             .post_stream("/responses", &stream_parameters, None)
             .await)
     }
+
+    #[cfg(feature = "stream")]
+    /// Answers the function calls of the response like `submit_tool_outputs`, and streams the next response.
+    /// The outputs are checked before the stream is opened.
+    pub async fn submit_tool_outputs_stream(
+        &self,
+        response: &ResponseObject,
+        outputs: Vec<ToolOutput>,
+        parameters: ResponseParameters,
+    ) -> Result<ResponseStream, APIError> {
+        let parameters = tool_output_parameters(response, outputs, parameters)?;
+
+        self.create_stream(parameters).await
+    }
+}
+
+/// Checks that the outputs answer every function call of the response exactly once,
+/// and returns the parameters of the next response with the outputs as input.
+fn tool_output_parameters(
+    response: &ResponseObject,
+    outputs: Vec<ToolOutput>,
+    mut parameters: ResponseParameters,
+) -> Result<ResponseParameters, APIError> {
+    let calls: Vec<&str> = response
+        .output
+        .iter()
+        .filter_map(|output| match output {
+            ResponseOutput::FunctionToolCall(call) => Some(call.call_id.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for output in &outputs {
+        *counts.entry(output.tool_call_id.as_str()).or_default() += 1;
+    }
+
+    let missing: Vec<&str> = calls
+        .iter()
+        .copied()
+        .filter(|call| !counts.contains_key(call))
+        .collect();
+
+    let mut extra: Vec<&str> = counts
+        .keys()
+        .copied()
+        .filter(|id| !calls.contains(id))
+        .collect();
+    extra.sort();
+
+    let mut duplicate: Vec<&str> = counts
+        .iter()
+        .filter(|(id, count)| **count > 1 && calls.contains(id))
+        .map(|(id, _)| *id)
+        .collect();
+    duplicate.sort();
+
+    let problems: Vec<String> = [
+        ("missing", missing),
+        ("unknown", extra),
+        ("duplicate", duplicate),
+    ]
+    .into_iter()
+    .filter(|(_, ids)| !ids.is_empty())
+    .map(|(kind, ids)| format!("{kind} {}", ids.join(", ")))
+    .collect();
+
+    if !problems.is_empty() {
+        return Err(APIError::ValidationError {
+            field: "tool_outputs".to_string(),
+            value: problems.join("; "),
+            allowed: format!(
+                "one output for each function call of {}: [{}]",
+                response.id,
+                calls.join(", ")
+            ),
+        });
+    }
+
+    if parameters.model.is_empty() {
+        parameters.model = response.model.clone();
+    }
+
+    parameters.previous_response_id = Some(response.id.clone());
+    parameters.input = ResponseInput::List(
+        outputs
+            .into_iter()
+            .map(|output| ResponseInputItem::Item(InputItem::FunctionToolCallOutput(output.into())))
+            .collect(),
+    );
+
+    Ok(parameters)
+}
+
+/// A file citation in the output text of a response, with the range of the text it cites.
+struct CitedSpan<'a> {
+    file_id: &'a str,
+    index_range: Range<u64>,
+    text: String,
+}
+
+/// Returns the file citations of the output text in order. A citation cites the text from the previous annotation of the same text,
+/// or the start of the text, up to its index, so citations at the same index cite the same text.
+fn cited_spans(response: &ResponseObject) -> Vec<CitedSpan<'_>> {
+    let texts = response
+        .output
+        .iter()
+        .filter_map(|output| match output {
+            ResponseOutput::Message(message) => Some(&message.content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|content| match content {
+            OutputContent::Text { text, annotations } => Some((text, annotations)),
+            _ => None,
+        });
+
+    let mut spans = vec![];
+
+    for (text, annotations) in texts {
+        let positions: Vec<u64> = annotations
+            .iter()
+            .map(|annotation| match annotation {
+                Annotation::FileCitation { index, .. } | Annotation::FilePath { index, .. } => {
+                    *index
+                }
+                Annotation::URLCitation { end_index, .. } => *end_index,
+            })
+            .collect();
+
+        for annotation in annotations {
+            let Annotation::FileCitation { file_id, index } = annotation else {
+                continue;
+            };
+
+            let start = positions
+                .iter()
+                .copied()
+                .filter(|position| position < index)
+                .max()
+                .unwrap_or_default();
+
+            // the indexes count characters, not bytes
+            let text = text
+                .chars()
+                .skip(start as usize)
+                .take((index - start) as usize)
+                .collect();
+
+            spans.push(CitedSpan {
+                file_id,
+                index_range: start..*index,
+                text,
+            });
+        }
+    }
+
+    spans
+}
+
+/// Returns the chunk that shares the most words with the cited text, the first chunk if none shares any.
+fn best_quote(cited: &str, chunks: &[String]) -> Option<String> {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|character: char| !character.is_alphanumeric())
+            .filter(|word| word.chars().count() > 2)
+            .map(str::to_lowercase)
+            .collect()
+    };
+
+    let cited = words(cited);
+
+    chunks
+        .iter()
+        .enumerate()
+        .max_by_key(|(position, chunk)| {
+            // on a tie the earlier chunk wins
+            (
+                words(chunk).intersection(&cited).count(),
+                std::cmp::Reverse(*position),
+            )
+        })
+        .map(|(_, chunk)| chunk.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::error::APIError;
+    use crate::v1::resources::response::items::ToolOutput;
+    use crate::v1::resources::response::request::{
+        ContentInput, InputMessage, ResponseInclude, ResponseInput, ResponseInputItem,
+        ResponseParameters,
+    };
+    use crate::v1::resources::response::response::{
+        ReasoningSummary, ResponseObject, ResponseReasoning, Role,
+    };
+    use crate::v1::resources::shared::ReasoningEffort;
+
+    fn response_with_calls() -> ResponseObject {
+        let call = |call_id: &str| serde_json::json!({"type": "function_call", "id": format!("fc_{call_id}"), "call_id": call_id, "name": "get_weather", "arguments": "{}", "status": "completed"});
+
+        serde_json::from_value(serde_json::json!({
+            "id": "resp_1",
+            "object": "response",
+            "created_at": 0,
+            "model": "gpt-4o",
+            "status": "completed",
+            "tools": [],
+            "output": [call("call_1"), call("call_2")]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_submit_tool_outputs_answers_the_function_calls() {
+        let client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move {
+                client
+                    .responses()
+                    .submit_tool_outputs(
+                        &response_with_calls(),
+                        vec![
+                            ToolOutput::json("call_2", serde_json::json!({"celsius": 21})),
+                            ToolOutput::text("call_1", "sunny"),
+                        ],
+                        ResponseParameters::default(),
+                    )
+                    .await
+            })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(body) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert_eq!(body["model"], "gpt-4o");
+        assert_eq!(body["previous_response_id"], "resp_1");
+        assert_eq!(
+            body["input"],
+            serde_json::json!([
+                {"type": "function_call_output", "call_id": "call_2", "output": "{\"celsius\":21}", "status": "completed"},
+                {"type": "function_call_output", "call_id": "call_1", "output": "sunny", "status": "completed"}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_tool_outputs_lists_missing_and_unknown_ids() {
+        // nothing listens here, the outputs are checked before anything is sent
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url("http://127.0.0.1:9");
+
+        let error = client
+            .responses()
+            .submit_tool_outputs(
+                &response_with_calls(),
+                vec![
+                    ToolOutput::text("call_1", "sunny"),
+                    ToolOutput::text("call_1", "rainy"),
+                    ToolOutput::text("call_9", "windy"),
+                ],
+                ResponseParameters::default(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            APIError::ValidationError {
+                field: "tool_outputs".to_string(),
+                value: "missing call_2; unknown call_9; duplicate call_1".to_string(),
+                allowed: "one output for each function call of resp_1: [call_1, call_2]"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_reasoning_is_sent_back_unchanged() {
+        // the content is opaque to the client and must be sent back byte for byte
+        let encrypted_content = "gAAAAABo9x+/Kq3Zr1dW0mYv8L2sT4uNcE6hJpQ7iXoRbAfGk==";
+
+        let response: ResponseObject = serde_json::from_value(serde_json::json!({
+            "id": "resp_1",
+            "object": "response",
+            "created_at": 0,
+            "model": "o4-mini",
+            "status": "completed",
+            "tools": [],
+            "output": [
+                {
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [
+                        {"type": "summary_text", "text": "**Checking the forecast**"},
+                        {"type": "summary_text", "text": "It will be sunny."}
+                    ],
+                    "encrypted_content": encrypted_content
+                },
+                {
+                    "type": "message",
+                    "id": "msg_1",
+                    "role": "assistant",
+                    "status": "completed",
+                    "content": [{"type": "output_text", "text": "Sunny, 21°C.", "annotations": []}]
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            response.reasoning_summary_text().as_deref(),
+            Some("**Checking the forecast**\n\nIt will be sunny.")
+        );
+
+        let mut input = response.output_as_input();
+        input.push(response_input_message("And tomorrow?"));
+
+        let parameters = ResponseParameters {
+            model: "o4-mini".to_string(),
+            input: ResponseInput::List(input),
+            store: Some(false),
+            include: Some(vec![ResponseInclude::ReasoningEncryptedContent]),
+            reasoning: Some(ResponseReasoning {
+                effort: Some(ReasoningEffort::Low),
+                summary: Some(ReasoningSummary::Auto),
+            }),
+            ..Default::default()
+        };
+
+        let client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move { client.responses().create(parameters).await })
+            .await
+            .unwrap();
+
+        let PreparedBody::Json(body) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+
+        assert_eq!(
+            body["include"],
+            serde_json::json!(["reasoning.encrypted_content"])
+        );
+        assert_eq!(
+            body["reasoning"],
+            serde_json::json!({"effort": "low", "summary": "auto"})
+        );
+        assert_eq!(body["input"][0]["type"], "reasoning");
+        assert_eq!(body["input"][0]["encrypted_content"], encrypted_content);
+        assert_eq!(
+            body["input"][1],
+            serde_json::json!({"type": "message", "role": "assistant", "content": "Sunny, 21°C."})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_citations_quotes_the_best_chunk_of_each_cited_span() {
+        let result = |file_id: &str, text: &str| serde_json::json!({"file_id": file_id, "filename": format!("{file_id}.txt"), "score": 0.5, "text": text, "attributes": {}});
+        let citation = |file_id: &str, index: u64| serde_json::json!({"type": "file_citation", "file_id": file_id, "index": index});
+
+        let response: ResponseObject = serde_json::from_value(serde_json::json!({
+            "id": "resp_1",
+            "object": "response",
+            "created_at": 0,
+            "model": "gpt-4o",
+            "status": "completed",
+            "tools": [],
+            "output": [
+                {
+                    "type": "file_search_call",
+                    "id": "fs_1",
+                    "queries": ["paris"],
+                    "status": "completed",
+                    "results": [
+                        result("file_a", "Lyon is a city in France."),
+                        result("file_a", "Paris is the capital of France and its largest city."),
+                        result("file_a", "About two million people live in the city of Paris."),
+                        result("file_b", "The population is about two million people."),
+                    ]
+                },
+                {
+                    "type": "message",
+                    "id": "msg_1",
+                    "role": "assistant",
+                    "status": "completed",
+                    "content": [{
+                        "type": "output_text",
+                        "text": "Paris is the capital of France. About two million people live in Paris.",
+                        // two files cite the second sentence
+                        "annotations": [citation("file_a", 31), citation("file_b", 71), citation("file_a", 71)]
+                    }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        // nothing listens here, so the metadata of the files can't be retrieved
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url("http://127.0.0.1:9");
+
+        let citations = client.responses().resolve_citations(&response).await;
+
+        let resolved: Vec<(&str, std::ops::Range<u64>, Option<&str>)> = citations
+            .iter()
+            .map(|citation| {
+                (
+                    citation.file_id.as_str(),
+                    citation.index_range.clone(),
+                    citation.quote.as_deref(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            resolved,
+            vec![
+                (
+                    "file_a",
+                    0..31,
+                    Some("Paris is the capital of France and its largest city.")
+                ),
+                (
+                    "file_b",
+                    31..71,
+                    Some("The population is about two million people.")
+                ),
+                (
+                    "file_a",
+                    31..71,
+                    Some("About two million people live in the city of Paris.")
+                ),
+            ]
+        );
+        assert!(citations
+            .iter()
+            .all(|citation| citation.filename.is_none() && citation.error.is_some()));
+    }
+
+    #[test]
+    fn test_cited_spans_count_characters() {
+        let response: ResponseObject = serde_json::from_value(serde_json::json!({
+            "id": "resp_1",
+            "object": "response",
+            "created_at": 0,
+            "model": "gpt-4o",
+            "status": "completed",
+            "tools": [],
+            "output": [{
+                "type": "message",
+                "id": "msg_1",
+                "role": "assistant",
+                "status": "completed",
+                "content": [{
+                    "type": "output_text",
+                    "text": "Île-de-France. Café au lait.",
+                    "annotations": [
+                        {"type": "file_citation", "file_id": "file_a", "index": 14},
+                        {"type": "url_citation", "title": "Café", "url": "https://example.com", "start_index": 15, "end_index": 20},
+                        {"type": "file_citation", "file_id": "file_b", "index": 28}
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let spans: Vec<(&str, std::ops::Range<u64>, String)> = super::cited_spans(&response)
+            .into_iter()
+            .map(|span| (span.file_id, span.index_range, span.text))
+            .collect();
+
+        assert_eq!(
+            spans,
+            vec![
+                ("file_a", 0..14, "Île-de-France.".to_string()),
+                ("file_b", 20..28, "au lait.".to_string()),
+            ]
+        );
+        assert_eq!(super::best_quote("anything", &[]), None);
+    }
+
+    fn response_input_message(text: &str) -> ResponseInputItem {
+        ResponseInputItem::Message(InputMessage {
+            role: Role::User,
+            content: ContentInput::Text(text.to_string()),
+        })
+    }
 }
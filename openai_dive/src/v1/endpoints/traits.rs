@@ -0,0 +1,374 @@
+//! Object-safe traits of the endpoint groups, so an endpoint can be replaced by a test double or wrapped by a decorator (ie. a cache).
+//!
+//! The endpoint structs (ie. `Chat`) implement them for a borrowed client, `Client` implements them as well,
+//! so `Client::chat_api` can hand out an `Arc<dyn ChatApi>` that doesn't borrow the client.
+//! Streaming is a trait of its own, `ChatStreamApi`, so the traits are the same with and without the `stream` feature.
+use crate::v1::api::Client;
+use crate::v1::endpoints::audio::Audio;
+use crate::v1::endpoints::chat::Chat;
+use crate::v1::endpoints::embeddings::Embeddings;
+use crate::v1::endpoints::models::Models;
+use crate::v1::endpoints::moderations::Moderations;
+#[cfg(feature = "stream")]
+use crate::v1::error::APIError;
+use crate::v1::future::ApiFuture;
+use crate::v1::resources::audio::{
+    AudioSpeechParameters, AudioSpeechResponse, AudioTranscriptionParameters,
+    AudioTranslationParameters,
+};
+#[cfg(feature = "stream")]
+use crate::v1::resources::chat::ChatCompletionChunkResponse;
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponse};
+use crate::v1::resources::embedding::{EmbeddingParameters, EmbeddingResponse};
+use crate::v1::resources::model::{ListModelResponse, Model};
+use crate::v1::resources::moderation::{ModerationParameters, ModerationResponse};
+#[cfg(feature = "stream")]
+use futures::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The stream of chunks of a streamed chat completion.
+#[cfg(feature = "stream")]
+pub type ChatCompletionStream =
+    Pin<Box<dyn Stream<Item = Result<ChatCompletionChunkResponse, APIError>> + Send>>;
+
+/// The chat completion endpoint, see `Chat`.
+pub trait ChatApi: Send + Sync {
+    fn create(&self, parameters: ChatCompletionParameters)
+        -> ApiFuture<'_, ChatCompletionResponse>;
+}
+
+/// The streamed chat completion endpoint, see `Chat::create_stream`.
+#[cfg(feature = "stream")]
+pub trait ChatStreamApi: ChatApi {
+    fn create_stream(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> ApiFuture<'_, ChatCompletionStream>;
+}
+
+/// The embeddings endpoint, see `Embeddings`.
+pub trait EmbeddingsApi: Send + Sync {
+    fn create(&self, parameters: EmbeddingParameters) -> ApiFuture<'_, EmbeddingResponse>;
+}
+
+/// The speech, transcription and translation endpoints, see `Audio`.
+pub trait AudioApi: Send + Sync {
+    fn create_speech(
+        &self,
+        parameters: AudioSpeechParameters,
+    ) -> ApiFuture<'_, AudioSpeechResponse>;
+
+    fn create_transcription(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> ApiFuture<'_, String>;
+
+    fn create_translation(&self, parameters: AudioTranslationParameters) -> ApiFuture<'_, String>;
+}
+
+/// The moderation endpoint, see `Moderations`.
+pub trait ModerationsApi: Send + Sync {
+    fn create(&self, parameters: ModerationParameters) -> ApiFuture<'_, ModerationResponse>;
+}
+
+/// The models endpoint, see `Models`.
+pub trait ModelsApi: Send + Sync {
+    fn list(&self) -> ApiFuture<'_, ListModelResponse>;
+
+    fn retrieve<'a>(&'a self, model_id: &'a str) -> ApiFuture<'a, Model>;
+}
+
+impl ChatApi for Chat<'_> {
+    fn create(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> ApiFuture<'_, ChatCompletionResponse> {
+        Box::pin(Chat::create(self, parameters))
+    }
+}
+
+#[cfg(feature = "stream")]
+impl ChatStreamApi for Chat<'_> {
+    fn create_stream(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> ApiFuture<'_, ChatCompletionStream> {
+        Box::pin(Chat::create_stream(self, parameters))
+    }
+}
+
+impl EmbeddingsApi for Embeddings<'_> {
+    fn create(&self, parameters: EmbeddingParameters) -> ApiFuture<'_, EmbeddingResponse> {
+        Box::pin(Embeddings::create(self, parameters))
+    }
+}
+
+impl AudioApi for Audio<'_> {
+    fn create_speech(
+        &self,
+        parameters: AudioSpeechParameters,
+    ) -> ApiFuture<'_, AudioSpeechResponse> {
+        Box::pin(Audio::create_speech(self, parameters))
+    }
+
+    fn create_transcription(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> ApiFuture<'_, String> {
+        Box::pin(Audio::create_transcription(self, parameters))
+    }
+
+    fn create_translation(&self, parameters: AudioTranslationParameters) -> ApiFuture<'_, String> {
+        Box::pin(Audio::create_translation(self, parameters))
+    }
+}
+
+impl ModerationsApi for Moderations<'_> {
+    fn create(&self, parameters: ModerationParameters) -> ApiFuture<'_, ModerationResponse> {
+        Box::pin(Moderations::create(self, parameters))
+    }
+}
+
+impl ModelsApi for Models<'_> {
+    fn list(&self) -> ApiFuture<'_, ListModelResponse> {
+        Box::pin(Models::list(self))
+    }
+
+    fn retrieve<'a>(&'a self, model_id: &'a str) -> ApiFuture<'a, Model> {
+        Box::pin(Models::retrieve(self, model_id))
+    }
+}
+
+impl ChatApi for Client {
+    fn create(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> ApiFuture<'_, ChatCompletionResponse> {
+        Box::pin(async move { self.chat().create(parameters).await })
+    }
+}
+
+#[cfg(feature = "stream")]
+impl ChatStreamApi for Client {
+    fn create_stream(
+        &self,
+        parameters: ChatCompletionParameters,
+    ) -> ApiFuture<'_, ChatCompletionStream> {
+        Box::pin(async move { self.chat().create_stream(parameters).await })
+    }
+}
+
+impl EmbeddingsApi for Client {
+    fn create(&self, parameters: EmbeddingParameters) -> ApiFuture<'_, EmbeddingResponse> {
+        Box::pin(async move { self.embeddings().create(parameters).await })
+    }
+}
+
+impl AudioApi for Client {
+    fn create_speech(
+        &self,
+        parameters: AudioSpeechParameters,
+    ) -> ApiFuture<'_, AudioSpeechResponse> {
+        Box::pin(async move { self.audio().create_speech(parameters).await })
+    }
+
+    fn create_transcription(
+        &self,
+        parameters: AudioTranscriptionParameters,
+    ) -> ApiFuture<'_, String> {
+        Box::pin(async move { self.audio().create_transcription(parameters).await })
+    }
+
+    fn create_translation(&self, parameters: AudioTranslationParameters) -> ApiFuture<'_, String> {
+        Box::pin(async move { self.audio().create_translation(parameters).await })
+    }
+}
+
+impl ModerationsApi for Client {
+    fn create(&self, parameters: ModerationParameters) -> ApiFuture<'_, ModerationResponse> {
+        Box::pin(async move { self.moderations().create(parameters).await })
+    }
+}
+
+impl ModelsApi for Client {
+    fn list(&self) -> ApiFuture<'_, ListModelResponse> {
+        Box::pin(async move { self.models().list().await })
+    }
+
+    fn retrieve<'a>(&'a self, model_id: &'a str) -> ApiFuture<'a, Model> {
+        Box::pin(async move { self.models().retrieve(model_id).await })
+    }
+}
+
+impl Client {
+    /// Returns the chat endpoint as trait object, with a clone of the client.
+    pub fn chat_api(&self) -> Arc<dyn ChatApi> {
+        Arc::new(self.clone())
+    }
+
+    /// Returns the chat endpoint with streaming as trait object, with a clone of the client.
+    #[cfg(feature = "stream")]
+    pub fn chat_stream_api(&self) -> Arc<dyn ChatStreamApi> {
+        Arc::new(self.clone())
+    }
+
+    /// Returns the embeddings endpoint as trait object, with a clone of the client.
+    pub fn embeddings_api(&self) -> Arc<dyn EmbeddingsApi> {
+        Arc::new(self.clone())
+    }
+
+    /// Returns the audio endpoints as trait object, with a clone of the client.
+    pub fn audio_api(&self) -> Arc<dyn AudioApi> {
+        Arc::new(self.clone())
+    }
+
+    /// Returns the moderation endpoint as trait object, with a clone of the client.
+    pub fn moderations_api(&self) -> Arc<dyn ModerationsApi> {
+        Arc::new(self.clone())
+    }
+
+    /// Returns the models endpoint as trait object, with a clone of the client.
+    pub fn models_api(&self) -> Arc<dyn ModelsApi> {
+        Arc::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::dry_run::PreparedBody;
+    use crate::v1::endpoints::traits::ChatApi;
+    #[cfg(feature = "stream")]
+    use crate::v1::endpoints::traits::{ChatCompletionStream, ChatStreamApi};
+    use crate::v1::future::ApiFuture;
+    use crate::v1::resources::chat::{
+        ChatCompletionParameters, ChatCompletionParametersBuilder, ChatCompletionResponse,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// A test double that answers every request with the same response.
+    struct FakeChat {
+        calls: AtomicUsize,
+    }
+
+    impl ChatApi for FakeChat {
+        fn create(
+            &self,
+            parameters: ChatCompletionParameters,
+        ) -> ApiFuture<'_, ChatCompletionResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            Box::pin(async move {
+                Ok(serde_json::from_value(serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 0,
+                    "model": parameters.model,
+                    "choices": []
+                }))
+                .unwrap())
+            })
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    impl ChatStreamApi for FakeChat {
+        fn create_stream(
+            &self,
+            _parameters: ChatCompletionParameters,
+        ) -> ApiFuture<'_, ChatCompletionStream> {
+            Box::pin(async { Ok(Box::pin(futures::stream::empty()) as ChatCompletionStream) })
+        }
+    }
+
+    /// A decorator that caches the responses by the serialized parameters.
+    struct CachingChat {
+        inner: Arc<dyn ChatApi>,
+        cache: Mutex<HashMap<String, ChatCompletionResponse>>,
+    }
+
+    impl ChatApi for CachingChat {
+        fn create(
+            &self,
+            parameters: ChatCompletionParameters,
+        ) -> ApiFuture<'_, ChatCompletionResponse> {
+            Box::pin(async move {
+                let key = serde_json::to_string(&parameters).unwrap();
+
+                if let Some(response) = self.cache.lock().unwrap().get(&key) {
+                    return Ok(response.clone());
+                }
+
+                let response = self.inner.create(parameters).await?;
+
+                self.cache.lock().unwrap().insert(key, response.clone());
+
+                Ok(response)
+            })
+        }
+    }
+
+    fn parameters() -> ChatCompletionParameters {
+        ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![])
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decorator_wraps_a_test_double() {
+        let fake = Arc::new(FakeChat {
+            calls: AtomicUsize::new(0),
+        });
+        let chat = CachingChat {
+            inner: fake.clone(),
+            cache: Mutex::new(HashMap::new()),
+        };
+
+        chat.create(parameters()).await.unwrap();
+        let response = chat.create(parameters()).await.unwrap();
+
+        assert_eq!(response.model, "gpt-4o");
+        assert_eq!(fake.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_stream_trait_object_also_creates() {
+        use futures::StreamExt;
+
+        let fake = Arc::new(FakeChat {
+            calls: AtomicUsize::new(0),
+        });
+        let chat: Arc<dyn ChatStreamApi> = fake.clone();
+
+        let stream = chat.create_stream(parameters()).await.unwrap();
+        chat.create(parameters()).await.unwrap();
+
+        assert_eq!(stream.count().await, 0);
+        assert_eq!(fake.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_hands_out_the_endpoint_as_trait_object() {
+        let client = Client::new("sk-test".to_string());
+
+        let prepared = client
+            .dry_run()
+            .prepare(|client| async move {
+                let chat: Arc<dyn ChatApi> = client.chat_api();
+
+                chat.create(parameters()).await
+            })
+            .await
+            .unwrap();
+
+        assert!(prepared.url.ends_with("/chat/completions"));
+        assert!(matches!(prepared.body, PreparedBody::Json(body) if body["model"] == "gpt-4o"));
+    }
+}
@@ -4,8 +4,11 @@ use crate::v1::{
     api::Client,
     error::APIError,
     helpers::format_response,
-    resources::upload::{
-        AddPartParameters, CompleteUploadParameters, CreateUploadParameters, Upload, UploadPart,
+    resources::{
+        shared::FileKind,
+        upload::{
+            AddPartParameters, CompleteUploadParameters, CreateUploadParameters, Upload, UploadPart,
+        },
     },
 };
 
@@ -36,14 +39,14 @@ impl Uploads<'_> {
         id: &str,
         parameters: AddPartParameters,
     ) -> Result<UploadPart, APIError> {
-        let mut form = reqwest::multipart::Form::new();
+        let mut form = self.client.multipart_form();
 
-        let file_part = parameters.data.into_part().await?;
+        let file_part = parameters.data.into_part(FileKind::Any).await?;
         form = form.part("data", file_part);
 
         let response = self
             .client
-            .post_with_form(&format!("/uploads/{id}/parts"), form)
+            .post_with_form(&format!("/uploads/{id}/parts"), form.into_form())
             .await?;
 
         let response: UploadPart = format_response(response)?;
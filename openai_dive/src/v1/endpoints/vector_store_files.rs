@@ -4,7 +4,9 @@ use crate::v1::{
     helpers::format_response,
     resources::{
         shared::{DeletedObject, ListParameters, ListResponse},
-        vector_store_file::{CreateVectorStoreFileParameters, VectorStoreFile},
+        vector_store_file::{
+            CreateVectorStoreFileParameters, VectorStoreFile, VectorStoreFileContent,
+        },
     },
 };
 
@@ -91,4 +93,22 @@ impl VectorStoreFiles<'_> {
 
         Ok(response)
     }
+
+    /// Retrieve the parsed contents of a vector store file.
+    pub async fn retrieve_content(
+        &self,
+        vector_store_id: &str,
+        vector_store_file_id: &str,
+    ) -> Result<VectorStoreFileContent, APIError> {
+        let response = self
+            .client
+            .get(&format!(
+                "/vector_stores/{vector_store_id}/files/{vector_store_file_id}/content"
+            ))
+            .await?;
+
+        let response: VectorStoreFileContent = format_response(response)?;
+
+        Ok(response)
+    }
 }
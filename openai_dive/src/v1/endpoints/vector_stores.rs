@@ -10,6 +10,7 @@ use crate::v1::{
         },
     },
 };
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub struct VectorStores<'a> {
     pub client: &'a Client,
@@ -86,7 +87,7 @@ impl VectorStores<'_> {
     pub async fn delete(&self, vector_store_id: &str) -> Result<DeletedObject, APIError> {
         let response = self
             .client
-            .delete(&format!("/threads/{vector_store_id}"))
+            .delete(&format!("/vector_stores/{vector_store_id}"))
             .await?;
 
         let response: DeletedObject = format_response(response)?;
@@ -113,4 +114,170 @@ impl VectorStores<'_> {
 
         Ok(response)
     }
+
+    /// Deletes the vector stores that expired, or were last active, longer than `older_than` ago, ie. in a cleanup job.
+    /// Returns the deleted stores, with `dry_run` the stores that would be deleted, without deleting them.
+    pub async fn cleanup_expired(
+        &self,
+        older_than: Duration,
+        dry_run: bool,
+    ) -> Result<Vec<VectorStore>, APIError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let cutoff = now.saturating_sub(older_than).as_secs();
+
+        let mut stale = vec![];
+        let mut after = None;
+
+        loop {
+            let page = self
+                .list(Some(ListParameters {
+                    limit: Some(100),
+                    order: None,
+                    after,
+                    before: None,
+                }))
+                .await?;
+
+            stale.extend(
+                page.data
+                    .into_iter()
+                    .filter(|vector_store| is_stale(vector_store, cutoff)),
+            );
+
+            match (page.has_more, page.last_id) {
+                (true, Some(last_id)) => after = Some(last_id),
+                _ => break,
+            }
+        }
+
+        if !dry_run {
+            for vector_store in &stale {
+                self.delete(&vector_store.id).await?;
+            }
+        }
+
+        Ok(stale)
+    }
+}
+
+/// Whether the store expired, or without expiration was last active (or created), at or before the cutoff.
+fn is_stale(vector_store: &VectorStore, cutoff: u64) -> bool {
+    let since = vector_store
+        .expires_at
+        .or(vector_store.last_active_at)
+        .unwrap_or(vector_store.created_at);
+
+    since as u64 <= cutoff
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::api::Client;
+    use crate::v1::test_support::{json_response, serve_with, Request};
+    use serde_json::{json, Value};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn vector_store(id: &str, expires_at: Option<u64>, last_active_at: u64) -> Value {
+        json!({
+            "id": id,
+            "object": "vector_store",
+            "created_at": last_active_at - DAY,
+            "name": id,
+            "usage_bytes": 1024,
+            "file_counts": {"in_progress": 0, "completed": 1, "failed": 0, "cancelled": 0, "total": 1},
+            "status": if expires_at.is_some() { "expired" } else { "completed" },
+            "expires_at": expires_at,
+            "last_active_at": last_active_at,
+            "metadata": {}
+        })
+    }
+
+    /// Answers with two pages of stores, or the deletes.
+    fn vector_stores_response(request: &Request, now: u64) -> String {
+        let route = request.route();
+
+        let body = if route.starts_with("DELETE") {
+            let id = route.rsplit('/').next().unwrap();
+
+            json!({"id": id, "object": "vector_store.deleted", "deleted": true})
+        } else if route.contains("after=vs_recent") {
+            json!({
+                "object": "list",
+                "data": [vector_store("vs_idle", None, now - 10 * DAY)],
+                "first_id": "vs_idle",
+                "last_id": "vs_idle",
+                "has_more": false
+            })
+        } else {
+            json!({
+                "object": "list",
+                "data": [
+                    vector_store("vs_expired", Some(now - 30 * DAY), now - 40 * DAY),
+                    vector_store("vs_recent", None, now - 60 * 60),
+                ],
+                "first_id": "vs_expired",
+                "last_id": "vs_recent",
+                "has_more": true
+            })
+        };
+
+        json_response("200 OK", &body.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_expired_lists_all_pages_and_deletes_stale_stores() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // two pages for the dry run, then two pages and two deletes
+        let (base_url, server) =
+            serve_with(6, move |request| vector_stores_response(request, now)).await;
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let older_than = Duration::from_secs(7 * DAY);
+
+        let stale = client
+            .vector_stores()
+            .cleanup_expired(older_than, true)
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = stale.iter().map(|store| store.id.as_str()).collect();
+
+        assert_eq!(ids, vec!["vs_expired", "vs_idle"]);
+        assert_eq!(stale[0].file_counts.completed, 1);
+        assert_eq!(stale[0].usage_bytes, 1024);
+
+        client
+            .vector_stores()
+            .cleanup_expired(older_than, false)
+            .await
+            .unwrap();
+
+        let routes: Vec<String> = server.await.unwrap().iter().map(Request::route).collect();
+
+        assert!(routes[..2].iter().all(|route| route.starts_with("GET")));
+
+        let deletes: Vec<&String> = routes
+            .iter()
+            .filter(|route| route.starts_with("DELETE"))
+            .collect();
+
+        assert_eq!(
+            deletes,
+            vec![
+                "DELETE /vector_stores/vs_expired",
+                "DELETE /vector_stores/vs_idle"
+            ]
+        );
+    }
 }
@@ -1,46 +1,669 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The errors of the client. The errors of a response keep its raw body as `message` (see `body`)
+/// and its `x-request-id` header as `request_id`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub enum APIError {
-    AuthenticationError(String),
-    BadRequestError(String),
-    PermissionError(String),
-    NotFoundError(String),
-    GoneError(String),
-    ServerError(String),
-    InvalidRequestError(String),
-    RateLimitError(String),
+    AuthenticationError {
+        message: String,
+        request_id: Option<String>,
+    },
+    BadRequestError {
+        message: String,
+        request_id: Option<String>,
+    },
+    PermissionError {
+        message: String,
+        request_id: Option<String>,
+    },
+    NotFoundError {
+        message: String,
+        request_id: Option<String>,
+    },
+    GoneError {
+        message: String,
+        request_id: Option<String>,
+    },
+    ServerError {
+        message: String,
+        request_id: Option<String>,
+    },
+    InvalidRequestError {
+        message: String,
+        request_id: Option<String>,
+    },
+    RateLimitError {
+        message: String,
+        /// How long to wait, if the response had the rate limit headers.
+        backpressure: Option<Backpressure>,
+        request_id: Option<String>,
+    },
     ParseError(String),
     FileError(String),
-    StreamError(String),
+    /// An error of a streamed response, see `StreamError` for the kinds.
+    StreamError(StreamError),
     WebSocketError(String),
-    UnknownError(u16, String),
+    /// Returned by a client in dry-run mode instead of sending the request.
+    DryRunError(String),
+    /// The TLS handshake failed, ie. an untrusted server certificate or a rejected client certificate.
+    /// The message contains the underlying errors.
+    TlsError(String),
+    /// The audio of a transcription or translation was too short, OpenAI requires at least 0.1 seconds.
+    /// The message is the raw error of the response, see `body`.
+    AudioTooShort {
+        message: String,
+        request_id: Option<String>,
+    },
+    /// A method of an optional feature was called while the feature is disabled, the text is the name of the feature (ie. `realtime`).
+    FeatureDisabled(String),
+    UnknownError {
+        status: u16,
+        message: String,
+        request_id: Option<String>,
+    },
+    ValidationError {
+        field: String,
+        value: String,
+        allowed: String,
+    },
+    /// A stage of the voice chat pipeline failed, the source is the error of that stage.
+    VoiceChatError {
+        stage: VoiceChatStage,
+        source: Box<APIError>,
+    },
+    /// A chunk of a long transcription failed after its retries, the times are in seconds of the original audio.
+    TranscriptionChunkError {
+        start: f32,
+        end: f32,
+        source: Box<APIError>,
+    },
+}
+
+/// The kinds of errors of a streamed response, the errors of its items.
+/// Only `Transport` and `Timeout` are retried (`APIError::is_retryable`), the other kinds fail again when the request is repeated.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamError {
+    /// The connection failed or dropped while reading the stream.
+    Transport(TransportError),
+    /// The server sent an error instead of the next event, or rejected the stream with an error status.
+    ServerEvent(ErrorBody),
+    /// A chunk couldn't be parsed, the payload is the (redacted) chunk.
+    Parse { payload: String, source: String },
+    /// The response isn't server-sent events, ie. a provider that answers without streaming. The payload is the (redacted) body.
+    NotAnEventStream {
+        content_type: String,
+        payload: String,
+    },
+    /// No data was received in time. A first byte timeout is safe to retry, as nothing was generated yet.
+    Timeout(TimeoutPhase),
+}
+
+/// A connection that failed or dropped, with the underlying error as `source`.
+/// It is serialized as its message, the source is neither serialized nor compared.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub struct TransportError {
+    message: String,
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+impl TransportError {
+    pub fn new(source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            message: source.to_string(),
+            source: Some(Arc::new(source)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPhase {
+    /// No data was received after the connection was accepted.
+    FirstByte,
+    /// No data was received for too long between two chunks of a streamed response.
+    Idle,
+}
+
+/// How long to wait after a rate limit, from the `retry-after-ms`, `retry-after` or `x-ratelimit-reset-*` headers, see `APIError::backpressure`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct Backpressure {
+    pub wait: Duration,
+    /// The limit that was reached.
+    pub scope: BackpressureScope,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressureScope {
+    /// The requests per minute.
+    Requests,
+    /// The tokens per minute.
+    Tokens,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceChatStage {
+    Transcription,
+    Chat,
+    Speech,
 }
 
 impl APIError {
     fn message(&self) -> String {
         match self {
-            APIError::AuthenticationError(message)
-            | APIError::BadRequestError(message)
-            | APIError::PermissionError(message)
-            | APIError::NotFoundError(message)
-            | APIError::GoneError(message)
-            | APIError::ServerError(message)
-            | APIError::InvalidRequestError(message)
-            | APIError::RateLimitError(message)
+            APIError::AuthenticationError { message, .. }
+            | APIError::BadRequestError { message, .. }
+            | APIError::PermissionError { message, .. }
+            | APIError::NotFoundError { message, .. }
+            | APIError::GoneError { message, .. }
+            | APIError::ServerError { message, .. }
+            | APIError::InvalidRequestError { message, .. }
+            | APIError::RateLimitError { message, .. }
+            | APIError::AudioTooShort { message, .. }
             | APIError::ParseError(message)
             | APIError::FileError(message)
-            | APIError::StreamError(message)
-            | APIError::WebSocketError(message) => message.to_string(),
-            APIError::UnknownError(status_code, message) => {
-                format!("{status_code}: {message}")
+            | APIError::WebSocketError(message)
+            | APIError::DryRunError(message)
+            | APIError::TlsError(message) => message.to_string(),
+            APIError::UnknownError {
+                status, message, ..
+            } => {
+                format!("{status}: {message}")
+            }
+            APIError::StreamError(error) => error.to_string(),
+            APIError::FeatureDisabled(feature) => {
+                format!("the `{feature}` feature of openai_dive is disabled")
+            }
+            APIError::ValidationError {
+                field,
+                value,
+                allowed,
+            } => {
+                format!("invalid value {value} for {field}, allowed: {allowed}")
+            }
+            APIError::VoiceChatError { stage, source } => {
+                format!("voice chat {stage} failed: {source}")
+            }
+            APIError::TranscriptionChunkError { start, end, source } => {
+                format!("transcription of {start:.1}s to {end:.1}s failed: {source}")
+            }
+        }
+    }
+
+    /// The request id of an error of a response, `request_id` also looks into the source of an error.
+    fn own_request_id(&self) -> Option<&str> {
+        match self {
+            APIError::AuthenticationError { request_id, .. }
+            | APIError::BadRequestError { request_id, .. }
+            | APIError::PermissionError { request_id, .. }
+            | APIError::NotFoundError { request_id, .. }
+            | APIError::GoneError { request_id, .. }
+            | APIError::ServerError { request_id, .. }
+            | APIError::InvalidRequestError { request_id, .. }
+            | APIError::RateLimitError { request_id, .. }
+            | APIError::AudioTooShort { request_id, .. }
+            | APIError::UnknownError { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl APIError {
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn map_message<F: Fn(&str) -> String>(self, f: F) -> Self {
+        match self {
+            APIError::AuthenticationError {
+                message,
+                request_id,
+            } => APIError::AuthenticationError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::BadRequestError {
+                message,
+                request_id,
+            } => APIError::BadRequestError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::PermissionError {
+                message,
+                request_id,
+            } => APIError::PermissionError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::NotFoundError {
+                message,
+                request_id,
+            } => APIError::NotFoundError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::GoneError {
+                message,
+                request_id,
+            } => APIError::GoneError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::ServerError {
+                message,
+                request_id,
+            } => APIError::ServerError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::InvalidRequestError {
+                message,
+                request_id,
+            } => APIError::InvalidRequestError {
+                message: f(&message),
+                request_id,
+            },
+            APIError::RateLimitError {
+                message,
+                backpressure,
+                request_id,
+            } => APIError::RateLimitError {
+                message: f(&message),
+                backpressure,
+                request_id,
+            },
+            APIError::AudioTooShort {
+                message,
+                request_id,
+            } => APIError::AudioTooShort {
+                message: f(&message),
+                request_id,
+            },
+            APIError::UnknownError {
+                status,
+                message,
+                request_id,
+            } => APIError::UnknownError {
+                status,
+                message: f(&message),
+                request_id,
+            },
+            APIError::ParseError(message) => APIError::ParseError(f(&message)),
+            APIError::FileError(message) => APIError::FileError(f(&message)),
+            APIError::StreamError(error) => APIError::StreamError(match error {
+                StreamError::Transport(error) => StreamError::Transport(TransportError {
+                    message: f(&error.message),
+                    ..error
+                }),
+                StreamError::ServerEvent(body) => StreamError::ServerEvent(ErrorBody {
+                    message: f(&body.message),
+                    ..body
+                }),
+                StreamError::Parse { payload, source } => StreamError::Parse {
+                    payload: f(&payload),
+                    source: f(&source),
+                },
+                StreamError::NotAnEventStream {
+                    content_type,
+                    payload,
+                } => StreamError::NotAnEventStream {
+                    content_type,
+                    payload: f(&payload),
+                },
+                StreamError::Timeout(phase) => StreamError::Timeout(phase),
+            }),
+            APIError::WebSocketError(message) => APIError::WebSocketError(f(&message)),
+            APIError::DryRunError(message) => APIError::DryRunError(f(&message)),
+            APIError::TlsError(message) => APIError::TlsError(f(&message)),
+            APIError::VoiceChatError { stage, source } => APIError::VoiceChatError {
+                stage,
+                source: Box::new(source.map_message(f)),
+            },
+            APIError::TranscriptionChunkError { start, end, source } => {
+                APIError::TranscriptionChunkError {
+                    start,
+                    end,
+                    source: Box::new(source.map_message(f)),
+                }
+            }
+            APIError::ValidationError { .. } | APIError::FeatureDisabled(_) => self,
+        }
+    }
+
+    /// Sets the request id of the failed response (see `request_id`).
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn with_request_id(mut self, request_id: Option<&str>) -> Self {
+        let Some(request_id) = request_id else {
+            return self;
+        };
+
+        match &mut self {
+            APIError::AuthenticationError { request_id: id, .. }
+            | APIError::BadRequestError { request_id: id, .. }
+            | APIError::PermissionError { request_id: id, .. }
+            | APIError::NotFoundError { request_id: id, .. }
+            | APIError::GoneError { request_id: id, .. }
+            | APIError::ServerError { request_id: id, .. }
+            | APIError::InvalidRequestError { request_id: id, .. }
+            | APIError::RateLimitError { request_id: id, .. }
+            | APIError::AudioTooShort { request_id: id, .. }
+            | APIError::UnknownError { request_id: id, .. }
+            | APIError::StreamError(StreamError::ServerEvent(ErrorBody {
+                request_id: id, ..
+            })) => *id = Some(request_id.to_string()),
+            _ => {}
+        }
+
+        self
+    }
+
+    /// Converts the bad request of an audio that is too short into `APIError::AudioTooShort`,
+    /// by the `audio_too_short` code or else by the message. A rejected stream is the error event of the stream.
+    /// Other errors are returned as is.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn into_audio_too_short(self) -> Self {
+        let (body, message, request_id) = match &self {
+            APIError::BadRequestError {
+                message,
+                request_id,
             }
+            | APIError::InvalidRequestError {
+                message,
+                request_id,
+            } => (
+                ErrorBody::parse(message),
+                message.clone(),
+                request_id.clone(),
+            ),
+            APIError::StreamError(StreamError::ServerEvent(body)) => (
+                body.clone(),
+                serde_json::json!({
+                    "error": { "message": body.message, "code": body.code, "param": body.param }
+                })
+                .to_string(),
+                body.request_id.clone(),
+            ),
+            _ => return self,
+        };
+
+        let too_short = match body.code.as_deref() {
+            Some(code) => code == "audio_too_short",
+            None => body
+                .message
+                .to_lowercase()
+                .contains("audio file is too short"),
+        };
+
+        match too_short {
+            true => APIError::AudioTooShort {
+                message,
+                request_id,
+            },
+            false => self,
         }
     }
+
+    /// Returns how long to wait before the request is sent again, the backpressure of a rate limit
+    /// or else an exponential backoff of 500ms doubled with every attempt.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn retry_delay(&self, attempt: u32) -> Duration {
+        self.backpressure()
+            .map(|backpressure| backpressure.wait)
+            .unwrap_or_else(|| crate::v1::clock::backoff(attempt))
+    }
+
+    /// Returns how long to wait before the attempt if the error is retryable, the delay of `clock::retry`.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn retry_after(&self, attempt: u32) -> Option<Duration> {
+        self.is_retryable().then(|| self.retry_delay(attempt))
+    }
 }
 
-impl std::error::Error for APIError {}
+impl APIError {
+    /// Whether the request can be sent again, after a rate limit, a timeout or a server error.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            APIError::RateLimitError { .. }
+            | APIError::ServerError { .. }
+            | APIError::StreamError(StreamError::Transport(_) | StreamError::Timeout(_)) => true,
+            APIError::UnknownError { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Returns how long to wait after a rate limit and which limit was reached, if the response had the headers.
+    pub fn backpressure(&self) -> Option<Backpressure> {
+        match self {
+            APIError::RateLimitError { backpressure, .. } => *backpressure,
+            APIError::VoiceChatError { source, .. }
+            | APIError::TranscriptionChunkError { source, .. } => source.backpressure(),
+            _ => None,
+        }
+    }
+
+    /// Returns the parameter that caused the error, if the API named one.
+    pub fn param(&self) -> Option<String> {
+        self.body()?.param
+    }
+
+    /// Returns the `x-request-id` of the failed response, if the server sent one.
+    pub fn request_id(&self) -> Option<String> {
+        match self {
+            APIError::StreamError(StreamError::ServerEvent(body)) => body.request_id.clone(),
+            APIError::VoiceChatError { source, .. }
+            | APIError::TranscriptionChunkError { source, .. } => source.request_id(),
+            error => error
+                .own_request_id()
+                .map(|request_id| request_id.to_string()),
+        }
+    }
+
+    /// Parses the response body of an API error, None if the error was not returned by the API.
+    pub fn body(&self) -> Option<ErrorBody> {
+        match self {
+            APIError::AuthenticationError { message, .. }
+            | APIError::BadRequestError { message, .. }
+            | APIError::PermissionError { message, .. }
+            | APIError::NotFoundError { message, .. }
+            | APIError::GoneError { message, .. }
+            | APIError::InvalidRequestError { message, .. }
+            | APIError::RateLimitError { message, .. }
+            | APIError::AudioTooShort { message, .. }
+            | APIError::UnknownError { message, .. } => Some(ErrorBody {
+                request_id: self
+                    .own_request_id()
+                    .map(|request_id| request_id.to_string()),
+                ..ErrorBody::parse(message)
+            }),
+            APIError::StreamError(StreamError::ServerEvent(body)) => Some(body.clone()),
+            APIError::VoiceChatError { source, .. }
+            | APIError::TranscriptionChunkError { source, .. } => source.body(),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for APIError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            APIError::StreamError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorBody {
+    /// The error message, or the raw body when it has an unknown shape.
+    pub message: String,
+    /// The error code, if any.
+    pub code: Option<String>,
+    /// The parameter that caused the error, ie. `speed` or `messages[1].content`.
+    #[serde(default)]
+    pub param: Option<String>,
+    /// The shape of the body, which shows what kind of server returned the error.
+    pub shape: ErrorShape,
+    /// The `x-request-id` header of the response, to quote when contacting support.
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorShape {
+    /// `{"error": {"message": "..."}}`, returned by OpenAI and most compatible servers.
+    OpenAI,
+    /// `{"error": {"message": "...", "innererror": {...}}}`, returned by Azure and gateways in front of it.
+    InnerError,
+    /// `{"error": "..."}`.
+    ErrorString,
+    /// `{"detail": "..."}` or a list of validation errors, returned by FastAPI servers such as vLLM.
+    Detail,
+    /// `{"message": "..."}`.
+    Message,
+    /// Any other body, ie. plain text returned by llama.cpp.
+    PlainText,
+}
+
+impl ErrorBody {
+    /// Parses an error body, trying the OpenAI shape first and falling back to the raw text.
+    pub fn parse(text: &str) -> Self {
+        let value: serde_json::Value = match serde_json::from_str(text) {
+            Ok(value) => value,
+            Err(_) => return Self::plain_text(text),
+        };
+
+        let string = |value: &serde_json::Value| match value {
+            serde_json::Value::String(string) => Some(string.to_string()),
+            serde_json::Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        };
+
+        if let Some(error) = value.get("error") {
+            if let Some(message) = error.get("message").and_then(string) {
+                let (code, shape) = match error.get("innererror") {
+                    Some(inner_error) => (
+                        inner_error
+                            .get("code")
+                            .and_then(string)
+                            .or_else(|| error.get("code").and_then(string)),
+                        ErrorShape::InnerError,
+                    ),
+                    None => (error.get("code").and_then(string), ErrorShape::OpenAI),
+                };
+
+                return Self {
+                    message,
+                    code,
+                    param: error.get("param").and_then(string),
+                    shape,
+                    request_id: None,
+                };
+            }
+
+            if let Some(message) = error.as_str() {
+                return Self {
+                    message: message.to_string(),
+                    code: None,
+                    param: None,
+                    shape: ErrorShape::ErrorString,
+                    request_id: None,
+                };
+            }
+        }
+
+        if let Some(detail) = value.get("detail") {
+            // the location of a single validation error, without the leading "body"
+            let param = match detail {
+                serde_json::Value::Array(errors) if errors.len() == 1 => errors[0]
+                    .get("loc")
+                    .and_then(|location| location.as_array())
+                    .map(|location| {
+                        location
+                            .iter()
+                            .skip_while(|part| part.as_str() == Some("body"))
+                            .filter_map(string)
+                            .collect::<Vec<String>>()
+                            .join(".")
+                    })
+                    .filter(|param| !param.is_empty()),
+                _ => None,
+            };
+
+            let message = match detail {
+                serde_json::Value::Array(errors) => errors
+                    .iter()
+                    .map(|error| {
+                        let location = error
+                            .get("loc")
+                            .and_then(|location| location.as_array())
+                            .map(|location| {
+                                location
+                                    .iter()
+                                    .filter_map(string)
+                                    .collect::<Vec<String>>()
+                                    .join(".")
+                            });
+
+                        let message = error
+                            .get("msg")
+                            .and_then(string)
+                            .unwrap_or_else(|| error.to_string());
+
+                        match location {
+                            Some(location) => format!("{location}: {message}"),
+                            None => message,
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join("; "),
+                _ => string(detail).unwrap_or_else(|| detail.to_string()),
+            };
+
+            return Self {
+                message,
+                code: None,
+                param,
+                shape: ErrorShape::Detail,
+                request_id: None,
+            };
+        }
+
+        if let Some(message) = value.get("message").and_then(string) {
+            return Self {
+                message,
+                code: value.get("code").and_then(string),
+                param: value.get("param").and_then(string),
+                shape: ErrorShape::Message,
+                request_id: None,
+            };
+        }
+
+        Self::plain_text(text)
+    }
+
+    fn plain_text(text: &str) -> Self {
+        Self {
+            message: text.trim().to_string(),
+            code: None,
+            param: None,
+            shape: ErrorShape::PlainText,
+            request_id: None,
+        }
+    }
+
+    /// Returns the field of the parameters (and their builder) to fix for the `param` of the error,
+    /// the top level field of a nested parameter, ie. `messages` for `messages[1].content`.
+    pub fn suggested_field(&self) -> Option<&str> {
+        let field = self.param.as_deref()?.split(['.', '[']).next()?;
+
+        (!field.is_empty()).then_some(field)
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InvalidRequestError {
@@ -52,7 +675,120 @@ pub struct InvalidRequestError {
 
 impl Display for APIError {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{}", self.message())
+        write!(f, "{}", self.message())?;
+
+        if let APIError::RateLimitError {
+            backpressure: Some(Backpressure { wait, scope }),
+            ..
+        } = self
+        {
+            let scope = match scope {
+                BackpressureScope::Requests => "requests",
+                BackpressureScope::Tokens => "tokens",
+            };
+
+            write!(f, " (retry after {}ms for {scope})", wait.as_millis())?;
+        }
+
+        match self.own_request_id() {
+            Some(request_id) => write!(f, " (request id: {request_id})"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<StreamError> for APIError {
+    fn from(error: StreamError) -> Self {
+        APIError::StreamError(error)
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Transport(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for TransportError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            source: None,
+        }
+    }
+}
+
+impl From<TransportError> for String {
+    fn from(error: TransportError) -> Self {
+        error.message
+    }
+}
+
+impl PartialEq for TransportError {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+    }
+}
+
+impl Display for TransportError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            StreamError::Transport(message) => write!(f, "stream transport error: {message}"),
+            StreamError::ServerEvent(body) => {
+                match &body.code {
+                    Some(code) => write!(f, "stream error event: {code} {}", body.message)?,
+                    None => write!(f, "stream error event: {}", body.message)?,
+                }
+
+                match &body.request_id {
+                    Some(request_id) => write!(f, " (request id: {request_id})"),
+                    None => Ok(()),
+                }
+            }
+            StreamError::Parse { payload, source } => {
+                write!(f, "failed to parse stream chunk: {source} {payload}")
+            }
+            StreamError::NotAnEventStream {
+                content_type,
+                payload,
+            } => write!(
+                f,
+                "the response isn't an event stream, content type {content_type:?}: {payload}"
+            ),
+            StreamError::Timeout(TimeoutPhase::FirstByte) => {
+                write!(f, "stream timed out waiting for the first byte")
+            }
+            StreamError::Timeout(TimeoutPhase::Idle) => {
+                write!(f, "stream timed out waiting for the next chunk")
+            }
+        }
+    }
+}
+
+impl Display for VoiceChatStage {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            VoiceChatStage::Transcription => write!(f, "transcription"),
+            VoiceChatStage::Chat => write!(f, "chat"),
+            VoiceChatStage::Speech => write!(f, "speech"),
+        }
     }
 }
 
@@ -65,6 +801,204 @@ impl Display for InvalidRequestError {
 #[cfg(feature = "realtime")]
 impl From<reqwest_websocket::Error> for APIError {
     fn from(error: reqwest_websocket::Error) -> Self {
+        if let Some(error) = tls_error(&error) {
+            return error;
+        }
+
         APIError::WebSocketError(error.to_string())
     }
 }
+
+/// Returns `TlsError` with the messages of all sources if one of the sources is a rustls error, ie. a failed handshake.
+#[cfg(any(feature = "rustls-tls", feature = "realtime"))]
+pub(crate) fn tls_error(error: &(dyn std::error::Error + 'static)) -> Option<APIError> {
+    let mut messages = vec![error.to_string()];
+    let mut is_tls = false;
+    let mut source = wrapped_error(error);
+
+    while let Some(error) = source {
+        messages.push(error.to_string());
+        is_tls |= error.is::<rustls::Error>();
+        source = wrapped_error(error);
+    }
+
+    is_tls.then(|| APIError::TlsError(messages.join(": ")))
+}
+
+/// The source of an error, the source of an `io::Error` is the source of the wrapped error instead of the wrapped error itself.
+#[cfg(any(feature = "rustls-tls", feature = "realtime"))]
+fn wrapped_error<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a (dyn std::error::Error + 'static)> {
+    match error.downcast_ref::<std::io::Error>() {
+        Some(error) => error.get_ref().map(|error| error as _),
+        None => error.source(),
+    }
+}
+
+#[cfg(all(
+    feature = "reqwest",
+    not(any(feature = "rustls-tls", feature = "realtime"))
+))]
+pub(crate) fn tls_error(_error: &(dyn std::error::Error + 'static)) -> Option<APIError> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::error::{APIError, ErrorBody, ErrorShape};
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_tls_errors_are_detected_by_type() {
+        use crate::v1::error::tls_error;
+        use std::io::{Error, ErrorKind};
+
+        let handshake = Error::other(Error::new(
+            ErrorKind::InvalidData,
+            rustls::Error::AlertReceived(rustls::AlertDescription::CertificateRequired),
+        ));
+        let error = Error::other(handshake);
+
+        assert!(matches!(
+            tls_error(&error),
+            Some(APIError::TlsError(message)) if message.contains("CertificateRequired")
+        ));
+
+        let error = Error::other(Error::new(
+            ErrorKind::NotFound,
+            "certificate file not found",
+        ));
+
+        assert!(tls_error(&error).is_none());
+    }
+
+    #[test]
+    fn test_parse_openai_error_body() {
+        let body = ErrorBody::parse(
+            r#"{"error":{"message":"Incorrect API key provided: sk-abc.","type":"invalid_request_error","param":null,"code":"invalid_api_key"}}"#,
+        );
+
+        assert_eq!(body.shape, ErrorShape::OpenAI);
+        assert_eq!(body.message, "Incorrect API key provided: sk-abc.");
+        assert_eq!(body.code.as_deref(), Some("invalid_api_key"));
+    }
+
+    #[test]
+    fn test_parse_vllm_error_bodies() {
+        let body = ErrorBody::parse(
+            r#"{"detail":[{"type":"missing","loc":["body","messages"],"msg":"Field required","input":{"model":"meta-llama/Llama-3.1-8B-Instruct"}}]}"#,
+        );
+
+        assert_eq!(body.shape, ErrorShape::Detail);
+        assert_eq!(body.message, "body.messages: Field required");
+
+        let body = ErrorBody::parse(r#"{"detail":"Not Found"}"#);
+
+        assert_eq!(body.shape, ErrorShape::Detail);
+        assert_eq!(body.message, "Not Found");
+    }
+
+    #[test]
+    fn test_parse_llama_cpp_error_bodies() {
+        let error = APIError::NotFoundError {
+            message: "File Not Found".to_string(),
+            request_id: None,
+        };
+        let body = error.body().unwrap();
+
+        assert_eq!(body.shape, ErrorShape::PlainText);
+        assert_eq!(body.message, "File Not Found");
+
+        let body = ErrorBody::parse(
+            r#"{"error":{"code":503,"message":"Loading model","type":"unavailable_error"}}"#,
+        );
+
+        assert_eq!(body.shape, ErrorShape::OpenAI);
+        assert_eq!(body.code.as_deref(), Some("503"));
+    }
+
+    #[test]
+    fn test_parse_gateway_error_body() {
+        let body = ErrorBody::parse(
+            r#"{"error":{"code":"content_filter","message":"The response was filtered due to the prompt triggering the content management policy.","param":"prompt","status":400,"innererror":{"code":"ResponsibleAIPolicyViolation","content_filter_result":{"hate":{"filtered":true,"severity":"high"}}}}}"#,
+        );
+
+        assert_eq!(body.shape, ErrorShape::InnerError);
+        assert_eq!(body.code.as_deref(), Some("ResponsibleAIPolicyViolation"));
+        assert!(body.message.starts_with("The response was filtered"));
+    }
+
+    #[test]
+    fn test_error_param_suggests_the_field_to_fix() {
+        let error = APIError::BadRequestError {
+            message: r#"{"error":{"message":"Invalid value for 'speed': must be between 0.25 and 4.0.","type":"invalid_request_error","param":"speed","code":"invalid_value"}}"#.to_string(),
+            request_id: None,
+        };
+
+        assert_eq!(error.param().as_deref(), Some("speed"));
+        assert_eq!(error.body().unwrap().suggested_field(), Some("speed"));
+
+        let body = ErrorBody::parse(
+            r#"{"error":{"message":"Invalid type for 'messages[1].content'.","type":"invalid_request_error","param":"messages[1].content","code":"invalid_type"}}"#,
+        );
+
+        assert_eq!(body.param.as_deref(), Some("messages[1].content"));
+        assert_eq!(body.suggested_field(), Some("messages"));
+
+        let body = ErrorBody::parse(
+            r#"{"detail":[{"type":"missing","loc":["body","messages"],"msg":"Field required"}]}"#,
+        );
+
+        assert_eq!(body.suggested_field(), Some("messages"));
+        assert_eq!(APIError::ParseError("invalid".to_string()).param(), None);
+    }
+
+    #[tokio::test]
+    async fn test_methods_of_disabled_features_return_feature_disabled() {
+        let disabled = |feature: &str| APIError::FeatureDisabled(feature.to_string());
+
+        #[cfg(not(feature = "image-processing"))]
+        {
+            use crate::v1::resources::chat::ChatMessageContentPart;
+            use crate::v1::resources::image_input::ImagePolicy;
+
+            let png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x10\0\0\0\x10";
+
+            assert_eq!(
+                ChatMessageContentPart::image_auto(png, ImagePolicy::default()),
+                Err(disabled("image-processing"))
+            );
+        }
+
+        #[cfg(not(feature = "audio-processing"))]
+        {
+            use crate::v1::audio_chunking::{probe_duration, trim_silence};
+            use crate::v1::resources::audio::AudioSpeechResponseFormat;
+
+            assert_eq!(
+                trim_silence(&[], AudioSpeechResponseFormat::Pcm, -40.0),
+                Err(disabled("audio-processing"))
+            );
+            assert_eq!(
+                probe_duration("audio.wav"),
+                Err(disabled("audio-processing"))
+            );
+        }
+
+        #[cfg(all(feature = "reqwest", not(feature = "realtime")))]
+        {
+            let client = crate::v1::api::Client::new("sk-test".to_string());
+
+            assert!(matches!(
+                client.realtime().audio_session("gpt-realtime").await,
+                Err(APIError::FeatureDisabled(feature)) if feature == "realtime"
+            ));
+        }
+
+        assert_eq!(
+            disabled("realtime").to_string(),
+            "the `realtime` feature of openai_dive is disabled"
+        );
+    }
+}
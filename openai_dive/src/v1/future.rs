@@ -0,0 +1,7 @@
+//! The boxed future returned by the methods of the object-safe traits, ie. `ChatApi`, `ConversationStore` and `VectorSink`.
+use crate::v1::error::APIError;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A boxed future of a result, the error is an `APIError` unless given.
+pub type ApiFuture<'a, T, E = APIError> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
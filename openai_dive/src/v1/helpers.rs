@@ -1,7 +1,15 @@
-use crate::v1::error::APIError;
+use crate::v1::error::{tls_error, APIError, Backpressure, BackpressureScope};
+use crate::v1::resources::shared::MultipartOrder;
+use reqwest::header::HeaderMap;
+use reqwest::multipart::{Form, Part};
 use reqwest::{Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
 #[cfg(feature = "download")]
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -10,41 +18,274 @@ pub(crate) async fn check_status_code(
 ) -> Result<Response, APIError> {
     match result {
         Ok(response) => {
-            if response.status().is_client_error() {
-                let status = response.status();
-                let text = response
-                    .text()
-                    .await
-                    .map_err(|error| APIError::ParseError(error.to_string()))?;
-
-                match status {
-                    StatusCode::BAD_REQUEST => {
-                        return Err(APIError::BadRequestError(text));
-                    }
-                    StatusCode::UNAUTHORIZED => {
-                        return Err(APIError::AuthenticationError(text));
-                    }
-                    StatusCode::FORBIDDEN => {
-                        return Err(APIError::PermissionError(text));
-                    }
-                    StatusCode::NOT_FOUND => {
-                        return Err(APIError::NotFoundError(text));
-                    }
-                    StatusCode::GONE => {
-                        return Err(APIError::GoneError(text));
-                    }
-                    StatusCode::TOO_MANY_REQUESTS => {
-                        return Err(APIError::RateLimitError(text));
-                    }
-                    _ => {
-                        return Err(APIError::UnknownError(status.as_u16(), text));
-                    }
-                }
+            let status = response.status();
+
+            if !status.is_client_error() && !status.is_server_error() {
+                return Ok(response);
+            }
+
+            let request_id = request_id(response.headers());
+            let backpressure = backpressure(response.headers());
+            let message = response
+                .text()
+                .await
+                .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+            let error = match status {
+                StatusCode::BAD_REQUEST => APIError::BadRequestError {
+                    message,
+                    request_id,
+                },
+                StatusCode::UNAUTHORIZED => APIError::AuthenticationError {
+                    message,
+                    request_id,
+                },
+                StatusCode::FORBIDDEN => APIError::PermissionError {
+                    message,
+                    request_id,
+                },
+                StatusCode::NOT_FOUND => APIError::NotFoundError {
+                    message,
+                    request_id,
+                },
+                StatusCode::GONE => APIError::GoneError {
+                    message,
+                    request_id,
+                },
+                StatusCode::TOO_MANY_REQUESTS => APIError::RateLimitError {
+                    message,
+                    backpressure,
+                    request_id,
+                },
+                _ => APIError::UnknownError {
+                    status: status.as_u16(),
+                    message,
+                    request_id,
+                },
+            };
+
+            Err(error)
+        }
+        Err(error) => Err(send_error(error)),
+    }
+}
+
+/// Reads the backpressure of a rate limit from the headers: the wait of `retry-after-ms` (milliseconds), `retry-after` (seconds)
+/// or else the `x-ratelimit-reset-*` of the limit that was reached (ie. `6m12s`). The limit with no remaining tokens is the tokens per minute.
+pub(crate) fn backpressure(headers: &HeaderMap) -> Option<Backpressure> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    let scope = match (
+        header("x-ratelimit-remaining-requests"),
+        header("x-ratelimit-remaining-tokens"),
+    ) {
+        (Some("0"), _) => BackpressureScope::Requests,
+        (_, Some("0")) => BackpressureScope::Tokens,
+        _ => BackpressureScope::Requests,
+    };
+
+    let reset = match scope {
+        BackpressureScope::Requests => "x-ratelimit-reset-requests",
+        BackpressureScope::Tokens => "x-ratelimit-reset-tokens",
+    };
+
+    // a header that can't be parsed, is negative or too large falls through to the next one
+    let wait = header("retry-after-ms")
+        .and_then(|milliseconds| milliseconds.trim().parse::<f64>().ok())
+        .and_then(|milliseconds| Duration::try_from_secs_f64(milliseconds / 1000.0).ok())
+        .or_else(|| {
+            header("retry-after")
+                .and_then(|seconds| seconds.trim().parse::<f64>().ok())
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        })
+        .or_else(|| header(reset).and_then(parse_reset_duration))?;
+
+    Some(Backpressure { wait, scope })
+}
+
+/// Parses the duration of a `x-ratelimit-reset-*` header, ie. `6m12s`, `1.5s` or `20ms`.
+fn parse_reset_duration(text: &str) -> Option<Duration> {
+    let mut rest = text.trim();
+    let mut seconds = 0.0;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let split = rest
+            .find(|character: char| !character.is_ascii_digit() && character != '.')
+            .filter(|split| *split > 0)?;
+        let (number, unit) = rest.split_at(split);
+        let number: f64 = number.parse().ok()?;
+
+        let (factor, length) = match unit {
+            unit if unit.starts_with("ms") => (0.001, 2),
+            unit if unit.starts_with('h') => (3600.0, 1),
+            unit if unit.starts_with('m') => (60.0, 1),
+            unit if unit.starts_with('s') => (1.0, 1),
+            _ => return None,
+        };
+
+        seconds += number * factor;
+        rest = &unit[length..];
+    }
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Returns the `x-request-id` header of a response, the id of the request at the provider.
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// Flattens a value into form fields in bracket notation, ie. `{"a": {"b": 1}}` into `a[b]=1`.
+/// Arrays of scalars repeat the field as `key[]`, other arrays are indexed as `key[0]`.
+/// Strings are sent without quotes, null values are skipped.
+pub(crate) fn bracket_form_fields(key: String, value: Value, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Null => {}
+        Value::String(text) => fields.push((key, text)),
+        Value::Object(map) => {
+            for (child, value) in map {
+                bracket_form_fields(format!("{key}[{child}]"), value, fields);
             }
+        }
+        Value::Array(items) => {
+            let scalars = items
+                .iter()
+                .all(|item| !item.is_object() && !item.is_array());
+
+            for (index, item) in items.into_iter().enumerate() {
+                let key = match scalars {
+                    true => format!("{key}[]"),
+                    false => format!("{key}[{index}]"),
+                };
 
-            Ok(response)
+                bracket_form_fields(key, item, fields);
+            }
         }
-        Err(error) => Err(APIError::ServerError(error.to_string())),
+        value => fields.push((key, value.to_string())),
+    }
+}
+
+/// Collects the fields of a multipart request, so the file parts can be placed according to the `MultipartOrder`.
+/// Within the file parts and the text fields the order they were added in is kept.
+pub(crate) struct MultipartForm {
+    order: MultipartOrder,
+    parts: Vec<(String, Part)>,
+    texts: Vec<(String, String)>,
+}
+
+impl MultipartForm {
+    pub(crate) fn new(order: MultipartOrder) -> Self {
+        Self {
+            order,
+            parts: vec![],
+            texts: vec![],
+        }
+    }
+
+    pub(crate) fn part(mut self, name: impl Into<String>, part: Part) -> Self {
+        self.parts.push((name.into(), part));
+
+        self
+    }
+
+    pub(crate) fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.texts.push((name.into(), value.into()));
+
+        self
+    }
+
+    /// Renames the fields that are in the map, other fields keep their name.
+    pub(crate) fn rename_fields(mut self, names: &HashMap<&str, &str>) -> Self {
+        let rename = |name: &mut String| {
+            if let Some(renamed) = names.get(name.as_str()) {
+                *name = renamed.to_string();
+            }
+        };
+
+        self.parts.iter_mut().for_each(|(name, _)| rename(name));
+        self.texts.iter_mut().for_each(|(name, _)| rename(name));
+
+        self
+    }
+
+    pub(crate) fn into_form(self) -> Form {
+        let mut form = Form::new();
+
+        if self.order == MultipartOrder::FileLast {
+            for (name, value) in self.texts {
+                form = form.text(name, value);
+            }
+
+            for (name, part) in self.parts {
+                form = form.part(name, part);
+            }
+
+            return form;
+        }
+
+        for (name, part) in self.parts {
+            form = form.part(name, part);
+        }
+
+        for (name, value) in self.texts {
+            form = form.text(name, value);
+        }
+
+        form
+    }
+}
+
+/// Maps an error of sending a request, a failed TLS handshake is returned as `TlsError`.
+pub(crate) fn send_error(error: reqwest::Error) -> APIError {
+    file_read_error(&error)
+        .or_else(|| tls_error(&error))
+        .unwrap_or_else(|| APIError::ServerError {
+            message: error.to_string(),
+            request_id: None,
+        })
+}
+
+/// Returns `FileError` if the request was aborted because a streamed file couldn't be read.
+pub(crate) fn file_read_error(error: &reqwest::Error) -> Option<APIError> {
+    let mut source = std::error::Error::source(error);
+
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<FileReadError>() {
+            return Some(APIError::FileError(error.to_string()));
+        }
+
+        source = error.source();
+    }
+
+    None
+}
+
+/// An error reading a file while its upload is streamed, ie. a disk read error.
+#[derive(Debug)]
+pub(crate) struct FileReadError(std::io::Error);
+
+impl From<std::io::Error> for FileReadError {
+    fn from(error: std::io::Error) -> Self {
+        Self(error)
+    }
+}
+
+impl std::fmt::Display for FileReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to read the uploaded file: {}", self.0)
+    }
+}
+
+impl std::error::Error for FileReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
     }
 }
 
@@ -54,7 +295,10 @@ pub(crate) fn validate_response(response: String) -> Result<Value, APIError> {
 
     if let Some(object) = value.as_object() {
         if object.len() == 1 && object.contains_key("error") {
-            return Err(APIError::InvalidRequestError(value["error"].to_string()));
+            return Err(APIError::InvalidRequestError {
+                message: value["error"].to_string(),
+                request_id: None,
+            });
         }
     }
 
@@ -70,6 +314,64 @@ pub(crate) fn format_response<R: DeserializeOwned>(response: String) -> Result<R
     Ok(value)
 }
 
+/// Deserializes the response straight into the typed struct, without a `Value` in between.
+/// Only if that fails the response is checked for an error object, as in `validate_response`.
+pub(crate) fn parse_response<R: DeserializeOwned>(response: &str) -> Result<R, APIError> {
+    serde_json::from_str(response).or_else(|error| {
+        validate_response(response.to_string())?;
+
+        Err(APIError::ParseError(error.to_string()))
+    })
+}
+
+/// Runs the futures with at most `concurrency` at a time and returns their outputs in the order of the futures.
+/// Like `futures::StreamExt::buffered`, which is only available with the streaming features.
+pub(crate) async fn buffered<F: Future>(futures: Vec<F>, concurrency: usize) -> Vec<F::Output> {
+    let mut pending: Vec<Option<Pin<Box<F>>>> = futures
+        .into_iter()
+        .map(|future| Some(Box::pin(future)))
+        .collect();
+    let mut outputs: Vec<Option<F::Output>> = pending.iter().map(|_| None).collect();
+    let mut running: Vec<usize> = vec![];
+    let mut next = 0;
+
+    std::future::poll_fn(|cx| loop {
+        while running.len() < concurrency.max(1) && next < pending.len() {
+            running.push(next);
+            next += 1;
+        }
+
+        let polled = running.len();
+
+        running.retain(|&index| {
+            let Some(future) = pending[index].as_mut() else {
+                return false;
+            };
+
+            match future.as_mut().poll(cx) {
+                Poll::Ready(output) => {
+                    outputs[index] = Some(output);
+                    pending[index] = None;
+                    false
+                }
+                Poll::Pending => true,
+            }
+        });
+
+        if running.is_empty() && next >= pending.len() {
+            return Poll::Ready(());
+        }
+
+        // start the next futures in the slots that became free
+        if running.len() == polled {
+            return Poll::Pending;
+        }
+    })
+    .await;
+
+    outputs.into_iter().flatten().collect()
+}
+
 #[cfg(feature = "download")]
 pub fn generate_file_name(path: &str, length: u32, file_type: &str) -> String {
     const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
@@ -90,3 +392,52 @@ pub fn generate_file_name(path: &str, length: u32, file_type: &str) -> String {
 
     format!("{path}/{random_str}.{file_type}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::backpressure;
+    use reqwest::header::HeaderMap;
+    use std::time::Duration;
+
+    fn wait(headers: &[(&'static str, &'static str)]) -> Option<Duration> {
+        let mut header_map = HeaderMap::new();
+
+        for (name, value) in headers {
+            header_map.insert(*name, value.parse().unwrap());
+        }
+
+        backpressure(&header_map).map(|backpressure| backpressure.wait)
+    }
+
+    #[test]
+    fn test_backpressure_ignores_waits_that_overflow() {
+        assert_eq!(wait(&[("retry-after", "1e30")]), None);
+        assert_eq!(
+            wait(&[("x-ratelimit-reset-requests", "99999999999999999999h")]),
+            None
+        );
+        assert_eq!(
+            wait(&[
+                ("retry-after", "1e30"),
+                ("x-ratelimit-reset-requests", "6m12s")
+            ]),
+            Some(Duration::from_secs(372))
+        );
+    }
+
+    #[test]
+    fn test_backpressure_falls_back_to_the_next_header() {
+        assert_eq!(
+            wait(&[("retry-after-ms", "-5"), ("retry-after", "2")]),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            wait(&[("retry-after-ms", "soon"), ("retry-after", "1.5")]),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            wait(&[("retry-after-ms", "250"), ("retry-after", "2")]),
+            Some(Duration::from_millis(250))
+        );
+    }
+}
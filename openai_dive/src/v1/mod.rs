@@ -1,9 +1,33 @@
+pub mod adapters;
 #[cfg(feature = "reqwest")]
 pub mod api;
+pub mod audio_chunking;
+#[cfg(feature = "reqwest")]
+pub mod backend;
+#[cfg(feature = "reqwest")]
+pub mod cache_lint;
+#[cfg(feature = "reqwest")]
+pub mod clock;
+pub(crate) mod content_hash;
+#[cfg(feature = "reqwest")]
+pub mod conversation;
+#[cfg(feature = "reqwest")]
+pub mod dry_run;
 #[cfg(feature = "reqwest")]
 pub mod endpoints;
 pub mod error;
+pub mod future;
 #[cfg(feature = "reqwest")]
 pub mod helpers;
 pub mod models;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod paths;
+pub mod profiles;
 pub mod resources;
+#[cfg(all(test, feature = "reqwest"))]
+pub(crate) mod test_support;
+#[cfg(feature = "reqwest")]
+pub mod token_budget;
+pub mod validation;
+pub mod vector_sink;
@@ -0,0 +1,338 @@
+//! Mapping of chat completions to the OpenTelemetry GenAI semantic conventions.
+//!
+//! The attributes convert into `opentelemetry::KeyValue` with `From`, ie. to set them on a span with `span.set_attributes`.
+//! For a streamed response, collect the chunks with a `ChatCompletionAccumulator` and pass `accumulator.finish()` as the response.
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatCompletionResponse, ChatMessage};
+use crate::v1::resources::shared::{FinishReason, Usage};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyValue {
+    /// The attribute key, ie. `gen_ai.request.model`.
+    pub key: &'static str,
+    /// The attribute value.
+    pub value: AttributeValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    I64(i64),
+    F64(f64),
+    StringArray(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// The event name, ie. `gen_ai.user.message`.
+    pub name: &'static str,
+    /// The event attributes.
+    pub attributes: Vec<KeyValue>,
+}
+
+impl KeyValue {
+    fn new(key: &'static str, value: AttributeValue) -> Self {
+        Self { key, value }
+    }
+}
+
+impl From<KeyValue> for opentelemetry::KeyValue {
+    fn from(attribute: KeyValue) -> Self {
+        opentelemetry::KeyValue::new(attribute.key, attribute.value)
+    }
+}
+
+impl From<AttributeValue> for opentelemetry::Value {
+    fn from(value: AttributeValue) -> Self {
+        match value {
+            AttributeValue::String(value) => value.into(),
+            AttributeValue::I64(value) => value.into(),
+            AttributeValue::F64(value) => value.into(),
+            AttributeValue::StringArray(values) => opentelemetry::Value::Array(
+                opentelemetry::Array::String(values.into_iter().map(Into::into).collect()),
+            ),
+        }
+    }
+}
+
+/// Returns the GenAI semantic-convention attributes for a chat completion request and its response.
+pub fn attributes_for(
+    parameters: &ChatCompletionParameters,
+    response: &ChatCompletionResponse,
+) -> Vec<KeyValue> {
+    let mut attributes = request_attributes(parameters);
+
+    let finish_reasons = response
+        .choices
+        .iter()
+        .filter_map(|choice| choice.finish_reason.as_ref())
+        .map(finish_reason_name)
+        .collect();
+
+    attributes.extend(response_attributes(
+        response.id.as_deref(),
+        &response.model,
+        finish_reasons,
+        response.usage.as_ref(),
+    ));
+
+    attributes
+}
+
+/// Returns the prompt and completion content as GenAI events.
+/// Content is only recorded when `record_content` is set, as it may contain sensitive data.
+pub fn content_events_for(
+    parameters: &ChatCompletionParameters,
+    response: &ChatCompletionResponse,
+    record_content: bool,
+) -> Vec<Event> {
+    if !record_content {
+        return vec![];
+    }
+
+    let mut events: Vec<Event> = parameters.messages.iter().map(message_event).collect();
+
+    events.extend(response.choices.iter().map(|choice| {
+        let mut attributes = vec![KeyValue::new(
+            "index",
+            AttributeValue::I64(choice.index as i64),
+        )];
+
+        if let Some(finish_reason) = &choice.finish_reason {
+            attributes.push(KeyValue::new(
+                "finish_reason",
+                AttributeValue::String(finish_reason_name(finish_reason)),
+            ));
+        }
+
+        if let Some(content) = choice.message.message() {
+            attributes.push(KeyValue::new(
+                "message.content",
+                AttributeValue::String(content.to_string()),
+            ));
+        }
+
+        Event {
+            name: "gen_ai.choice",
+            attributes,
+        }
+    }));
+
+    events
+}
+
+fn request_attributes(parameters: &ChatCompletionParameters) -> Vec<KeyValue> {
+    let mut attributes = vec![
+        KeyValue::new(
+            "gen_ai.operation.name",
+            AttributeValue::String("chat".to_string()),
+        ),
+        KeyValue::new(
+            "gen_ai.request.model",
+            AttributeValue::String(parameters.model.clone()),
+        ),
+    ];
+
+    if let Some(temperature) = parameters.temperature {
+        attributes.push(KeyValue::new(
+            "gen_ai.request.temperature",
+            AttributeValue::F64(temperature as f64),
+        ));
+    }
+
+    if let Some(top_p) = parameters.top_p {
+        attributes.push(KeyValue::new(
+            "gen_ai.request.top_p",
+            AttributeValue::F64(top_p as f64),
+        ));
+    }
+
+    if let Some(max_tokens) = parameters.max_completion_tokens.or(parameters.max_tokens) {
+        attributes.push(KeyValue::new(
+            "gen_ai.request.max_tokens",
+            AttributeValue::I64(max_tokens as i64),
+        ));
+    }
+
+    attributes
+}
+
+fn response_attributes(
+    id: Option<&str>,
+    model: &str,
+    finish_reasons: Vec<String>,
+    usage: Option<&Usage>,
+) -> Vec<KeyValue> {
+    let mut attributes = vec![];
+
+    if let Some(id) = id {
+        attributes.push(KeyValue::new(
+            "gen_ai.response.id",
+            AttributeValue::String(id.to_string()),
+        ));
+    }
+
+    if !model.is_empty() {
+        attributes.push(KeyValue::new(
+            "gen_ai.response.model",
+            AttributeValue::String(model.to_string()),
+        ));
+    }
+
+    if !finish_reasons.is_empty() {
+        attributes.push(KeyValue::new(
+            "gen_ai.response.finish_reasons",
+            AttributeValue::StringArray(finish_reasons),
+        ));
+    }
+
+    if let Some(usage) = usage {
+        if let Some(prompt_tokens) = usage.prompt_tokens {
+            attributes.push(KeyValue::new(
+                "gen_ai.usage.input_tokens",
+                AttributeValue::I64(prompt_tokens as i64),
+            ));
+        }
+
+        if let Some(completion_tokens) = usage.completion_tokens {
+            attributes.push(KeyValue::new(
+                "gen_ai.usage.output_tokens",
+                AttributeValue::I64(completion_tokens as i64),
+            ));
+        }
+    }
+
+    attributes
+}
+
+fn message_event(message: &ChatMessage) -> Event {
+    let name = match message {
+        ChatMessage::Developer { .. } | ChatMessage::System { .. } => "gen_ai.system.message",
+        ChatMessage::User { .. } => "gen_ai.user.message",
+        ChatMessage::Assistant { .. } => "gen_ai.assistant.message",
+        ChatMessage::Tool { .. } => "gen_ai.tool.message",
+    };
+
+    let content = match message {
+        ChatMessage::Tool { content, .. } => Some(content),
+        _ => message.message(),
+    };
+
+    Event {
+        name,
+        attributes: content
+            .map(|content| {
+                vec![KeyValue::new(
+                    "content",
+                    AttributeValue::String(content.to_string()),
+                )]
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn finish_reason_name(finish_reason: &FinishReason) -> String {
+    serde_json::to_string(finish_reason)
+        .unwrap_or_default()
+        .trim_matches('"')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::otel::{attributes_for, content_events_for, AttributeValue, KeyValue};
+    use crate::v1::resources::chat::{
+        ChatCompletionParametersBuilder, ChatCompletionResponse, ChatMessage, ChatMessageContent,
+    };
+
+    #[test]
+    fn test_attributes_for_chat_completion() {
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![ChatMessage::User {
+                content: ChatMessageContent::Text("Hello!".to_string()),
+                name: None,
+            }])
+            .temperature(0.5)
+            .max_completion_tokens(100u32)
+            .build()
+            .unwrap();
+
+        let response: ChatCompletionResponse = serde_json::from_str(
+            r#"{
+                "id": "chatcmpl-123",
+                "created": 1700000000,
+                "model": "gpt-4o-2024-08-06",
+                "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi!"}, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 9, "completion_tokens": 3, "total_tokens": 12}
+            }"#,
+        )
+        .unwrap();
+
+        let attributes = attributes_for(&parameters, &response);
+
+        let get = |key: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .map(|attribute: &KeyValue| attribute.value.clone())
+        };
+
+        assert_eq!(
+            get("gen_ai.request.model"),
+            Some(AttributeValue::String("gpt-4o".to_string()))
+        );
+        assert_eq!(
+            get("gen_ai.request.temperature"),
+            Some(AttributeValue::F64(0.5))
+        );
+        assert_eq!(
+            get("gen_ai.request.max_tokens"),
+            Some(AttributeValue::I64(100))
+        );
+        assert_eq!(
+            get("gen_ai.response.model"),
+            Some(AttributeValue::String("gpt-4o-2024-08-06".to_string()))
+        );
+        assert_eq!(
+            get("gen_ai.response.finish_reasons"),
+            Some(AttributeValue::StringArray(vec!["stop".to_string()]))
+        );
+        assert_eq!(
+            get("gen_ai.usage.input_tokens"),
+            Some(AttributeValue::I64(9))
+        );
+        assert_eq!(
+            get("gen_ai.usage.output_tokens"),
+            Some(AttributeValue::I64(3))
+        );
+
+        assert!(content_events_for(&parameters, &response, false).is_empty());
+        assert_eq!(content_events_for(&parameters, &response, true).len(), 2);
+    }
+
+    #[test]
+    fn test_attributes_convert_into_opentelemetry_key_values() {
+        let attribute = KeyValue {
+            key: "gen_ai.response.finish_reasons",
+            value: AttributeValue::StringArray(vec!["stop".to_string(), "length".to_string()]),
+        };
+
+        assert_eq!(
+            opentelemetry::KeyValue::from(attribute),
+            opentelemetry::KeyValue::new(
+                "gen_ai.response.finish_reasons",
+                opentelemetry::Value::Array(
+                    vec![
+                        opentelemetry::StringValue::from("stop"),
+                        opentelemetry::StringValue::from("length"),
+                    ]
+                    .into()
+                ),
+            )
+        );
+        assert_eq!(
+            opentelemetry::Value::from(AttributeValue::I64(9)),
+            opentelemetry::Value::I64(9)
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Overrides of the URL paths of endpoints, for compatible providers that serve a route at another path.
+use std::collections::HashMap;
+
+/// A route the client calls, with its path relative to the base URL. Set another path with `Client::set_path_override`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    AudioSpeech,
+    AudioTranscriptions,
+    AudioTranslations,
+    Batches,
+    Batch,
+    BatchCancel,
+    ChatCompletions,
+    Embeddings,
+    Files,
+    File,
+    FileContent,
+    FineTuningJobs,
+    FineTuningJob,
+    FineTuningJobCancel,
+    FineTuningJobCheckpoints,
+    FineTuningJobEvents,
+    ImageEdits,
+    ImageGenerations,
+    ImageVariations,
+    Models,
+    Model,
+    Moderations,
+    AuditLogs,
+    Costs,
+    Invites,
+    Invite,
+    Projects,
+    Project,
+    ProjectApiKeys,
+    ProjectApiKey,
+    ProjectArchive,
+    ProjectRateLimits,
+    ProjectRateLimit,
+    ProjectServiceAccounts,
+    ProjectServiceAccount,
+    ProjectUsers,
+    ProjectUser,
+    UsageAudioSpeeches,
+    UsageAudioTranscriptions,
+    UsageCodeInterpreterSessions,
+    UsageCompletions,
+    UsageEmbeddings,
+    UsageImages,
+    UsageModerations,
+    UsageVectorStores,
+    Users,
+    User,
+    Realtime,
+    Responses,
+    Response,
+    Uploads,
+    UploadCancel,
+    UploadComplete,
+    UploadParts,
+    VectorStores,
+    VectorStore,
+    VectorStoreFiles,
+    VectorStoreFile,
+    VectorStoreFileContent,
+    VectorStoreSearch,
+    Videos,
+    Video,
+    VideoContent,
+    VideoRemix,
+}
+
+impl Endpoint {
+    /// Every route, in the order they are matched.
+    pub const ALL: [Endpoint; 64] = [
+        Endpoint::AudioSpeech,
+        Endpoint::AudioTranscriptions,
+        Endpoint::AudioTranslations,
+        Endpoint::Batches,
+        Endpoint::Batch,
+        Endpoint::BatchCancel,
+        Endpoint::ChatCompletions,
+        Endpoint::Embeddings,
+        Endpoint::Files,
+        Endpoint::File,
+        Endpoint::FileContent,
+        Endpoint::FineTuningJobs,
+        Endpoint::FineTuningJob,
+        Endpoint::FineTuningJobCancel,
+        Endpoint::FineTuningJobCheckpoints,
+        Endpoint::FineTuningJobEvents,
+        Endpoint::ImageEdits,
+        Endpoint::ImageGenerations,
+        Endpoint::ImageVariations,
+        Endpoint::Models,
+        Endpoint::Model,
+        Endpoint::Moderations,
+        Endpoint::AuditLogs,
+        Endpoint::Costs,
+        Endpoint::Invites,
+        Endpoint::Invite,
+        Endpoint::Projects,
+        Endpoint::Project,
+        Endpoint::ProjectApiKeys,
+        Endpoint::ProjectApiKey,
+        Endpoint::ProjectArchive,
+        Endpoint::ProjectRateLimits,
+        Endpoint::ProjectRateLimit,
+        Endpoint::ProjectServiceAccounts,
+        Endpoint::ProjectServiceAccount,
+        Endpoint::ProjectUsers,
+        Endpoint::ProjectUser,
+        Endpoint::UsageAudioSpeeches,
+        Endpoint::UsageAudioTranscriptions,
+        Endpoint::UsageCodeInterpreterSessions,
+        Endpoint::UsageCompletions,
+        Endpoint::UsageEmbeddings,
+        Endpoint::UsageImages,
+        Endpoint::UsageModerations,
+        Endpoint::UsageVectorStores,
+        Endpoint::Users,
+        Endpoint::User,
+        Endpoint::Realtime,
+        Endpoint::Responses,
+        Endpoint::Response,
+        Endpoint::Uploads,
+        Endpoint::UploadCancel,
+        Endpoint::UploadComplete,
+        Endpoint::UploadParts,
+        Endpoint::VectorStores,
+        Endpoint::VectorStore,
+        Endpoint::VectorStoreFiles,
+        Endpoint::VectorStoreFile,
+        Endpoint::VectorStoreFileContent,
+        Endpoint::VectorStoreSearch,
+        Endpoint::Videos,
+        Endpoint::Video,
+        Endpoint::VideoContent,
+        Endpoint::VideoRemix,
+    ];
+
+    /// The path of the route, the IDs in the path are placeholders in braces, ie. `/files/{file_id}`.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Endpoint::AudioSpeech => "/audio/speech",
+            Endpoint::AudioTranscriptions => "/audio/transcriptions",
+            Endpoint::AudioTranslations => "/audio/translations",
+            Endpoint::Batches => "/batches",
+            Endpoint::Batch => "/batches/{batch_id}",
+            Endpoint::BatchCancel => "/batches/{batch_id}/cancel",
+            Endpoint::ChatCompletions => "/chat/completions",
+            Endpoint::Embeddings => "/embeddings",
+            Endpoint::Files => "/files",
+            Endpoint::File => "/files/{file_id}",
+            Endpoint::FileContent => "/files/{file_id}/content",
+            Endpoint::FineTuningJobs => "/fine_tuning/jobs",
+            Endpoint::FineTuningJob => "/fine_tuning/jobs/{job_id}",
+            Endpoint::FineTuningJobCancel => "/fine_tuning/jobs/{job_id}/cancel",
+            Endpoint::FineTuningJobCheckpoints => "/fine_tuning/jobs/{job_id}/checkpoints",
+            Endpoint::FineTuningJobEvents => "/fine_tuning/jobs/{job_id}/events",
+            Endpoint::ImageEdits => "/images/edits",
+            Endpoint::ImageGenerations => "/images/generations",
+            Endpoint::ImageVariations => "/images/variations",
+            Endpoint::Models => "/models",
+            Endpoint::Model => "/models/{model_id}",
+            Endpoint::Moderations => "/moderations",
+            Endpoint::AuditLogs => "/organization/audit_logs",
+            Endpoint::Costs => "/organization/costs",
+            Endpoint::Invites => "/organization/invites",
+            Endpoint::Invite => "/organization/invites/{invite_id}",
+            Endpoint::Projects => "/organization/projects",
+            Endpoint::Project => "/organization/projects/{project_id}",
+            Endpoint::ProjectApiKeys => "/organization/projects/{project_id}/api_keys",
+            Endpoint::ProjectApiKey => "/organization/projects/{project_id}/api_keys/{api_key_id}",
+            Endpoint::ProjectArchive => "/organization/projects/{project_id}/archive",
+            Endpoint::ProjectRateLimits => "/organization/projects/{project_id}/rate_limits",
+            Endpoint::ProjectRateLimit => {
+                "/organization/projects/{project_id}/rate_limits/{rate_limit_id}"
+            }
+            Endpoint::ProjectServiceAccounts => {
+                "/organization/projects/{project_id}/service_accounts"
+            }
+            Endpoint::ProjectServiceAccount => {
+                "/organization/projects/{project_id}/service_accounts/{service_account_id}"
+            }
+            Endpoint::ProjectUsers => "/organization/projects/{project_id}/users",
+            Endpoint::ProjectUser => "/organization/projects/{project_id}/users/{user_id}",
+            Endpoint::UsageAudioSpeeches => "/organization/usage/audio_speeches",
+            Endpoint::UsageAudioTranscriptions => "/organization/usage/audio_transcriptions",
+            Endpoint::UsageCodeInterpreterSessions => {
+                "/organization/usage/code_interpreter_sessions"
+            }
+            Endpoint::UsageCompletions => "/organization/usage/completions",
+            Endpoint::UsageEmbeddings => "/organization/usage/embeddings",
+            Endpoint::UsageImages => "/organization/usage/images",
+            Endpoint::UsageModerations => "/organization/usage/moderations",
+            Endpoint::UsageVectorStores => "/organization/usage/vector_stores",
+            Endpoint::Users => "/organization/users",
+            Endpoint::User => "/organization/users/{user_id}",
+            Endpoint::Realtime => "/realtime",
+            Endpoint::Responses => "/responses",
+            Endpoint::Response => "/responses/{response_id}",
+            Endpoint::Uploads => "/uploads",
+            Endpoint::UploadCancel => "/uploads/{upload_id}/cancel",
+            Endpoint::UploadComplete => "/uploads/{upload_id}/complete",
+            Endpoint::UploadParts => "/uploads/{upload_id}/parts",
+            Endpoint::VectorStores => "/vector_stores",
+            Endpoint::VectorStore => "/vector_stores/{vector_store_id}",
+            Endpoint::VectorStoreFiles => "/vector_stores/{vector_store_id}/files",
+            Endpoint::VectorStoreFile => "/vector_stores/{vector_store_id}/files/{file_id}",
+            Endpoint::VectorStoreFileContent => {
+                "/vector_stores/{vector_store_id}/files/{file_id}/content"
+            }
+            Endpoint::VectorStoreSearch => "/vector_stores/{vector_store_id}/search",
+            Endpoint::Videos => "/videos",
+            Endpoint::Video => "/videos/{video_id}",
+            Endpoint::VideoContent => "/videos/{video_id}/content",
+            Endpoint::VideoRemix => "/videos/{video_id}/remix",
+        }
+    }
+
+    /// Returns the route of a path, ie. `Endpoint::File` for `/files/file-abc`, with the IDs in the path.
+    pub fn of_path(path: &str) -> Option<(Endpoint, Vec<&str>)> {
+        Endpoint::ALL
+            .iter()
+            .find_map(|endpoint| Some((*endpoint, endpoint.ids(path)?)))
+    }
+
+    /// Returns the IDs in the path if it is a path of this route.
+    fn ids<'a>(&self, path: &'a str) -> Option<Vec<&'a str>> {
+        let mut template = self.path().split('/');
+        let mut segments = path.split('/');
+        let mut ids = vec![];
+
+        loop {
+            match (template.next(), segments.next()) {
+                (None, None) => return Some(ids),
+                (Some(expected), Some(segment)) if is_placeholder(expected) => {
+                    if segment.is_empty() {
+                        return None;
+                    }
+
+                    ids.push(segment);
+                }
+                (Some(expected), Some(segment)) if expected == segment => {}
+                _ => return None,
+            }
+        }
+    }
+}
+
+fn is_placeholder(segment: &str) -> bool {
+    segment.starts_with('{') && segment.ends_with('}')
+}
+
+/// The paths that replace the default paths of routes, see `Client::set_path_override`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PathOverrides {
+    paths: HashMap<Endpoint, String>,
+}
+
+impl PathOverrides {
+    /// Replaces the path of the route. The placeholders in braces are filled with the IDs of the path in order,
+    /// ie. `/api/v2/files/{id}` for `Endpoint::File`.
+    pub fn insert(&mut self, endpoint: Endpoint, path: &str) {
+        let path = match path.starts_with('/') {
+            true => path.to_string(),
+            false => format!("/{path}"),
+        };
+
+        self.paths.insert(endpoint, path);
+    }
+
+    /// Returns the path that replaces the default path of the route, if any.
+    pub fn get(&self, endpoint: Endpoint) -> Option<&str> {
+        self.paths.get(&endpoint).map(String::as_str)
+    }
+
+    pub fn remove(&mut self, endpoint: Endpoint) {
+        self.paths.remove(&endpoint);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns the path with the override of its route, paths of routes without an override are returned as is.
+    /// A query string is kept.
+    pub fn resolve(&self, path: &str) -> String {
+        if self.paths.is_empty() {
+            return path.to_string();
+        }
+
+        let (route, query) = match path.split_once('?') {
+            Some((route, query)) => (route, Some(query)),
+            None => (path, None),
+        };
+
+        let Some((endpoint, ids)) = Endpoint::of_path(route) else {
+            return path.to_string();
+        };
+
+        let Some(template) = self.paths.get(&endpoint) else {
+            return path.to_string();
+        };
+
+        let mut ids = ids.into_iter();
+
+        let mut resolved = template
+            .split('/')
+            .map(|segment| match is_placeholder(segment) {
+                true => ids.next().unwrap_or(segment),
+                false => segment,
+            })
+            .collect::<Vec<&str>>()
+            .join("/");
+
+        if let Some(query) = query {
+            resolved.push('?');
+            resolved.push_str(query);
+        }
+
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::paths::{Endpoint, PathOverrides};
+
+    #[test]
+    fn test_every_endpoint_matches_its_own_path() {
+        for endpoint in Endpoint::ALL {
+            assert_eq!(
+                Endpoint::of_path(endpoint.path()).map(|(endpoint, _)| endpoint),
+                Some(endpoint),
+                "{endpoint:?}"
+            );
+        }
+
+        assert_eq!(
+            Endpoint::of_path("/vector_stores/vs_1/files/file-2/content"),
+            Some((Endpoint::VectorStoreFileContent, vec!["vs_1", "file-2"]))
+        );
+        assert_eq!(Endpoint::of_path("/files/"), None);
+        assert_eq!(Endpoint::of_path("/unknown"), None);
+    }
+
+    #[test]
+    fn test_path_overrides_fill_ids_and_keep_query() {
+        let mut overrides = PathOverrides::default();
+
+        overrides.insert(Endpoint::ChatCompletions, "/api/chat/completions");
+        overrides.insert(Endpoint::FileContent, "v2/file/{id}/download");
+        overrides.insert(Endpoint::Realtime, "/live");
+
+        assert_eq!(
+            overrides.resolve("/chat/completions"),
+            "/api/chat/completions"
+        );
+        assert_eq!(
+            overrides.resolve("/files/file-abc/content"),
+            "/v2/file/file-abc/download"
+        );
+        assert_eq!(
+            overrides.resolve("/realtime?model=gpt-realtime"),
+            "/live?model=gpt-realtime"
+        );
+        assert_eq!(overrides.resolve("/files/file-abc"), "/files/file-abc");
+    }
+}
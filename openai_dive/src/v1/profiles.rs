@@ -0,0 +1,414 @@
+//! Named sets of chat completion parameters, ie. tuned sampling parameters per use case, stored outside the code.
+use crate::v1::error::APIError;
+use crate::v1::resources::chat::{ChatCompletionParameters, ChatMessage};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The key of a profile that names the profile it is layered on.
+const EXTENDS: &str = "extends";
+
+/// Profiles of chat completion parameters, everything except the messages, by name.
+///
+/// The file is a JSON object with the profiles as members:
+///
+/// ```json
+/// {
+///     "base": { "model": "gpt-4o", "temperature": 0.2, "top_p": 0.9 },
+///     "creative": { "extends": "base", "temperature": 0.9, "top_k": 40 }
+/// }
+/// ```
+///
+/// With the `toml-profiles` feature, the profiles can be a TOML file with a table per profile instead.
+///
+/// A profile with `extends` is layered on the named profile, its keys override the ones of the base.
+/// Keys that aren't chat completion parameters (ie. `top_k` above) are sent in `extra_body`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProfileStore {
+    /// The file name or another description of where the profiles come from, used in errors.
+    source: String,
+    profiles: BTreeMap<String, Map<String, Value>>,
+}
+
+impl ProfileStore {
+    /// Reads the profiles from a JSON file, or a TOML file if the extension is `.toml` (`toml-profiles` feature).
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, APIError> {
+        let path = path.as_ref();
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| APIError::FileError(format!("{}: {error}", path.display())))?;
+
+        #[cfg(feature = "toml-profiles")]
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            return Self::from_toml(path.display().to_string(), &text);
+        }
+
+        Self::from_json(path.display().to_string(), &text)
+    }
+
+    /// Parses the profiles from JSON text.
+    pub fn from_json(source: impl Into<String>, text: &str) -> Result<Self, APIError> {
+        let source = source.into();
+
+        let value = serde_json::from_str(text)
+            .map_err(|error| APIError::ParseError(format!("{source}: {error}")))?;
+
+        Self::from_value(source, value)
+    }
+
+    /// Parses the profiles from TOML text, a table per profile.
+    #[cfg(feature = "toml-profiles")]
+    pub fn from_toml(source: impl Into<String>, text: &str) -> Result<Self, APIError> {
+        let source = source.into();
+
+        let value = toml::from_str(text)
+            .map_err(|error| APIError::ParseError(format!("{source}: {error}")))?;
+
+        Self::from_value(source, value)
+    }
+
+    /// Reads the profiles from a JSON value, ie. profiles in another format deserialized into `serde_json::Value`.
+    /// Every profile is checked, an invalid value for a known parameter returns a `ValidationError` with the source and the key.
+    pub fn from_value(source: impl Into<String>, value: Value) -> Result<Self, APIError> {
+        let source = source.into();
+
+        let Value::Object(members) = value else {
+            return Err(APIError::ValidationError {
+                field: source,
+                value: type_name(&value).to_string(),
+                allowed: "an object of profiles".to_string(),
+            });
+        };
+
+        let mut profiles = BTreeMap::new();
+
+        for (name, profile) in members {
+            let Value::Object(profile) = profile else {
+                return Err(APIError::ValidationError {
+                    field: format!("{source}: {name}"),
+                    value: type_name(&profile).to_string(),
+                    allowed: "an object of parameters".to_string(),
+                });
+            };
+
+            profiles.insert(name, profile);
+        }
+
+        let store = Self { source, profiles };
+
+        for (name, profile) in &store.profiles {
+            store.check_profile(name, profile)?;
+        }
+
+        Ok(store)
+    }
+
+    /// Adds the parameters, without the messages, as a profile. It replaces a profile with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, parameters: &ChatCompletionParameters) {
+        let mut profile = match serde_json::to_value(parameters) {
+            Ok(Value::Object(profile)) => profile,
+            _ => Map::new(),
+        };
+
+        profile.remove("messages");
+
+        self.profiles.insert(name.into(), profile);
+    }
+
+    /// Returns the profiles as JSON, which `from_json` reads back.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.profiles).unwrap_or_default()
+    }
+
+    /// Returns the names of the profiles, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Returns the parameters of the profile, layered on the profiles it extends, with the messages.
+    pub fn apply(
+        &self,
+        profile_name: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Result<ChatCompletionParameters, APIError> {
+        self.apply_layers(&[profile_name], messages)
+    }
+
+    /// Returns the parameters of the profiles layered in order, ie. `&["base", "low-latency"]`, with the messages.
+    /// The keys of a later profile override the ones of the earlier profiles.
+    pub fn apply_layers(
+        &self,
+        profile_names: &[&str],
+        messages: Vec<ChatMessage>,
+    ) -> Result<ChatCompletionParameters, APIError> {
+        let mut merged = Map::new();
+
+        for profile_name in profile_names {
+            merged.extend(self.resolve(profile_name)?);
+        }
+
+        let mut parameters = self.parse(profile_names.join("+").as_str(), merged)?;
+
+        parameters.messages = messages;
+
+        Ok(parameters)
+    }
+
+    /// Returns the keys of the profile, layered on the profiles it extends.
+    fn resolve(&self, profile_name: &str) -> Result<Map<String, Value>, APIError> {
+        let mut chain = vec![];
+        let mut next = Some(profile_name.to_string());
+
+        while let Some(name) = next {
+            if chain.contains(&name) {
+                return Err(APIError::ValidationError {
+                    field: format!("{}: {}.{EXTENDS}", self.source, chain.last().unwrap()),
+                    value: name,
+                    allowed: "a profile that doesn't extend itself".to_string(),
+                });
+            }
+
+            let profile = self.profiles.get(&name).ok_or_else(|| match chain.last() {
+                Some(extending) => APIError::ValidationError {
+                    field: format!("{}: {extending}.{EXTENDS}", self.source),
+                    value: name.clone(),
+                    allowed: self.allowed_names(),
+                },
+                None => APIError::NotFoundError {
+                    message: format!("{}: profile {name} doesn't exist", self.source),
+                    request_id: None,
+                },
+            })?;
+
+            next = match profile.get(EXTENDS) {
+                Some(Value::String(base)) => Some(base.clone()),
+                _ => None,
+            };
+
+            chain.push(name);
+        }
+
+        let mut merged = Map::new();
+
+        // the base first, so the extending profiles override its keys
+        for name in chain.iter().rev() {
+            merged.extend(self.profiles[name].clone());
+        }
+
+        merged.remove(EXTENDS);
+
+        Ok(merged)
+    }
+
+    /// Checks the keys of a profile one by one, so an error points at the key.
+    fn check_profile(&self, name: &str, profile: &Map<String, Value>) -> Result<(), APIError> {
+        for (key, value) in profile {
+            let field = format!("{}: {name}.{key}", self.source);
+
+            match key.as_str() {
+                "messages" => {
+                    return Err(APIError::ValidationError {
+                        field,
+                        value: "messages".to_string(),
+                        allowed: "parameters other than the messages".to_string(),
+                    })
+                }
+                EXTENDS => {
+                    if !value.is_string() {
+                        return Err(APIError::ValidationError {
+                            field,
+                            value: type_name(value).to_string(),
+                            allowed: "the name of a profile".to_string(),
+                        });
+                    }
+                }
+                _ => {
+                    let single = Map::from_iter([(key.clone(), value.clone())]);
+
+                    if let Err(APIError::ParseError(error)) = self.parse(name, single) {
+                        return Err(APIError::ValidationError {
+                            field,
+                            value: value.to_string(),
+                            allowed: error,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deserializes the keys into parameters without messages, unknown keys end up in `extra_body`.
+    fn parse(
+        &self,
+        name: &str,
+        mut profile: Map<String, Value>,
+    ) -> Result<ChatCompletionParameters, APIError> {
+        profile.insert("messages".to_string(), Value::Array(vec![]));
+        profile
+            .entry("model")
+            .or_insert_with(|| Value::String(String::new()));
+
+        let mut parameters: ChatCompletionParameters =
+            serde_json::from_value(Value::Object(profile)).map_err(|error| {
+                APIError::ParseError(format!("{}: {name}: {error}", self.source))
+            })?;
+
+        if parameters
+            .extra_body
+            .as_ref()
+            .is_some_and(|extra_body| extra_body.as_object().is_some_and(Map::is_empty))
+        {
+            parameters.extra_body = None;
+        }
+
+        Ok(parameters)
+    }
+
+    fn allowed_names(&self) -> String {
+        self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::error::APIError;
+    use crate::v1::profiles::ProfileStore;
+    use crate::v1::resources::chat::{ChatMessage, ChatMessageContent};
+    use serde_json::json;
+
+    fn messages() -> Vec<ChatMessage> {
+        vec![ChatMessage::User {
+            content: ChatMessageContent::Text("Hello!".to_string()),
+            name: None,
+        }]
+    }
+
+    #[test]
+    fn test_profiles_are_layered_and_unknown_keys_go_to_extra_body() {
+        let store = ProfileStore::from_json(
+            "profiles.json",
+            r#"{
+                "base": {"model": "gpt-4o", "temperature": 0.2, "top_p": 0.9},
+                "creative": {"extends": "base", "temperature": 0.9, "top_k": 40},
+                "short": {"max_completion_tokens": 64}
+            }"#,
+        )
+        .unwrap();
+
+        let parameters = store.apply("creative", messages()).unwrap();
+
+        assert_eq!(parameters.model, "gpt-4o");
+        assert_eq!(parameters.temperature, Some(0.9));
+        assert_eq!(parameters.top_p, Some(0.9));
+        assert_eq!(parameters.extra_body, Some(json!({"top_k": 40})));
+        assert_eq!(parameters.messages, messages());
+
+        let parameters = store
+            .apply_layers(&["creative", "short"], messages())
+            .unwrap();
+
+        assert_eq!(parameters.temperature, Some(0.9));
+        assert_eq!(parameters.max_completion_tokens, Some(64));
+        assert_eq!(store.apply("short", messages()).unwrap().extra_body, None);
+
+        // exported profiles are read back as they were
+        let mut exported = ProfileStore::default();
+        exported.insert("creative", &parameters);
+
+        let imported = ProfileStore::from_json("exported.json", &exported.to_json()).unwrap();
+
+        assert_eq!(imported.apply("creative", messages()).unwrap(), parameters);
+    }
+
+    #[cfg(feature = "toml-profiles")]
+    #[test]
+    fn test_profiles_from_toml() {
+        let store = ProfileStore::from_toml(
+            "profiles.toml",
+            r#"
+                [base]
+                model = "gpt-4o"
+                temperature = 0.2
+                top_p = 0.9
+
+                [creative]
+                extends = "base"
+                temperature = 0.9
+                top_k = 40
+            "#,
+        )
+        .unwrap();
+
+        let parameters = store.apply("creative", messages()).unwrap();
+
+        assert_eq!(parameters.model, "gpt-4o");
+        assert_eq!(parameters.temperature, Some(0.9));
+        assert_eq!(parameters.top_p, Some(0.9));
+        assert_eq!(parameters.extra_body, Some(json!({"top_k": 40})));
+
+        let error = ProfileStore::from_toml("profiles.toml", "[base]\ntemperature = \"hot\"\n")
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, APIError::ValidationError { field, .. } if field == "profiles.toml: base.temperature"),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn test_profile_errors_point_at_the_file_and_key() {
+        let error = ProfileStore::from_json(
+            "profiles.json",
+            r#"{"base": {"model": "gpt-4o", "temperature": "hot"}}"#,
+        )
+        .unwrap_err();
+
+        assert!(
+            matches!(error, APIError::ValidationError { field, value, .. } if field == "profiles.json: base.temperature" && value == "\"hot\"")
+        );
+
+        let error =
+            ProfileStore::from_json("profiles.json", r#"{"base": {"messages": []}}"#).unwrap_err();
+
+        assert!(
+            matches!(error, APIError::ValidationError { field, .. } if field == "profiles.json: base.messages")
+        );
+
+        let store = ProfileStore::from_json(
+            "profiles.json",
+            r#"{"creative": {"extends": "missing"}, "loop": {"extends": "loop"}}"#,
+        )
+        .unwrap();
+
+        assert!(
+            matches!(store.apply("creative", messages()), Err(APIError::ValidationError { field, value, .. }) if field == "profiles.json: creative.extends" && value == "missing")
+        );
+        assert!(matches!(
+            store.apply("loop", messages()),
+            Err(APIError::ValidationError { .. })
+        ));
+        assert!(matches!(
+            store.apply("unknown", messages()),
+            Err(APIError::NotFoundError { .. })
+        ));
+
+        assert!(ProfileStore::from_value("inline", json!({"base": {"model": 4}})).is_err());
+        assert!(ProfileStore::from_value("inline", json!(["base"])).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 #[cfg(feature = "tokio")]
 use crate::v1::error::APIError;
+use crate::v1::resources::chat::ChatMessage;
 use crate::v1::resources::shared::FileUpload;
 use bytes::Bytes;
 use derive_builder::Builder;
@@ -17,9 +18,9 @@ pub struct AudioSpeechParameters {
     pub model: String,
     /// The text to generate audio for. The maximum length is 4096 characters.
     pub input: String,
-    /// The voice to use when generating the audio.
+    /// The voice to use when generating the audio, or a weighted blend of voices for providers that support it.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub voice: Option<String>,
+    pub voice: Option<SpeechVoice>,
     /// The original voice text to, some models need it.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub voice_text: Option<String>,
@@ -32,6 +33,13 @@ pub struct AudioSpeechParameters {
     /// The speed of the generated audio. Select a value from 0.25 to 4.0. 1.0 is the default.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<f32>,
+    /// The format to stream the audio in, raw audio bytes or server-sent events. Only used by `create_speech_stream`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_format: Option<SpeechStreamFormat>,
+    /// The Accept header of the request, by default the mime type of the response format.
+    /// Not part of the body, for providers that select the format by the Accept header.
+    #[serde(skip)]
+    pub accept: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Builder, Clone, PartialEq)]
@@ -60,16 +68,32 @@ pub struct AudioTranscriptionParameters {
     /// The sampling temperature, between 0 and 1. Higher values like 0.8 will make the output more random,
     /// while lower values like 0.2 will make it more focused and deterministic.
     /// If set to 0, the model will use log probability to automatically increase the temperature until certain thresholds are hit.
+    /// A list of fallback temperatures can be set for Whisper servers that accept it.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub temperature: Option<f32>,
+    pub temperature: Option<Temperature>,
     /// The timestamp granularities to populate for this transcription. response_format must be set verbose_json to use timestamp granularities.
     /// Either or both of these options are supported: word, or segment.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp_granularities: Option<Vec<TimestampGranularity>>,
+    /// Additional information to include in the response, logprobs is only supported by gpt-4o-transcribe and gpt-4o-mini-transcribe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<TranscriptionInclude>>,
+    /// Words to recognize more often (ie. names and jargon) with their weights, for compatible providers that accept a word-boost list.
+    /// Sent as `word_boost`, `[{"word": "Kubernetes", "boost": 2.0}]` in JSON and `word_boost[]` fields of `Kubernetes:2` in forms,
+    /// use a `RequestConverter` or `extra_body` for other shapes. OpenAI has no such parameter, the words are appended to the prompt instead.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "word_boost")]
+    pub word_boost: Option<Vec<(String, f32)>>,
     /// Allows to pass arbitrary json as an extra_body parameter, for specific features/openai-compatible endpoints.
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extra_body: Option<Value>,
+    /// The Accept header of the request, by default the mime type of the response format.
+    /// Not part of the body, for providers that select the format by the Accept header.
+    #[serde(skip)]
+    pub accept: Option<String>,
+    /// Skips the check of the parameters against the model, ie. for providers that accept other parameters for a model of the same name.
+    #[serde(skip)]
+    pub skip_validation: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Builder, Clone, PartialEq)]
@@ -89,8 +113,250 @@ pub struct AudioTranslationParameters {
     /// The sampling temperature, between 0 and 1. Higher values like 0.8 will make the output more random,
     /// while lower values like 0.2 will make it more focused and deterministic.
     /// If set to 0, the model will use log probability to automatically increase the temperature until certain thresholds are hit.
+    /// A list of fallback temperatures can be set for Whisper servers that accept it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<Temperature>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerboseTranscriptionResponse {
+    /// The task that was performed, either transcribe or translate.
+    pub task: String,
+    /// The language of the input audio.
+    pub language: String,
+    /// The duration of the input audio in seconds.
+    pub duration: f32,
+    /// The transcribed text.
+    pub text: String,
+    /// Extracted words and their corresponding timestamps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<TranscriptionWord>>,
+    /// Segments of the transcribed text and their corresponding details.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerboseTranslationResponse {
+    /// The task that was performed, always translate.
+    pub task: String,
+    /// The detected language of the input audio.
+    pub language: String,
+    /// The duration of the input audio in seconds.
+    pub duration: f32,
+    /// The translated text, in English.
+    pub text: String,
+    /// Segments of the translated text and their corresponding details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub temperature: Option<f32>,
+    pub segments: Option<Vec<TranscriptionSegment>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionWord {
+    /// The text content of the word.
+    pub word: String,
+    /// Start time of the word in seconds.
+    pub start: f32,
+    /// End time of the word in seconds.
+    pub end: f32,
+}
+
+/// A segment of a verbose transcription. Providers that only return the id, times and text (ie. with diarization)
+/// leave the decoding statistics at their defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionSegment {
+    /// Unique identifier of the segment.
+    pub id: u32,
+    /// Seek offset of the segment.
+    #[serde(default)]
+    pub seek: u32,
+    /// Start time of the segment in seconds.
+    pub start: f32,
+    /// End time of the segment in seconds.
+    pub end: f32,
+    /// Text content of the segment.
+    pub text: String,
+    /// Array of token IDs for the text content.
+    #[serde(default)]
+    pub tokens: Vec<u32>,
+    /// Temperature parameter used for generating the segment.
+    #[serde(default)]
+    pub temperature: f32,
+    /// Average logprob of the segment. If the value is lower than -1, consider the logprobs failed.
+    #[serde(default)]
+    pub avg_logprob: f32,
+    /// Compression ratio of the segment. If the value is greater than 2.4, consider the compression failed.
+    #[serde(default)]
+    pub compression_ratio: f32,
+    /// Probability of no speech in the segment.
+    /// If the value is higher than 1.0 and the avg_logprob is below -1, consider this segment silent.
+    #[serde(default)]
+    pub no_speech_prob: f32,
+    /// The speaker label of the segment, only returned by providers that support diarization.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_speaker",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub speaker: Option<String>,
+}
+
+impl VerboseTranscriptionResponse {
+    /// Groups the segments by speaker, in order of the first segment of each speaker.
+    /// Segments without a speaker label are grouped under None.
+    pub fn segments_by_speaker(&self) -> Vec<(Option<&str>, Vec<&TranscriptionSegment>)> {
+        let mut groups: Vec<(Option<&str>, Vec<&TranscriptionSegment>)> = vec![];
+
+        for segment in self.segments.iter().flatten() {
+            let speaker = segment.speaker.as_deref();
+
+            match groups.iter_mut().find(|(label, _)| *label == speaker) {
+                Some((_, segments)) => segments.push(segment),
+                None => groups.push((speaker, vec![segment])),
+            }
+        }
+
+        groups
+    }
+
+    /// Returns the duration of the input audio in seconds, which the transcription is billed by.
+    pub fn duration_seconds(&self) -> f64 {
+        self.duration as f64
+    }
+
+    /// Estimates the cost of the transcription from the price per minute of audio, ie. 0.006 for whisper-1.
+    /// The price is applied per second, in the currency of the price.
+    pub fn estimated_cost(&self, price_per_minute: f64) -> f64 {
+        self.duration_seconds() / 60.0 * price_per_minute
+    }
+
+    /// Formats the segments as WebVTT captions, the speaker labels as voice spans (ie. `<v A>`).
+    /// Without segments the whole text is one cue over the duration.
+    pub fn to_vtt(&self) -> String {
+        subtitles(
+            self.segments.as_deref(),
+            &self.text,
+            self.duration,
+            SubtitleFormat::Vtt,
+        )
+    }
+
+    /// Formats the segments as SubRip (SRT) subtitles, the speaker labels prefix the text (ie. `A: `).
+    /// Without segments the whole text is one cue over the duration.
+    pub fn to_srt(&self) -> String {
+        subtitles(
+            self.segments.as_deref(),
+            &self.text,
+            self.duration,
+            SubtitleFormat::Srt,
+        )
+    }
+}
+
+impl VerboseTranslationResponse {
+    /// Formats the segments as WebVTT captions, see `VerboseTranscriptionResponse::to_vtt`.
+    pub fn to_vtt(&self) -> String {
+        subtitles(
+            self.segments.as_deref(),
+            &self.text,
+            self.duration,
+            SubtitleFormat::Vtt,
+        )
+    }
+
+    /// Formats the segments as SubRip (SRT) subtitles, see `VerboseTranscriptionResponse::to_srt`.
+    pub fn to_srt(&self) -> String {
+        subtitles(
+            self.segments.as_deref(),
+            &self.text,
+            self.duration,
+            SubtitleFormat::Srt,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SubtitleFormat {
+    Vtt,
+    Srt,
+}
+
+fn subtitles(
+    segments: Option<&[TranscriptionSegment]>,
+    text: &str,
+    duration: f32,
+    format: SubtitleFormat,
+) -> String {
+    let cues: Vec<(f32, f32, &str, Option<&str>)> = match segments {
+        Some(segments) if !segments.is_empty() => segments
+            .iter()
+            .map(|segment| {
+                (
+                    segment.start,
+                    segment.end,
+                    segment.text.trim(),
+                    segment.speaker.as_deref(),
+                )
+            })
+            .collect(),
+        _ => vec![(0.0, duration, text.trim(), None)],
+    };
+
+    let mut output = match format {
+        SubtitleFormat::Vtt => "WEBVTT\n\n".to_string(),
+        SubtitleFormat::Srt => String::new(),
+    };
+
+    for (index, (start, end, text, speaker)) in cues.into_iter().enumerate() {
+        let line = match (format, speaker) {
+            (SubtitleFormat::Vtt, Some(speaker)) => format!("<v {speaker}>{text}"),
+            (SubtitleFormat::Srt, Some(speaker)) => format!("{speaker}: {text}"),
+            (_, None) => text.to_string(),
+        };
+
+        if format == SubtitleFormat::Srt {
+            output.push_str(&format!("{}\n", index + 1));
+        }
+
+        output.push_str(&format!(
+            "{} --> {}\n{line}\n\n",
+            subtitle_timestamp(start, format),
+            subtitle_timestamp(end, format)
+        ));
+    }
+
+    output
+}
+
+/// Formats seconds as `HH:MM:SS.mmm` for WebVTT and `HH:MM:SS,mmm` for SRT.
+fn subtitle_timestamp(seconds: f32, format: SubtitleFormat) -> String {
+    let milliseconds = (seconds.max(0.0) as f64 * 1000.0).round() as u64;
+    let separator = match format {
+        SubtitleFormat::Vtt => '.',
+        SubtitleFormat::Srt => ',',
+    };
+
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
+
+/// Accepts both string and numeric speaker labels, as providers differ.
+fn deserialize_speaker<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+    Ok(match value {
+        Some(serde_json::Value::String(speaker)) => Some(speaker),
+        Some(serde_json::Value::Number(speaker)) => Some(speaker.to_string()),
+        _ => None,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +364,118 @@ pub struct AudioSpeechResponse {
     pub bytes: Bytes,
 }
 
+#[derive(Debug, Default, Builder, Clone, PartialEq)]
+#[builder(name = "VoiceChatParametersBuilder")]
+#[builder(setter(into, strip_option), default)]
+pub struct VoiceChatParameters {
+    /// The audio file object (not file name) with the user's message.
+    pub audio_in: FileUpload,
+    /// The previous messages of the conversation, ie. a system message and earlier turns.
+    pub history: Vec<ChatMessage>,
+    /// The model used to transcribe the audio, ie. gpt-4o-transcribe.
+    pub transcription_model: String,
+    /// The model used to generate the reply, ie. gpt-4o.
+    pub chat_model: String,
+    /// The parameters used to speak the reply, the input is set to the reply text.
+    pub speech: AudioSpeechParameters,
+}
+
+#[derive(Debug, Clone)]
+pub struct VoiceChatResponse {
+    /// The transcript of the user's audio.
+    pub transcript_in: String,
+    /// The text of the reply.
+    pub reply_text: String,
+    /// The spoken reply.
+    pub reply_audio: AudioSpeechResponse,
+    /// The history with the user's message and the reply appended.
+    pub updated_history: Vec<ChatMessage>,
+}
+
+/// The options of `Audio::create_transcriptions`.
+#[derive(Debug, Clone)]
+pub struct BatchTranscriptionOptions {
+    /// The number of files transcribed at the same time. Defaults to 4.
+    pub concurrency: usize,
+    /// Called with the number of finished files and the total, after each file.
+    pub on_progress: Option<TranscriptionProgress>,
+}
+
+impl BatchTranscriptionOptions {
+    /// Calls the callback with the number of finished files and the total after each file, ie. for a progress bar.
+    pub fn with_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(TranscriptionProgress(std::sync::Arc::new(on_progress)));
+
+        self
+    }
+}
+
+impl Default for BatchTranscriptionOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            on_progress: None,
+        }
+    }
+}
+
+/// Receives the number of finished files and the total of a batch of transcriptions.
+#[derive(Clone)]
+pub struct TranscriptionProgress(pub std::sync::Arc<dyn Fn(usize, usize) + Send + Sync>);
+
+impl std::fmt::Debug for TranscriptionProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TranscriptionProgress")
+    }
+}
+
+/// The transcriptions of a batch in the order of the files, with the number of files that succeeded and failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchTranscriptionResult {
+    pub results: Vec<Result<String, crate::v1::error::APIError>>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+#[cfg(feature = "long-transcription")]
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(name = "LongTranscriptionOptionsBuilder")]
+#[builder(setter(into, strip_option), default)]
+pub struct LongTranscriptionOptions {
+    /// ID of the model to use, ie. whisper-1. The model needs to support verbose_json timestamps.
+    pub model: String,
+    /// The maximum duration of a chunk, chunks are also limited to 25 MB. Defaults to 5 minutes.
+    pub chunk_duration: std::time::Duration,
+    /// The duration by which consecutive chunks overlap, so words at a boundary are not cut. Defaults to 5 seconds.
+    pub overlap: std::time::Duration,
+    /// The number of chunks transcribed at the same time. Defaults to 4.
+    pub concurrency: usize,
+    /// The number of retries of a chunk after a rate limit, server error or timeout. Defaults to 2.
+    pub max_retries: u32,
+    /// The language of the input audio in ISO-639-1 format.
+    pub language: Option<String>,
+    /// An optional text to guide the model's style, sent with every chunk.
+    pub prompt: Option<String>,
+}
+
+#[cfg(feature = "long-transcription")]
+impl Default for LongTranscriptionOptions {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            chunk_duration: std::time::Duration::from_secs(300),
+            overlap: std::time::Duration::from_secs(5),
+            concurrency: 4,
+            max_retries: 2,
+            language: None,
+            prompt: None,
+        }
+    }
+}
+
 #[cfg(feature = "stream")]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct StreamAudioSpeechParameters {
@@ -105,9 +483,9 @@ pub struct StreamAudioSpeechParameters {
     pub model: String,
     /// The text to generate audio for. The maximum length is 4096 characters.
     pub input: String,
-    /// The voice to use when generating the audio.
+    /// The voice to use when generating the audio, or a weighted blend of voices for providers that support it.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub voice: Option<String>,
+    pub voice: Option<SpeechVoice>,
     /// The original voice text to, some models need it.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub voice_text: Option<String>,
@@ -117,6 +495,9 @@ pub struct StreamAudioSpeechParameters {
     /// The speed of the generated audio. Select a value from 0.25 to 4.0. 1.0 is the default.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<f32>,
+    /// The format to stream the audio in, raw audio bytes or server-sent events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_format: Option<SpeechStreamFormat>,
     pub stream: bool,
 }
 
@@ -126,6 +507,194 @@ pub struct AudioSpeechResponseChunkResponse {
     pub bytes: Bytes,
 }
 
+#[cfg(feature = "stream")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptionStreamEvent {
+    /// A part of the transcribed text.
+    Delta(String),
+    /// A complete segment, finalized by the provider or synthesized from the deltas at a sentence boundary.
+    Segment(TranscriptionSegment),
+    /// The complete transcribed text, this is the last event.
+    Done(String),
+}
+
+#[cfg(all(feature = "reqwest", feature = "stream"))]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub(crate) enum TranscriptionStreamResponse {
+    #[serde(rename = "transcript.text.delta")]
+    Delta { delta: String },
+    #[serde(rename = "transcript.text.segment")]
+    Segment {
+        #[serde(default)]
+        start: f32,
+        #[serde(default)]
+        end: f32,
+        text: String,
+        #[serde(default, deserialize_with = "deserialize_speaker")]
+        speaker: Option<String>,
+    },
+    #[serde(rename = "transcript.text.done")]
+    Done { text: String },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(feature = "stream")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranslationStreamEvent {
+    /// A part of the English translation.
+    Delta(String),
+    /// The complete translation, this is the last event.
+    Done(String),
+}
+
+#[cfg(all(feature = "reqwest", feature = "stream"))]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub(crate) enum TranslationStreamResponse {
+    /// Some providers send the events of transcriptions for translations as well.
+    #[serde(rename = "translation.text.delta", alias = "transcript.text.delta")]
+    Delta { delta: String },
+    #[serde(rename = "translation.text.done", alias = "transcript.text.done")]
+    Done { text: String },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(all(feature = "reqwest", feature = "stream"))]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub(crate) enum SpeechStreamResponse {
+    /// A chunk of the audio, base64 encoded.
+    #[serde(rename = "speech.audio.delta")]
+    Delta { audio: String },
+    #[serde(rename = "speech.audio.done")]
+    Done,
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(all(feature = "reqwest", feature = "stream"))]
+/// Turns the streamed responses into events, numbering the segments and synthesizing them if enabled.
+#[derive(Debug, Default)]
+pub(crate) struct TranscriptionSegmenter {
+    synthesize: bool,
+    provider_segments: bool,
+    pending: String,
+    next_id: u32,
+}
+
+#[cfg(all(feature = "reqwest", feature = "stream"))]
+impl TranscriptionSegmenter {
+    const SENTENCE_ENDINGS: [char; 7] = ['.', '!', '?', '…', '。', '！', '？'];
+
+    pub(crate) fn new(synthesize: bool) -> Self {
+        Self {
+            synthesize,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        response: TranscriptionStreamResponse,
+    ) -> Vec<TranscriptionStreamEvent> {
+        match response {
+            TranscriptionStreamResponse::Delta { delta } => {
+                let mut events = vec![];
+
+                if self.synthesize && !self.provider_segments {
+                    self.pending.push_str(&delta);
+
+                    while let Some(sentence) = self.take_sentence() {
+                        events.push(self.segment(0.0, 0.0, sentence, None));
+                    }
+                }
+
+                events.insert(0, TranscriptionStreamEvent::Delta(delta));
+
+                events
+            }
+            TranscriptionStreamResponse::Segment {
+                start,
+                end,
+                text,
+                speaker,
+            } => {
+                // the provider finalizes segments itself, so stop synthesizing them
+                self.provider_segments = true;
+                self.pending.clear();
+
+                vec![self.segment(start, end, text, speaker)]
+            }
+            TranscriptionStreamResponse::Done { text } => {
+                let mut events = vec![];
+
+                let rest = std::mem::take(&mut self.pending).trim().to_string();
+
+                if !rest.is_empty() {
+                    events.push(self.segment(0.0, 0.0, rest, None));
+                }
+
+                events.push(TranscriptionStreamEvent::Done(text));
+
+                events
+            }
+            TranscriptionStreamResponse::Other => vec![],
+        }
+    }
+
+    /// Takes the first sentence of the pending text, once the whitespace after its ending has been received.
+    fn take_sentence(&mut self) -> Option<String> {
+        loop {
+            let (end, rest) = self
+                .pending
+                .char_indices()
+                .zip(self.pending.char_indices().skip(1))
+                .find(|((_, character), (_, next))| {
+                    Self::SENTENCE_ENDINGS.contains(character) && next.is_whitespace()
+                })
+                .map(|((index, character), (next_index, _))| {
+                    (index + character.len_utf8(), next_index)
+                })?;
+
+            let sentence = self.pending[..end].trim().to_string();
+
+            self.pending = self.pending[rest..].trim_start().to_string();
+
+            if !sentence.is_empty() {
+                return Some(sentence);
+            }
+        }
+    }
+
+    fn segment(
+        &mut self,
+        start: f32,
+        end: f32,
+        text: String,
+        speaker: Option<String>,
+    ) -> TranscriptionStreamEvent {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        TranscriptionStreamEvent::Segment(TranscriptionSegment {
+            id,
+            seek: 0,
+            start,
+            end,
+            text,
+            tokens: vec![],
+            temperature: 0.0,
+            avg_logprob: 0.0,
+            compression_ratio: 0.0,
+            no_speech_prob: 0.0,
+            speaker,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum AudioOutputFormat {
@@ -147,6 +716,151 @@ pub enum AudioSpeechResponseFormat {
     Pcm,
 }
 
+/// The voice of a speech request, `.voice("alloy")` sets a single voice.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SpeechVoice {
+    Single(String),
+    /// Voices with their weights, sent as `[{"voice": "af_bella", "weight": 0.7}, ...]`.
+    /// OpenAI only accepts a single voice, a blend is rejected before it is sent.
+    #[serde(with = "voice_blend")]
+    VoiceBlend(Vec<(String, f32)>),
+}
+
+impl SpeechVoice {
+    /// A weighted blend of voices, ie. `SpeechVoice::blend([("af_bella", 0.7), ("af_sky", 0.3)])`.
+    pub fn blend<S: Into<String>>(voices: impl IntoIterator<Item = (S, f32)>) -> Self {
+        SpeechVoice::VoiceBlend(
+            voices
+                .into_iter()
+                .map(|(voice, weight)| (voice.into(), weight))
+                .collect(),
+        )
+    }
+
+    /// Returns the voice if it is a single voice, ie. to read the voice of parameters that used to be a `String`.
+    pub fn as_single(&self) -> Option<&str> {
+        match self {
+            SpeechVoice::Single(voice) => Some(voice),
+            SpeechVoice::VoiceBlend(_) => None,
+        }
+    }
+}
+
+impl From<&str> for SpeechVoice {
+    fn from(voice: &str) -> Self {
+        SpeechVoice::Single(voice.to_string())
+    }
+}
+
+impl From<String> for SpeechVoice {
+    fn from(voice: String) -> Self {
+        SpeechVoice::Single(voice)
+    }
+}
+
+impl From<AudioVoice> for SpeechVoice {
+    fn from(voice: AudioVoice) -> Self {
+        let voice = serde_json::to_value(voice).unwrap_or_default();
+
+        SpeechVoice::Single(voice.as_str().unwrap_or_default().to_string())
+    }
+}
+
+mod voice_blend {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct WeightedVoice {
+        voice: String,
+        weight: f32,
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        voices: &[(String, f32)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(voices.iter().map(|(voice, weight)| WeightedVoice {
+            voice: voice.clone(),
+            weight: *weight,
+        }))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(String, f32)>, D::Error> {
+        Ok(Vec::<WeightedVoice>::deserialize(deserializer)?
+            .into_iter()
+            .map(|voice| (voice.voice, voice.weight))
+            .collect())
+    }
+}
+
+mod word_boost {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct BoostedWord {
+        word: String,
+        boost: f32,
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        words: &Option<Vec<(String, f32)>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(words.iter().flatten().map(|(word, boost)| BoostedWord {
+            word: word.clone(),
+            boost: *boost,
+        }))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<(String, f32)>>, D::Error> {
+        Ok(
+            Option::<Vec<BoostedWord>>::deserialize(deserializer)?.map(|words| {
+                words
+                    .into_iter()
+                    .map(|word| (word.word, word.boost))
+                    .collect()
+            }),
+        )
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl AudioTranscriptionParameters {
+    /// Appends the words of `word_boost` to the prompt by decreasing weight, for providers without a word-boost list.
+    pub(crate) fn fold_word_boost_into_prompt(&mut self) {
+        let Some(mut words) = self.word_boost.take() else {
+            return;
+        };
+
+        words.sort_by(|(_, left), (_, right)| right.total_cmp(left));
+
+        let words = words
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.prompt = Some(match self.prompt.take() {
+            Some(prompt) if !prompt.is_empty() => format!("{prompt} {words}"),
+            _ => words,
+        });
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeechStreamFormat {
+    /// The audio bytes, as in the response of a speech request that isn't streamed.
+    Audio,
+    /// Server-sent events with base64 encoded chunks of the audio, terminated by `data: [DONE]`.
+    Sse,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum AudioVoice {
@@ -162,6 +876,13 @@ pub enum AudioVoice {
     Shimmer,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionInclude {
+    /// The log probabilities of the tokens of the transcript.
+    Logprobs,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum TimestampGranularity {
@@ -195,6 +916,18 @@ pub enum VadConfigType {
     ServerVad,
 }
 
+impl AudioOutputFormat {
+    /// Returns the mime type of the format, as sent in the Accept header.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioOutputFormat::Json | AudioOutputFormat::VerboseJson => "application/json",
+            AudioOutputFormat::Text => "text/plain",
+            AudioOutputFormat::Srt => "application/x-subrip",
+            AudioOutputFormat::Vtt => "text/vtt",
+        }
+    }
+}
+
 impl Display for AudioOutputFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -224,6 +957,58 @@ impl Display for TimestampGranularity {
     }
 }
 
+impl Display for TranscriptionInclude {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TranscriptionInclude::Logprobs => "logprobs",
+            }
+        )
+    }
+}
+
+/// The sampling temperature of a transcription or translation, `0.2.into()` for a single temperature.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Temperature {
+    /// A single temperature, between 0 and 1.
+    Fixed(f32),
+    /// The temperatures to try in order (ie. `[0.0, 0.2, 0.4, 0.6, 0.8, 1.0]`), the next one is used when the
+    /// transcription fails the compression ratio or log probability thresholds. Sent as comma separated list.
+    Fallback(Vec<f32>),
+}
+
+impl From<f32> for Temperature {
+    fn from(temperature: f32) -> Self {
+        Temperature::Fixed(temperature)
+    }
+}
+
+impl From<Vec<f32>> for Temperature {
+    fn from(temperatures: Vec<f32>) -> Self {
+        Temperature::Fallback(temperatures)
+    }
+}
+
+impl Display for Temperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Temperature::Fixed(temperature) => temperature.fmt(f),
+            Temperature::Fallback(temperatures) => write!(
+                f,
+                "{}",
+                temperatures
+                    .iter()
+                    .map(|temperature| temperature.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
 impl Display for TranscriptionChunkingStrategy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -240,7 +1025,102 @@ impl Display for VadConfig {
     }
 }
 
+impl AudioSpeechResponseFormat {
+    /// All supported speech response formats.
+    pub const ALL: [AudioSpeechResponseFormat; 6] = [
+        AudioSpeechResponseFormat::Mp3,
+        AudioSpeechResponseFormat::Opus,
+        AudioSpeechResponseFormat::Aac,
+        AudioSpeechResponseFormat::Flac,
+        AudioSpeechResponseFormat::Wav,
+        AudioSpeechResponseFormat::Pcm,
+    ];
+
+    /// Returns the mime type of the format, as returned in the Content-Type header of the API.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioSpeechResponseFormat::Mp3 => "audio/mpeg",
+            AudioSpeechResponseFormat::Opus => "audio/opus",
+            AudioSpeechResponseFormat::Aac => "audio/aac",
+            AudioSpeechResponseFormat::Flac => "audio/flac",
+            AudioSpeechResponseFormat::Wav => "audio/wav",
+            AudioSpeechResponseFormat::Pcm => "audio/pcm",
+        }
+    }
+
+    /// Returns the file extension of the format, without the leading dot.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            AudioSpeechResponseFormat::Mp3 => "mp3",
+            AudioSpeechResponseFormat::Opus => "opus",
+            AudioSpeechResponseFormat::Aac => "aac",
+            AudioSpeechResponseFormat::Flac => "flac",
+            AudioSpeechResponseFormat::Wav => "wav",
+            AudioSpeechResponseFormat::Pcm => "pcm",
+        }
+    }
+
+    /// Returns the format of a file extension, with or without the leading dot, case-insensitive.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.trim_start_matches('.');
+
+        Self::ALL
+            .into_iter()
+            .find(|format| format.file_extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Returns the format of a mime type, ignoring parameters such as the charset.
+    /// Common aliases (ie. audio/mp3 and audio/x-wav) are also recognized.
+    pub fn from_mime(mime_type: &str) -> Option<Self> {
+        let mime_type = mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let format = match mime_type.as_str() {
+            "audio/mp3" => AudioSpeechResponseFormat::Mp3,
+            "audio/ogg" => AudioSpeechResponseFormat::Opus,
+            "audio/x-aac" => AudioSpeechResponseFormat::Aac,
+            "audio/x-flac" => AudioSpeechResponseFormat::Flac,
+            "audio/x-wav" | "audio/wave" => AudioSpeechResponseFormat::Wav,
+            "audio/l16" => AudioSpeechResponseFormat::Pcm,
+            _ => {
+                return Self::ALL
+                    .into_iter()
+                    .find(|format| format.mime_type() == mime_type)
+            }
+        };
+
+        Some(format)
+    }
+}
+
 impl AudioSpeechResponse {
+    /// Decodes audio that a provider returns as base64 in JSON, at the JSON pointer, ie. `/audio` for `{"audio": "..."}`.
+    /// Disable `Client::set_detect_speech_json_errors` for these providers, so the JSON body is returned as is.
+    #[cfg(any(feature = "stream", feature = "download"))]
+    pub fn from_json_base64(json: &[u8], pointer: &str) -> Result<Self, APIError> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let value: Value = serde_json::from_slice(json)
+            .map_err(|error| APIError::ParseError(error.to_string()))?;
+
+        let audio = value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .ok_or_else(|| APIError::ParseError(format!("no base64 audio at {pointer}")))?;
+
+        let bytes = general_purpose::STANDARD
+            .decode(audio)
+            .map_err(|error| APIError::ParseError(format!("{pointer}: {error}")))?;
+
+        Ok(Self {
+            bytes: bytes.into(),
+        })
+    }
+
     #[cfg(feature = "tokio")]
     pub async fn save<P: AsRef<Path>>(&self, file_path: P) -> Result<(), APIError> {
         let directory = file_path.as_ref().parent();
@@ -267,11 +1147,66 @@ impl AudioSpeechResponse {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+    use crate::v1::resources::audio::AudioTranscriptionParameters;
     use crate::v1::resources::audio::{
-        AudioTranscriptionParameters, AudioTranscriptionParametersBuilder,
-        TranscriptionChunkingStrategy, VadConfig, VadConfigType,
+        AudioSpeechParametersBuilder, AudioSpeechResponseFormat,
+        AudioTranscriptionParametersBuilder, AudioVoice, SpeechVoice, Temperature,
+        TranscriptionChunkingStrategy, TranscriptionSegment, VadConfig, VadConfigType,
+        VerboseTranscriptionResponse, VerboseTranslationResponse,
     };
-    use crate::v1::resources::shared::FileUpload;
+    use crate::v1::resources::shared::{FileUpload, FileUploadBytes};
+
+    #[test]
+    fn test_speech_voice_serialization() {
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .voice("alloy")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&parameters).unwrap()["voice"],
+            serde_json::json!("alloy")
+        );
+        assert_eq!(
+            SpeechVoice::from(AudioVoice::Shimmer),
+            SpeechVoice::Single("shimmer".to_string())
+        );
+
+        // the enum of the OpenAI voices still sets a single voice
+        let parameters = AudioSpeechParametersBuilder::default()
+            .model("tts-1")
+            .input("Hello!")
+            .voice(AudioVoice::Coral)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            parameters.voice.as_ref().and_then(SpeechVoice::as_single),
+            Some("coral")
+        );
+
+        let blend = SpeechVoice::blend([("af_bella", 0.75), ("af_sky", 0.25)]);
+        let serialized = serde_json::to_value(&blend).unwrap();
+
+        assert_eq!(
+            serialized,
+            serde_json::json!([
+                {"voice": "af_bella", "weight": 0.75},
+                {"voice": "af_sky", "weight": 0.25}
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<SpeechVoice>(serialized).unwrap(),
+            blend
+        );
+        assert_eq!(
+            serde_json::from_str::<SpeechVoice>(r#""alloy""#).unwrap(),
+            SpeechVoice::Single("alloy".to_string())
+        );
+    }
 
     #[test]
     fn test_audio_transcription_chunking_strategy_auto_serialization_deserialization() {
@@ -303,6 +1238,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "tokio", feature = "tokio-util"))]
     fn test_audio_transcription_extra_body_serialization_deserialization() {
         let mut builder = &mut AudioTranscriptionParametersBuilder::default();
         builder = builder.file(FileUpload::File("test.wav".to_string()));
@@ -322,4 +1258,353 @@ mod tests {
             serde_json::from_str(serialized.as_str()).unwrap();
         assert_eq!(deserialized, params)
     }
+
+    #[test]
+    #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+    fn test_audio_transcription_word_boost_serialization_deserialization() {
+        let params = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::File("test.wav".to_string()))
+            .model("test")
+            .word_boost(vec![
+                ("Kubernetes".to_string(), 2.0),
+                ("kubectl".to_string(), 1.5),
+            ])
+            .build()
+            .unwrap();
+
+        let serialized = serde_json::to_string(&params).unwrap();
+        assert_eq!(serialized, "{\"file\":{\"File\":\"test.wav\"},\"model\":\"test\",\"word_boost\":[{\"word\":\"Kubernetes\",\"boost\":2.0},{\"word\":\"kubectl\",\"boost\":1.5}]}");
+
+        let deserialized: AudioTranscriptionParameters =
+            serde_json::from_str(serialized.as_str()).unwrap();
+        assert_eq!(deserialized.word_boost, params.word_boost);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_fold_word_boost_into_prompt() {
+        let mut folded = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 4],
+                "test.wav",
+            )))
+            .model("test")
+            .prompt("A talk about clusters.")
+            .word_boost(vec![
+                ("kubectl".to_string(), 1.5),
+                ("Kubernetes".to_string(), 2.0),
+            ])
+            .build()
+            .unwrap();
+        folded.fold_word_boost_into_prompt();
+
+        assert_eq!(folded.word_boost, None);
+        assert_eq!(
+            folded.prompt.as_deref(),
+            Some("A talk about clusters. Kubernetes, kubectl")
+        );
+    }
+
+    #[test]
+    fn test_verbose_transcription_response_deserialization() {
+        let json = r#"{
+            "task": "transcribe",
+            "language": "english",
+            "duration": 8.470000267028809,
+            "text": "The beach was a popular spot on a hot summer day.",
+            "segments": [
+                {
+                    "id": 0,
+                    "seek": 0,
+                    "start": 0.0,
+                    "end": 3.319999933242798,
+                    "text": " The beach was a popular spot on a hot summer day.",
+                    "tokens": [50364, 440, 7534, 390, 257, 3743, 4008, 322, 257, 2368, 4266, 786, 13, 50530],
+                    "temperature": 0.0,
+                    "avg_logprob": -0.2860786020755768,
+                    "compression_ratio": 1.2363636493682861,
+                    "no_speech_prob": 0.00985979475080967
+                }
+            ]
+        }"#;
+
+        let response: VerboseTranscriptionResponse = serde_json::from_str(json).unwrap();
+
+        let segments = response.segments.unwrap();
+
+        assert_eq!(
+            segments[0],
+            TranscriptionSegment {
+                id: 0,
+                seek: 0,
+                start: 0.0,
+                end: 3.32,
+                text: " The beach was a popular spot on a hot summer day.".to_string(),
+                tokens: vec![
+                    50364, 440, 7534, 390, 257, 3743, 4008, 322, 257, 2368, 4266, 786, 13, 50530
+                ],
+                temperature: 0.0,
+                avg_logprob: -0.2860786,
+                compression_ratio: 1.2363636,
+                no_speech_prob: 0.009859795,
+                speaker: None,
+            }
+        );
+        assert_eq!(response.words, None);
+    }
+
+    #[test]
+    fn test_verbose_transcription_response_estimated_cost() {
+        let response: VerboseTranscriptionResponse = serde_json::from_str(
+            r#"{"task": "transcribe", "language": "english", "duration": 90.0, "text": "Hello!"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.duration_seconds(), 90.0);
+        // one and a half minutes at 0.006 per minute
+        assert!((response.estimated_cost(0.006) - 0.009).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_verbose_transcription_response_to_vtt_and_srt() {
+        let response: VerboseTranscriptionResponse = serde_json::from_value(serde_json::json!({
+            "task": "transcribe",
+            "language": "english",
+            "duration": 3725.5,
+            "text": "Hello there. General Kenobi.",
+            "segments": [
+                {
+                    "id": 0, "seek": 0, "start": 0.0, "end": 2.5, "text": " Hello there.",
+                    "tokens": [], "temperature": 0.0, "avg_logprob": -0.2,
+                    "compression_ratio": 1.1, "no_speech_prob": 0.01
+                },
+                {
+                    "id": 1, "seek": 0, "start": 3723.04, "end": 3725.5, "text": " General Kenobi.",
+                    "tokens": [], "temperature": 0.0, "avg_logprob": -0.2,
+                    "compression_ratio": 1.1, "no_speech_prob": 0.01, "speaker": "B"
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            response.to_vtt(),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello there.\n\n01:02:03.040 --> 01:02:05.500\n<v B>General Kenobi.\n\n"
+        );
+        assert_eq!(
+            response.to_srt(),
+            "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n2\n01:02:03,040 --> 01:02:05,500\nB: General Kenobi.\n\n"
+        );
+
+        let without_segments = VerboseTranscriptionResponse {
+            segments: None,
+            ..response
+        };
+
+        assert_eq!(
+            without_segments.to_srt(),
+            "1\n00:00:00,000 --> 01:02:05,500\nHello there. General Kenobi.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_verbose_translation_response_deserialization() {
+        let json = r#"{
+            "task": "translate",
+            "language": "german",
+            "duration": 4.21999979019165,
+            "text": "Hello, how are you?",
+            "segments": [
+                {
+                    "id": 0,
+                    "seek": 0,
+                    "start": 0.0,
+                    "end": 4.0,
+                    "text": " Hello, how are you?",
+                    "tokens": [50364, 2425, 11, 577, 366, 291, 30, 50564],
+                    "temperature": 0.0,
+                    "avg_logprob": -0.4110170900821686,
+                    "compression_ratio": 0.7916666865348816,
+                    "no_speech_prob": 0.0317622609436512
+                }
+            ]
+        }"#;
+
+        let response: VerboseTranslationResponse = serde_json::from_str(json).unwrap();
+
+        assert_eq!(response.task, "translate");
+        assert_eq!(response.language, "german");
+        assert_eq!(response.duration, 4.22);
+        assert_eq!(response.text, "Hello, how are you?");
+
+        let segments = response.segments.unwrap();
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start, segments[0].end), (0.0, 4.0));
+        assert_eq!(segments[0].text, " Hello, how are you?");
+    }
+
+    #[test]
+    fn test_verbose_transcription_response_with_speaker_labels() {
+        let segment = |id: u32, speaker: &str| {
+            format!(
+                r#"{{"id":{id},"seek":0,"start":0.0,"end":1.0,"text":"Segment {id}","tokens":[],"temperature":0.0,"avg_logprob":-0.2,"compression_ratio":1.0,"no_speech_prob":0.01{speaker}}}"#
+            )
+        };
+
+        let json = format!(
+            r#"{{"task":"transcribe","language":"english","duration":4.0,"text":"","segments":[{},{},{},{}]}}"#,
+            segment(0, r#","speaker":"A""#),
+            segment(1, r#","speaker":1"#),
+            segment(2, r#","speaker":"A""#),
+            segment(3, ""),
+        );
+
+        let response: VerboseTranscriptionResponse = serde_json::from_str(&json).unwrap();
+
+        let groups: Vec<(Option<&str>, Vec<u32>)> = response
+            .segments_by_speaker()
+            .into_iter()
+            .map(|(speaker, segments)| {
+                (speaker, segments.iter().map(|segment| segment.id).collect())
+            })
+            .collect();
+
+        assert_eq!(
+            groups,
+            vec![
+                (Some("A"), vec![0, 2]),
+                (Some("1"), vec![1]),
+                (None, vec![3])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_minimal_transcription_segment_deserialization() {
+        let segment: TranscriptionSegment = serde_json::from_str(
+            r#"{"id":3,"start":1.5,"end":2.25,"text":"Hello there.","speaker":"B"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(segment.id, 3);
+        assert_eq!(segment.start, 1.5);
+        assert_eq!(segment.end, 2.25);
+        assert_eq!(segment.text, "Hello there.");
+        assert_eq!(segment.speaker.as_deref(), Some("B"));
+        assert_eq!(segment.seek, 0);
+        assert!(segment.tokens.is_empty());
+        assert_eq!(segment.no_speech_prob, 0.0);
+    }
+
+    #[test]
+    fn test_audio_speech_response_format_mime_type_and_extension_round_trip() {
+        for format in AudioSpeechResponseFormat::ALL {
+            assert_eq!(
+                AudioSpeechResponseFormat::from_mime(format.mime_type()),
+                Some(format.clone())
+            );
+            assert_eq!(
+                AudioSpeechResponseFormat::from_extension(format.file_extension()),
+                Some(format.clone())
+            );
+
+            let serialized = serde_json::to_string(&format).unwrap();
+            assert_eq!(serialized, format!("\"{}\"", format.file_extension()));
+        }
+
+        assert_eq!(
+            AudioSpeechResponseFormat::from_mime("audio/mpeg; charset=binary"),
+            Some(AudioSpeechResponseFormat::Mp3)
+        );
+        assert_eq!(
+            AudioSpeechResponseFormat::from_extension(".WAV"),
+            Some(AudioSpeechResponseFormat::Wav)
+        );
+        assert_eq!(AudioSpeechResponseFormat::from_extension("txt"), None);
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "stream"))]
+    #[test]
+    fn test_transcription_segmenter_synthesizes_segments_at_sentence_boundaries() {
+        use crate::v1::resources::audio::{
+            TranscriptionSegmenter, TranscriptionStreamEvent, TranscriptionStreamResponse,
+        };
+
+        let mut segmenter = TranscriptionSegmenter::new(true);
+
+        let mut events = vec![];
+
+        for delta in ["Hello", " there.", " How are", " you? I'm", " fine"] {
+            events.extend(segmenter.push(TranscriptionStreamResponse::Delta {
+                delta: delta.to_string(),
+            }));
+        }
+
+        events.extend(segmenter.push(TranscriptionStreamResponse::Done {
+            text: "Hello there. How are you? I'm fine".to_string(),
+        }));
+
+        let segments: Vec<(u32, String)> = events
+            .iter()
+            .filter_map(|event| match event {
+                TranscriptionStreamEvent::Segment(segment) => {
+                    Some((segment.id, segment.text.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            segments,
+            vec![
+                (0, "Hello there.".to_string()),
+                (1, "How are you?".to_string()),
+                (2, "I'm fine".to_string()),
+            ]
+        );
+
+        // the first segment is emitted once the whitespace after its sentence ending arrives
+        assert!(
+            matches!(&events[3], TranscriptionStreamEvent::Segment(segment) if segment.id == 0)
+        );
+        assert!(matches!(
+            events.last(),
+            Some(TranscriptionStreamEvent::Done(_))
+        ));
+    }
+
+    #[test]
+    fn test_temperature_serialization() {
+        let parameters = AudioTranscriptionParametersBuilder::default()
+            .file(FileUpload::Bytes(FileUploadBytes::new(
+                vec![0; 4],
+                "test.wav",
+            )))
+            .model("whisper-1")
+            .temperature(0.2)
+            .build()
+            .unwrap();
+
+        assert_eq!(parameters.temperature, Some(Temperature::Fixed(0.2)));
+        assert_eq!(
+            serde_json::to_value(&parameters).unwrap()["temperature"],
+            serde_json::json!(0.2f32)
+        );
+
+        let fallback = Temperature::from(vec![0.0, 0.2, 0.4, 1.0]);
+
+        assert_eq!(
+            serde_json::to_value(&fallback).unwrap(),
+            serde_json::json!([0.0, 0.2f32, 0.4f32, 1.0])
+        );
+        assert_eq!(
+            serde_json::from_str::<Temperature>("[0.0, 0.5]").unwrap(),
+            Temperature::Fallback(vec![0.0, 0.5])
+        );
+
+        // the multipart form values
+        assert_eq!(Temperature::Fixed(0.2).to_string(), "0.2");
+        assert_eq!(fallback.to_string(), "0,0.2,0.4,1");
+    }
 }
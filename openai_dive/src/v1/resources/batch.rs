@@ -1,6 +1,8 @@
+use crate::v1::error::ErrorBody;
+use crate::v1::resources::shared::Metadata;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Batch {
@@ -55,7 +57,7 @@ pub struct Batch {
     pub request_counts: BatchRequestCounts,
     /// Optional custom metadata of the batch.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<HashMap<String, String>>,
+    pub metadata: Option<Metadata>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Builder, Clone, PartialEq)]
@@ -70,7 +72,7 @@ pub struct CreateBatchParameters {
     /// The time frame within which the batch should be processed. Currently only "24h" is supported.
     pub completion_window: BatchCompletionWindow,
     /// Optional custom metadata for the batch.
-    pub metadata: Option<HashMap<String, String>>,
+    pub metadata: Option<Metadata>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -123,3 +125,154 @@ pub enum BatchCompletionWindow {
     #[serde(rename = "24h")]
     H24,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct FailedRequests {
+    /// The failed requests, in order of the error file.
+    pub requests: Vec<FailedRequest>,
+    /// The lines of the error file that could not be parsed.
+    pub parse_errors: Vec<BatchLineError>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FailedRequest {
+    /// The custom ID of the request in the input file.
+    pub custom_id: String,
+    /// The HTTP status code of the response, if the request was sent.
+    pub status_code: Option<u16>,
+    /// The parsed error of the request.
+    pub error: ErrorBody,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchLineError {
+    /// The ID of the file that contains the line.
+    pub file_id: String,
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// Why the line could not be parsed.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResubmittedBatch {
+    /// The new batch with the failed requests.
+    pub batch: Batch,
+    /// The custom IDs of the failed requests that were resubmitted.
+    pub resubmitted_custom_ids: Vec<String>,
+    /// The custom IDs of failed requests for which no original request was found.
+    pub missing_custom_ids: Vec<String>,
+    /// The lines of the error file and input file that could not be parsed.
+    pub parse_errors: Vec<BatchLineError>,
+}
+
+/// Returns the original request line of a batch for a custom ID.
+pub type RequestLookup<'a> = Box<dyn Fn(&str) -> Option<Value> + Send + Sync + 'a>;
+
+/// Where the original requests of a batch are taken from when resubmitting failed requests.
+pub enum OriginalRequests<'a> {
+    /// The ID of a JSONL file with the original requests, ie. the input file of the batch.
+    InputFile(String),
+    /// Returns the original request line (with custom_id, method, url and body) for a custom ID.
+    Lookup(RequestLookup<'a>),
+}
+
+impl FailedRequests {
+    /// Parses the error file of a batch, lines that can't be parsed are reported in `parse_errors`.
+    pub fn from_error_file(file_id: &str, content: &str) -> Self {
+        let mut failed_requests = FailedRequests::default();
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_failed_request(line) {
+                Ok(request) => failed_requests.requests.push(request),
+                Err(message) => failed_requests.parse_errors.push(BatchLineError {
+                    file_id: file_id.to_string(),
+                    line: index + 1,
+                    message,
+                }),
+            }
+        }
+
+        failed_requests
+    }
+}
+
+fn parse_failed_request(line: &str) -> Result<FailedRequest, String> {
+    let value: Value = serde_json::from_str(line).map_err(|error| error.to_string())?;
+
+    let custom_id = value
+        .get("custom_id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "the line has no custom_id".to_string())?
+        .to_string();
+
+    let response = value.get("response").filter(|response| !response.is_null());
+
+    let status_code = response
+        .and_then(|response| response.get("status_code"))
+        .and_then(Value::as_u64)
+        .map(|status_code| status_code as u16);
+
+    // errors of requests that were not sent are in `error`, errors returned by the API are in the response body
+    let error = match (
+        value.get("error").filter(|error| !error.is_null()),
+        response,
+    ) {
+        (Some(error), _) => ErrorBody::parse(&serde_json::json!({ "error": error }).to_string()),
+        (None, Some(response)) => match response.get("body") {
+            Some(Value::String(body)) => ErrorBody::parse(body),
+            Some(body) => ErrorBody::parse(&body.to_string()),
+            None => return Err("the response has no body".to_string()),
+        },
+        (None, None) => return Err("the line has no error or response".to_string()),
+    };
+
+    Ok(FailedRequest {
+        custom_id,
+        status_code,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::error::ErrorShape;
+    use crate::v1::resources::batch::FailedRequests;
+
+    #[test]
+    fn test_failed_requests_from_error_file() {
+        let content = [
+            r#"{"id": "batch_req_1", "custom_id": "request-1", "response": {"status_code": 400, "request_id": "req_1", "body": {"error": {"message": "Invalid model", "type": "invalid_request_error", "code": "model_not_found"}}}, "error": null}"#,
+            r#"{"id": "batch_req_2", "custom_id": "request-2", "response": null, "error": {"code": "batch_expired", "message": "This request could not be executed before the completion window expired."}}"#,
+            "",
+            r#"{"id": "batch_req_3", "response": null"#,
+        ]
+        .join("\n");
+
+        let failed_requests = FailedRequests::from_error_file("file-errors", &content);
+
+        assert_eq!(failed_requests.requests.len(), 2);
+
+        assert_eq!(failed_requests.requests[0].custom_id, "request-1");
+        assert_eq!(failed_requests.requests[0].status_code, Some(400));
+        assert_eq!(failed_requests.requests[0].error.message, "Invalid model");
+        assert_eq!(
+            failed_requests.requests[0].error.code.as_deref(),
+            Some("model_not_found")
+        );
+        assert_eq!(failed_requests.requests[0].error.shape, ErrorShape::OpenAI);
+
+        assert_eq!(failed_requests.requests[1].status_code, None);
+        assert_eq!(
+            failed_requests.requests[1].error.code.as_deref(),
+            Some("batch_expired")
+        );
+
+        assert_eq!(failed_requests.parse_errors.len(), 1);
+        assert_eq!(failed_requests.parse_errors[0].line, 4);
+    }
+}
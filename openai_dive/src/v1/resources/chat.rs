@@ -1,9 +1,13 @@
 use super::shared::{ReasoningEffort, WebSearchContextSize};
+use crate::v1::content_hash::{allowed_fields, hash_fields};
+use crate::v1::resources::lenient;
 use crate::v1::resources::shared::StopToken;
-use crate::v1::resources::shared::{FinishReason, Usage};
+use crate::v1::resources::shared::{FinishReason, Metadata, Usage};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+#[cfg(feature = "stream")]
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt::Display;
 
@@ -16,6 +20,7 @@ pub struct ChatCompletionResponse {
     pub choices: Vec<ChatCompletionChoice>,
     /// The Unix timestamp (in seconds) of when the chat completion was created.
     #[serde(default = "default_created")]
+    #[serde(deserialize_with = "lenient::u32")]
     pub created: u32,
     /// The model used for the chat completion.
     #[serde(default = "default_model")]
@@ -44,6 +49,7 @@ pub struct ChatCompletionChunkResponse {
     pub choices: Vec<ChatCompletionChunkChoice>,
     /// The Unix timestamp (in seconds) of when the chat completion was created. Each chunk has the same timestamp.
     #[serde(default = "default_created")]
+    #[serde(deserialize_with = "lenient::u32")]
     pub created: u32,
     /// The model to generate the completion.
     #[serde(default = "default_model")]
@@ -88,7 +94,7 @@ pub struct ChatCompletionParameters {
     pub reasoning_effort: Option<ReasoningEffort>,
     /// Developer-defined tags and values used for filtering completions in the dashboard.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<HashMap<String, String>>,
+    pub metadata: Option<Metadata>,
     /// Number between -2.0 and 2.0. Positive values penalize new tokens based on their existing frequency in the text so far,
     /// decreasing the model's likelihood to repeat the same line verbatim.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -170,7 +176,11 @@ pub struct ChatCompletionParameters {
     /// Whether to enable parallel function calling during tool use.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parallel_tool_calls: Option<bool>,
+    /// A unique identifier representing your end-user. Replaced by safety_identifier and prompt_cache_key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
     /// A stable identifier used to help detect users of your application that may be violating OpenAI's usage policies.
+    /// Set from `Client::set_end_user` when not set.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub safety_identifier: Option<String>,
     /// Used by OpenAI to cache responses for similar requests to optimize your cache hit rates.
@@ -231,6 +241,26 @@ pub enum ChatCompletionResponseFormat {
     JsonSchema { json_schema: JsonSchema },
 }
 
+/// The options of `Chat::create_json_with_options`.
+#[derive(Debug, Default, Builder, Clone, PartialEq)]
+#[builder(name = "JsonModeOptionsBuilder")]
+#[builder(setter(into), default)]
+pub struct JsonModeOptions {
+    /// Whether the model is asked once to fix content that isn't valid JSON for the type, false by default.
+    pub repair: bool,
+}
+
+/// The typed value of a JSON mode completion, with the content it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonCompletion<T> {
+    pub value: T,
+    /// The content of the message the value was parsed from, with the prose around the JSON.
+    pub content: String,
+    /// The content of the first response, if it had to be repaired by the model.
+    pub repaired_from: Option<String>,
+    pub response: ChatCompletionResponse,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Builder, Clone, PartialEq)]
 #[builder(name = "JsonSchemaBuilder")]
 #[builder(setter(into, strip_option), default)]
@@ -308,6 +338,93 @@ pub enum ChatMessage {
     },
 }
 
+impl ChatCompletionParameters {
+    /// The fields that determine the completion. `store`, `metadata`, `stream`, `stream_options`, `user`,
+    /// `safety_identifier`, `prompt_cache_key` and `query_params` are left out of the content hash.
+    const CONTENT_HASH_FIELDS: &'static [&'static str] = &[
+        "messages",
+        "model",
+        "reasoning_effort",
+        "frequency_penalty",
+        "logit_bias",
+        "logprobs",
+        "top_logprobs",
+        "max_tokens",
+        "max_completion_tokens",
+        "n",
+        "modalities",
+        "prediction",
+        "audio",
+        "presence_penalty",
+        "response_format",
+        "seed",
+        "stop",
+        "temperature",
+        "top_p",
+        "tools",
+        "tool_choice",
+        "parallel_tool_calls",
+        "web_search_options",
+    ];
+
+    /// Returns the SHA-256 digest (lowercase hex) of the fields that determine the completion, to use as cache or dedup key.
+    /// Fields are serialized with sorted keys and without `None` values, so fields added in later versions don't change the hash while they aren't set.
+    /// The `extra_body` is part of the hash as a whole.
+    pub fn content_hash(&self) -> String {
+        let mut fields = allowed_fields(self, Self::CONTENT_HASH_FIELDS);
+
+        if let Some(extra_body) = &self.extra_body {
+            fields.insert("extra_body".to_string(), extra_body.clone());
+        }
+
+        hash_fields(fields)
+    }
+}
+
+impl ChatCompletionResponse {
+    /// Returns the choices ordered by the average log probability of their tokens, highest first.
+    /// Choices without log probabilities (ie. when logprobs were not requested) are placed last.
+    pub fn choices_sorted_by_logprob(&self) -> Vec<&ChatCompletionChoice> {
+        let mut choices: Vec<&ChatCompletionChoice> = self.choices.iter().collect();
+
+        choices.sort_by(|a, b| {
+            let a = a.average_logprob().unwrap_or(f32::NEG_INFINITY);
+            let b = b.average_logprob().unwrap_or(f32::NEG_INFINITY);
+
+            b.total_cmp(&a)
+        });
+
+        choices
+    }
+
+    /// Returns the choice with the highest score, the first one if several have the same score.
+    pub fn best_choice<F>(&self, score: F) -> Option<&ChatCompletionChoice>
+    where
+        F: Fn(&ChatCompletionChoice) -> f32,
+    {
+        // max_by returns the last maximum, so iterate in reverse to return the first one
+        self.choices
+            .iter()
+            .rev()
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+    }
+}
+
+impl ChatCompletionChoice {
+    /// Returns the average log probability of the content tokens, None if log probabilities are missing.
+    pub fn average_logprob(&self) -> Option<f32> {
+        let content = self.logprobs.as_ref()?.content.as_ref()?;
+
+        if content.is_empty() {
+            return None;
+        }
+
+        let total: f32 = content.iter().map(|token| token.token_info.logprob).sum();
+
+        Some(total / content.len() as f32)
+    }
+}
+
 impl ChatMessage {
     /// Get the ChatMessageContent data, if it exists.
     pub fn message(&self) -> Option<&ChatMessageContent> {
@@ -355,6 +472,17 @@ impl ChatMessage {
             ChatMessage::Tool { .. } => None,
         }
     }
+
+    /// Get the role of the message sender.
+    pub fn role(&self) -> Role {
+        match self {
+            ChatMessage::Developer { .. } => Role::Developer,
+            ChatMessage::System { .. } => Role::System,
+            ChatMessage::User { .. } => Role::User,
+            ChatMessage::Assistant { .. } => Role::Assistant,
+            ChatMessage::Tool { .. } => Role::Tool,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -435,14 +563,16 @@ pub struct ToolCall {
 pub struct DeltaToolCall {
     /// The index of the tool call in the list of tool calls.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub index: Option<u32>,
-    /// /// The ID of the tool call.
+    /// The ID of the tool call.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     /// The type of the tool. Currently, only 'function' is supported.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub r#type: Option<String>,
-    /// The function that the model called.
+    /// The fragment of the function that the model called, empty when the delta has no function.
+    #[serde(default, skip_serializing_if = "DeltaFunction::is_empty")]
     pub function: DeltaFunction,
 }
 
@@ -454,9 +584,10 @@ pub struct Function {
     pub arguments: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// A fragment of the function of a streamed tool call. Some providers split the name across deltas as well as the arguments.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct DeltaFunction {
-    /// The name of the function to call.
+    /// The fragment of the name of the function to call.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// The arguments to call the function with, as generated by the model in JSON format.
@@ -467,6 +598,7 @@ pub struct DeltaFunction {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ChatCompletionChoice {
     /// The index of the choice in the list of choices.
+    #[serde(deserialize_with = "lenient::u32")]
     pub index: u32,
     /// A chat completion message generated by the model.
     pub message: ChatMessage,
@@ -526,6 +658,7 @@ pub struct LogProbsContentInfo {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ChatCompletionChunkChoice {
     /// The index of the choice in the list of choices.
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub index: Option<u32>,
     /// A chat completion delta generated by streamed model responses.
     pub delta: DeltaChatMessage,
@@ -583,6 +716,16 @@ pub enum Modality {
     Audio,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Developer,
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ImageUrlDetail {
@@ -738,10 +881,456 @@ impl Default for ChatMessageContent {
     }
 }
 
+impl DeltaChatMessage {
+    /// Get the role of the message sender, if the delta contains it.
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            DeltaChatMessage::Developer { .. } => Some(Role::Developer),
+            DeltaChatMessage::System { .. } => Some(Role::System),
+            DeltaChatMessage::User { .. } => Some(Role::User),
+            DeltaChatMessage::Assistant { .. } => Some(Role::Assistant),
+            DeltaChatMessage::Tool { .. } => Some(Role::Tool),
+            DeltaChatMessage::Untagged { .. } => None,
+        }
+    }
+}
+
+impl From<DeltaFunction> for Function {
+    fn from(value: DeltaFunction) -> Self {
+        Self {
+            name: value.name.unwrap_or_default(),
+            arguments: value.arguments.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<DeltaToolCall> for ToolCall {
+    fn from(value: DeltaToolCall) -> Self {
+        Self {
+            id: value.id.unwrap_or_default(),
+            r#type: value.r#type.unwrap_or_else(|| "function".to_string()),
+            function: value.function.into(),
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+/// Collects the chunks of a streamed chat completion into a `ChatCompletionResponse`,
+/// so streamed and non-streamed responses can be handled the same way.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ChatCompletionAccumulator {
+    id: Option<String>,
+    created: Option<u32>,
+    model: String,
+    system_fingerprint: Option<String>,
+    usage: Option<Usage>,
+    choices: BTreeMap<u32, AccumulatedChoice>,
+}
+
+#[cfg(feature = "stream")]
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AccumulatedChoice {
+    role: Option<Role>,
+    content: Option<String>,
+    reasoning_content: Option<String>,
+    refusal: Option<String>,
+    name: Option<String>,
+    tool_calls: Vec<DeltaToolCall>,
+    tool_call_id: Option<String>,
+    finish_reason: Option<FinishReason>,
+    logprobs: Option<LogProps>,
+}
+
+#[cfg(feature = "stream")]
+impl ChatCompletionAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk of the streamed response.
+    pub fn push(&mut self, chunk: ChatCompletionChunkResponse) {
+        if self.id.is_none() {
+            self.id = chunk.id;
+        }
+
+        if self.created.is_none() {
+            self.created = Some(chunk.created);
+        }
+
+        if self.model.is_empty() {
+            self.model = chunk.model;
+        }
+
+        if chunk.system_fingerprint.is_some() {
+            self.system_fingerprint = chunk.system_fingerprint;
+        }
+
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        for choice in chunk.choices {
+            let accumulated = self
+                .choices
+                .entry(choice.index.unwrap_or_default())
+                .or_default();
+
+            accumulated.push(choice);
+        }
+    }
+
+    /// Returns the response collected from all chunks.
+    pub fn finish(self) -> ChatCompletionResponse {
+        ChatCompletionResponse {
+            id: self.id,
+            choices: self
+                .choices
+                .into_iter()
+                .map(|(index, choice)| choice.into_choice(index))
+                .collect(),
+            created: self.created.unwrap_or_else(default_created),
+            model: self.model,
+            service_tier: None,
+            system_fingerprint: self.system_fingerprint,
+            object: Some("chat.completion".to_string()),
+            usage: self.usage,
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl AccumulatedChoice {
+    fn push(&mut self, choice: ChatCompletionChunkChoice) {
+        if let Some(role) = choice.delta.role() {
+            self.role.get_or_insert(role);
+        }
+
+        let (content, reasoning_content, refusal, name, tool_calls, tool_call_id) =
+            match choice.delta {
+                DeltaChatMessage::Developer { content, name }
+                | DeltaChatMessage::System { content, name }
+                | DeltaChatMessage::User { content, name } => {
+                    (Some(content), None, None, name, None, None)
+                }
+                DeltaChatMessage::Assistant {
+                    content,
+                    reasoning_content,
+                    refusal,
+                    name,
+                    tool_calls,
+                } => (content, reasoning_content, refusal, name, tool_calls, None),
+                DeltaChatMessage::Tool {
+                    content,
+                    tool_call_id,
+                } => (
+                    Some(ChatMessageContent::Text(content)),
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(tool_call_id),
+                ),
+                DeltaChatMessage::Untagged {
+                    content,
+                    reasoning_content,
+                    refusal,
+                    name,
+                    tool_calls,
+                    tool_call_id,
+                } => (
+                    content,
+                    reasoning_content,
+                    refusal,
+                    name,
+                    tool_calls,
+                    tool_call_id,
+                ),
+            };
+
+        append(
+            &mut self.content,
+            content.map(|content| content.to_string()),
+        );
+        append(&mut self.reasoning_content, reasoning_content);
+        append(&mut self.refusal, refusal);
+
+        if self.name.is_none() {
+            self.name = name;
+        }
+
+        if self.tool_call_id.is_none() {
+            self.tool_call_id = tool_call_id;
+        }
+
+        for tool_call in tool_calls.unwrap_or_default() {
+            let existing = self
+                .tool_calls
+                .iter_mut()
+                .find(|existing| tool_call.index.is_some() && existing.index == tool_call.index);
+
+            match existing {
+                Some(existing) => {
+                    // a provider that resends the whole tool call with every delta repeats its id and name,
+                    // only the arguments are new
+                    let resent = tool_call.id.is_some()
+                        && tool_call.id == existing.id
+                        && tool_call.function.name == existing.function.name;
+
+                    if existing.id.is_none() {
+                        existing.id = tool_call.id;
+                    }
+
+                    if existing.r#type.is_none() {
+                        existing.r#type = tool_call.r#type;
+                    }
+
+                    match resent {
+                        true => existing.function.merge(&DeltaFunction {
+                            name: None,
+                            ..tool_call.function
+                        }),
+                        false => existing.function.merge(&tool_call.function),
+                    }
+                }
+                None => self.tool_calls.push(tool_call),
+            }
+        }
+
+        if choice.finish_reason.is_some() {
+            self.finish_reason = choice.finish_reason;
+        }
+
+        if let Some(logprobs) = choice.logprobs {
+            let accumulated = self.logprobs.get_or_insert(LogProps {
+                content: None,
+                refusal: None,
+            });
+
+            if let Some(content) = logprobs.content {
+                accumulated
+                    .content
+                    .get_or_insert_with(Vec::new)
+                    .extend(content);
+            }
+
+            if let Some(refusal) = logprobs.refusal {
+                accumulated
+                    .refusal
+                    .get_or_insert_with(Vec::new)
+                    .extend(refusal);
+            }
+        }
+    }
+
+    fn into_choice(self, index: u32) -> ChatCompletionChoice {
+        let content = self.content.map(ChatMessageContent::Text);
+
+        let message = match self.role.unwrap_or(Role::Assistant) {
+            Role::Developer => ChatMessage::Developer {
+                content: content.unwrap_or_default(),
+                name: self.name,
+            },
+            Role::System => ChatMessage::System {
+                content: content.unwrap_or_default(),
+                name: self.name,
+            },
+            Role::User => ChatMessage::User {
+                content: content.unwrap_or_default(),
+                name: self.name,
+            },
+            Role::Assistant => ChatMessage::Assistant {
+                content,
+                reasoning_content: self.reasoning_content,
+                refusal: self.refusal,
+                name: self.name,
+                audio: None,
+                tool_calls: if self.tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(self.tool_calls.into_iter().map(ToolCall::from).collect())
+                },
+            },
+            Role::Tool => ChatMessage::Tool {
+                content: content.unwrap_or_default(),
+                tool_call_id: self.tool_call_id.unwrap_or_default(),
+            },
+        };
+
+        ChatCompletionChoice {
+            index,
+            message,
+            finish_reason: self.finish_reason,
+            logprobs: self.logprobs,
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+/// An event of a streamed chat completion, as sent by `ChatStreamExt::forward_to`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatStreamEvent {
+    /// A content delta of the choice with the given index.
+    Content { index: u32, delta: String },
+    /// A reasoning content delta of the choice with the given index.
+    ReasoningContent { index: u32, delta: String },
+    /// A refusal delta of the choice with the given index.
+    Refusal { index: u32, delta: String },
+    /// A tool call delta of the choice with the given index, merge the deltas by their own index.
+    ToolCall { index: u32, delta: DeltaToolCall },
+    /// The choice with the given index is finished.
+    Finished { index: u32, reason: FinishReason },
+    /// The usage of the request, sent with the last chunk if `stream_options.include_usage` is set.
+    Usage(Usage),
+    /// The response collected from all chunks, always the last event.
+    Done(ChatCompletionResponse),
+}
+
+#[cfg(feature = "stream")]
+impl ChatStreamEvent {
+    /// Returns the events of a chunk, in order of its choices. Empty deltas are skipped.
+    pub fn from_chunk(chunk: &ChatCompletionChunkResponse) -> Vec<Self> {
+        let mut events = vec![];
+
+        for choice in &chunk.choices {
+            let index = choice.index.unwrap_or_default();
+
+            let (content, reasoning_content, refusal, tool_calls) = match &choice.delta {
+                DeltaChatMessage::Developer { content, .. }
+                | DeltaChatMessage::System { content, .. }
+                | DeltaChatMessage::User { content, .. } => {
+                    (Some(content.to_string()), None, None, None)
+                }
+                DeltaChatMessage::Assistant {
+                    content,
+                    reasoning_content,
+                    refusal,
+                    tool_calls,
+                    ..
+                }
+                | DeltaChatMessage::Untagged {
+                    content,
+                    reasoning_content,
+                    refusal,
+                    tool_calls,
+                    ..
+                } => (
+                    content.as_ref().map(|content| content.to_string()),
+                    reasoning_content.clone(),
+                    refusal.clone(),
+                    tool_calls.as_ref(),
+                ),
+                DeltaChatMessage::Tool { content, .. } => (Some(content.clone()), None, None, None),
+            };
+
+            let non_empty = |delta: Option<String>| delta.filter(|delta| !delta.is_empty());
+
+            if let Some(delta) = non_empty(reasoning_content) {
+                events.push(ChatStreamEvent::ReasoningContent { index, delta });
+            }
+
+            if let Some(delta) = non_empty(content) {
+                events.push(ChatStreamEvent::Content { index, delta });
+            }
+
+            if let Some(delta) = non_empty(refusal) {
+                events.push(ChatStreamEvent::Refusal { index, delta });
+            }
+
+            for delta in tool_calls.into_iter().flatten() {
+                events.push(ChatStreamEvent::ToolCall {
+                    index,
+                    delta: delta.clone(),
+                });
+            }
+
+            if let Some(reason) = &choice.finish_reason {
+                events.push(ChatStreamEvent::Finished {
+                    index,
+                    reason: reason.clone(),
+                });
+            }
+        }
+
+        if let Some(usage) = &chunk.usage {
+            events.push(ChatStreamEvent::Usage(usage.clone()));
+        }
+
+        events
+    }
+}
+
+#[cfg(feature = "stream")]
+/// An event of a streamed chat completion for UIs, as returned by `ChatStreamExt::into_ui_events`.
+/// Only the first choice is followed, appends are never empty.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiEvent {
+    /// Text to append to the message, the content or a refusal.
+    TextAppend(String),
+    /// A tool call with the given index started, sent once with its first fragment.
+    ToolCallStarted { index: u32 },
+    /// A fragment of the function name of the tool call with the given index, some providers split the name.
+    ToolCallNameAppend { index: u32, fragment: String },
+    /// A fragment of the JSON arguments of the tool call with the given index.
+    ToolCallArgumentsAppend { index: u32, fragment: String },
+    /// Reasoning content to append.
+    ReasoningAppend(String),
+    /// The stream is finished, always the last event. The usage is only sent if `stream_options.include_usage` is set.
+    Done {
+        finish_reason: Option<FinishReason>,
+        usage: Option<Usage>,
+    },
+}
+
+#[cfg(feature = "stream")]
+/// The options of `ChatStreamExt::into_ui_events_with_options`.
+#[derive(Debug, Default, Builder, Clone)]
+#[builder(name = "UiEventOptionsBuilder")]
+#[builder(setter(into, strip_option), default)]
+pub struct UiEventOptions {
+    /// Consecutive appends of the same kind within this window of the first one are merged into one event,
+    /// ie. 30 ms to render at most about 30 times per second. Disabled by default.
+    pub coalesce_window: Option<std::time::Duration>,
+    /// The clock of the window, the system clock by default.
+    #[cfg(feature = "reqwest")]
+    pub clock: Option<std::sync::Arc<dyn crate::v1::clock::Clock>>,
+}
+
+#[cfg(feature = "stream")]
+fn append(target: &mut Option<String>, value: Option<String>) {
+    if let Some(value) = value {
+        target.get_or_insert_with(String::new).push_str(&value);
+    }
+}
+
+#[cfg(feature = "stream")]
+impl From<ChatCompletionChunkResponse> for ChatCompletionResponse {
+    fn from(value: ChatCompletionChunkResponse) -> Self {
+        let mut accumulator = ChatCompletionAccumulator::new();
+
+        accumulator.push(value);
+
+        accumulator.finish()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl FromIterator<ChatCompletionChunkResponse> for ChatCompletionResponse {
+    fn from_iter<T: IntoIterator<Item = ChatCompletionChunkResponse>>(iter: T) -> Self {
+        let mut accumulator = ChatCompletionAccumulator::new();
+
+        for chunk in iter {
+            accumulator.push(chunk);
+        }
+
+        accumulator.finish()
+    }
+}
+
 impl DeltaFunction {
+    /// Appends the fragments of the name and the arguments of the next delta.
     pub fn merge(&mut self, other: &Self) {
-        if self.name.is_none() && other.name.is_some() {
-            self.name.clone_from(&other.name);
+        if let Some(name) = other.name.as_deref().filter(|name| !name.is_empty()) {
+            self.name.get_or_insert_with(String::new).push_str(name);
         }
 
         if let Some(arguments) = &other.arguments {
@@ -873,4 +1462,179 @@ mod tests {
         let deserialized: ChatMessage = serde_json::from_str(serialized.as_str()).unwrap();
         assert_eq!(deserialized, tool_message)
     }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_chat_completion_accumulator_collects_chunks_into_response() {
+        use crate::v1::resources::chat::{
+            ChatCompletionAccumulator, ChatCompletionChunkResponse, ToolCall,
+        };
+        use crate::v1::resources::shared::FinishReason;
+
+        let chunks = [
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"content":"lo","tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":"{\"city\":"}}]}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Hanoi\"}"}}]},"finish_reason":"tool_calls"}]}"#,
+        ];
+
+        let mut accumulator = ChatCompletionAccumulator::new();
+
+        for chunk in chunks {
+            accumulator.push(serde_json::from_str::<ChatCompletionChunkResponse>(chunk).unwrap());
+        }
+
+        let response = accumulator.finish();
+
+        assert_eq!(response.id, Some("chatcmpl-1".to_string()));
+        assert_eq!(response.model, "gpt-4o");
+        assert_eq!(response.choices.len(), 1);
+        assert_eq!(
+            response.choices[0].finish_reason,
+            Some(FinishReason::ToolCalls)
+        );
+
+        match &response.choices[0].message {
+            ChatMessage::Assistant {
+                content,
+                tool_calls,
+                ..
+            } => {
+                assert_eq!(
+                    content,
+                    &Some(ChatMessageContent::Text("Hello".to_string()))
+                );
+
+                let tool_calls: &Vec<ToolCall> = tool_calls.as_ref().unwrap();
+                assert_eq!(tool_calls.len(), 1);
+                assert_eq!(tool_calls[0].id, "call_1");
+                assert_eq!(tool_calls[0].function.name, "get_weather");
+                assert_eq!(tool_calls[0].function.arguments, "{\"city\":\"Hanoi\"}");
+            }
+            _ => panic!("Accumulated message should be an assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_choices_sorted_by_logprob_and_best_choice() {
+        use crate::v1::resources::chat::ChatCompletionResponse;
+
+        let response: ChatCompletionResponse = serde_json::from_str(
+            r#"{"choices":[
+                {"index":0,"message":{"role":"assistant","content":"A"},"logprobs":{"content":[{"token":"A","logprob":-1.5,"bytes":null,"top_logprobs":[]}]}},
+                {"index":1,"message":{"role":"assistant","content":"B"}},
+                {"index":2,"message":{"role":"assistant","content":"C C"},"logprobs":{"content":[{"token":"C","logprob":-0.1,"bytes":null,"top_logprobs":[]},{"token":" C","logprob":-0.3,"bytes":null,"top_logprobs":[]}]}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let indexes: Vec<u32> = response
+            .choices_sorted_by_logprob()
+            .iter()
+            .map(|choice| choice.index)
+            .collect();
+
+        assert_eq!(indexes, vec![2, 0, 1]);
+
+        let best = response
+            .best_choice(|choice| {
+                choice
+                    .message
+                    .message()
+                    .map(|content| content.to_string().len() as f32)
+                    .unwrap_or_default()
+            })
+            .unwrap();
+
+        assert_eq!(best.index, 2);
+        assert_eq!(response.best_choice(|_| 0.0).unwrap().index, 0);
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_chat_completion_accumulator_demultiplexes_interleaved_choices() {
+        use crate::v1::resources::chat::{ChatCompletionAccumulator, ChatCompletionChunkResponse};
+        use crate::v1::resources::shared::FinishReason;
+
+        let chunks = [
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":2,"delta":{"role":"assistant","content":"Ho"}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":1,"delta":{"role":"assistant","content":"Hi"}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"content":"Hel"}},{"index":2,"delta":{"content":"wdy"}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":1,"delta":{"content":" there"},"finish_reason":"stop"}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"content":"lo"},"finish_reason":"stop"}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":2,"delta":{},"finish_reason":"length"}]}"#,
+        ];
+
+        let mut accumulator = ChatCompletionAccumulator::new();
+
+        for chunk in chunks {
+            accumulator.push(serde_json::from_str::<ChatCompletionChunkResponse>(chunk).unwrap());
+        }
+
+        let response = accumulator.finish();
+
+        let messages: Vec<(u32, String, Option<FinishReason>)> = response
+            .choices
+            .iter()
+            .map(|choice| {
+                (
+                    choice.index,
+                    choice.message.message().unwrap().to_string(),
+                    choice.finish_reason.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    0,
+                    "Hello".to_string(),
+                    Some(FinishReason::StopSequenceReached)
+                ),
+                (
+                    1,
+                    "Hi there".to_string(),
+                    Some(FinishReason::StopSequenceReached)
+                ),
+                (
+                    2,
+                    "Howdy".to_string(),
+                    Some(FinishReason::TokenLimitReached)
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_chat_completion_accumulator_ignores_a_resent_tool_call_name() {
+        use crate::v1::resources::chat::{ChatCompletionAccumulator, ChatCompletionChunkResponse};
+
+        // the id and the whole name are sent again with every delta
+        let chunks = [
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":"{\"city\":"}}]}}]}"#,
+            r#"{"id":"chatcmpl-1","created":1,"model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":"\"Paris\"}"}}]}}]}"#,
+        ];
+
+        let mut accumulator = ChatCompletionAccumulator::new();
+
+        for chunk in chunks {
+            accumulator.push(serde_json::from_str::<ChatCompletionChunkResponse>(chunk).unwrap());
+        }
+
+        let ChatMessage::Assistant {
+            tool_calls: Some(tool_calls),
+            ..
+        } = &accumulator.finish().choices[0].message
+        else {
+            panic!("expected an assistant message with tool calls");
+        };
+
+        assert_eq!(tool_calls[0].id, "call_1");
+        assert_eq!(tool_calls[0].r#type, "function");
+        assert_eq!(tool_calls[0].function.name, "get_weather");
+        assert_eq!(tool_calls[0].function.arguments, r#"{"city":"Paris"}"#);
+    }
 }
@@ -1,6 +1,10 @@
+use crate::v1::content_hash::content_hash;
+use crate::v1::resources::lenient;
 use crate::v1::resources::shared::Usage;
 use derive_builder::Builder;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 
 #[derive(Serialize, Deserialize, Debug, Default, Builder, Clone, PartialEq)]
 #[builder(name = "EmbeddingParametersBuilder")]
@@ -48,16 +52,56 @@ pub enum EmbeddingInput {
     IntegerArrayArray(Vec<Vec<u32>>),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl EmbeddingParameters {
+    /// The fields that determine the embeddings, `user` is left out of the content hash.
+    const CONTENT_HASH_FIELDS: &'static [&'static str] =
+        &["input", "model", "encoding_format", "dimensions"];
+
+    /// Returns the SHA-256 digest (lowercase hex) of the fields that determine the embeddings, to use as cache or dedup key.
+    /// Fields are serialized with sorted keys and without `None` values, so fields added in later versions don't change the hash while they aren't set.
+    pub fn content_hash(&self) -> String {
+        content_hash(self, Self::CONTENT_HASH_FIELDS)
+    }
+}
+
+impl From<&str> for EmbeddingInput {
+    fn from(input: &str) -> Self {
+        EmbeddingInput::String(input.to_string())
+    }
+}
+
+impl From<String> for EmbeddingInput {
+    fn from(input: String) -> Self {
+        EmbeddingInput::String(input)
+    }
+}
+
+impl From<Vec<String>> for EmbeddingInput {
+    fn from(inputs: Vec<String>) -> Self {
+        EmbeddingInput::StringArray(inputs)
+    }
+}
+
+/// The type of the embedding values. The API returns values with f32 precision,
+/// the `embeddings-f64` feature stores them as f64.
+#[cfg(not(feature = "embeddings-f64"))]
+pub type EmbeddingFloat = f32;
+/// The type of the embedding values. The API returns values with f32 precision,
+/// the `embeddings-f64` feature stores them as f64.
+#[cfg(feature = "embeddings-f64")]
+pub type EmbeddingFloat = f64;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum EmbeddingOutput {
-    Float(Vec<f64>),
+    Float(Vec<EmbeddingFloat>),
     Base64(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Embedding {
     /// The index of the embedding in the list of embeddings.
+    #[serde(deserialize_with = "lenient::u32")]
     pub index: u32,
     /// The embedding vector, which is a list of floats. Or String when encoding format is set to 'base64'.
     pub embedding: EmbeddingOutput,
@@ -72,8 +116,94 @@ pub enum EmbeddingEncodingFormat {
     Base64,
 }
 
+impl<'de> Deserialize<'de> for EmbeddingOutput {
+    /// Reads the values straight into the vector. An untagged enum would buffer the whole array first.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EmbeddingOutputVisitor;
+
+        impl<'de> Visitor<'de> for EmbeddingOutputVisitor {
+            type Value = EmbeddingOutput;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a list of floats or a base64 string")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+
+                Ok(EmbeddingOutput::Float(values))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(EmbeddingOutput::Base64(value.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+                Ok(EmbeddingOutput::Base64(value))
+            }
+        }
+
+        deserializer.deserialize_any(EmbeddingOutputVisitor)
+    }
+}
+
 impl Default for EmbeddingInput {
     fn default() -> Self {
         EmbeddingInput::String(String::new())
     }
 }
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use crate::v1::error::APIError;
+    use crate::v1::helpers::parse_response;
+    use crate::v1::resources::embedding::{EmbeddingOutput, EmbeddingResponse};
+
+    #[test]
+    fn test_embedding_response_deserialization() {
+        let json = r#"{
+            "object": "list",
+            "data": [
+                {"object": "embedding", "index": 0, "embedding": [0.0023064255, -0.009327292, 1e-7]},
+                {"object": "embedding", "index": 1, "embedding": "AAAAAA=="}
+            ],
+            "model": "text-embedding-3-small",
+            "usage": {"prompt_tokens": 8, "total_tokens": 8}
+        }"#;
+
+        let response: EmbeddingResponse = parse_response(json).unwrap();
+
+        assert_eq!(
+            response.data[0].embedding,
+            EmbeddingOutput::Float(vec![0.0023064255, -0.009327292, 1e-7])
+        );
+        assert_eq!(
+            response.data[1].embedding,
+            EmbeddingOutput::Base64("AAAAAA==".to_string())
+        );
+
+        // the values are written back as they were received
+        let serialized = serde_json::to_string(&response.data[0]).unwrap();
+
+        assert!(serialized.contains("[0.0023064255,-0.009327292,1e-7]"));
+
+        assert!(matches!(
+            parse_response::<EmbeddingResponse>(r#"{"error": {"message": "Invalid model"}}"#),
+            Err(APIError::InvalidRequestError { .. })
+        ));
+        assert!(matches!(
+            parse_response::<EmbeddingResponse>(r#"{"object": "list"}"#),
+            Err(APIError::ParseError(_))
+        ));
+    }
+}
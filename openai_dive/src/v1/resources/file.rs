@@ -17,6 +17,20 @@ pub struct File {
     pub object: String,
     /// The intended purpose of the file.
     pub purpose: FilePurpose,
+    /// Whether the file has been processed, fine-tuning jobs can only be created once it is `processed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<FileStatus>,
+    /// Why the file could not be processed, when the status is `error`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_details: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Uploaded,
+    Processed,
+    Error,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -1,5 +1,9 @@
+use crate::v1::resources::chat::{ChatCompletionTool, ChatMessage};
+use crate::v1::resources::shared::{FileUpload, Metadata};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FineTuningJob {
@@ -73,6 +77,136 @@ pub struct CreateFineTuningJobParameters {
     /// If a seed is not specified, one will be generated for you.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u32>,
+    /// The method used for fine-tuning, supervised by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<FineTuningMethod>,
+    /// Set of 16 key-value pairs that can be attached to the job.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningMethod {
+    /// The type of method, which is either supervised, dpo or reinforcement.
+    pub r#type: FineTuningMethodType,
+    /// The configuration of the supervised method, ie. its hyperparameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supervised: Option<Value>,
+    /// The configuration of the DPO method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpo: Option<Value>,
+    /// The configuration of the reinforcement method, ie. its grader.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reinforcement: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FineTuningMethodType {
+    Supervised,
+    Dpo,
+    Reinforcement,
+}
+
+/// The files and parameters of `FineTuning::create_from_files`.
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(name = "CreateFromFilesBuilder")]
+#[builder(setter(into, strip_option), default)]
+pub struct CreateFromFiles {
+    /// The JSONL file with the training data, uploaded with purpose `fine-tune`.
+    pub training: FileUpload,
+    /// The JSONL file with the validation data, uploaded with purpose `fine-tune`.
+    pub validation: Option<FileUpload>,
+    /// The name of the model to fine-tune.
+    pub base_model: String,
+    /// The method used for fine-tuning, supervised by default.
+    pub method: Option<FineTuningMethod>,
+    /// A string of up to 18 characters that will be added to your fine-tuned model name.
+    pub suffix: Option<String>,
+    /// Set of 16 key-value pairs that can be attached to the job.
+    pub metadata: Option<Metadata>,
+    /// How often the status of the uploaded files is checked. Defaults to 5 seconds.
+    pub poll_interval: Duration,
+    /// How long to wait until the uploaded files are processed. Defaults to 10 minutes.
+    pub timeout: Duration,
+    /// Whether every line of the files is checked to be a JSON object before uploading. Defaults to true.
+    pub validate_lines: bool,
+}
+
+impl Default for CreateFromFiles {
+    fn default() -> Self {
+        Self {
+            training: FileUpload::default(),
+            validation: None,
+            base_model: String::new(),
+            method: None,
+            suffix: None,
+            metadata: None,
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(10 * 60),
+            validate_lines: true,
+        }
+    }
+}
+
+/// A line of a chat fine-tuning dataset, see `to_jsonl_line`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuneExample {
+    pub messages: Vec<FineTuneMessage>,
+    /// The tools the assistant messages can call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ChatCompletionTool>>,
+}
+
+impl FineTuneExample {
+    pub fn new(messages: Vec<FineTuneMessage>) -> Self {
+        Self {
+            messages,
+            tools: None,
+        }
+    }
+
+    /// Returns the example as a line of a JSONL file, without the line break.
+    pub fn to_jsonl_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A message of a fine-tuning example, a chat message with the `weight` of the fine-tuning format.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuneMessage {
+    #[serde(flatten)]
+    pub message: ChatMessage,
+    /// Whether the model is trained on the message, 0 excludes it from the loss. Only allowed on assistant messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u8>,
+}
+
+impl FineTuneMessage {
+    /// Sets the weight of an assistant message, 0 to exclude it from training and 1 to include it.
+    pub fn with_weight(mut self, weight: u8) -> Self {
+        self.weight = Some(weight);
+
+        self
+    }
+}
+
+impl From<ChatMessage> for FineTuneMessage {
+    fn from(message: ChatMessage) -> Self {
+        Self {
+            message,
+            weight: None,
+        }
+    }
+}
+
+/// The job created by `FineTuning::create_from_files`, with the IDs of the uploaded files.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FineTuningJobFromFiles {
+    pub job: FineTuningJob,
+    pub training_file_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_file_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -1,3 +1,4 @@
+use crate::v1::content_hash::content_hash;
 #[cfg(feature = "download")]
 use crate::v1::error::APIError;
 #[cfg(feature = "download")]
@@ -290,15 +291,15 @@ pub enum ResponseFormat {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ImageUsage {
     /// The number of tokens (images and text) in the input prompt.
-    #[serde(default="zero")]
+    #[serde(default = "zero")]
     pub input_tokens: u32,
     /// The input tokens detailed information for the image generation.
     pub input_tokens_details: Option<InputTokensDetails>,
     /// The number of output tokens generated by the model.
-    #[serde(default="zero")]
+    #[serde(default = "zero")]
     pub output_tokens: u32,
     /// The total number of tokens (images and text) used for the image generation.
-    #[serde(default="zero")]
+    #[serde(default = "zero")]
     pub total_tokens: u32,
 }
 fn zero() -> u32 {
@@ -328,6 +329,30 @@ pub enum ImageData {
     },
 }
 
+impl CreateImageParameters {
+    /// The fields that determine the generated images, `user`, `safety_identifier`, `prompt_cache_key`,
+    /// `stream` and `partial_images` are left out of the content hash.
+    const CONTENT_HASH_FIELDS: &'static [&'static str] = &[
+        "prompt",
+        "background",
+        "model",
+        "moderation",
+        "n",
+        "output_compression",
+        "output_format",
+        "quality",
+        "response_format",
+        "size",
+        "style",
+    ];
+
+    /// Returns the SHA-256 digest (lowercase hex) of the fields that determine the generated images, to use as cache or dedup key.
+    /// Fields are serialized with sorted keys and without `None` values, so fields added in later versions don't change the hash while they aren't set.
+    pub fn content_hash(&self) -> String {
+        content_hash(self, Self::CONTENT_HASH_FIELDS)
+    }
+}
+
 impl ImageResponse {
     #[cfg(feature = "download")]
     pub async fn save(&self, path: &str) -> Result<Vec<String>, APIError> {
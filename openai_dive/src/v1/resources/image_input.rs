@@ -0,0 +1,385 @@
+use crate::v1::error::APIError;
+use crate::v1::resources::chat::{
+    ChatMessageContentPart, ChatMessageImageContentPart, ImageUrlDetail, ImageUrlType,
+};
+#[cfg(feature = "image-processing")]
+use base64::{engine::general_purpose, Engine as _};
+
+const LOW_DETAIL_TOKENS: u32 = 85;
+const HIGH_DETAIL_TILE_TOKENS: u32 = 170;
+const HIGH_DETAIL_MAX_DIMENSION: u32 = 2048;
+const HIGH_DETAIL_SHORTEST_SIDE: u32 = 768;
+const LOW_DETAIL_MAX_DIMENSION: u32 = 512;
+const TILE_SIZE: u32 = 512;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePolicy {
+    /// The maximum width and height of the image that is sent.
+    pub max_dimension: u32,
+    /// The detail level the image is sent with.
+    pub target_detail: ImageUrlDetail,
+    /// The quality used when re-encoding the image as JPEG.
+    pub jpeg_quality: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInput {
+    /// The content part with the image as data URL.
+    pub part: ChatMessageContentPart,
+    /// The width of the image that is sent, after applying the EXIF orientation and downscaling.
+    pub width: u32,
+    /// The height of the image that is sent, after applying the EXIF orientation and downscaling.
+    pub height: u32,
+    /// The estimated number of input tokens of the image.
+    pub estimated_tokens: u32,
+}
+
+impl Default for ImagePolicy {
+    fn default() -> Self {
+        Self {
+            max_dimension: HIGH_DETAIL_MAX_DIMENSION,
+            target_detail: ImageUrlDetail::High,
+            jpeg_quality: 85,
+        }
+    }
+}
+
+impl ImageFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+
+    /// Detects the format from the magic bytes, the error lists the detected format if it is not supported by the API.
+    pub fn detect(bytes: &[u8]) -> Result<Self, APIError> {
+        let detected = match bytes {
+            [0x89, b'P', b'N', b'G', ..] => return Ok(ImageFormat::Png),
+            [0xFF, 0xD8, 0xFF, ..] => return Ok(ImageFormat::Jpeg),
+            [b'G', b'I', b'F', b'8', ..] => return Ok(ImageFormat::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+                return Ok(ImageFormat::Webp)
+            }
+            [b'B', b'M', ..] => "bmp",
+            [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => "tiff",
+            [_, _, _, _, b'f', b't', b'y', b'p', b'h', b'e', b'i', b'c', ..]
+            | [_, _, _, _, b'f', b't', b'y', b'p', b'm', b'i', b'f', b'1', ..] => "heic",
+            [_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f', ..] => "avif",
+            _ => "unknown",
+        };
+
+        Err(APIError::ValidationError {
+            field: "image".to_string(),
+            value: detected.to_string(),
+            allowed: "png, jpeg, gif or webp".to_string(),
+        })
+    }
+}
+
+impl ChatMessageContentPart {
+    /// Builds an image content part from the image bytes. The image is decoded, rotated by its EXIF orientation,
+    /// downscaled to fit the policy and re-encoded as JPEG with the quality of the policy (or as PNG if it has transparency).
+    /// Returns the dimensions of the image that is sent and the estimated token cost.
+    /// Without the `image-processing` feature a supported image returns `APIError::FeatureDisabled`.
+    pub fn image_auto(bytes: &[u8], policy: ImagePolicy) -> Result<ImageInput, APIError> {
+        let format = ImageFormat::detect(bytes)?;
+
+        let (url, width, height) = encode(format, bytes, &policy)?;
+
+        Ok(ImageInput {
+            part: ChatMessageContentPart::Image(ChatMessageImageContentPart {
+                r#type: "image_url".to_string(),
+                image_url: ImageUrlType {
+                    url,
+                    detail: Some(policy.target_detail.clone()),
+                },
+            }),
+            width,
+            height,
+            estimated_tokens: estimate_image_tokens(width, height, &policy.target_detail),
+        })
+    }
+}
+
+/// Decodes, orients, downscales and re-encodes the image, returns it as data URL with its dimensions.
+#[cfg(feature = "image-processing")]
+fn encode(
+    format: ImageFormat,
+    bytes: &[u8],
+    policy: &ImagePolicy,
+) -> Result<(String, u32, u32), APIError> {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::imageops::FilterType;
+    use image::{DynamicImage, ImageDecoder, ImageReader};
+    use std::io::Cursor;
+
+    let decode_error = |error: image::ImageError| {
+        APIError::ParseError(format!("could not decode the image: {error}"))
+    };
+    let encode_error = |error: image::ImageError| {
+        APIError::ParseError(format!("could not encode the image: {error}"))
+    };
+
+    let mut reader = ImageReader::new(Cursor::new(bytes));
+    reader.set_format(match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::Gif => image::ImageFormat::Gif,
+        ImageFormat::Webp => image::ImageFormat::WebP,
+    });
+
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let orientation = decoder.orientation().map_err(decode_error)?;
+
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+
+    let (width, height) = scaled_dimensions(image.width(), image.height(), policy);
+
+    if (width, height) != (image.width(), image.height()) {
+        image = image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+
+    let mut encoded = vec![];
+
+    // JPEG has no alpha channel, so transparent images keep it as PNG
+    let format = if image.color().has_alpha() {
+        image
+            .write_to(&mut Cursor::new(&mut encoded), image::ImageFormat::Png)
+            .map_err(encode_error)?;
+
+        ImageFormat::Png
+    } else {
+        image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(
+                &mut encoded,
+                policy.jpeg_quality,
+            ))
+            .map_err(encode_error)?;
+
+        ImageFormat::Jpeg
+    };
+
+    let url = format!(
+        "data:{};base64,{}",
+        format.mime_type(),
+        general_purpose::STANDARD.encode(encoded)
+    );
+
+    Ok((url, width, height))
+}
+
+#[cfg(not(feature = "image-processing"))]
+fn encode(
+    _format: ImageFormat,
+    _bytes: &[u8],
+    _policy: &ImagePolicy,
+) -> Result<(String, u32, u32), APIError> {
+    Err(APIError::FeatureDisabled("image-processing".to_string()))
+}
+
+/// Returns the dimensions that fit the policy, keeping the aspect ratio.
+pub fn scaled_dimensions(width: u32, height: u32, policy: &ImagePolicy) -> (u32, u32) {
+    let max_dimension = match policy.target_detail {
+        ImageUrlDetail::Low => policy.max_dimension.min(LOW_DETAIL_MAX_DIMENSION),
+        _ => policy.max_dimension,
+    };
+
+    fit_within(width, height, max_dimension)
+}
+
+/// Estimates the input tokens of an image with the tile-based formula of the vision models.
+/// Auto is estimated as high detail, as the model may choose it.
+pub fn estimate_image_tokens(width: u32, height: u32, detail: &ImageUrlDetail) -> u32 {
+    if *detail == ImageUrlDetail::Low {
+        return LOW_DETAIL_TOKENS;
+    }
+
+    let (width, height) = fit_within(width, height, HIGH_DETAIL_MAX_DIMENSION);
+
+    let shortest_side = width.min(height);
+
+    let (width, height) = if shortest_side > HIGH_DETAIL_SHORTEST_SIDE {
+        (
+            scale(width, HIGH_DETAIL_SHORTEST_SIDE, shortest_side),
+            scale(height, HIGH_DETAIL_SHORTEST_SIDE, shortest_side),
+        )
+    } else {
+        (width, height)
+    };
+
+    let tiles = width.div_ceil(TILE_SIZE) * height.div_ceil(TILE_SIZE);
+
+    HIGH_DETAIL_TILE_TOKENS * tiles + LOW_DETAIL_TOKENS
+}
+
+fn fit_within(width: u32, height: u32, max_dimension: u32) -> (u32, u32) {
+    let longest_side = width.max(height);
+
+    if longest_side <= max_dimension {
+        return (width, height);
+    }
+
+    (
+        scale(width, max_dimension, longest_side).max(1),
+        scale(height, max_dimension, longest_side).max(1),
+    )
+}
+
+fn scale(value: u32, numerator: u32, denominator: u32) -> u32 {
+    (value as u64 * numerator as u64 / denominator as u64) as u32
+}
+
+#[cfg(all(test, feature = "image-processing"))]
+mod tests {
+    use crate::v1::error::APIError;
+    use crate::v1::resources::chat::{ChatMessageContentPart, ImageUrlDetail};
+    use crate::v1::resources::image_input::{estimate_image_tokens, ImageInput, ImagePolicy};
+    use base64::{engine::general_purpose, Engine as _};
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{DynamicImage, RgbImage, RgbaImage};
+    use std::io::Cursor;
+
+    fn encoded(image: DynamicImage, format: image::ImageFormat) -> Vec<u8> {
+        let mut bytes = vec![];
+        image
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+
+        bytes
+    }
+
+    /// Returns the mime type and the decoded image of the data URL.
+    fn sent_image(input: &ImageInput) -> (String, DynamicImage) {
+        let ChatMessageContentPart::Image(part) = &input.part else {
+            panic!("expected an image part");
+        };
+
+        let (mime_type, data) = part
+            .image_url
+            .url
+            .strip_prefix("data:")
+            .and_then(|url| url.split_once(";base64,"))
+            .unwrap();
+
+        let bytes = general_purpose::STANDARD.decode(data).unwrap();
+
+        (
+            mime_type.to_string(),
+            image::load_from_memory(&bytes).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_image_auto_downscales_and_reencodes() {
+        let png = encoded(
+            DynamicImage::ImageRgb8(RgbImage::new(2400, 600)),
+            image::ImageFormat::Png,
+        );
+
+        let image = ChatMessageContentPart::image_auto(&png, ImagePolicy::default()).unwrap();
+        let (mime_type, sent) = sent_image(&image);
+
+        assert_eq!((image.width, image.height), (2048, 512));
+        assert_eq!((sent.width(), sent.height()), (2048, 512));
+        assert_eq!(mime_type, "image/jpeg");
+        assert_eq!(image.estimated_tokens, 765);
+
+        let policy = ImagePolicy {
+            target_detail: ImageUrlDetail::Low,
+            ..Default::default()
+        };
+
+        let image = ChatMessageContentPart::image_auto(&png, policy).unwrap();
+
+        assert_eq!((image.width, image.height), (512, 128));
+        assert_eq!(image.estimated_tokens, 85);
+
+        // the quality of the policy is used for the JPEG
+        let noise = RgbImage::from_fn(256, 256, |x, y| {
+            image::Rgb([(x * 7) as u8, (y * 13) as u8, (x ^ y) as u8])
+        });
+        let png = encoded(DynamicImage::ImageRgb8(noise), image::ImageFormat::Png);
+        let size = |jpeg_quality| {
+            let policy = ImagePolicy {
+                jpeg_quality,
+                ..Default::default()
+            };
+
+            match ChatMessageContentPart::image_auto(&png, policy)
+                .unwrap()
+                .part
+            {
+                ChatMessageContentPart::Image(part) => part.image_url.url.len(),
+                _ => unreachable!(),
+            }
+        };
+
+        assert!(size(30) < size(95));
+    }
+
+    #[test]
+    fn test_image_auto_keeps_transparency_as_png() {
+        let png = encoded(
+            DynamicImage::ImageRgba8(RgbaImage::new(64, 32)),
+            image::ImageFormat::Png,
+        );
+
+        let image = ChatMessageContentPart::image_auto(&png, ImagePolicy::default()).unwrap();
+        let (mime_type, sent) = sent_image(&image);
+
+        assert_eq!(mime_type, "image/png");
+        assert_eq!((sent.width(), sent.height()), (64, 32));
+        assert_eq!(image.estimated_tokens, 255);
+
+        assert_eq!(
+            estimate_image_tokens(2048, 4096, &ImageUrlDetail::High),
+            1105
+        );
+
+        let bmp = b"BM\0\0\0\0\0\0\0\0";
+
+        assert!(matches!(
+            ChatMessageContentPart::image_auto(bmp, ImagePolicy::default()),
+            Err(APIError::ValidationError { value, .. }) if value == "bmp"
+        ));
+    }
+
+    #[test]
+    fn test_image_auto_respects_exif_orientation() {
+        let mut jpeg = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(40, 30))
+            .write_with_encoder(JpegEncoder::new(&mut jpeg))
+            .unwrap();
+
+        // APP1 with an EXIF orientation of 6 (rotated 90 degrees), right after the start of image marker
+        let mut exif = vec![0xFF, 0xE1, 0x00, 0x22];
+        exif.extend_from_slice(b"Exif\0\0MM\0\x2a\0\0\0\x08");
+        exif.extend_from_slice(&[0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        exif.extend_from_slice(&[0x00, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        jpeg.splice(2..2, exif);
+
+        let image = ChatMessageContentPart::image_auto(&jpeg, ImagePolicy::default()).unwrap();
+        let (_, sent) = sent_image(&image);
+
+        assert_eq!((image.width, image.height), (30, 40));
+        assert_eq!((sent.width(), sent.height()), (30, 40));
+
+        assert!(matches!(
+            ChatMessageContentPart::image_auto(&jpeg[..64], ImagePolicy::default()),
+            Err(APIError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! Deserializers for response fields that some compatible servers send in another representation,
+//! ie. numbers as strings (`"created": "1715000000"`) and booleans as 0/1.
+//! Serialization is not affected, the fields are always sent back in the standard form.
+use serde::de::{Error, Unexpected};
+use serde::{Deserialize, Deserializer};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    Float(f64),
+    String(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoolOrNumber {
+    Bool(bool),
+    Number(u64),
+    String(String),
+}
+
+impl NumberOrString {
+    fn into_u32<E: Error>(self) -> Result<u32, E> {
+        let number = match self {
+            NumberOrString::Number(number) => number,
+            NumberOrString::Float(float) if float.fract() == 0.0 && float >= 0.0 => float as u64,
+            NumberOrString::Float(float) => {
+                return Err(E::invalid_value(Unexpected::Float(float), &"an integer"))
+            }
+            NumberOrString::String(text) => text
+                .trim()
+                .parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(&text), &"an integer"))?,
+        };
+
+        u32::try_from(number)
+            .map_err(|_| E::invalid_value(Unexpected::Unsigned(number), &"a 32-bit integer"))
+    }
+}
+
+/// Accepts an integer, or an integer in a string.
+pub(crate) fn u32<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    NumberOrString::deserialize(deserializer)?.into_u32()
+}
+
+/// Accepts null, an integer, or an integer in a string. Use with `#[serde(default)]`.
+pub(crate) fn option_u32<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<NumberOrString>::deserialize(deserializer)?
+        .map(NumberOrString::into_u32)
+        .transpose()
+}
+
+/// Accepts a boolean, 0 or 1, or one of these in a string.
+pub(crate) fn bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match BoolOrNumber::deserialize(deserializer)? {
+        BoolOrNumber::Bool(value) => Ok(value),
+        BoolOrNumber::Number(0) => Ok(false),
+        BoolOrNumber::Number(1) => Ok(true),
+        BoolOrNumber::Number(number) => Err(D::Error::invalid_value(
+            Unexpected::Unsigned(number),
+            &"a boolean, 0 or 1",
+        )),
+        BoolOrNumber::String(text) => match text.trim() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(D::Error::invalid_value(
+                Unexpected::Str(&text),
+                &"a boolean, 0 or 1",
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Lenient {
+        #[serde(deserialize_with = "super::u32")]
+        created: u32,
+        #[serde(default, deserialize_with = "super::option_u32")]
+        tokens: Option<u32>,
+        #[serde(deserialize_with = "super::bool")]
+        flagged: bool,
+    }
+
+    #[test]
+    fn test_lenient_numbers_and_booleans() {
+        let parse = |json: &str| serde_json::from_str::<Lenient>(json);
+
+        let expected = Lenient {
+            created: 1715000000,
+            tokens: Some(12),
+            flagged: true,
+        };
+
+        assert_eq!(
+            parse(r#"{"created": 1715000000, "tokens": 12, "flagged": true}"#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(r#"{"created": "1715000000", "tokens": "12", "flagged": 1}"#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(r#"{"created": 1715000000.0, "tokens": null, "flagged": "0"}"#).unwrap(),
+            Lenient {
+                tokens: None,
+                flagged: false,
+                ..expected
+            }
+        );
+        assert_eq!(
+            parse(r#"{"created": 1, "flagged": false}"#).unwrap().tokens,
+            None
+        );
+
+        assert!(parse(r#"{"created": "soon", "flagged": true}"#).is_err());
+        assert!(parse(r#"{"created": 1.5, "flagged": true}"#).is_err());
+        assert!(parse(r#"{"created": 1, "flagged": 2}"#).is_err());
+    }
+}
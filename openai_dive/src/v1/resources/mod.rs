@@ -6,9 +6,10 @@ pub mod embedding;
 pub mod file;
 pub mod fine_tuning;
 pub mod image;
+pub mod image_input;
+pub(crate) mod lenient;
 pub mod model;
 pub mod moderation;
-#[cfg(feature = "realtime")]
 pub mod realtime;
 pub mod response;
 pub mod shared;
@@ -1,15 +1,91 @@
+use crate::v1::resources::lenient;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const DEFAULT_MODEL_CAPABILITIES_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The keys compatible servers use for the context length of a model, in `Model::extra`.
+const CONTEXT_LENGTH_KEYS: [&str; 4] = [
+    "context_length",
+    "context_window",
+    "max_model_len",
+    "max_context_length",
+];
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Model {
     /// The model identifier, which can be referenced in the API endpoints.
     pub id: String,
     /// The Unix timestamp (in seconds) when the model was created.
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub created: Option<u32>,
     /// The object type, which is always "model".
+    #[serde(default = "default_model_object")]
     pub object: String,
     /// The organization that owns the model.
+    #[serde(default)]
     pub owned_by: String,
+    /// The model this model was derived from, ie. the base model of a fine-tuned model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// The root model of this model, returned by some compatible servers (ie. vLLM).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// The permissions of the model, returned by some compatible servers (ie. vLLM).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<Vec<ModelPermission>>,
+    /// The fields that are not part of the OpenAI model object, ie. the context length or pricing of compatible servers.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModelPermission {
+    /// The permission identifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The organization the permission applies to, "*" for all organizations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// The group the permission applies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Whether the model can be used to generate completions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_sampling: Option<bool>,
+    /// Whether log probabilities can be returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_logprobs: Option<bool>,
+    /// Whether the model can be viewed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_view: Option<bool>,
+    /// Whether the model can be fine-tuned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fine_tuning: Option<bool>,
+    /// The other fields of the permission.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Model {
+    /// Returns the context length of the model, if the server includes it (ie. OpenRouter, vLLM or Groq).
+    pub fn context_length(&self) -> Option<u64> {
+        CONTEXT_LENGTH_KEYS
+            .iter()
+            .find_map(|key| self.extra.get(*key))
+            .and_then(|value| match value {
+                Value::Number(number) => number.as_u64(),
+                Value::String(text) => text.trim().parse().ok(),
+                _ => None,
+            })
+    }
+}
+
+fn default_model_object() -> String {
+    "model".to_string()
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -19,3 +95,224 @@ pub struct ListModelResponse {
     /// A list of model objects.
     pub data: Vec<Model>,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModelCapabilities {
+    /// Whether the model can generate audio from text.
+    pub speech: bool,
+    /// Whether the model can transcribe audio.
+    pub transcription: bool,
+    /// Whether the model can stream transcriptions.
+    pub transcription_streaming: bool,
+    /// Whether the model can translate audio into English.
+    pub translation: bool,
+    /// Whether the model can stream translations.
+    pub translation_streaming: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelCapability {
+    Speech,
+    Transcription,
+    TranscriptionStreaming,
+    Translation,
+    TranslationStreaming,
+}
+
+/// Caches the models available to the account, as returned by the models endpoint.
+#[derive(Debug, Clone)]
+pub struct ModelCapabilityCache {
+    /// How long the fetched models are used before they are fetched again.
+    pub ttl: Duration,
+    state: Arc<RwLock<Option<CachedModels>>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedModels {
+    loaded_at: Instant,
+    /// The available model IDs, or None when fetching the models failed and only the static table is used.
+    models: Option<HashSet<String>>,
+}
+
+impl ModelCapabilities {
+    /// Returns the capabilities of well-known models, None if the model is unknown.
+    pub fn from_model_id(model_id: &str) -> Option<Self> {
+        let speech = Self {
+            speech: true,
+            ..Default::default()
+        };
+
+        let table = [
+            ("tts-1", speech),
+            ("gpt-4o-mini-tts", speech),
+            (
+                "whisper-1",
+                Self {
+                    transcription: true,
+                    translation: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "gpt-4o-transcribe",
+                Self {
+                    transcription: true,
+                    transcription_streaming: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "gpt-4o-mini-transcribe",
+                Self {
+                    transcription: true,
+                    transcription_streaming: true,
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        table
+            .into_iter()
+            .find(|(prefix, _)| model_id.starts_with(prefix))
+            .map(|(_, capabilities)| capabilities)
+    }
+
+    pub fn supports(&self, capability: ModelCapability) -> bool {
+        match capability {
+            ModelCapability::Speech => self.speech,
+            ModelCapability::Transcription => self.transcription,
+            ModelCapability::TranscriptionStreaming => self.transcription_streaming,
+            ModelCapability::Translation => self.translation,
+            ModelCapability::TranslationStreaming => self.translation_streaming,
+        }
+    }
+}
+
+impl std::fmt::Display for ModelCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ModelCapability::Speech => "speech",
+                ModelCapability::Transcription => "transcription",
+                ModelCapability::TranscriptionStreaming => "transcription streaming",
+                ModelCapability::Translation => "translation",
+                ModelCapability::TranslationStreaming => "translation streaming",
+            }
+        )
+    }
+}
+
+impl Default for ModelCapabilityCache {
+    fn default() -> Self {
+        Self {
+            ttl: DEFAULT_MODEL_CAPABILITIES_TTL,
+            state: Arc::new(RwLock::new(None)),
+        }
+    }
+}
+
+impl ModelCapabilityCache {
+    /// Whether the capabilities have been loaded at least once.
+    pub fn is_loaded(&self) -> bool {
+        self.state
+            .read()
+            .map(|state| state.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Whether the capabilities have been loaded and are older than the TTL.
+    pub fn is_expired(&self) -> bool {
+        self.state
+            .read()
+            .map(|state| {
+                state
+                    .as_ref()
+                    .is_some_and(|cached| cached.loaded_at.elapsed() > self.ttl)
+            })
+            .unwrap_or(false)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn store(&self, models: Option<HashSet<String>>) {
+        if let Ok(mut state) = self.state.write() {
+            *state = Some(CachedModels {
+                loaded_at: Instant::now(),
+                models,
+            });
+        }
+    }
+
+    /// Returns whether the model is available to the account, None if this is unknown.
+    pub fn is_available(&self, model_id: &str) -> Option<bool> {
+        let state = self.state.read().ok()?;
+
+        let models = state.as_ref()?.models.as_ref()?;
+
+        Some(models.contains(model_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::resources::model::{ListModelResponse, Model};
+
+    #[test]
+    fn test_model_deserialization_of_compatible_servers() {
+        let openai: Model = serde_json::from_str(
+            r#"{"id": "ft:gpt-4o-mini-2024-07-18:acme::9abc", "object": "model", "created": 1721172741, "owned_by": "acme"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(openai.owned_by, "acme");
+        assert_eq!(openai.parent, None);
+        assert!(openai.extra.is_empty());
+        assert_eq!(openai.context_length(), None);
+
+        let vllm: ListModelResponse = serde_json::from_str(
+            r#"{"object": "list", "data": [{
+                "id": "my-lora", "object": "model", "created": 1715000000, "owned_by": "vllm",
+                "root": "/models/llama-3-8b-lora", "parent": "meta-llama/Meta-Llama-3-8B-Instruct", "max_model_len": 8192,
+                "permission": [{
+                    "id": "modelperm-1", "object": "model_permission", "created": 1715000000,
+                    "allow_create_engine": false, "allow_sampling": true, "allow_logprobs": true,
+                    "allow_search_indices": false, "allow_view": true, "allow_fine_tuning": false,
+                    "organization": "*", "group": null, "is_blocking": false
+                }]
+            }]}"#,
+        )
+        .unwrap();
+
+        let model = &vllm.data[0];
+        let permission = &model.permission.as_ref().unwrap()[0];
+
+        assert_eq!(
+            model.parent.as_deref(),
+            Some("meta-llama/Meta-Llama-3-8B-Instruct")
+        );
+        assert_eq!(model.root.as_deref(), Some("/models/llama-3-8b-lora"));
+        assert_eq!(model.context_length(), Some(8192));
+        assert_eq!(permission.allow_sampling, Some(true));
+        assert_eq!(permission.organization.as_deref(), Some("*"));
+        assert_eq!(permission.extra["is_blocking"], false);
+
+        // OpenRouter doesn't send owned_by and object
+        let openrouter: Model = serde_json::from_str(
+            r#"{"id": "anthropic/claude-3.5-sonnet", "name": "Claude 3.5 Sonnet", "created": 1718841600,
+                "context_length": 200000, "pricing": {"prompt": "0.000003", "completion": "0.000015"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(openrouter.object, "model");
+        assert_eq!(openrouter.owned_by, "");
+        assert_eq!(openrouter.context_length(), Some(200000));
+        assert_eq!(openrouter.extra["pricing"]["prompt"], "0.000003");
+
+        // the extra fields are serialized back at the top level
+        let serialized = serde_json::to_value(&openrouter).unwrap();
+
+        assert_eq!(serialized["context_length"], 200000);
+        assert_eq!(serialized["name"], "Claude 3.5 Sonnet");
+    }
+}
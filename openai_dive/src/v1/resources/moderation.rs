@@ -1,3 +1,4 @@
+use crate::v1::resources::lenient;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,42 @@ pub struct ModerationParameters {
     pub model: String,
 }
 
+/// The options of `Moderations::create_batched`.
+#[derive(Debug, Builder, Clone, PartialEq)]
+#[builder(name = "ModerationBatchOptionsBuilder")]
+#[builder(setter(into), default)]
+pub struct ModerationBatchOptions {
+    /// The content moderation model you would like to use, "omni-moderation-latest" by default.
+    pub model: String,
+    /// The number of inputs per request, 32 by default (the maximum of the API).
+    pub chunk_size: usize,
+    /// The number of requests that are sent at the same time, 4 by default.
+    pub concurrency: usize,
+    /// The number of retries of a request after a rate limit, a timeout or a server error, 2 by default.
+    pub max_retries: u32,
+}
+
+impl Default for ModerationBatchOptions {
+    fn default() -> Self {
+        Self {
+            model: "omni-moderation-latest".to_string(),
+            chunk_size: 32,
+            concurrency: 4,
+            max_retries: 2,
+        }
+    }
+}
+
+/// The moderation result of one input of `Moderations::create_batched`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchedModerationResult {
+    /// The identifier of the moderation request of the chunk with the input.
+    pub id: String,
+    /// The model that was actually used for the chunk, ie. the snapshot behind "omni-moderation-latest".
+    pub model: String,
+    pub result: Results,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum ModerationInput {
@@ -72,6 +109,7 @@ pub struct ModerationResponse {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Results {
     /// Whether any of the below categories are flagged.
+    #[serde(deserialize_with = "lenient::bool")]
     pub flagged: bool,
     /// A list of the categories, and whether they are flagged or not.
     pub categories: Categories,
@@ -83,26 +121,39 @@ pub struct Results {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Categories {
+    #[serde(deserialize_with = "lenient::bool")]
     pub hate: bool,
     #[serde(rename = "hate/threatening")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub hate_threatening: bool,
+    #[serde(deserialize_with = "lenient::bool")]
     pub harassment: bool,
     #[serde(rename = "harassment/threatening")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub harassment_threatening: bool,
+    #[serde(deserialize_with = "lenient::bool")]
     pub illicit: bool,
     #[serde(rename = "illicit/violent")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub illicit_violent: bool,
     #[serde(rename = "self-harm")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub self_harm: bool,
     #[serde(rename = "self-harm/intent")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub self_harm_intent: bool,
     #[serde(rename = "self-harm/instructions")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub self_harm_instructions: bool,
+    #[serde(deserialize_with = "lenient::bool")]
     pub sexual: bool,
     #[serde(rename = "sexual/minors")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub sexual_minors: bool,
+    #[serde(deserialize_with = "lenient::bool")]
     pub violence: bool,
     #[serde(rename = "violence/graphic")]
+    #[serde(deserialize_with = "lenient::bool")]
     pub violence_graphic: bool,
 }
 
@@ -388,3 +388,75 @@ pub struct RateLimitsUpdated {
     /// List of rate limit information.
     pub rate_limits: Vec<RateLimit>,
 }
+
+/// The server events of an experimental Realtime audio session.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RealtimeEvent {
+    Error(Error),
+    SessionCreated(SessionCreated),
+    SessionUpdated(SessionUpdated),
+    InputAudioBufferSpeechStarted(InputAudioBufferSpeechStarted),
+    InputAudioBufferSpeechStopped(InputAudioBufferSpeechStopped),
+    InputAudioTranscriptionCompleted(ConversationItemInputAudioTranscriptionCompleted),
+    InputAudioTranscriptionFailed(ConversationItemInputAudioTranscriptionFailed),
+    AudioDelta(ResponseAudioDelta),
+    AudioDone(ResponseAudioDone),
+    AudioTranscriptDelta(ResponseAudioTranscriptDelta),
+    AudioTranscriptDone(ResponseAudioTranscriptDone),
+    ResponseDone(Box<ResponseDone>),
+    /// Any other server event.
+    Other(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for RealtimeEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        let event_type = value
+            .get("type")
+            .and_then(|event_type| event_type.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let event = match event_type.as_str() {
+            "error" => serde_json::from_value(value).map(RealtimeEvent::Error),
+            "session.created" => serde_json::from_value(value).map(RealtimeEvent::SessionCreated),
+            "session.updated" => serde_json::from_value(value).map(RealtimeEvent::SessionUpdated),
+            "input_audio_buffer.speech_started" => {
+                serde_json::from_value(value).map(RealtimeEvent::InputAudioBufferSpeechStarted)
+            }
+            "input_audio_buffer.speech_stopped" => {
+                serde_json::from_value(value).map(RealtimeEvent::InputAudioBufferSpeechStopped)
+            }
+            "conversation.item.input_audio_transcription.completed" => {
+                serde_json::from_value(value).map(RealtimeEvent::InputAudioTranscriptionCompleted)
+            }
+            "conversation.item.input_audio_transcription.failed" => {
+                serde_json::from_value(value).map(RealtimeEvent::InputAudioTranscriptionFailed)
+            }
+            "response.output_audio.delta" => {
+                serde_json::from_value(value).map(RealtimeEvent::AudioDelta)
+            }
+            "response.output_audio.done" => {
+                serde_json::from_value(value).map(RealtimeEvent::AudioDone)
+            }
+            "response.output_audio_transcript.delta" => {
+                serde_json::from_value(value).map(RealtimeEvent::AudioTranscriptDelta)
+            }
+            "response.output_audio_transcript.done" => {
+                serde_json::from_value(value).map(RealtimeEvent::AudioTranscriptDone)
+            }
+            "response.done" => serde_json::from_value(value)
+                .map(|done| RealtimeEvent::ResponseDone(Box::new(done))),
+            _ => Ok(RealtimeEvent::Other(value)),
+        };
+
+        event.map_err(D::Error::custom)
+    }
+}
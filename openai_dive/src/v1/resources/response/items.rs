@@ -46,12 +46,59 @@ pub struct FunctionToolCall {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FunctionToolCallOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     pub call_id: String,
     pub output: String,
     pub status: InputItemStatus,
 }
 
+/// The output of a function call of a response, see `Responses::submit_tool_outputs`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolOutput {
+    /// The `call_id` of the function call.
+    pub tool_call_id: String,
+    pub output: ToolOutputValue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ToolOutputValue {
+    Text(String),
+    /// Sent as JSON string, the API only accepts text outputs.
+    Json(serde_json::Value),
+}
+
+impl ToolOutput {
+    pub fn text(tool_call_id: impl Into<String>, output: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: tool_call_id.into(),
+            output: ToolOutputValue::Text(output.into()),
+        }
+    }
+
+    pub fn json(tool_call_id: impl Into<String>, output: serde_json::Value) -> Self {
+        Self {
+            tool_call_id: tool_call_id.into(),
+            output: ToolOutputValue::Json(output),
+        }
+    }
+}
+
+impl From<ToolOutput> for FunctionToolCallOutput {
+    fn from(output: ToolOutput) -> Self {
+        Self {
+            id: None,
+            call_id: output.tool_call_id,
+            output: match output.output {
+                ToolOutputValue::Text(text) => text,
+                ToolOutputValue::Json(value) => value.to_string(),
+            },
+            status: InputItemStatus::Completed,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Reasoning {
     pub id: String,
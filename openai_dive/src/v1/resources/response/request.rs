@@ -1,5 +1,5 @@
 use crate::v1::resources::response::items::ComputerToolCallOutput;
-use crate::v1::resources::shared::WebSearchContextSize;
+use crate::v1::resources::shared::{Metadata, WebSearchContextSize};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -38,7 +38,7 @@ pub struct ResponseParameters {
     pub max_output_tokens: Option<u32>,
     /// Set of 16 key-value pairs that can be attached to an object.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<HashMap<String, String>>,
+    pub metadata: Option<Metadata>,
     /// Whether to allow the model to run tool calls in parallel.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parallel_tool_calls: Option<bool>,
@@ -72,9 +72,16 @@ pub struct ResponseParameters {
     /// The truncation strategy to use for the model response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub truncation: Option<TruncationStrategy>,
-    /// A unique identifier representing your end-user.
+    /// A unique identifier representing your end-user. Replaced by safety_identifier and prompt_cache_key.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// A stable identifier used to help detect users of your application that may be violating OpenAI's usage policies.
+    /// Set from `Client::set_end_user` when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_identifier: Option<String>,
+    /// Used by OpenAI to cache responses for similar requests to optimize your cache hit rates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -132,6 +139,24 @@ impl Default for ResponseInput {
     }
 }
 
+impl From<&str> for ResponseInput {
+    fn from(input: &str) -> Self {
+        ResponseInput::Text(input.to_string())
+    }
+}
+
+impl From<String> for ResponseInput {
+    fn from(input: String) -> Self {
+        ResponseInput::Text(input)
+    }
+}
+
+impl From<Vec<ResponseInputItem>> for ResponseInput {
+    fn from(items: Vec<ResponseInputItem>) -> Self {
+        ResponseInput::List(items)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ResponseInputItem {
@@ -175,6 +200,9 @@ pub enum ResponseInclude {
     MessageInputImageUrls,
     #[serde(rename = "computer_call_output.output.image_url")]
     ComputerCallOutputOutputImageUrls,
+    /// The encrypted reasoning of reasoning items, to pass the reasoning to the next turn when the response isn't stored.
+    #[serde(rename = "reasoning.encrypted_content")]
+    ReasoningEncryptedContent,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
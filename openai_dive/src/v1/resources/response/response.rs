@@ -1,19 +1,20 @@
 #[cfg(feature = "stream")]
 use super::items::{LogProbs, ReasoningSummaryPart};
 use super::{
+    items::ReasoningSummary as ReasoningSummaryText,
     items::{
         CodeInterpreterCall, ComputerToolCall, CustomToolCall, FileSearchToolCall,
         FunctionToolCall, ImageGenerationCall, LocalShellCall, McpApprovalRequest, McpListTools,
         McpToolCall, Reasoning, WebSearchToolCall,
     },
+    request::{ContentInput, InputItem, InputMessage, ResponseInputItem},
     shared::{
         Annotation, ResponseFormat, ResponseTool, ResponseToolChoice, ResponseUsage,
         TruncationStrategy,
     },
 };
-use crate::v1::resources::shared::ReasoningEffort;
+use crate::v1::resources::shared::{Metadata, ReasoningEffort};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResponseObject {
@@ -35,7 +36,7 @@ pub struct ResponseObject {
     pub max_output_tokens: Option<u32>,
     /// Set of 16 key-value pairs that can be attached to an object.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<HashMap<String, String>>,
+    pub metadata: Option<Metadata>,
     /// Model ID used to generate the response.
     pub model: String,
     /// The object type of this resource - always set to response.
@@ -78,6 +79,80 @@ pub struct ResponseObject {
     pub user: Option<String>,
 }
 
+impl ResponseObject {
+    /// Returns the text of the reasoning summaries in the output, separated by blank lines.
+    /// Summaries are only returned when `reasoning.summary` is set in the request.
+    pub fn reasoning_summary_text(&self) -> Option<String> {
+        let texts: Vec<&str> = self
+            .output
+            .iter()
+            .filter_map(|output| match output {
+                ResponseOutput::Reasoning(reasoning) => reasoning.summary.as_ref(),
+                _ => None,
+            })
+            .flatten()
+            .map(|summary| match summary {
+                ReasoningSummaryText::Text { text } => text.as_str(),
+            })
+            .collect();
+
+        if texts.is_empty() {
+            return None;
+        }
+
+        Some(texts.join("\n\n"))
+    }
+
+    /// Returns the output as input items for the next turn, to continue the conversation without `previous_response_id`.
+    /// Reasoning items are passed unchanged, so their `encrypted_content` is sent back exactly as it was received.
+    /// Output that can't be sent as input is passed as an item reference, which requires the response to be stored.
+    pub fn output_as_input(&self) -> Vec<ResponseInputItem> {
+        self.output
+            .iter()
+            .map(|output| match output {
+                ResponseOutput::Message(message) => ResponseInputItem::Message(InputMessage {
+                    role: message.role.clone(),
+                    content: ContentInput::Text(
+                        message
+                            .content
+                            .iter()
+                            .map(|content| match content {
+                                OutputContent::Text { text, .. } => text.as_str(),
+                                OutputContent::ReasoningText { text } => text.as_str(),
+                                OutputContent::Refusal { refusal } => refusal.as_str(),
+                            })
+                            .collect(),
+                    ),
+                }),
+                ResponseOutput::Reasoning(reasoning) => {
+                    ResponseInputItem::Item(InputItem::Reasoning(reasoning.clone()))
+                }
+                ResponseOutput::FunctionToolCall(call) => {
+                    ResponseInputItem::Item(InputItem::FunctionToolCall(call.clone()))
+                }
+                ResponseOutput::FileSearchToolCall(call) => {
+                    ResponseInputItem::Item(InputItem::FileSearchToolCall(call.clone()))
+                }
+                ResponseOutput::WebSearchToolCall(call) => {
+                    ResponseInputItem::Item(InputItem::WebSearchToolCall(call.clone()))
+                }
+                ResponseOutput::ComputerToolCall(call) => {
+                    ResponseInputItem::Item(InputItem::ComputerToolCall(call.clone()))
+                }
+                ResponseOutput::ImageGenerationCall(ImageGenerationCall { id, .. })
+                | ResponseOutput::CodeInterpreterCall(CodeInterpreterCall { id, .. })
+                | ResponseOutput::LocalShellCall(LocalShellCall { id, .. })
+                | ResponseOutput::McpToolCall(McpToolCall { id, .. })
+                | ResponseOutput::McpListTools(McpListTools { id, .. })
+                | ResponseOutput::McpApprovalRequest(McpApprovalRequest { id, .. })
+                | ResponseOutput::CustomToolCall(CustomToolCall { id, .. }) => {
+                    ResponseInputItem::ItemReference { id: id.clone() }
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResponseError {
     /// The error code for the response.
@@ -129,13 +204,14 @@ pub struct OutputMessage {
     pub content: Vec<OutputContent>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct ResponseReasoning {
     /// Constrains effort on reasoning for reasoning models.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effort: Option<ReasoningEffort>,
-    // A summary of the reasoning performed by the model.
-    // pub generate_summary: Option<ReasoningSummary>,
+    /// A summary of the reasoning performed by the model, returned in the summary of the reasoning output items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ReasoningSummary>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -147,6 +223,7 @@ pub struct ResponseText {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ReasoningSummary {
+    Auto,
     Concise,
     Detailed,
 }
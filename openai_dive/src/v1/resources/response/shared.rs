@@ -1,4 +1,3 @@
-#[cfg(feature = "stream")]
 use crate::v1::error::APIError;
 #[cfg(feature = "stream")]
 use crate::v1::resources::response::response::ResponseStreamEvent;
@@ -10,6 +9,7 @@ use crate::v1::resources::{
 use futures::Stream;
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Range;
 #[cfg(feature = "stream")]
 use std::pin::Pin;
 
@@ -251,6 +251,21 @@ pub enum Annotation {
     FilePath { file_id: String, index: u64 },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResolvedCitation {
+    /// The range of the output text that is cited, in characters: from the previous annotation of the text,
+    /// or the start of the text, up to the index of the citation. Citations at the same index have the same range.
+    pub index_range: Range<u64>,
+    /// The ID of the cited file.
+    pub file_id: String,
+    /// The name of the cited file, None if its metadata could not be retrieved.
+    pub filename: Option<String>,
+    /// The cited text, if available.
+    pub quote: Option<String>,
+    /// The error returned while retrieving the metadata of the cited file, ie. when the file was deleted.
+    pub error: Option<APIError>,
+}
+
 impl Serialize for ResponseToolChoice {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -1,19 +1,31 @@
-#[cfg(feature = "reqwest")]
 use crate::v1::error::APIError;
+use crate::v1::resources::lenient;
+#[cfg(feature = "reqwest")]
+use crate::v1::validation::ValidationWarning;
 use bytes::Bytes;
 #[cfg(feature = "reqwest")]
 use reqwest::{header::HeaderMap, multipart::Part};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(all(feature = "tokio", feature = "tokio-util"))]
+use std::path::PathBuf;
+#[cfg(feature = "stream")]
+use std::sync::{Arc, OnceLock};
+#[cfg(feature = "stream")]
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Usage {
     /// Number of tokens in the prompt.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub prompt_tokens: Option<u32>,
     /// Number of tokens in the completion.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub completion_tokens: Option<u32>,
     /// Number of tokens in the entire response.
+    #[serde(deserialize_with = "lenient::u32")]
     pub total_tokens: u32,
     /// Breakdown of tokens used in the prompt.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -23,6 +35,135 @@ pub struct Usage {
     pub completion_tokens_details: Option<CompletionTokensDetails>,
 }
 
+/// The maximum number of keys of `Metadata`.
+pub const MAX_METADATA_KEYS: usize = 16;
+/// The maximum length of a metadata key, in characters.
+pub const MAX_METADATA_KEY_LENGTH: usize = 64;
+/// The maximum length of a metadata value, in characters.
+pub const MAX_METADATA_VALUE_LENGTH: usize = 512;
+
+/// Key-value pairs attached to an object, at most 16 keys of up to 64 characters and values of up to 512 characters.
+/// `Metadata::try_from` and `insert` check the limits, metadata returned by the API is accepted as it is.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct Metadata(HashMap<String, String>);
+
+impl Metadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a pair, the metadata is unchanged if it would exceed the limits.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<(), APIError> {
+        let (key, value) = (key.into(), value.into());
+
+        check_metadata_pair(&key, &value)?;
+
+        if self.0.len() >= MAX_METADATA_KEYS && !self.0.contains_key(&key) {
+            return Err(metadata_keys_error(self.0.len() + 1));
+        }
+
+        self.0.insert(key, value);
+
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_map(&self) -> &HashMap<String, String> {
+        &self.0
+    }
+
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.0
+    }
+}
+
+impl TryFrom<HashMap<String, String>> for Metadata {
+    type Error = APIError;
+
+    /// Checks the limits, the error names the first offending key in alphabetical order.
+    fn try_from(map: HashMap<String, String>) -> Result<Self, Self::Error> {
+        if map.len() > MAX_METADATA_KEYS {
+            return Err(metadata_keys_error(map.len()));
+        }
+
+        let mut pairs: Vec<_> = map.iter().collect();
+        pairs.sort();
+
+        for (key, value) in pairs {
+            check_metadata_pair(key, value)?;
+        }
+
+        Ok(Self(map))
+    }
+}
+
+impl<const N: usize> TryFrom<[(&str, &str); N]> for Metadata {
+    type Error = APIError;
+
+    fn try_from(pairs: [(&str, &str); N]) -> Result<Self, Self::Error> {
+        Self::try_from(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+}
+
+impl From<Metadata> for HashMap<String, String> {
+    fn from(metadata: Metadata) -> Self {
+        metadata.0
+    }
+}
+
+fn check_metadata_pair(key: &str, value: &str) -> Result<(), APIError> {
+    let key_length = key.chars().count();
+
+    if key_length > MAX_METADATA_KEY_LENGTH {
+        return Err(APIError::ValidationError {
+            field: "metadata".to_string(),
+            value: format!("key {key:?} of {key_length} characters"),
+            allowed: format!("keys of at most {MAX_METADATA_KEY_LENGTH} characters"),
+        });
+    }
+
+    let value_length = value.chars().count();
+
+    if value_length > MAX_METADATA_VALUE_LENGTH {
+        return Err(APIError::ValidationError {
+            field: "metadata".to_string(),
+            value: format!("value of key {key:?} of {value_length} characters"),
+            allowed: format!("values of at most {MAX_METADATA_VALUE_LENGTH} characters"),
+        });
+    }
+
+    Ok(())
+}
+
+fn metadata_keys_error(keys: usize) -> APIError {
+    APIError::ValidationError {
+        field: "metadata".to_string(),
+        value: format!("{keys} keys"),
+        allowed: format!("at most {MAX_METADATA_KEYS} keys"),
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InputTokensDetails {
     /// The number of tokens that were retrieved from the cache.
@@ -39,9 +180,11 @@ pub struct OutputTokensDetails {
 pub struct PromptTokensDetails {
     /// Audio input tokens present in the prompt.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub audio_tokens: Option<u32>,
     /// Cached tokens present in the prompt.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub cached_tokens: Option<u32>,
 }
 
@@ -49,15 +192,19 @@ pub struct PromptTokensDetails {
 pub struct CompletionTokensDetails {
     /// Tokens generated by the model for reasoning.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub reasoning_tokens: Option<u32>,
     /// Audio input tokens generated by the model.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub audio_tokens: Option<u32>,
     /// When using Predicted Outputs, the number of tokens in the prediction that appeared in the completion.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub accepted_prediction_tokens: Option<u32>,
     /// When using Predicted Outputs, the number of tokens in the prediction that did not appear in the completion.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "lenient::option_u32")]
     pub rejected_prediction_tokens: Option<u32>,
 }
 
@@ -67,6 +214,13 @@ pub struct ResponseWrapper<T> {
     pub headers: Headers,
 }
 
+#[cfg(feature = "stream")]
+#[derive(Debug, Default, Clone)]
+pub struct StreamMetadata {
+    time_to_first_byte: Arc<OnceLock<Duration>>,
+    request_id: Arc<OnceLock<String>>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Headers {
     /// The maximum number of requests that are permitted before exhausting the rate limit.
@@ -87,6 +241,15 @@ pub struct Headers {
     /// The time until the rate limit (based on tokens) resets to its initial state.
     #[serde(rename = "x-ratelimit-reset-tokens")]
     pub x_ratelimit_reset_tokens: Option<String>,
+    /// The organization that served the request.
+    #[serde(rename = "openai-organization")]
+    pub openai_organization: Option<String>,
+    /// The project that served the request.
+    #[serde(rename = "openai-project")]
+    pub openai_project: Option<String>,
+    /// The id of the request, to quote when contacting support.
+    #[serde(rename = "x-request-id")]
+    pub x_request_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -124,6 +287,7 @@ pub struct ListResponse<T> {
     /// The ID of the last objects in the list.
     pub last_id: Option<String>,
     /// Indicates whether there are more objects to retrieve.
+    #[serde(deserialize_with = "lenient::bool")]
     pub has_more: bool,
 }
 
@@ -134,6 +298,7 @@ pub struct DeletedObject {
     /// The object type.
     pub object: String,
     /// Indicates whether the file was successfully deleted.
+    #[serde(deserialize_with = "lenient::bool")]
     pub deleted: bool,
 }
 
@@ -201,6 +366,7 @@ pub enum WebSearchContextSize {
     Large,
 }
 
+/// The stop sequences of a completion, a single string or an array of up to 4 strings. Both JSON shapes are accepted.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum StopToken {
@@ -208,9 +374,80 @@ pub enum StopToken {
     Array(Vec<String>),
 }
 
+impl StopToken {
+    /// The maximum number of stop sequences accepted by OpenAI.
+    pub const MAX_SEQUENCES: usize = 4;
+
+    /// Returns the stop sequences.
+    pub fn sequences(&self) -> Vec<&str> {
+        match self {
+            StopToken::String(sequence) => vec![sequence.as_str()],
+            StopToken::Array(sequences) => sequences.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl From<&str> for StopToken {
+    fn from(sequence: &str) -> Self {
+        StopToken::String(sequence.to_string())
+    }
+}
+
+impl From<String> for StopToken {
+    fn from(sequence: String) -> Self {
+        StopToken::String(sequence)
+    }
+}
+
+impl From<Vec<String>> for StopToken {
+    fn from(sequences: Vec<String>) -> Self {
+        StopToken::Array(sequences)
+    }
+}
+
+impl From<Vec<&str>> for StopToken {
+    fn from(sequences: Vec<&str>) -> Self {
+        StopToken::Array(sequences.into_iter().map(str::to_string).collect())
+    }
+}
+
+#[cfg(feature = "stream")]
+impl StreamMetadata {
+    /// The time between sending the request and receiving the first chunk, once the first chunk has arrived.
+    pub fn time_to_first_byte(&self) -> Option<Duration> {
+        self.time_to_first_byte.get().copied()
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn set_time_to_first_byte(&self, time_to_first_byte: Duration) {
+        let _ = self.time_to_first_byte.set(time_to_first_byte);
+    }
+
+    /// The `x-request-id` of the response, once its headers have arrived (before the first chunk).
+    pub fn request_id(&self) -> Option<String> {
+        self.request_id.get().cloned()
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn set_request_id(&self, request_id: String) {
+        let _ = self.request_id.set(request_id);
+    }
+}
+
 #[cfg(feature = "reqwest")]
 impl From<HeaderMap> for Headers {
     fn from(value: HeaderMap) -> Self {
+        let header = |key: &str| {
+            value
+                .get(key)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        let openai_organization = header("openai-organization");
+        let openai_project = header("openai-project");
+        let x_request_id = header("x-request-id");
+
         if value.get("x-ratelimit-limit-requests").is_none()
             || value.get("x-ratelimit-limit-tokens").is_none()
             || value.get("x-ratelimit-remaining-requests").is_none()
@@ -225,6 +462,9 @@ impl From<HeaderMap> for Headers {
                 x_ratelimit_remaining_tokens: None,
                 x_ratelimit_reset_requests: None,
                 x_ratelimit_reset_tokens: None,
+                openai_organization,
+                openai_project,
+                x_request_id,
             };
         }
 
@@ -281,130 +521,646 @@ impl From<HeaderMap> for Headers {
                     .unwrap()
                     .to_string(),
             ),
+            openai_organization,
+            openai_project,
+            x_request_id,
         }
     }
 }
 
+/// Where the file parts of a multipart request are placed, see `Client::set_multipart_order`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MultipartOrder {
+    /// The file parts before the text fields, like the OpenAI SDKs send them.
+    #[default]
+    FileFirst,
+    /// The text fields before the file parts, for servers that read the fields before the file.
+    FileLast,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FileUploadBytes {
     pub bytes: Bytes,
     pub filename: String,
+    /// The MIME type of the part, inferred from the filename extension when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
 }
 impl FileUploadBytes {
     pub fn new(bytes: impl Into<Bytes>, filename: impl Into<String>) -> Self {
         Self {
             bytes: bytes.into(),
             filename: filename.into(),
+            mime_type: None,
         }
     }
 
+    /// Set the MIME type of the part, instead of inferring it from the filename extension.
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+
+        self
+    }
+
     #[cfg(feature = "reqwest")]
-    pub(crate) fn into_part(self) -> Result<Part, APIError> {
+    pub(crate) fn into_part(
+        self,
+        kind: FileKind,
+        options: &FileUploadOptions,
+    ) -> Result<Part, APIError> {
+        let filename = options.filename.clone().unwrap_or(self.filename);
+        let mime_type = options.mime_type.as_deref().or(self.mime_type.as_deref());
+
+        let (filename, mime_type) = kind.resolve(&filename, mime_type)?;
+
         reqwest::multipart::Part::bytes(self.bytes.to_vec())
-            .file_name(self.filename.clone())
-            .mime_str("application/octet-stream")
+            .file_name(filename)
+            .mime_str(&mime_type)
             .map_err(|error| APIError::FileError(error.to_string()))
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FileUploadOptions {
+    /// The filename of the part, instead of the file name of the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// The MIME type of the part, instead of the one inferred from the filename extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum FileUpload {
+    /// A file in memory, sent with its length, so the request has a Content-Length.
     Bytes(FileUploadBytes),
     BytesArray(Vec<FileUploadBytes>),
+    /// A file on disk, streamed while it is read, so the request uses chunked encoding.
     #[cfg(all(feature = "tokio", feature = "tokio-util"))]
     File(String),
     #[cfg(all(feature = "tokio", feature = "tokio-util"))]
     FileArray(Vec<String>),
+    /// A file on disk, the path doesn't need to be valid UTF-8. Streamed like `File`.
+    /// The multipart filename is the file name of the path, non UTF-8 bytes are replaced with U+FFFD.
+    #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+    FilePath(PathBuf),
+    /// A URL the provider fetches the file from, only supported for transcriptions by some compatible providers.
+    /// OpenAI doesn't support this, see `Client::set_allow_file_urls`.
+    Url(String),
+    /// A file with an explicit filename or MIME type, see `with_filename` and `with_mime_type`.
+    WithOptions(Box<FileUpload>, FileUploadOptions),
 }
+
 impl FileUpload {
+    /// Set the filename of the uploaded part, the API uses its extension to detect the format.
+    pub fn with_filename(self, filename: impl Into<String>) -> Self {
+        let (file, options) = self.split_options();
+
+        FileUpload::WithOptions(
+            Box::new(file),
+            FileUploadOptions {
+                filename: Some(filename.into()),
+                ..options
+            },
+        )
+    }
+
+    /// Set the MIME type of the uploaded part, instead of inferring it from the filename extension.
+    pub fn with_mime_type(self, mime_type: impl Into<String>) -> Self {
+        let (file, options) = self.split_options();
+
+        FileUpload::WithOptions(
+            Box::new(file),
+            FileUploadOptions {
+                mime_type: Some(mime_type.into()),
+                ..options
+            },
+        )
+    }
+
+    /// Whether the upload contains multiple files.
+    pub fn is_array(&self) -> bool {
+        match self {
+            FileUpload::BytesArray(_) => true,
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::FileArray(_) => true,
+            FileUpload::WithOptions(file, _) => file.is_array(),
+            _ => false,
+        }
+    }
+
+    /// Returns a warning if the MIME type of the part falls back to application/octet-stream,
+    /// because it isn't set and the filename has no known extension. Kinds that require an extension never fall back.
     #[cfg(feature = "reqwest")]
-    pub(crate) async fn into_part(self) -> Result<Part, APIError> {
+    pub(crate) fn mime_type_warning(&self, kind: FileKind) -> Option<ValidationWarning> {
+        if !kind.allowed_extensions().is_empty() {
+            return None;
+        }
+
+        let (filename, mime_type) = self.part_name()?;
+
+        let extension = std::path::Path::new(&filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+
+        if mime_type.is_some() || extension.as_deref().and_then(mime_type_of).is_some() {
+            return None;
+        }
+
+        Some(ValidationWarning::UnknownMimeType { filename })
+    }
+
+    /// Returns the filename and MIME type that a single part is sent with, None for multiple files or a URL.
+    #[cfg(feature = "reqwest")]
+    fn part_name(&self) -> Option<(String, Option<String>)> {
         match self {
-            FileUpload::Bytes(bytes) => bytes.into_part(),
-            FileUpload::BytesArray(_) => {
-                unimplemented!("BytesArray is not supported for this route")
-            }
+            FileUpload::Bytes(bytes) => Some((bytes.filename.clone(), bytes.mime_type.clone())),
             #[cfg(all(feature = "tokio", feature = "tokio-util"))]
-            FileUpload::File(path) => {
-                use tokio::fs::File;
-                use tokio_util::codec::{BytesCodec, FramedRead};
+            FileUpload::File(path) => Some((path_file_name(std::path::Path::new(path)), None)),
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::FilePath(path) => Some((path_file_name(path), None)),
+            FileUpload::WithOptions(file, options) => {
+                let (filename, mime_type) = file.part_name()?;
 
-                let file = File::open(&path)
-                    .await
-                    .map_err(|error| APIError::FileError(error.to_string()))?;
+                Some((
+                    options.filename.clone().unwrap_or(filename),
+                    options.mime_type.clone().or(mime_type),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits off the options, the outermost options take precedence.
+    fn split_options(self) -> (FileUpload, FileUploadOptions) {
+        let mut file = self;
+        let mut options = FileUploadOptions::default();
 
-                let stream = FramedRead::new(file, BytesCodec::new());
-                let file_body = reqwest::Body::wrap_stream(stream);
+        while let FileUpload::WithOptions(inner, inner_options) = file {
+            options.filename = options.filename.or(inner_options.filename);
+            options.mime_type = options.mime_type.or(inner_options.mime_type);
+
+            file = *inner;
+        }
+
+        (file, options)
+    }
 
-                let file_part = reqwest::multipart::Part::stream(file_body).file_name(path);
-                // .mime_str("application/octet-stream")
-                // .unwrap();
+    #[cfg(feature = "reqwest")]
+    pub(crate) async fn into_part(self, kind: FileKind) -> Result<Part, APIError> {
+        let (file, options) = self.split_options();
 
-                Ok(file_part)
+        match file {
+            FileUpload::Bytes(bytes) => bytes.into_part(kind, &options),
+            FileUpload::BytesArray(_) => {
+                unimplemented!("BytesArray is not supported for this route")
+            }
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::File(path) => {
+                file_path_part(std::path::Path::new(&path), kind, &options).await
             }
             #[cfg(all(feature = "tokio", feature = "tokio-util"))]
             FileUpload::FileArray(_) => {
                 unimplemented!("FileArray is not supported for this route")
             }
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::FilePath(path) => file_path_part(&path, kind, &options).await,
+            FileUpload::Url(_) => Err(APIError::FileError(
+                "a file URL is not supported for this route".to_string(),
+            )),
+            FileUpload::WithOptions(..) => unreachable!("the options are split off"),
         }
     }
 
+    /// Returns the contents of a single file, None for multiple files or a URL.
     #[cfg(feature = "reqwest")]
-    pub(crate) async fn into_parts(self) -> Result<Vec<Part>, APIError> {
+    pub(crate) async fn contents(&self) -> Result<Option<Bytes>, APIError> {
         match self {
-            FileUpload::Bytes(bytes) => bytes.into_part().map(|part| vec![part]),
+            FileUpload::Bytes(bytes) => Ok(Some(bytes.bytes.clone())),
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::File(path) => read_file(std::path::Path::new(path)).await.map(Some),
+            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
+            FileUpload::FilePath(path) => read_file(path).await.map(Some),
+            FileUpload::WithOptions(file, _) => Box::pin(file.contents()).await,
+            _ => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub(crate) async fn into_parts(self, kind: FileKind) -> Result<Vec<Part>, APIError> {
+        let (file, options) = self.split_options();
+
+        match file {
             FileUpload::BytesArray(bytes) => bytes
                 .into_iter()
-                .map(|bytes| bytes.into_part())
+                .map(|bytes| bytes.into_part(kind, &options))
                 .collect::<Result<Vec<Part>, APIError>>(),
             #[cfg(all(feature = "tokio", feature = "tokio-util"))]
-            FileUpload::File(path) => {
-                use tokio::fs::File;
-                use tokio_util::codec::{BytesCodec, FramedRead};
-
-                let file = File::open(&path)
-                    .await
-                    .map_err(|error| APIError::FileError(error.to_string()))?;
-
-                let stream = FramedRead::new(file, BytesCodec::new());
-                let file_body = reqwest::Body::wrap_stream(stream);
+            FileUpload::FileArray(paths) => {
+                let mut file_parts = vec![];
 
-                let file_part = reqwest::multipart::Part::stream(file_body)
-                    .file_name(path)
-                    .mime_str("application/octet-stream")
-                    .unwrap();
+                for path in paths {
+                    file_parts
+                        .push(file_path_part(std::path::Path::new(&path), kind, &options).await?);
+                }
 
-                Ok(vec![file_part])
+                Ok(file_parts)
             }
-            #[cfg(all(feature = "tokio", feature = "tokio-util"))]
-            FileUpload::FileArray(paths) => {
-                use tokio::fs::File;
-                use tokio_util::codec::{BytesCodec, FramedRead};
+            file => FileUpload::WithOptions(Box::new(file), options)
+                .into_part(kind)
+                .await
+                .map(|part| vec![part]),
+        }
+    }
+}
 
-                let mut file_parts = vec![];
-                for path in paths {
-                    let file = File::open(&path)
-                        .await
-                        .map_err(|error| APIError::FileError(error.to_string()))?;
+/// The kind of file an endpoint accepts, used to validate the filename extension and to infer the MIME type.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FileKind {
+    Any,
+    Audio,
+    Image,
+    Png,
+    Jsonl,
+}
 
-                    let stream = FramedRead::new(file, BytesCodec::new());
-                    let file_body = reqwest::Body::wrap_stream(stream);
+#[cfg(feature = "reqwest")]
+impl FileKind {
+    fn allowed_extensions(&self) -> &'static [&'static str] {
+        match self {
+            FileKind::Any => &[],
+            FileKind::Audio => &[
+                "flac", "mp3", "mp4", "mpeg", "mpga", "m4a", "ogg", "oga", "wav", "webm",
+            ],
+            FileKind::Image => &["png", "jpg", "jpeg", "webp"],
+            FileKind::Png => &["png"],
+            FileKind::Jsonl => &["jsonl"],
+        }
+    }
 
-                    let file_part = reqwest::multipart::Part::stream(file_body)
-                        .file_name(path)
-                        .mime_str("application/octet-stream")
-                        .unwrap();
+    /// Returns the filename and MIME type of a part, the MIME type is inferred from the extension when not set.
+    pub(crate) fn resolve(
+        &self,
+        filename: &str,
+        mime_type: Option<&str>,
+    ) -> Result<(String, String), APIError> {
+        let extension = std::path::Path::new(filename)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
 
-                    file_parts.push(file_part);
-                }
+        let allowed_extensions = self.allowed_extensions();
 
-                Ok(file_parts)
-            }
+        if !allowed_extensions.is_empty()
+            && !extension
+                .as_deref()
+                .is_some_and(|extension| allowed_extensions.contains(&extension))
+        {
+            return Err(APIError::ValidationError {
+                field: "file".to_string(),
+                value: format!("filename {filename:?}"),
+                allowed: format!(
+                    "a filename with extension {}",
+                    allowed_extensions.join(", ")
+                ),
+            });
         }
+
+        let mime_type = mime_type
+            .map(|mime_type| mime_type.to_string())
+            .or_else(|| {
+                extension
+                    .as_deref()
+                    .and_then(mime_type_of)
+                    .map(|mime_type| mime_type.to_string())
+            })
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        Ok((filename.to_string(), mime_type))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn mime_type_of(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "flac" => "audio/flac",
+        "mp3" | "mpeg" | "mpga" => "audio/mpeg",
+        "mp4" | "m4a" => "audio/mp4",
+        "ogg" | "oga" => "audio/ogg",
+        "wav" => "audio/wav",
+        "webm" => "audio/webm",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "jsonl" => "application/jsonl",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        _ => return None,
+    })
+}
+
+/// The file name of the path, or the whole path if it has none.
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+fn path_file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+async fn file_path_part(
+    path: &std::path::Path,
+    kind: FileKind,
+    options: &FileUploadOptions,
+) -> Result<Part, APIError> {
+    use tokio::fs::File;
+
+    let file_name = options
+        .filename
+        .clone()
+        .unwrap_or_else(|| path_file_name(path));
+
+    let (file_name, mime_type) = kind.resolve(&file_name, options.mime_type.as_deref())?;
+
+    let file = File::open(path)
+        .await
+        .map_err(|error| APIError::FileError(error.to_string()))?;
+
+    reader_part(file, file_name, &mime_type)
+}
+
+/// A part streamed from the reader. A read error aborts the request, which returns it as `APIError::FileError`.
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+fn reader_part<R>(reader: R, file_name: String, mime_type: &str) -> Result<Part, APIError>
+where
+    R: tokio::io::AsyncRead + Send + Sync + 'static,
+{
+    use tokio_util::codec::FramedRead;
+
+    let stream = FramedRead::new(reader, FileCodec);
+    let file_body = reqwest::Body::wrap_stream(stream);
+
+    reqwest::multipart::Part::stream(file_body)
+        .file_name(file_name)
+        .mime_str(mime_type)
+        .map_err(|error| APIError::FileError(error.to_string()))
+}
+
+/// Passes the bytes through like `BytesCodec`, returning read errors as `FileReadError` so they can be told apart from connection errors.
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+struct FileCodec;
+
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+impl tokio_util::codec::Decoder for FileCodec {
+    type Item = bytes::BytesMut;
+    type Error = crate::v1::helpers::FileReadError;
+
+    fn decode(&mut self, source: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Ok((!source.is_empty()).then(|| source.split()))
     }
 }
+
+#[cfg(all(feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+async fn read_file(path: &std::path::Path) -> Result<Bytes, APIError> {
+    tokio::fs::read(path)
+        .await
+        .map(Bytes::from)
+        .map_err(|error| APIError::FileError(format!("{}: {error}", path.display())))
+}
+
 impl Default for FileUpload {
     fn default() -> Self {
         Self::Bytes(FileUploadBytes::new(Bytes::new(), ""))
     }
 }
+
+#[cfg(all(test, feature = "reqwest", feature = "tokio", feature = "tokio-util"))]
+mod tests {
+    use crate::v1::error::APIError;
+    use crate::v1::resources::shared::{
+        FileKind, FileUpload, FileUploadBytes, FileUploadOptions, Headers, Metadata,
+    };
+    use reqwest::header::HeaderMap;
+
+    #[tokio::test]
+    async fn test_read_error_of_a_streamed_file_aborts_the_upload() {
+        use crate::v1::api::Client;
+        use crate::v1::test_support::{listen, read_request};
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        /// Returns a few chunks of the file, then fails like a disk read error.
+        struct FailingReader {
+            chunks: usize,
+        }
+
+        impl AsyncRead for FailingReader {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+                buffer: &mut ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                if self.chunks == 0 {
+                    return Poll::Ready(Err(std::io::Error::other("input/output error")));
+                }
+
+                self.chunks -= 1;
+                buffer.put_slice(&[0; 1024]);
+
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        // reads the request without ever answering it
+        let (listener, base_url) = listen().await;
+
+        // the request is never complete, it is read until the client aborts the upload
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            read_request(&mut socket).await;
+        });
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+
+        let part = super::reader_part(
+            FailingReader { chunks: 4 },
+            "audio.wav".to_string(),
+            "audio/wav",
+        )
+        .unwrap();
+        let form = reqwest::multipart::Form::new().part("file", part);
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client.post_with_form("/audio/transcriptions", form),
+        )
+        .await
+        .expect("the upload wasn't aborted");
+
+        assert_eq!(
+            result,
+            Err(APIError::FileError(
+                "failed to read the uploaded file: input/output error".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_file_kind_resolves_filename_and_mime_type() {
+        assert_eq!(
+            FileKind::Audio.resolve("speech.WAV", None).unwrap(),
+            ("speech.WAV".to_string(), "audio/wav".to_string())
+        );
+        assert_eq!(
+            FileKind::Audio
+                .resolve("speech.m4a", Some("audio/x-m4a"))
+                .unwrap(),
+            ("speech.m4a".to_string(), "audio/x-m4a".to_string())
+        );
+        assert_eq!(
+            FileKind::Jsonl.resolve("batch.jsonl", None).unwrap().1,
+            "application/jsonl"
+        );
+        assert_eq!(
+            FileKind::Image.resolve("photo.jpg", None).unwrap().1,
+            "image/jpeg"
+        );
+        assert_eq!(
+            FileKind::Any.resolve("blob", None).unwrap().1,
+            "application/octet-stream"
+        );
+
+        assert!(matches!(
+            FileKind::Audio.resolve("blob", None),
+            Err(APIError::ValidationError { field, .. }) if field == "file"
+        ));
+        assert!(FileKind::Jsonl.resolve("batch.json", None).is_err());
+        assert!(FileKind::Png.resolve("mask.jpg", None).is_err());
+        assert!(FileKind::Image.resolve("photo.gif", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_upload_options_override_filename_and_mime_type() {
+        let upload = FileUpload::Bytes(FileUploadBytes::new(vec![0; 4], "blob"))
+            .with_filename("speech.ogg")
+            .with_mime_type("audio/opus");
+
+        assert_eq!(
+            upload,
+            FileUpload::WithOptions(
+                Box::new(FileUpload::Bytes(FileUploadBytes::new(vec![0; 4], "blob"))),
+                FileUploadOptions {
+                    filename: Some("speech.ogg".to_string()),
+                    mime_type: Some("audio/opus".to_string()),
+                }
+            )
+        );
+
+        assert!(upload.into_part(FileKind::Audio).await.is_ok());
+
+        let result = FileUpload::Bytes(FileUploadBytes::new(vec![0; 4], "blob"))
+            .into_part(FileKind::Audio)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_headers_include_organization_and_project() {
+        let mut header_map = HeaderMap::new();
+        header_map.insert("openai-organization", "org-abc".parse().unwrap());
+        header_map.insert("openai-project", "proj_abc".parse().unwrap());
+
+        let headers: Headers = header_map.into();
+
+        assert_eq!(headers.openai_organization.as_deref(), Some("org-abc"));
+        assert_eq!(headers.openai_project.as_deref(), Some("proj_abc"));
+        assert_eq!(headers.x_ratelimit_limit_requests, None);
+    }
+
+    #[test]
+    fn test_metadata_enforces_the_limits_and_names_the_key() {
+        let long_key = "k".repeat(65);
+        let long_value = "v".repeat(513);
+
+        let mut pairs: std::collections::HashMap<String, String> = (0..16)
+            .map(|index| (format!("key_{index}"), "value".to_string()))
+            .collect();
+
+        let mut metadata = Metadata::try_from(pairs.clone()).unwrap();
+
+        assert!(matches!(
+            metadata.insert("key_16", "value"),
+            Err(APIError::ValidationError { value, .. }) if value == "17 keys"
+        ));
+        assert_eq!(metadata.len(), 16);
+
+        metadata.insert("key_0", "replaced").unwrap();
+        assert_eq!(metadata.get("key_0"), Some("replaced"));
+
+        pairs.insert("key_16".to_string(), "value".to_string());
+        assert!(Metadata::try_from(pairs).is_err());
+
+        assert!(matches!(
+            Metadata::try_from([("project", "demo"), (long_key.as_str(), "value")]),
+            Err(APIError::ValidationError { field, value, allowed })
+                if field == "metadata"
+                    && value == format!("key {long_key:?} of 65 characters")
+                    && allowed == "keys of at most 64 characters"
+        ));
+        assert!(matches!(
+            Metadata::try_from([("user", long_value.as_str())]),
+            Err(APIError::ValidationError { value, allowed, .. })
+                if value == "value of key \"user\" of 513 characters"
+                    && allowed == "values of at most 512 characters"
+        ));
+    }
+
+    #[test]
+    fn test_metadata_of_responses_is_not_validated() {
+        let pairs: serde_json::Map<String, serde_json::Value> = (0..20)
+            .map(|index| (format!("key_{index}"), "v".repeat(600).into()))
+            .collect();
+
+        let metadata: Metadata = serde_json::from_value(pairs.clone().into()).unwrap();
+
+        assert_eq!(metadata.len(), 20);
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::Value::Object(pairs)
+        );
+    }
+}
+
+#[cfg(all(
+    test,
+    unix,
+    feature = "reqwest",
+    feature = "tokio",
+    feature = "tokio-util"
+))]
+mod unix_tests {
+    use crate::v1::resources::shared::{FileKind, FileUpload};
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[tokio::test]
+    async fn test_file_path_with_non_utf8_file_name_into_part() {
+        let path = std::env::temp_dir().join(OsStr::from_bytes(b"openai-dive-\xff\xfe.wav"));
+
+        std::fs::write(&path, b"RIFF").unwrap();
+
+        let result = FileUpload::FilePath(path.clone())
+            .into_part(FileKind::Audio)
+            .await;
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_ok());
+    }
+}
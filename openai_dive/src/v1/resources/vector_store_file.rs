@@ -25,6 +25,24 @@ pub struct VectorStoreFile {
     pub chunking_strategy: Option<VectorStoreFileChunkingStrategy>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VectorStoreFileContent {
+    /// The identifier of the file.
+    pub file_id: String,
+    /// The name of the file.
+    pub filename: String,
+    /// The parsed content of the file.
+    pub content: Vec<VectorStoreFileContentPart>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VectorStoreFileContentPart {
+    /// The content type, which is always text.
+    pub r#type: String,
+    /// The text content.
+    pub text: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum VectorStoreFileStatus {
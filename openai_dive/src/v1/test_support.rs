@@ -0,0 +1,211 @@
+//! A mock HTTP server for the tests, that answers one request per connection.
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Request {
+    /// The request line and the headers.
+    pub head: String,
+    /// The body, decoded if it was sent in chunks.
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The method and the path, ie. `GET /models`.
+    pub fn route(&self) -> String {
+        self.head
+            .split(' ')
+            .take(2)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// The value of a header, the name is case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// Binds a listener on a free local port and returns it with its base URL.
+pub(crate) async fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    (listener, base_url)
+}
+
+/// Reads a request: the head, then a body of Content-Length bytes or in chunks until the last chunk.
+/// A connection that is closed early returns what was read so far.
+pub(crate) async fn read_request(socket: &mut TcpStream) -> Request {
+    let mut received = vec![];
+    let mut buffer = [0; 8192];
+
+    loop {
+        if let Some(request) = parse_request(&received) {
+            return request;
+        }
+
+        match socket.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+        }
+    }
+
+    match find(&received, b"\r\n\r\n") {
+        Some(end) => Request {
+            head: String::from_utf8_lossy(&received[..end]).to_string(),
+            body: received[end + 4..].to_vec(),
+        },
+        None => Request {
+            head: String::from_utf8_lossy(&received).to_string(),
+            body: vec![],
+        },
+    }
+}
+
+/// Returns the request once the head and the whole body are received.
+fn parse_request(received: &[u8]) -> Option<Request> {
+    let end = find(received, b"\r\n\r\n")?;
+
+    let mut request = Request {
+        head: String::from_utf8_lossy(&received[..end]).to_string(),
+        body: vec![],
+    };
+    let body = &received[end + 4..];
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+    {
+        request.body = decode_chunks(body)?;
+
+        return Some(request);
+    }
+
+    let length = request
+        .header("content-length")
+        .map_or(0, |length| length.parse::<usize>().unwrap());
+
+    if body.len() < length {
+        return None;
+    }
+
+    request.body = body[..length].to_vec();
+
+    Some(request)
+}
+
+/// Decodes a chunked body, `None` until the last chunk is received.
+fn decode_chunks(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = vec![];
+
+    loop {
+        let line_end = find(body, b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+
+        body = &body[line_end + 2..];
+
+        if size == 0 {
+            return Some(decoded);
+        }
+
+        if body.len() < size + 2 {
+            return None;
+        }
+
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// A response with the headers (ie. `content-type: text/plain`) and the body, the connection is closed after it.
+pub(crate) fn response(status: &str, headers: &[&str], body: &str) -> String {
+    let mut response = format!("HTTP/1.1 {status}\r\n");
+
+    for header in headers {
+        response.push_str(header);
+        response.push_str("\r\n");
+    }
+
+    response.push_str(&format!(
+        "content-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    ));
+
+    response
+}
+
+/// A JSON response, the connection is closed after it.
+pub(crate) fn json_response(status: &str, body: &str) -> String {
+    response(status, &["content-type: application/json"], body)
+}
+
+/// Starts a server that answers a connection per response, in order, and returns its base URL.
+/// The handle returns the requests once all responses are sent.
+pub(crate) async fn serve<R>(
+    responses: impl IntoIterator<Item = R>,
+) -> (String, JoinHandle<Vec<Request>>)
+where
+    R: Into<String>,
+{
+    let mut responses = responses
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<String>>()
+        .into_iter();
+    let connections = responses.len();
+
+    serve_with(connections, move |_| responses.next().unwrap()).await
+}
+
+/// Starts a server that answers the requests of `connections` connections with `respond`, and returns its base URL.
+/// The handle returns the requests once all responses are sent.
+pub(crate) async fn serve_with<F>(
+    connections: usize,
+    mut respond: F,
+) -> (String, JoinHandle<Vec<Request>>)
+where
+    F: FnMut(&Request) -> String + Send + 'static,
+{
+    let (listener, base_url) = listen().await;
+
+    let server = tokio::spawn(async move {
+        let mut requests = vec![];
+
+        for _ in 0..connections {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+
+            let _ = socket.write_all(respond(&request).as_bytes()).await;
+            let _ = socket.shutdown().await;
+
+            requests.push(request);
+        }
+
+        requests
+    });
+
+    (base_url, server)
+}
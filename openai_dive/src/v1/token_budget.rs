@@ -0,0 +1,321 @@
+//! A tokens-per-minute budget that is shared across tasks, for when the TPM limit is reached before the request limit.
+use crate::v1::clock::{Clock, SystemClock};
+use crate::v1::resources::chat::ChatCompletionParameters;
+use crate::v1::resources::embedding::EmbeddingInput;
+use crate::v1::resources::fine_tuning::FineTuneExample;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A token bucket that refills continuously at the tokens-per-minute rate.
+/// Clones share the same budget, so one budget can be handed to every task (or set on the client with `Client::set_token_budget`).
+#[derive(Debug, Clone)]
+pub struct TokenBudget {
+    state: Arc<Mutex<BudgetState>>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    tokens_per_minute: u32,
+    /// Negative after a request that is larger than the budget, until it is refilled.
+    available: f64,
+    refilled_at: Instant,
+    /// Set by `pause`, no tokens are taken before.
+    paused_until: Option<Instant>,
+}
+
+impl BudgetState {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+
+        self.available = (self.available + elapsed.as_secs_f64() * self.rate())
+            .min(self.tokens_per_minute as f64);
+        self.refilled_at = now;
+    }
+
+    /// The tokens per second.
+    fn rate(&self) -> f64 {
+        self.tokens_per_minute as f64 / 60.0
+    }
+}
+
+/// The tokens taken for a request by `TokenBudget::acquire`.
+/// Dropping it keeps the estimate, `settle` corrects it with the actual usage.
+#[derive(Debug)]
+pub struct TokenReservation {
+    state: Arc<Mutex<BudgetState>>,
+    estimated_tokens: u32,
+}
+
+impl TokenBudget {
+    /// A budget of `tokens_per_minute`, which starts full.
+    pub fn new(tokens_per_minute: u32) -> Self {
+        Self::with_clock(tokens_per_minute, Arc::new(SystemClock))
+    }
+
+    /// A budget that refills and waits on the given clock, ie. `MockClock` in tests.
+    pub fn with_clock(tokens_per_minute: u32, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BudgetState {
+                tokens_per_minute: tokens_per_minute.max(1),
+                available: tokens_per_minute.max(1) as f64,
+                refilled_at: clock.now(),
+                paused_until: None,
+            })),
+            clock,
+        }
+    }
+
+    /// Waits until the estimated tokens are available and takes them.
+    /// A request that is larger than the whole budget waits until the budget is full and then goes through alone,
+    /// the following requests wait until it has been refilled.
+    pub async fn acquire(&self, estimated_tokens: u32) -> TokenReservation {
+        loop {
+            match self.try_acquire(estimated_tokens) {
+                Ok(reservation) => return reservation,
+                Err(wait) => self.clock.sleep(wait).await,
+            }
+        }
+    }
+
+    /// Takes the estimated tokens, or returns how long to wait until they are available.
+    fn try_acquire(&self, estimated_tokens: u32) -> Result<TokenReservation, Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = self.clock.now();
+
+        if let Some(paused) = state
+            .paused_until
+            .map(|until| until.saturating_duration_since(now))
+            .filter(|paused| !paused.is_zero())
+        {
+            return Err(paused);
+        }
+
+        state.refill(now);
+
+        let capacity = state.tokens_per_minute as f64;
+        let needed = (estimated_tokens as f64).min(capacity);
+
+        if state.available < needed {
+            return Err(Duration::from_secs_f64(
+                (needed - state.available) / state.rate(),
+            ));
+        }
+
+        if estimated_tokens > state.tokens_per_minute {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "request of {estimated_tokens} tokens exceeds the budget of {} tokens per minute",
+                state.tokens_per_minute
+            );
+        }
+
+        state.available -= estimated_tokens as f64;
+
+        Ok(TokenReservation {
+            state: self.state.clone(),
+            estimated_tokens,
+        })
+    }
+
+    /// Makes the requests that acquire tokens wait for the duration, ie. the backpressure of a rate limit (`APIError::backpressure`).
+    /// The client pauses its budget after every rate limit with backpressure, so concurrent requests slow down as well.
+    pub fn pause(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let until = self.clock.now() + duration;
+
+        state.paused_until = Some(state.paused_until.map_or(until, |paused| paused.max(until)));
+    }
+
+    /// Returns the share of the budget that is in use, from 0.0 (full budget available) to 1.0.
+    /// It is above 1.0 after a request that is larger than the budget.
+    pub fn utilization(&self) -> f64 {
+        let mut state = self.state.lock().unwrap();
+
+        state.refill(self.clock.now());
+
+        1.0 - state.available / state.tokens_per_minute as f64
+    }
+
+    /// Returns the tokens that can be acquired right away.
+    pub fn available_tokens(&self) -> u32 {
+        let mut state = self.state.lock().unwrap();
+
+        state.refill(self.clock.now());
+
+        state.available.max(0.0) as u32
+    }
+
+    pub fn tokens_per_minute(&self) -> u32 {
+        self.state.lock().unwrap().tokens_per_minute
+    }
+}
+
+impl TokenReservation {
+    /// Corrects the estimate with the actual usage of the request (ie. `usage.total_tokens` of the response).
+    /// Tokens that were estimated but not used are returned to the budget, additional tokens are taken.
+    pub fn settle(self, actual_tokens: u32) {
+        let mut state = self.state.lock().unwrap();
+
+        state.available = (state.available + self.estimated_tokens as f64 - actual_tokens as f64)
+            .min(state.tokens_per_minute as f64);
+    }
+
+    pub fn estimated_tokens(&self) -> u32 {
+        self.estimated_tokens
+    }
+}
+
+/// Estimates the tokens of a text, about 4 characters per token for English text.
+/// Use a tokenizer for exact counts, the estimate is only corrected by `TokenReservation::settle`.
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4)
+}
+
+/// Estimates the tokens of a chat completion, the messages and the maximum completion tokens.
+pub(crate) fn estimate_chat_tokens(parameters: &ChatCompletionParameters) -> u32 {
+    let messages = serde_json::to_string(&parameters.messages).unwrap_or_default();
+
+    estimate_tokens(&messages)
+        + parameters
+            .max_completion_tokens
+            .or(parameters.max_tokens)
+            .unwrap_or_default()
+}
+
+/// Estimates the tokens trained on in a fine-tuning dataset, the messages of the examples without those of weight 0.
+pub fn estimate_training_tokens(examples: &[FineTuneExample]) -> u32 {
+    examples
+        .iter()
+        .flat_map(|example| &example.messages)
+        .filter(|message| message.weight != Some(0))
+        .map(|message| {
+            estimate_tokens(&serde_json::to_string(&message.message).unwrap_or_default())
+        })
+        .sum()
+}
+
+/// Estimates the tokens of an embedding input, token arrays are counted exactly.
+pub(crate) fn estimate_embedding_tokens(input: &EmbeddingInput) -> u32 {
+    match input {
+        EmbeddingInput::String(text) => estimate_tokens(text),
+        EmbeddingInput::StringArray(texts) => texts.iter().map(|text| estimate_tokens(text)).sum(),
+        EmbeddingInput::IntegerArray(tokens) => tokens.len() as u32,
+        EmbeddingInput::IntegerArrayArray(tokens) => {
+            tokens.iter().map(|tokens| tokens.len() as u32).sum()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::clock::{Clock, MockClock};
+    use crate::v1::resources::fine_tuning::{FineTuneExample, FineTuneMessage};
+    use crate::v1::token_budget::{estimate_tokens, estimate_training_tokens, TokenBudget};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_token_budget_waits_settles_and_lets_large_requests_through() {
+        let clock = MockClock::auto_advancing();
+        let budget = TokenBudget::with_clock(600, Arc::new(clock.clone()) as Arc<dyn Clock>);
+
+        let first = budget.acquire(400).await;
+
+        assert!(clock.sleeps().is_empty());
+        assert_eq!(budget.available_tokens(), 200);
+
+        // 200 more tokens at 10 tokens per second
+        let second = budget.acquire(400).await;
+
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(20)]);
+        assert_eq!(budget.utilization(), 1.0);
+
+        first.settle(100);
+        second.settle(400);
+
+        assert_eq!(budget.available_tokens(), 300);
+
+        // larger than the budget: waits until the budget is full, then goes through alone
+        let large = budget.acquire(900).await;
+
+        assert_eq!(clock.sleeps()[1], Duration::from_secs(30));
+        assert_eq!(budget.utilization(), 1.5);
+
+        drop(large);
+
+        budget.acquire(60).await;
+
+        assert_eq!(clock.sleeps()[2], Duration::from_secs(36));
+    }
+
+    #[tokio::test]
+    async fn test_client_settles_budget_with_usage_of_response() {
+        use crate::v1::api::Client;
+        use crate::v1::resources::embedding::{EmbeddingInput, EmbeddingParametersBuilder};
+        use crate::v1::test_support::{response, serve};
+
+        let (base_url, _) = serve([response(
+            "200 OK",
+            &[],
+            r#"{"object": "list", "data": [], "model": "text-embedding-3-small", "usage": {"prompt_tokens": 8, "total_tokens": 8}}"#,
+        )])
+        .await;
+
+        let clock = MockClock::new();
+        let budget = TokenBudget::with_clock(1000, Arc::new(clock) as Arc<dyn Clock>);
+
+        let mut client = Client::new("sk-test".to_string());
+        client.set_base_url(&base_url);
+        client.set_token_budget(budget.clone());
+
+        let parameters = EmbeddingParametersBuilder::default()
+            .model("text-embedding-3-small")
+            .input(EmbeddingInput::String("a".repeat(400)))
+            .build()
+            .unwrap();
+
+        client.embeddings().create(parameters).await.unwrap();
+
+        // the estimate of 100 tokens is replaced by the 8 tokens used
+        assert_eq!(budget.available_tokens(), 992);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello!"), 2);
+        assert_eq!(estimate_tokens(&"a".repeat(400)), 100);
+    }
+
+    #[test]
+    fn test_estimate_training_tokens_skips_messages_of_weight_zero() {
+        let message = |role: &str, content: &str| -> FineTuneMessage {
+            serde_json::from_value(serde_json::json!({"role": role, "content": content})).unwrap()
+        };
+
+        let mut example = FineTuneExample::new(vec![
+            message("user", "Hello!"),
+            message("assistant", "Hi, how can I help?").with_weight(0),
+            message("assistant", "Hello!").with_weight(1),
+        ]);
+
+        assert_eq!(
+            example.to_jsonl_line(),
+            r#"{"messages":[{"role":"user","content":"Hello!"},{"role":"assistant","content":"Hi, how can I help?","weight":0},{"role":"assistant","content":"Hello!","weight":1}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<FineTuneExample>(&example.to_jsonl_line()).unwrap(),
+            example
+        );
+
+        let trained = estimate_training_tokens(std::slice::from_ref(&example));
+
+        example.messages[1].weight = Some(1);
+
+        assert_eq!(
+            estimate_training_tokens(&[example]),
+            trained + estimate_tokens(r#"{"role":"assistant","content":"Hi, how can I help?"}"#)
+        );
+    }
+}
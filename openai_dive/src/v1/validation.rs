@@ -0,0 +1,583 @@
+use crate::v1::error::APIError;
+use crate::v1::resources::audio::{AudioOutputFormat, AudioTranscriptionParameters};
+use crate::v1::resources::chat::ChatCompletionParameters;
+use crate::v1::resources::shared::StopToken;
+#[cfg(feature = "reqwest")]
+use crate::v1::token_budget::estimate_tokens;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+/// Controls which sampling parameter values are accepted before a request is sent.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum ValidationProfile {
+    /// The ranges documented by OpenAI. Sampling parameters are rejected for o-series models.
+    OpenAI,
+    /// Nothing is checked locally, values are sent to the server as-is.
+    #[default]
+    Lenient,
+    /// User defined ranges, parameters without a range are not checked.
+    Custom(ParameterRanges),
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParameterRanges {
+    /// The allowed range for the temperature parameter.
+    pub temperature: Option<RangeInclusive<f32>>,
+    /// The allowed range for the top_p parameter.
+    pub top_p: Option<RangeInclusive<f32>>,
+    /// The allowed range for the frequency_penalty parameter.
+    pub frequency_penalty: Option<RangeInclusive<f32>>,
+    /// The allowed range for the presence_penalty parameter.
+    pub presence_penalty: Option<RangeInclusive<f32>>,
+}
+
+impl ParameterRanges {
+    /// The ranges accepted by the OpenAI API.
+    pub fn openai() -> Self {
+        Self {
+            temperature: Some(0.0..=2.0),
+            top_p: Some(0.0..=1.0),
+            frequency_penalty: Some(-2.0..=2.0),
+            presence_penalty: Some(-2.0..=2.0),
+        }
+    }
+}
+
+impl ValidationProfile {
+    /// Validates the sampling parameters of a chat completion request against this profile.
+    /// The OpenAI profile also checks the number of stop sequences.
+    pub fn validate_chat(&self, parameters: &ChatCompletionParameters) -> Result<(), APIError> {
+        let ranges = match self {
+            ValidationProfile::Lenient => return Ok(()),
+            ValidationProfile::OpenAI => {
+                check_stop_sequences(parameters.stop.as_ref())?;
+
+                if is_o_series_model(&parameters.model) {
+                    return reject_sampling_parameters(parameters);
+                }
+
+                ParameterRanges::openai()
+            }
+            ValidationProfile::Custom(ranges) => ranges.clone(),
+        };
+
+        check_range("temperature", parameters.temperature, &ranges.temperature)?;
+        check_range("top_p", parameters.top_p, &ranges.top_p)?;
+        check_range(
+            "frequency_penalty",
+            parameters.frequency_penalty,
+            &ranges.frequency_penalty,
+        )?;
+        check_range(
+            "presence_penalty",
+            parameters.presence_penalty,
+            &ranges.presence_penalty,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The parameters a family of transcription models accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TranscriptionModelRules {
+    /// The model names of the family, snapshots (ie. `gpt-4o-transcribe-2025-03-20`) match as well.
+    pub models: &'static [&'static str],
+    /// The accepted values of response_format.
+    pub response_formats: &'static [&'static str],
+    /// The parameters that are rejected by the models.
+    pub unsupported: &'static [&'static str],
+}
+
+/// The rules of the OpenAI transcription models, the first family that matches the model applies.
+/// Models that aren't in the table are passed through unchecked.
+pub const TRANSCRIPTION_MODEL_RULES: &[TranscriptionModelRules] = &[
+    TranscriptionModelRules {
+        models: &["gpt-4o-transcribe-diarize"],
+        response_formats: &["json", "text"],
+        unsupported: &["timestamp_granularities", "include", "prompt"],
+    },
+    TranscriptionModelRules {
+        models: &["gpt-4o-transcribe", "gpt-4o-mini-transcribe"],
+        response_formats: &["json"],
+        unsupported: &["timestamp_granularities"],
+    },
+    TranscriptionModelRules {
+        models: &["whisper-1"],
+        response_formats: &["json", "text", "srt", "verbose_json", "vtt"],
+        unsupported: &["include"],
+    },
+];
+
+impl TranscriptionModelRules {
+    /// Returns the rules of the model, None if the model is unknown.
+    pub fn for_model(model: &str) -> Option<&'static TranscriptionModelRules> {
+        TRANSCRIPTION_MODEL_RULES.iter().find(|rules| {
+            rules.models.iter().any(|name| {
+                model == *name
+                    || model
+                        .strip_prefix(name)
+                        .is_some_and(|snapshot| snapshot.starts_with("-20"))
+            })
+        })
+    }
+}
+
+/// Validates a transcription request against the rules of its model, with the response format that is sent.
+/// All incompatible parameters are returned in one `ValidationError`, comma separated in its field.
+pub fn validate_transcription(
+    parameters: &AudioTranscriptionParameters,
+    response_format: Option<&AudioOutputFormat>,
+) -> Result<(), APIError> {
+    let Some(rules) = TranscriptionModelRules::for_model(&parameters.model) else {
+        return Ok(());
+    };
+
+    let set_parameters = [
+        (
+            "timestamp_granularities",
+            parameters.timestamp_granularities.is_some(),
+        ),
+        ("include", parameters.include.is_some()),
+        ("prompt", parameters.prompt.is_some()),
+        ("chunking_strategy", parameters.chunking_strategy.is_some()),
+        ("stream", parameters.stream.is_some()),
+    ];
+
+    let mut incompatible: Vec<&str> = set_parameters
+        .iter()
+        .filter(|(field, set)| *set && rules.unsupported.contains(field))
+        .map(|(field, _)| *field)
+        .collect();
+
+    if let Some(response_format) = response_format {
+        if !rules
+            .response_formats
+            .contains(&response_format.to_string().as_str())
+        {
+            incompatible.push("response_format");
+        }
+    }
+
+    if incompatible.is_empty() {
+        return Ok(());
+    }
+
+    Err(APIError::ValidationError {
+        field: incompatible.join(", "),
+        value: parameters.model.clone(),
+        allowed: format!(
+            "unset for model {}, response_format one of {}",
+            parameters.model,
+            rules.response_formats.join(", ")
+        ),
+    })
+}
+
+/// The maximum tokens of a transcription prompt, OpenAI truncates longer prompts without an error.
+pub const MAX_TRANSCRIPTION_PROMPT_TOKENS: u32 = 224;
+
+/// A parameter that the server is likely to change (ie. truncate) instead of rejecting it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationWarning {
+    /// The estimated tokens of the transcription prompt exceed the limit.
+    PromptTooLong { estimated_tokens: u32, limit: u32 },
+    /// The MIME type of the file can't be detected, it is sent as application/octet-stream.
+    UnknownMimeType { filename: String },
+    /// The prompt differs from the previous request to the model before the minimum cached prefix, see `Client::set_cache_lint`.
+    CachePrefixDiverged {
+        model: String,
+        byte_offset: usize,
+        prefix_tokens: u32,
+        previous: String,
+        current: String,
+    },
+    /// The backend doesn't support the parameter, it was left out of the request, see `Client::set_degrade_gracefully`.
+    UnsupportedParameter { parameter: String },
+    /// A stored record of a conversation can't be read and was skipped, ie. a line of a JSON lines file that was cut off.
+    UnreadableConversationRecord {
+        /// The file of the store.
+        source: String,
+        /// The line or row of the record.
+        record: String,
+        error: String,
+    },
+}
+
+impl Display for ValidationWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationWarning::PromptTooLong {
+                estimated_tokens,
+                limit,
+            } => write!(
+                f,
+                "the prompt of about {estimated_tokens} tokens exceeds the limit of {limit} tokens and is likely truncated"
+            ),
+            ValidationWarning::UnknownMimeType { filename } => write!(
+                f,
+                "the MIME type of file {filename:?} can't be detected, it is sent as application/octet-stream; set a filename with an extension (`with_filename`) or a MIME type (`with_mime_type`)"
+            ),
+            ValidationWarning::CachePrefixDiverged {
+                model,
+                byte_offset,
+                prefix_tokens,
+                previous,
+                current,
+            } => write!(
+                f,
+                "the prompt for {model} differs from the previous request at byte {byte_offset}, after about {prefix_tokens} tokens, so it isn't cached: {previous:?} became {current:?}"
+            ),
+            ValidationWarning::UnsupportedParameter { parameter } => write!(
+                f,
+                "the backend doesn't support {parameter}, it is left out of the request"
+            ),
+            ValidationWarning::UnreadableConversationRecord {
+                source,
+                record,
+                error,
+            } => write!(f, "{source}: skipping the unreadable {record}: {error}"),
+        }
+    }
+}
+
+impl From<ValidationWarning> for APIError {
+    fn from(warning: ValidationWarning) -> Self {
+        match warning {
+            ValidationWarning::PromptTooLong {
+                estimated_tokens,
+                limit,
+            } => APIError::ValidationError {
+                field: "prompt".to_string(),
+                value: format!("about {estimated_tokens} tokens"),
+                allowed: format!("at most {limit} tokens"),
+            },
+            ValidationWarning::UnknownMimeType { filename } => APIError::ValidationError {
+                field: "file".to_string(),
+                value: format!("filename {filename:?}"),
+                allowed: "a filename with a known extension, or a MIME type".to_string(),
+            },
+            ValidationWarning::CachePrefixDiverged { prefix_tokens, .. } => {
+                APIError::ValidationError {
+                    field: "messages".to_string(),
+                    value: format!("a prefix that differs after about {prefix_tokens} tokens"),
+                    allowed: "a prefix identical to the previous request for at least 1024 tokens"
+                        .to_string(),
+                }
+            }
+            ValidationWarning::UnsupportedParameter { parameter } => APIError::ValidationError {
+                field: parameter,
+                value: "set".to_string(),
+                allowed: "only parameters the backend supports, see `Client::detect_capabilities`"
+                    .to_string(),
+            },
+            ValidationWarning::UnreadableConversationRecord {
+                source,
+                record,
+                error,
+            } => APIError::ValidationError {
+                field: format!("{source}: {record}"),
+                value: error,
+                allowed: "a stored chat message".to_string(),
+            },
+        }
+    }
+}
+
+/// Checks the length of the transcription prompt, estimated with `token_budget::estimate_tokens`.
+/// The estimate is rough for other languages than English, so this is a warning and not an error.
+#[cfg(feature = "reqwest")]
+pub fn check_transcription_prompt(
+    parameters: &AudioTranscriptionParameters,
+    limit: u32,
+) -> Option<ValidationWarning> {
+    let estimated_tokens = estimate_tokens(parameters.prompt.as_ref()?);
+
+    (estimated_tokens > limit).then_some(ValidationWarning::PromptTooLong {
+        estimated_tokens,
+        limit,
+    })
+}
+
+fn is_o_series_model(model: &str) -> bool {
+    let mut chars = model.chars();
+
+    chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+fn reject_sampling_parameters(parameters: &ChatCompletionParameters) -> Result<(), APIError> {
+    let sampling_parameters = [
+        ("temperature", parameters.temperature),
+        ("top_p", parameters.top_p),
+        ("frequency_penalty", parameters.frequency_penalty),
+        ("presence_penalty", parameters.presence_penalty),
+    ];
+
+    for (field, value) in sampling_parameters {
+        if let Some(value) = value {
+            return Err(APIError::ValidationError {
+                field: field.to_string(),
+                value: value.to_string(),
+                allowed: format!("unset for model {}", parameters.model),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn check_range(
+    field: &str,
+    value: Option<f32>,
+    range: &Option<RangeInclusive<f32>>,
+) -> Result<(), APIError> {
+    match (value, range) {
+        (Some(value), Some(range)) if !range.contains(&value) => Err(APIError::ValidationError {
+            field: field.to_string(),
+            value: value.to_string(),
+            allowed: format!("{} to {}", range.start(), range.end()),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn check_stop_sequences(stop: Option<&StopToken>) -> Result<(), APIError> {
+    match stop.map(StopToken::sequences) {
+        Some(sequences) if sequences.len() > StopToken::MAX_SEQUENCES => {
+            Err(APIError::ValidationError {
+                field: "stop".to_string(),
+                value: format!("{} sequences", sequences.len()),
+                allowed: format!("up to {} sequences", StopToken::MAX_SEQUENCES),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1::error::APIError;
+    use crate::v1::resources::chat::ChatCompletionParametersBuilder;
+    use crate::v1::validation::{ParameterRanges, ValidationProfile};
+
+    #[test]
+    fn test_openai_profile_rejects_out_of_range_temperature() {
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![])
+            .temperature(2.5)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            ValidationProfile::OpenAI.validate_chat(&parameters),
+            Err(APIError::ValidationError {
+                field: "temperature".to_string(),
+                value: "2.5".to_string(),
+                allowed: "0 to 2".to_string(),
+            })
+        );
+        assert!(ValidationProfile::Lenient
+            .validate_chat(&parameters)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_openai_profile_rejects_sampling_parameters_for_o_series_models() {
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("o3-mini")
+            .messages(vec![])
+            .top_p(0.5)
+            .build()
+            .unwrap();
+
+        let result = ValidationProfile::OpenAI.validate_chat(&parameters);
+
+        assert!(matches!(result, Err(APIError::ValidationError { field, .. }) if field == "top_p"));
+    }
+
+    #[test]
+    fn test_transcription_rules_per_model_family() {
+        use crate::v1::resources::audio::{
+            AudioOutputFormat, AudioTranscriptionParameters, TimestampGranularity,
+            TranscriptionInclude,
+        };
+        use crate::v1::validation::{validate_transcription, TranscriptionModelRules};
+
+        let base = |model: &str| AudioTranscriptionParameters {
+            model: model.to_string(),
+            ..Default::default()
+        };
+        let granularities = |model: &str| AudioTranscriptionParameters {
+            timestamp_granularities: Some(vec![TimestampGranularity::Word]),
+            ..base(model)
+        };
+        let include = |model: &str| AudioTranscriptionParameters {
+            include: Some(vec![TranscriptionInclude::Logprobs]),
+            ..base(model)
+        };
+        let prompt = |model: &str| AudioTranscriptionParameters {
+            prompt: Some("Acme, Inc.".to_string()),
+            ..base(model)
+        };
+
+        let verbose_json = Some(&AudioOutputFormat::VerboseJson);
+        let json = Some(&AudioOutputFormat::Json);
+        let text = Some(&AudioOutputFormat::Text);
+
+        // (model, parameters, response format, accepted)
+        let matrix = [
+            ("whisper-1", granularities("whisper-1"), verbose_json, true),
+            (
+                "whisper-1",
+                prompt("whisper-1"),
+                Some(&AudioOutputFormat::Srt),
+                true,
+            ),
+            ("whisper-1", include("whisper-1"), json, false),
+            (
+                "gpt-4o-transcribe",
+                include("gpt-4o-transcribe"),
+                json,
+                true,
+            ),
+            ("gpt-4o-transcribe", prompt("gpt-4o-transcribe"), None, true),
+            ("gpt-4o-transcribe", base("gpt-4o-transcribe"), text, false),
+            (
+                "gpt-4o-transcribe",
+                granularities("gpt-4o-transcribe"),
+                json,
+                false,
+            ),
+            (
+                "gpt-4o-mini-transcribe-2025-03-20",
+                base("gpt-4o-mini-transcribe-2025-03-20"),
+                verbose_json,
+                false,
+            ),
+            (
+                "gpt-4o-transcribe-diarize",
+                base("gpt-4o-transcribe-diarize"),
+                text,
+                true,
+            ),
+            (
+                "gpt-4o-transcribe-diarize",
+                include("gpt-4o-transcribe-diarize"),
+                json,
+                false,
+            ),
+            (
+                "gpt-4o-transcribe-diarize",
+                prompt("gpt-4o-transcribe-diarize"),
+                json,
+                false,
+            ),
+            (
+                "whisper-large-v3",
+                include("whisper-large-v3"),
+                verbose_json,
+                true,
+            ),
+        ];
+
+        for (model, parameters, response_format, accepted) in matrix {
+            assert_eq!(
+                validate_transcription(&parameters, response_format).is_ok(),
+                accepted,
+                "{model} {parameters:?} {response_format:?}"
+            );
+        }
+
+        let parameters = AudioTranscriptionParameters {
+            timestamp_granularities: Some(vec![TimestampGranularity::Segment]),
+            ..base("gpt-4o-transcribe")
+        };
+
+        assert_eq!(
+            validate_transcription(&parameters, verbose_json),
+            Err(APIError::ValidationError {
+                field: "timestamp_granularities, response_format".to_string(),
+                value: "gpt-4o-transcribe".to_string(),
+                allowed: "unset for model gpt-4o-transcribe, response_format one of json"
+                    .to_string(),
+            })
+        );
+
+        assert!(
+            TranscriptionModelRules::for_model("gpt-4o-transcribe-diarize")
+                .is_some_and(|rules| rules.unsupported.contains(&"prompt"))
+        );
+        assert_eq!(
+            TranscriptionModelRules::for_model("gpt-4o-transcriber"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_custom_profile_uses_given_ranges() {
+        let profile = ValidationProfile::Custom(ParameterRanges {
+            temperature: Some(0.0..=1.0),
+            ..Default::default()
+        });
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("custom-model")
+            .messages(vec![])
+            .temperature(1.5)
+            .presence_penalty(10.0)
+            .build()
+            .unwrap();
+
+        let result = profile.validate_chat(&parameters);
+
+        assert!(
+            matches!(result, Err(APIError::ValidationError { field, .. }) if field == "temperature")
+        );
+    }
+
+    #[test]
+    fn test_stop_sequences_accept_both_shapes_and_are_limited() {
+        use crate::v1::resources::chat::ChatCompletionParameters;
+        use crate::v1::resources::shared::StopToken;
+
+        // requests stored by other SDKs, with either shape
+        for (stop, expected) in [
+            (r#""END""#, StopToken::from("END")),
+            (r#"["END", "\n\n"]"#, StopToken::from(vec!["END", "\n\n"])),
+        ] {
+            let parameters: ChatCompletionParameters = serde_json::from_str(&format!(
+                r#"{{"model": "gpt-4o", "messages": [], "stop": {stop}}}"#
+            ))
+            .unwrap();
+
+            assert_eq!(parameters.stop, Some(expected));
+
+            let serialized = serde_json::to_value(&parameters).unwrap();
+
+            assert_eq!(
+                serialized["stop"],
+                serde_json::from_str::<serde_json::Value>(stop).unwrap()
+            );
+        }
+
+        let parameters = ChatCompletionParametersBuilder::default()
+            .model("gpt-4o")
+            .messages(vec![])
+            .stop(vec!["a", "b", "c", "d", "e"])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            ValidationProfile::OpenAI.validate_chat(&parameters),
+            Err(APIError::ValidationError {
+                field: "stop".to_string(),
+                value: "5 sequences".to_string(),
+                allowed: "up to 4 sequences".to_string(),
+            })
+        );
+        assert!(ValidationProfile::Lenient
+            .validate_chat(&parameters)
+            .is_ok());
+    }
+}
@@ -0,0 +1,170 @@
+//! Writing embeddings to a vector database, the destination of `Embeddings::embed_and_sink`.
+use crate::v1::error::APIError;
+use crate::v1::future::ApiFuture;
+use crate::v1::resources::embedding::EmbeddingFloat;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// A destination of embedded documents, ie. a collection of a vector database.
+pub trait VectorSink: Debug + Send + Sync {
+    /// Inserts the records, or replaces the records with the same ids.
+    fn upsert(&self, records: Vec<VectorRecord>) -> ApiFuture<'_, (), SinkError>;
+}
+
+/// A document to embed, the text is embedded and the metadata is stored with the vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorDocument {
+    pub id: String,
+    pub text: String,
+    pub metadata: Value,
+}
+
+/// An embedded document as it is written to the sink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorRecord {
+    pub id: String,
+    pub vector: Vec<EmbeddingFloat>,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SinkError {
+    /// The sink can't be written right now (ie. a dropped connection), the batch is upserted again.
+    Transient(String),
+    /// The sink rejected the records (ie. vectors of the wrong dimensions), the batch fails without retries.
+    Rejected(String),
+}
+
+impl Display for SinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SinkError::Transient(message) => write!(f, "sink unavailable: {message}"),
+            SinkError::Rejected(message) => write!(f, "sink rejected the records: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+/// Keeps the records in memory by id, ie. for tests. Clones share the same records.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryVectorSink {
+    records: Arc<Mutex<BTreeMap<String, VectorRecord>>>,
+}
+
+impl MemoryVectorSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the records, ordered by id.
+    pub fn records(&self) -> Vec<VectorRecord> {
+        self.records.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<VectorRecord> {
+        self.records.lock().unwrap().get(id).cloned()
+    }
+}
+
+impl VectorSink for MemoryVectorSink {
+    fn upsert(&self, records: Vec<VectorRecord>) -> ApiFuture<'_, (), SinkError> {
+        let mut stored = self.records.lock().unwrap();
+
+        for record in records {
+            stored.insert(record.id.clone(), record);
+        }
+
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// The options of `Embeddings::embed_and_sink`.
+#[derive(Debug, Clone)]
+pub struct EmbedAndSinkOptions {
+    /// The embedding model.
+    pub model: String,
+    /// The number of dimensions of the vectors, for models that support it.
+    pub dimensions: Option<u32>,
+    /// The number of documents embedded in one request and upserted together. Defaults to 100.
+    pub batch_size: usize,
+    /// The number of batches processed at the same time. Defaults to 4.
+    pub concurrency: usize,
+    /// The retries of a batch after rate limits, server errors and transient sink errors. Defaults to 3.
+    pub max_retries: u32,
+    /// Called with the number of finished documents and the total, after each batch.
+    pub on_progress: Option<SinkProgress>,
+}
+
+impl EmbedAndSinkOptions {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            dimensions: None,
+            batch_size: 100,
+            concurrency: 4,
+            max_retries: 3,
+            on_progress: None,
+        }
+    }
+
+    /// Calls the callback with the number of finished documents and the total after each batch, ie. for a progress bar.
+    pub fn with_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(SinkProgress(Arc::new(on_progress)));
+
+        self
+    }
+}
+
+/// Receives the number of finished documents and the total of `Embeddings::embed_and_sink`.
+#[derive(Clone)]
+pub struct SinkProgress(pub Arc<dyn Fn(usize, usize) + Send + Sync>);
+
+impl Debug for SinkProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SinkProgress")
+    }
+}
+
+/// The outcome of `Embeddings::embed_and_sink`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbedAndSinkResult {
+    /// The number of documents that were written to the sink.
+    pub upserted: usize,
+    /// The ids of the documents whose batch failed after its retries, in the order of the documents.
+    pub failed_ids: Vec<String>,
+    /// The error of every failed batch.
+    pub errors: Vec<EmbedAndSinkError>,
+}
+
+/// The error of a batch of `Embeddings::embed_and_sink`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbedAndSinkError {
+    /// The documents couldn't be embedded.
+    Embedding(APIError),
+    /// The sink didn't take the records.
+    Sink(SinkError),
+}
+
+impl Display for EmbedAndSinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbedAndSinkError::Embedding(error) => write!(f, "embedding failed: {error}"),
+            EmbedAndSinkError::Sink(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl std::error::Error for EmbedAndSinkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmbedAndSinkError::Embedding(error) => Some(error),
+            EmbedAndSinkError::Sink(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Golden tests for the chat completion types against the JSON fixtures in `testdata/chat`.
+//!
+//! Request fixtures are compared with the serialized parameters, response fixtures are deserialized,
+//! serialized again and compared with the fixture, so fields that get lost show up in the diff.
+//! Values are compared as JSON, so the key order doesn't matter, and `null` is treated as a missing key.
+use openai_dive::v1::resources::chat::{
+    ChatCompletionFunction, ChatCompletionParameters, ChatCompletionParametersBuilder,
+    ChatCompletionResponse, ChatCompletionResponseFormat, ChatCompletionStreamOptions,
+    ChatCompletionTool, ChatCompletionToolChoice, ChatCompletionToolChoiceFunction,
+    ChatCompletionToolChoiceFunctionName, ChatCompletionToolType, ChatMessage, ChatMessageContent,
+    ChatMessageContentPart, ChatMessageImageContentPart, ChatMessageTextContentPart, Function,
+    ImageUrlDetail, ImageUrlType, JsonSchemaBuilder, ToolCall,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/testdata/chat/{name}", env!("CARGO_MANIFEST_DIR"));
+
+    std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{path}: {error}"))
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    // serialize to a string first, so f32 values are compared as written (0.2, not 0.2000000029)
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+fn assert_request(name: &str, parameters: ChatCompletionParameters) {
+    let expected: Value = serde_json::from_str(&fixture(name)).unwrap();
+
+    // the request must not send nulls, only the fixture may spell out unset fields as null
+    assert_eq!(to_json(&parameters), without_nulls(expected), "{name}");
+}
+
+fn assert_round_trip<T: Serialize + DeserializeOwned>(name: &str, json: &str) {
+    let expected: Value = serde_json::from_str(json).unwrap();
+    let parsed: T = serde_json::from_str(json).unwrap_or_else(|error| panic!("{name}: {error}"));
+
+    assert_eq!(
+        without_nulls(to_json(&parsed)),
+        without_nulls(expected),
+        "{name}"
+    );
+}
+
+fn user(text: &str) -> ChatMessage {
+    ChatMessage::User {
+        content: ChatMessageContent::Text(text.to_string()),
+        name: None,
+    }
+}
+
+#[test]
+fn test_text_request_fixture() {
+    let parameters = ChatCompletionParametersBuilder::default()
+        .model("gpt-4o-mini")
+        .messages(vec![
+            ChatMessage::Developer {
+                content: ChatMessageContent::Text("You are a helpful assistant.".to_string()),
+                name: None,
+            },
+            user("Hello!"),
+        ])
+        .temperature(0.2)
+        .max_completion_tokens(256u32)
+        .build()
+        .unwrap();
+
+    assert_request("text.request.json", parameters);
+}
+
+#[test]
+fn test_vision_request_fixture() {
+    let parameters = ChatCompletionParametersBuilder::default()
+        .model("gpt-4o")
+        .messages(vec![ChatMessage::User {
+            content: ChatMessageContent::ContentPart(vec![
+                ChatMessageContentPart::Text(ChatMessageTextContentPart {
+                    r#type: "text".to_string(),
+                    text: "What is in this image?".to_string(),
+                }),
+                ChatMessageContentPart::Image(ChatMessageImageContentPart {
+                    r#type: "image_url".to_string(),
+                    image_url: ImageUrlType {
+                        url: "https://upload.wikimedia.org/wikipedia/commons/thumb/d/dd/Gfp-wisconsin-madison-the-nature-boardwalk.jpg/2560px-Gfp-wisconsin-madison-the-nature-boardwalk.jpg".to_string(),
+                        detail: Some(ImageUrlDetail::Low),
+                    },
+                }),
+            ]),
+            name: None,
+        }])
+        .max_tokens(300u32)
+        .build()
+        .unwrap();
+
+    assert_request("vision.request.json", parameters);
+}
+
+#[test]
+fn test_tool_calls_request_fixture() {
+    let parameters = ChatCompletionParametersBuilder::default()
+        .model("gpt-4o")
+        .messages(vec![
+            user("What's the weather like in Boston today?"),
+            ChatMessage::Assistant {
+                content: None,
+                reasoning_content: None,
+                refusal: None,
+                name: None,
+                audio: None,
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_Ndt4BMJOnAR4mgBqMnXmEz9i".to_string(),
+                    r#type: "function".to_string(),
+                    function: Function {
+                        name: "get_current_weather".to_string(),
+                        arguments: r#"{"location":"Boston, MA"}"#.to_string(),
+                    },
+                }]),
+            },
+            ChatMessage::Tool {
+                content: ChatMessageContent::Text(
+                    r#"{"temperature":22,"unit":"celsius"}"#.to_string(),
+                ),
+                tool_call_id: "call_Ndt4BMJOnAR4mgBqMnXmEz9i".to_string(),
+            },
+        ])
+        .tools(vec![ChatCompletionTool {
+            r#type: ChatCompletionToolType::Function,
+            function: ChatCompletionFunction {
+                name: "get_current_weather".to_string(),
+                description: Some("Get the current weather in a given location".to_string()),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "location": {"type": "string", "description": "The city and state, e.g. San Francisco, CA"},
+                        "unit": {"type": "string", "enum": ["celsius", "fahrenheit"]}
+                    },
+                    "required": ["location"]
+                }),
+            },
+        }])
+        .tool_choice(ChatCompletionToolChoice::ChatCompletionToolChoiceFunction(
+            ChatCompletionToolChoiceFunction {
+                r#type: Some(ChatCompletionToolType::Function),
+                function: ChatCompletionToolChoiceFunctionName {
+                    name: "get_current_weather".to_string(),
+                },
+            },
+        ))
+        .build()
+        .unwrap();
+
+    assert_request("tool_calls.request.json", parameters);
+}
+
+#[test]
+fn test_structured_output_request_fixture() {
+    let parameters = ChatCompletionParametersBuilder::default()
+        .model("gpt-4o-2024-08-06")
+        .messages(vec![
+            ChatMessage::System {
+                content: ChatMessageContent::Text("Extract the event information.".to_string()),
+                name: None,
+            },
+            user("Alice and Bob are going to a science fair on Friday."),
+        ])
+        .response_format(ChatCompletionResponseFormat::JsonSchema {
+            json_schema: JsonSchemaBuilder::default()
+                .name("calendar_event")
+                .strict(true)
+                .schema(json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "date": {"type": "string"},
+                        "participants": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["name", "date", "participants"],
+                    "additionalProperties": false
+                }))
+                .build()
+                .unwrap(),
+        })
+        .build()
+        .unwrap();
+
+    assert_request("structured_output.request.json", parameters);
+}
+
+#[test]
+fn test_stream_request_fixture() {
+    let parameters = ChatCompletionParametersBuilder::default()
+        .model("gpt-4o-mini")
+        .messages(vec![user("Say hello")])
+        .stream(true)
+        .stream_options(ChatCompletionStreamOptions {
+            include_usage: Some(true),
+            continuous_usage_stats: None,
+        })
+        .build()
+        .unwrap();
+
+    assert_request("stream.request.json", parameters);
+}
+
+#[test]
+fn test_response_fixtures_round_trip() {
+    for name in [
+        "text.response.json",
+        "vision.response.json",
+        "tool_calls.response.json",
+        "structured_output.response.json",
+    ] {
+        assert_round_trip::<ChatCompletionResponse>(name, &fixture(name));
+    }
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn test_stream_chunk_fixtures_round_trip() {
+    use openai_dive::v1::resources::chat::{
+        ChatCompletionAccumulator, ChatCompletionChunkResponse,
+    };
+
+    let chunks = fixture("stream.chunks.jsonl");
+
+    let mut accumulator = ChatCompletionAccumulator::new();
+
+    for (line, chunk) in chunks.lines().enumerate() {
+        assert_round_trip::<ChatCompletionChunkResponse>(
+            &format!("stream.chunks.jsonl:{}", line + 1),
+            chunk,
+        );
+
+        accumulator.push(serde_json::from_str(chunk).unwrap());
+    }
+
+    let response = accumulator.finish();
+
+    assert_eq!(response.choices[0].message.text(), Some("Hello!"));
+    assert_eq!(response.usage.unwrap().total_tokens, 11);
+}
+
+#[test]
+fn test_lenient_numbers_response_fixture() {
+    let response: ChatCompletionResponse =
+        serde_json::from_str(&fixture("lenient_numbers.response.json")).unwrap();
+
+    assert_eq!(response.created, 1715000000);
+    assert_eq!(response.choices[0].index, 0);
+
+    let usage = response.usage.as_ref().unwrap();
+
+    assert_eq!(
+        (
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens
+        ),
+        (Some(21), Some(8), 29)
+    );
+
+    // the standard representation is sent back
+    let serialized = to_json(&response);
+
+    assert_eq!(serialized["created"], json!(1715000000));
+    assert_eq!(serialized["choices"][0]["index"], json!(0));
+    assert_eq!(serialized["usage"]["total_tokens"], json!(29));
+    assert_eq!(
+        serialized["usage"]["prompt_tokens_details"]["cached_tokens"],
+        json!(0)
+    );
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn test_lenient_numbers_chunk_fixtures() {
+    use openai_dive::v1::resources::chat::{ChatCompletionChunkResponse, DeltaChatMessage};
+
+    let chunks: Vec<ChatCompletionChunkResponse> = fixture("lenient_numbers.chunks.jsonl")
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert!(chunks.iter().all(|chunk| chunk.created == 1715000001));
+    assert_eq!(chunks[0].choices[0].index, Some(0));
+    assert_eq!(chunks[1].usage.as_ref().unwrap().prompt_tokens, Some(12));
+
+    let DeltaChatMessage::Untagged {
+        tool_calls: Some(tool_calls),
+        ..
+    } = &chunks[1].choices[0].delta
+    else {
+        panic!("expected a tool call delta");
+    };
+
+    assert_eq!(tool_calls[0].index, Some(0));
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn test_split_tool_call_name_chunk_fixtures() {
+    use openai_dive::v1::resources::chat::{
+        ChatCompletionAccumulator, ChatCompletionChunkResponse,
+    };
+
+    // the shape of a Claude completion proxied by LiteLLM, which streams the name in fragments
+    // and repeats the type with every delta, the second name starts with a doubled letter
+    let chunks = fixture("split_tool_call_name.chunks.jsonl");
+
+    let mut accumulator = ChatCompletionAccumulator::new();
+
+    for (line, chunk) in chunks.lines().enumerate() {
+        assert_round_trip::<ChatCompletionChunkResponse>(
+            &format!("split_tool_call_name.chunks.jsonl:{}", line + 1),
+            chunk,
+        );
+
+        accumulator.push(serde_json::from_str(chunk).unwrap());
+    }
+
+    let response = accumulator.finish();
+
+    let ChatMessage::Assistant {
+        tool_calls: Some(tool_calls),
+        ..
+    } = &response.choices[0].message
+    else {
+        panic!("expected an assistant message with tool calls");
+    };
+
+    assert_eq!(tool_calls.len(), 2);
+    assert_eq!(tool_calls[0].id, "toolu_01A2b3C4d5E6f7G8h9J0k1L2");
+    assert_eq!(tool_calls[0].r#type, "function");
+    assert_eq!(tool_calls[0].function.name, "get_current_weather");
+
+    let arguments: Value = serde_json::from_str(&tool_calls[0].function.arguments).unwrap();
+
+    assert_eq!(
+        arguments,
+        json!({"location": "Amsterdam", "unit": "celsius"})
+    );
+
+    assert_eq!(tool_calls[1].id, "toolu_01M3n4P5q6R7s8T9u0V1w2X3");
+    assert_eq!(tool_calls[1].function.name, "llama_search");
+}
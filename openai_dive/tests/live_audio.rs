@@ -0,0 +1,96 @@
+//! Integration tests against the live OpenAI API.
+//!
+//! These tests are only compiled with the `live-tests` feature and require `OPENAI_API_KEY` to be set:
+//!
+//! ```sh
+//! cargo test -p openai_dive --features live-tests --test live_audio -- --ignored
+//! ```
+#![cfg(feature = "live-tests")]
+
+use openai_dive::v1::api::Client;
+use openai_dive::v1::models::{TTSModel, TranscriptionModel};
+use openai_dive::v1::resources::audio::{
+    AudioOutputFormat, AudioSpeechParametersBuilder, AudioSpeechResponseFormat,
+    AudioTranscriptionParametersBuilder,
+};
+use openai_dive::v1::resources::shared::{FileUpload, FileUploadBytes};
+
+// the first five seconds of the audio of the transcription example
+const FIXTURE: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/testdata/audio/micro-machines.mp3"
+));
+
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+async fn transcribe(client: &Client, bytes: Vec<u8>, filename: &str) -> String {
+    let parameters = AudioTranscriptionParametersBuilder::default()
+        .file(FileUpload::Bytes(FileUploadBytes::new(bytes, filename)))
+        .model(TranscriptionModel::Whisper1.to_string())
+        .response_format(AudioOutputFormat::Text)
+        .build()
+        .unwrap();
+
+    client
+        .audio()
+        .create_transcription(parameters)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "requires OPENAI_API_KEY and network access"]
+async fn test_speech_transcription_round_trip() {
+    let client = Client::new_from_env();
+
+    let input = "The quick brown fox jumps over the lazy dog.";
+
+    let parameters = AudioSpeechParametersBuilder::default()
+        .model(TTSModel::Tts1.to_string())
+        .input(input)
+        .voice("alloy")
+        .response_format(AudioSpeechResponseFormat::Mp3)
+        .build()
+        .unwrap();
+
+    let speech = client.audio().create_speech(parameters).await.unwrap();
+
+    assert!(!speech.bytes.is_empty());
+
+    let transcript = transcribe(&client, speech.bytes.to_vec(), "speech.mp3").await;
+
+    let expected = normalized_words(input);
+    let actual = normalized_words(&transcript);
+
+    let matching = expected.iter().filter(|word| actual.contains(word)).count();
+
+    assert!(
+        matching * 10 >= expected.len() * 8,
+        "transcript `{transcript}` does not match input `{input}`"
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires OPENAI_API_KEY and network access"]
+async fn test_transcription_of_fixture() {
+    let client = Client::new_from_env();
+
+    let transcript = transcribe(&client, FIXTURE.to_vec(), "micro-machines.mp3").await;
+
+    assert!(
+        normalized_words(&transcript)
+            .iter()
+            .any(|word| word.starts_with("micro")),
+        "unexpected transcript `{transcript}`"
+    );
+}
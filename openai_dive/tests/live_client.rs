@@ -0,0 +1,77 @@
+//! Integration tests against the live OpenAI API.
+//!
+//! These tests are only compiled with the `live-tests` feature and require `OPENAI_API_KEY` to be set:
+//!
+//! ```sh
+//! cargo test -p openai_dive --features live-tests --test live_client -- --ignored --nocapture
+//! ```
+#![cfg(feature = "live-tests")]
+
+use openai_dive::v1::api::Client;
+use std::time::{Duration, Instant};
+
+const SAMPLES: usize = 5;
+
+/// The median latency of the first call of `SAMPLES` new clients, each warmed up first if `warmup` is set.
+async fn median_first_call_latency(warmup: bool) -> Duration {
+    let mut latencies = vec![];
+
+    for _ in 0..SAMPLES {
+        let client = Client::new_from_env();
+
+        if warmup {
+            client.warmup().await.unwrap();
+        }
+
+        let start = Instant::now();
+        client.models().list().await.unwrap();
+        latencies.push(start.elapsed());
+    }
+
+    latencies.sort();
+
+    latencies[SAMPLES / 2]
+}
+
+#[tokio::test]
+#[ignore = "measures network latency"]
+async fn test_warmup_first_call_latency() {
+    let cold = median_first_call_latency(false).await;
+    let warm = median_first_call_latency(true).await;
+
+    println!("median first call latency without warmup: {cold:?}, with warmup: {warm:?}");
+
+    // the warmed up client reuses the open connection and skips the TLS handshake,
+    // the tolerance absorbs the jitter of the network and the API
+    let tolerance = Duration::from_millis(100);
+
+    assert!(
+        warm <= cold + tolerance,
+        "the first call after warmup ({warm:?}) is slower than without warmup ({cold:?})"
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires network access to httpbin.org"]
+async fn test_redirect_policy_is_applied() {
+    let mut client = Client::new("sk-test".to_string());
+
+    let response = client
+        .http_client
+        .get("https://httpbin.org/redirect/6")
+        .send()
+        .await;
+
+    assert!(response.is_err());
+
+    client.set_redirect_policy(reqwest::redirect::Policy::none());
+
+    let response = client
+        .http_client
+        .get("https://httpbin.org/redirect/1")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::FOUND);
+}
@@ -0,0 +1,143 @@
+//! Mutual TLS against a local mock server, with the test certificates in `testdata/tls`.
+//!
+//! The server presents a certificate of a test CA for `localhost` and requires a client certificate of the same CA.
+//! The certificates are valid for 100 years, regenerate them with openssl if they ever expire.
+#![cfg(feature = "rustls-tls")]
+
+use openai_dive::v1::api::Client;
+use openai_dive::v1::error::APIError;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::crypto::aws_lc_rs;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+fn testdata(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/testdata/tls/{name}", env!("CARGO_MANIFEST_DIR"));
+
+    std::fs::read(&path).unwrap_or_else(|error| panic!("{path}: {error}"))
+}
+
+/// Starts a server that answers every request with a model, and returns its base URL.
+async fn mock_server() -> String {
+    let provider = Arc::new(aws_lc_rs::default_provider());
+
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(testdata("ca.der"))).unwrap();
+
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .unwrap();
+
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![CertificateDer::from_pem_slice(&testdata("server.pem")).unwrap()],
+            PrivateKeyDer::from_pem_slice(&testdata("server.key")).unwrap(),
+        )
+        .unwrap();
+
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+
+            tokio::spawn(async move {
+                // a failed handshake is expected in some of the tests
+                let Ok(mut stream) = acceptor.accept(socket).await else {
+                    return;
+                };
+
+                let mut buffer = [0; 4096];
+                let _ = stream.read(&mut buffer).await;
+
+                let body = r#"{"id": "gpt-4o", "object": "model", "created": 1715367049, "owned_by": "system"}"#;
+
+                let _ = stream
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                        .as_bytes(),
+                    )
+                    .await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    format!("https://localhost:{port}")
+}
+
+fn client_for(base_url: &str) -> Client {
+    let mut client = Client::new("sk-test".to_string());
+    client.set_base_url(base_url);
+
+    client
+}
+
+#[tokio::test]
+async fn test_request_with_client_certificate_and_pinned_ca() {
+    let base_url = mock_server().await;
+
+    let mut client = client_for(&base_url);
+    client
+        .set_identity_pem(&testdata("client.identity.pem"))
+        .unwrap()
+        .set_pinned_certificate(&testdata("ca.der"))
+        .unwrap();
+
+    let model = client.models().get("gpt-4o").await.unwrap();
+
+    assert_eq!(model.id, "gpt-4o");
+}
+
+#[tokio::test]
+async fn test_handshake_failures_are_tls_errors() {
+    let base_url = mock_server().await;
+
+    // the server requires a client certificate
+    let mut client = client_for(&base_url);
+    client.set_pinned_certificate(&testdata("ca.der")).unwrap();
+
+    let error = client.models().get("gpt-4o").await.unwrap_err();
+
+    assert!(matches!(error, APIError::TlsError(_)), "{error:?}");
+
+    // the certificate of the server is not signed by the pinned CA
+    let mut client = client_for(&base_url);
+    client
+        .set_identity_pem(&testdata("client.identity.pem"))
+        .unwrap()
+        .set_pinned_certificate(&testdata("other_ca.der"))
+        .unwrap();
+
+    let error = client.models().get("gpt-4o").await.unwrap_err();
+
+    assert!(
+        matches!(&error, APIError::TlsError(message) if message.contains("certificate")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn test_invalid_identity_is_rejected() {
+    let mut client = Client::new("sk-test".to_string());
+
+    assert!(matches!(
+        client.set_identity_pem(b"not a certificate"),
+        Err(APIError::TlsError(_))
+    ));
+    assert!(!client.config_summary().has_client_identity);
+}